@@ -0,0 +1,374 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+use crate::{
+    errors::Error,
+    exchange::AssetPrecision,
+    info::info_client::{info_request, InfoRequest},
+    meta::{Meta, SpotMeta},
+    prelude::*,
+    req::HttpClient,
+};
+
+/// Spot assets are addressed as `10000 + index` on the wire.
+pub const SPOT_ASSET_OFFSET: u32 = 10_000;
+/// How long metadata is trusted before the next lookup reloads it.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+/// Minimum time between reloads triggered by unknown assets, so typos don't
+/// turn into a request per lookup.
+pub const DEFAULT_MISS_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A tradable perp or spot pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetInfo {
+    /// Perp name, or the spot pair name from `spotMeta` (e.g. `PURR/USDC` or `@107`).
+    pub name: String,
+    /// Index used in exchange actions.
+    pub asset: u32,
+    pub sz_decimals: u32,
+    /// `None` for spot pairs.
+    pub max_leverage: Option<usize>,
+    pub is_spot: bool,
+    /// Base and quote token names of a spot pair.
+    pub spot_tokens: Option<(String, String)>,
+}
+
+impl AssetInfo {
+    pub fn precision(&self) -> AssetPrecision {
+        if self.is_spot {
+            AssetPrecision::spot(self.sz_decimals)
+        } else {
+            AssetPrecision::perp(self.sz_decimals)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    by_name: HashMap<String, Arc<AssetInfo>>,
+    by_index: HashMap<u32, Arc<AssetInfo>>,
+    loaded_at: Instant,
+}
+
+impl Snapshot {
+    fn build(meta: &Meta, spot_meta: &SpotMeta) -> Self {
+        let mut by_name = HashMap::new();
+        let mut by_index = HashMap::new();
+
+        for (index, asset) in meta.universe.iter().enumerate() {
+            let info = Arc::new(AssetInfo {
+                name: asset.name.clone(),
+                asset: index as u32,
+                sz_decimals: asset.sz_decimals,
+                max_leverage: Some(asset.max_leverage),
+                is_spot: false,
+                spot_tokens: None,
+            });
+            by_name.insert(asset.name.clone(), info.clone());
+            by_index.insert(info.asset, info);
+        }
+
+        let tokens: HashMap<usize, _> = spot_meta
+            .tokens
+            .iter()
+            .map(|token| (token.index, token))
+            .collect();
+        for pair in spot_meta.universe.iter() {
+            let Some(base) = tokens.get(&pair.tokens[0]) else {
+                continue;
+            };
+            let quote = tokens.get(&pair.tokens[1]);
+            let info = Arc::new(AssetInfo {
+                name: pair.name.clone(),
+                asset: SPOT_ASSET_OFFSET + pair.index as u32,
+                sz_decimals: base.sz_decimals.into(),
+                max_leverage: None,
+                is_spot: true,
+                spot_tokens: quote.map(|quote| (base.name.clone(), quote.name.clone())),
+            });
+            if let Some(quote) = quote {
+                by_name.insert(format!("{}/{}", base.name, quote.name), info.clone());
+            }
+            by_name.insert(format!("@{}", pair.index), info.clone());
+            by_name.insert(pair.name.clone(), info.clone());
+            by_index.insert(info.asset, info);
+        }
+
+        Self {
+            by_name,
+            by_index,
+            loaded_at: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    http_client: HttpClient,
+    refresh_interval: Duration,
+    miss_refresh_interval: Duration,
+    snapshot: RwLock<Option<Arc<Snapshot>>>,
+    last_miss_refresh: Mutex<Option<Instant>>,
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+/// Resolves perp and spot names (including `@index` spot aliases) and asset
+/// indices from `meta` and `spotMeta`.
+///
+/// Metadata is loaded on first use, reloaded once it is older than the
+/// refresh interval, and reloaded early when a lookup misses, so newly listed
+/// assets become tradable without a restart. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct AssetRegistry {
+    inner: Arc<Inner>,
+}
+
+impl AssetRegistry {
+    pub(crate) fn new(http_client: HttpClient) -> Self {
+        Self::with_intervals(
+            http_client,
+            DEFAULT_REFRESH_INTERVAL,
+            DEFAULT_MISS_REFRESH_INTERVAL,
+        )
+    }
+
+    pub(crate) fn with_intervals(
+        http_client: HttpClient,
+        refresh_interval: Duration,
+        miss_refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                http_client,
+                refresh_interval,
+                miss_refresh_interval,
+                snapshot: RwLock::new(None),
+                last_miss_refresh: Mutex::new(None),
+                refreshing: tokio::sync::Mutex::new(()),
+            }),
+        }
+    }
+
+    /// Replaces the current metadata without a request, e.g. with metadata
+    /// the caller already has.
+    pub fn seed(&self, meta: &Meta, spot_meta: &SpotMeta) {
+        self.store(Snapshot::build(meta, spot_meta));
+    }
+
+    /// Loads the metadata unless it is already loaded and fresh.
+    pub async fn ensure_loaded(&self) -> Result<()> {
+        self.fresh_snapshot().await.map(|_| ())
+    }
+
+    /// Reloads `meta` and `spotMeta`.
+    pub async fn refresh(&self) -> Result<()> {
+        self.refresh_if_older_than(Instant::now()).await
+    }
+
+    /// Looks up an asset by perp name, spot pair name, `BASE/QUOTE` or `@index`.
+    pub async fn resolve(&self, coin: &str) -> Result<AssetInfo> {
+        self.lookup(|snapshot| snapshot.by_name.get(coin).cloned())
+            .await
+    }
+
+    /// Looks up an asset by the index used in exchange actions.
+    pub async fn resolve_index(&self, asset: u32) -> Result<AssetInfo> {
+        self.lookup(|snapshot| snapshot.by_index.get(&asset).cloned())
+            .await
+    }
+
+    /// Looks up an asset in the metadata already loaded, without any request.
+    pub fn get(&self, coin: &str) -> Option<AssetInfo> {
+        self.current()?
+            .by_name
+            .get(coin)
+            .map(|info| info.as_ref().clone())
+    }
+
+    /// All perps and spot pairs, each listed once.
+    pub async fn assets(&self) -> Result<Vec<AssetInfo>> {
+        let snapshot = self.fresh_snapshot().await?;
+        let mut assets: Vec<AssetInfo> = snapshot
+            .by_index
+            .values()
+            .map(|info| info.as_ref().clone())
+            .collect();
+        assets.sort_by_key(|info| info.asset);
+        Ok(assets)
+    }
+
+    async fn lookup(
+        &self,
+        find: impl Fn(&Snapshot) -> Option<Arc<AssetInfo>>,
+    ) -> Result<AssetInfo> {
+        let snapshot = self.fresh_snapshot().await?;
+        if let Some(info) = find(&snapshot) {
+            return Ok(info.as_ref().clone());
+        }
+
+        // Metadata that was just loaded won't know the asset either
+        if snapshot.loaded_at.elapsed() < self.inner.miss_refresh_interval
+            || !self.claim_miss_refresh()
+        {
+            return Err(Error::AssetNotFound);
+        }
+        self.refresh_if_older_than(Instant::now()).await?;
+        let snapshot = self.current().ok_or(Error::AssetNotFound)?;
+        find(&snapshot)
+            .map(|info| info.as_ref().clone())
+            .ok_or(Error::AssetNotFound)
+    }
+
+    /// Current metadata, loading it if missing and reloading it if stale. A
+    /// failed reload keeps serving the previous metadata.
+    async fn fresh_snapshot(&self) -> Result<Arc<Snapshot>> {
+        match self.current() {
+            Some(snapshot) if snapshot.loaded_at.elapsed() < self.inner.refresh_interval => {
+                Ok(snapshot)
+            }
+            Some(snapshot) => {
+                let stale_at = snapshot.loaded_at + self.inner.refresh_interval;
+                if let Err(e) = self.refresh_if_older_than(stale_at).await {
+                    warn!("Failed to refresh asset metadata, using cached copy: {e}");
+                }
+                Ok(self.current().unwrap_or(snapshot))
+            }
+            None => {
+                self.refresh_if_older_than(Instant::now()).await?;
+                self.current().ok_or(Error::AssetNotFound)
+            }
+        }
+    }
+
+    /// Reloads unless another caller already did so after `threshold`, so
+    /// concurrent lookups share one request.
+    async fn refresh_if_older_than(&self, threshold: Instant) -> Result<()> {
+        let _guard = self.inner.refreshing.lock().await;
+        if self
+            .current()
+            .is_some_and(|snapshot| snapshot.loaded_at >= threshold)
+        {
+            return Ok(());
+        }
+
+        let http_client = &self.inner.http_client;
+        let (meta, spot_meta) = tokio::try_join!(
+            info_request::<Meta>(http_client, InfoRequest::Meta),
+            info_request::<SpotMeta>(http_client, InfoRequest::SpotMeta),
+        )?;
+        self.store(Snapshot::build(&meta, &spot_meta));
+        Ok(())
+    }
+
+    fn claim_miss_refresh(&self) -> bool {
+        let mut last = self
+            .inner
+            .last_miss_refresh
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if last.is_some_and(|last| last.elapsed() < self.inner.miss_refresh_interval) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
+    fn current(&self) -> Option<Arc<Snapshot>> {
+        self.inner
+            .snapshot
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn store(&self, snapshot: Snapshot) {
+        *self
+            .inner
+            .snapshot
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(snapshot));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_server::spawn_stand_in_server;
+
+    fn token(name: &str, index: usize, sz_decimals: u8) -> serde_json::Value {
+        json!({
+            "name": name, "szDecimals": sz_decimals, "weiDecimals": 8, "index": index,
+            "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054", "isCanonical": true
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resolves_perps_and_spot_aliases() -> Result<()> {
+        let server = spawn_stand_in_server().await;
+        *server.meta.lock().unwrap() = json!({"universe": [
+            {"name": "BTC", "szDecimals": 5, "maxLeverage": 40}
+        ]});
+        *server.spot_meta.lock().unwrap() = json!({
+            "universe": [
+                {"tokens": [1, 0], "name": "PURR/USDC", "index": 0, "isCanonical": true},
+                {"tokens": [2, 0], "name": "@107", "index": 107, "isCanonical": false}
+            ],
+            "tokens": [token("USDC", 0, 8), token("PURR", 1, 0), token("HYPE", 2, 2)]
+        });
+        let http_client = HttpClient::build(None, Some(server.url.clone()), None, None)?;
+        let registry = AssetRegistry::new(http_client);
+
+        let btc = registry.resolve("BTC").await?;
+        assert_eq!((btc.asset, btc.max_leverage), (0, Some(40)));
+        assert_eq!(registry.resolve("PURR/USDC").await?.asset, 10_000);
+        let hype = registry.resolve("@107").await?;
+        assert_eq!(hype, registry.resolve("HYPE/USDC").await?);
+        assert_eq!(
+            (hype.asset, hype.sz_decimals, hype.is_spot),
+            (10_107, 2, true)
+        );
+        assert_eq!(registry.resolve_index(10_107).await?.name, "@107");
+        assert_eq!(registry.assets().await?.len(), 3);
+        assert_eq!(server.meta_requests.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_on_miss_for_new_listings() -> Result<()> {
+        let server = spawn_stand_in_server().await;
+        *server.meta.lock().unwrap() = json!({"universe": [
+            {"name": "BTC", "szDecimals": 5, "maxLeverage": 40}
+        ]});
+        let http_client = HttpClient::build(None, Some(server.url.clone()), None, None)?;
+        let registry =
+            AssetRegistry::with_intervals(http_client, DEFAULT_REFRESH_INTERVAL, Duration::ZERO);
+
+        assert!(matches!(
+            registry.resolve("NEW").await,
+            Err(Error::AssetNotFound)
+        ));
+        *server.meta.lock().unwrap() = json!({"universe": [
+            {"name": "BTC", "szDecimals": 5, "maxLeverage": 40},
+            {"name": "NEW", "szDecimals": 1, "maxLeverage": 3}
+        ]});
+        let new = registry.resolve("NEW").await?;
+        assert_eq!((new.asset, new.max_leverage), (1, Some(3)));
+        // The initial load, the miss above and the lookup that found it
+        assert_eq!(server.meta_requests.load(Ordering::SeqCst), 3);
+        // Known assets are served from the cache
+        registry.resolve("BTC").await?;
+        assert_eq!(server.meta_requests.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+}
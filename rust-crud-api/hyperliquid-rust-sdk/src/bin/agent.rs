@@ -1,6 +1,7 @@
 use alloy::signers::local::PrivateKeySigner;
 use hyperliquid_rust_sdk::{BaseUrl, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient};
 use log::info;
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() {
@@ -35,8 +36,8 @@ async fn main() {
         asset: "ETH".to_string(),
         is_buy: true,
         reduce_only: false,
-        limit_px: 1795.0,
-        sz: 0.01,
+        limit_px: dec!(1795.0),
+        sz: dec!(0.01),
         cloid: None,
         order_type: ClientOrder::Limit(ClientLimit {
             tif: "Gtc".to_string(),
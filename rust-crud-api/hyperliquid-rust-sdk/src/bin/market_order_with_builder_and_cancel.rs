@@ -6,6 +6,7 @@ use hyperliquid_rust_sdk::{
     MarketCloseParams, MarketOrderParams,
 };
 use log::info;
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() {
@@ -24,9 +25,9 @@ async fn main() {
     let market_open_params = MarketOrderParams {
         asset: "ETH",
         is_buy: true,
-        sz: 0.01,
+        sz: dec!(0.01),
         px: None,
-        slippage: Some(0.01), // 1% slippage
+        slippage: Some(dec!(0.01)), // 1% slippage
         cloid: None,
         wallet: None,
     };
@@ -65,7 +66,7 @@ async fn main() {
         asset: "ETH",
         sz: None, // Close entire position
         px: None,
-        slippage: Some(0.01), // 1% slippage
+        slippage: Some(dec!(0.01)), // 1% slippage
         cloid: None,
         wallet: None,
     };
@@ -5,6 +5,7 @@ use hyperliquid_rust_sdk::{
     BaseUrl, ClientCancelRequestCloid, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient,
 };
 use log::info;
+use rust_decimal_macros::dec;
 use uuid::Uuid;
 
 #[tokio::main]
@@ -26,8 +27,8 @@ async fn main() {
         asset: "ETH".to_string(),
         is_buy: true,
         reduce_only: false,
-        limit_px: 1800.0,
-        sz: 0.01,
+        limit_px: dec!(1800.0),
+        sz: dec!(0.01),
         cloid: Some(cloid),
         order_type: ClientOrder::Limit(ClientLimit {
             tif: "Gtc".to_string(),
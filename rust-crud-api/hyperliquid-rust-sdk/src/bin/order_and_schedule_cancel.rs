@@ -5,6 +5,7 @@ use hyperliquid_rust_sdk::{
     BaseUrl, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient, ExchangeDataStatus,
     ExchangeResponseStatus,
 };
+use rust_decimal_macros::dec;
 use std::{thread::sleep, time::Duration};
 
 #[tokio::main]
@@ -27,8 +28,8 @@ async fn main() {
         asset: "ETH".to_string(),
         is_buy: true,
         reduce_only: false,
-        limit_px: 100.0,
-        sz: 0.01,
+        limit_px: dec!(100.0),
+        sz: dec!(0.1),
         cloid: None,
         order_type: ClientOrder::Limit(ClientLimit {
             tif: "Gtc".to_string(),
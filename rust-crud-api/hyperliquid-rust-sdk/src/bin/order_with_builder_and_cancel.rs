@@ -6,6 +6,7 @@ use hyperliquid_rust_sdk::{
     ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus,
 };
 use log::info;
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() {
@@ -24,8 +25,8 @@ async fn main() {
         asset: "ETH".to_string(),
         is_buy: true,
         reduce_only: false,
-        limit_px: 1800.0,
-        sz: 0.01,
+        limit_px: dec!(1800.0),
+        sz: dec!(0.01),
         cloid: None,
         order_type: ClientOrder::Limit(ClientLimit {
             tif: "Gtc".to_string(),
@@ -6,6 +6,7 @@ use hyperliquid_rust_sdk::{
     ExchangeDataStatus, ExchangeResponseStatus,
 };
 use log::info;
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() {
@@ -24,8 +25,8 @@ async fn main() {
         asset: "XYZTWO/USDC".to_string(),
         is_buy: true,
         reduce_only: false,
-        limit_px: 0.00002378,
-        sz: 1000000.0,
+        limit_px: dec!(0.00002378),
+        sz: dec!(1000000.0),
         cloid: None,
         order_type: ClientOrder::Limit(ClientLimit {
             tif: "Gtc".to_string(),
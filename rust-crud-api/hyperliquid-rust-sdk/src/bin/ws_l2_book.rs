@@ -1,8 +1,10 @@
-use hyperliquid_rust_sdk::{BaseUrl, InfoClient, Message, Subscription};
+use futures_util::StreamExt;
+use hyperliquid_rust_sdk::{
+    BaseUrl, InfoClient, Message, OverflowPolicy, StreamConfig, Subscription,
+};
 use log::info;
 use tokio::{
     spawn,
-    sync::mpsc::unbounded_channel,
     time::{sleep, Duration},
 };
 
@@ -12,16 +14,20 @@ async fn main() {
 
     let mut info_client = InfoClient::new(None, Some(BaseUrl::Testnet)).await.unwrap();
 
-    let (sender, mut receiver) = unbounded_channel();
-    let subscription_id = info_client
-        .subscribe(
+    // Each book replaces the last, so a slow consumer only needs the newest one
+    let mut stream = info_client
+        .subscribe_stream(
             Subscription::L2Book {
                 coin: "ETH".to_string(),
             },
-            sender,
+            StreamConfig {
+                capacity: 1,
+                overflow: OverflowPolicy::KeepLatest,
+            },
         )
         .await
         .unwrap();
+    let subscription_id = stream.subscription_id();
 
     spawn(async move {
         sleep(Duration::from_secs(30)).await;
@@ -30,7 +36,8 @@ async fn main() {
     });
 
     // This loop ends when we unsubscribe
-    while let Some(Message::L2Book(l2_book)) = receiver.recv().await {
+    while let Some(Message::L2Book(l2_book)) = stream.next().await {
         info!("Received l2 book data: {l2_book:?}");
     }
+    info!("Skipped {} stale books", stream.lagged());
 }
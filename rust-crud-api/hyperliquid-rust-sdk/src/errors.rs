@@ -49,12 +49,20 @@ pub enum Error {
     RandGen(String),
     #[error("Private key parse error: {0:?}")]
     PrivateKeyParse(String),
+    #[error("Subscription does not deliver the requested data: {0}")]
+    SubscriptionDataMismatch(String),
     #[error("Cannot subscribe to multiple user events")]
     UserEvents,
     #[error("Rmp parse error: {0:?}")]
     RmpParse(String),
     #[error("Invalid input number")]
     FloatStringParse,
+    #[error("Number has more decimal places than the wire format allows: {0}")]
+    DecimalPrecision(String),
+    #[error("Invalid order: {0}")]
+    InvalidOrder(String),
+    #[error("Inconsistent order book: {0}")]
+    InconsistentBook(String),
     #[error("No cloid found in order request when expected")]
     NoCloid,
     #[error("ECDSA signature failed: {0:?}")]
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{sync::Arc, time::Duration};
 
 use alloy::{
     primitives::{keccak256, Address, Signature, B256},
@@ -6,9 +6,12 @@ use alloy::{
 };
 use log::debug;
 use reqwest::Client;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
 use crate::{
+    asset_registry::AssetRegistry,
     exchange::{
         actions::{
             ApproveAgent, ApproveBuilderFee, BulkCancel, BulkModify, BulkOrder, ClaimRewards,
@@ -18,7 +21,8 @@ use crate::{
         cancel::{CancelRequest, CancelRequestCloid, ClientCancelRequestCloid},
         modify::{ClientModifyRequest, ModifyRequest},
         order::{MarketCloseParams, MarketOrderParams},
-        BuilderInfo, ClientCancelRequest, ClientLimit, ClientOrder, ClientOrderRequest,
+        validate_order, AssetPrecision, BuilderInfo, ClientCancelRequest, ClientLimit, ClientOrder,
+        ClientOrderRequest, MIN_ORDER_NOTIONAL,
     },
     helpers::{next_nonce, uuid_to_hex_string},
     info::info_client::InfoClient,
@@ -27,16 +31,129 @@ use crate::{
     req::HttpClient,
     signature::{sign_l1_action, sign_typed_data},
     BaseUrl, BulkCancelCloid, ClassTransfer, Error, ExchangeResponseStatus, SpotSend, SpotUser,
-    VaultTransfer, Withdraw3,
+    VaultTransfer, Withdraw3, WsConfig,
 };
 
 #[derive(Debug)]
 pub struct ExchangeClient {
     pub http_client: HttpClient,
     pub wallet: PrivateKeySigner,
-    pub meta: Meta,
     pub vault_address: Option<Address>,
-    pub coin_to_asset: HashMap<String, u32>,
+    /// Resolves coin names to asset indices and precision, shared with `info_client`.
+    pub asset_registry: AssetRegistry,
+    /// Orders below this value (other than reduce-only ones) are rejected locally.
+    pub min_order_notional: Decimal,
+    /// Used for the mids and positions market orders need; shares the HTTP client by default.
+    pub info_client: Arc<InfoClient>,
+}
+
+/// Builds an [`ExchangeClient`] against a named network or any API URL, with
+/// optional shared HTTP client, `InfoClient` and asset registry.
+#[derive(Debug)]
+pub struct ExchangeClientBuilder {
+    wallet: PrivateKeySigner,
+    client: Option<Client>,
+    api_url: Option<String>,
+    mainnet: Option<bool>,
+    timeout: Option<Duration>,
+    meta: Option<Meta>,
+    asset_registry: Option<AssetRegistry>,
+    info_client: Option<Arc<InfoClient>>,
+    vault_address: Option<Address>,
+    min_order_notional: Decimal,
+}
+
+impl ExchangeClientBuilder {
+    pub fn base_url(mut self, base_url: BaseUrl) -> Self {
+        self.api_url = Some(base_url.get_url());
+        self
+    }
+
+    /// Any HTTP(S) API URL, e.g. a local stand-in server.
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+
+    /// Whether actions are signed for mainnet; by default only `MAINNET_API_URL` is.
+    pub fn mainnet(mut self, mainnet: bool) -> Self {
+        self.mainnet = Some(mainnet);
+        self
+    }
+
+    /// Shares an existing HTTP client (and its connection pool).
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Request timeout for the HTTP client the builder creates. Ignored when
+    /// a client is passed with [`ExchangeClientBuilder::client`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Perp metadata to start the asset registry with instead of fetching it.
+    pub fn meta(mut self, meta: Meta) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Asset registry shared with other clients. Defaults to the registry of
+    /// the `InfoClient`.
+    pub fn asset_registry(mut self, asset_registry: AssetRegistry) -> Self {
+        self.asset_registry = Some(asset_registry);
+        self
+    }
+
+    /// Minimum order value checked before sending (defaults to `MIN_ORDER_NOTIONAL`).
+    pub fn min_order_notional(mut self, min_order_notional: Decimal) -> Self {
+        self.min_order_notional = min_order_notional;
+        self
+    }
+
+    /// `InfoClient` for metadata, mids and positions. Defaults to one on the same API URL.
+    pub fn info_client(mut self, info_client: Arc<InfoClient>) -> Self {
+        self.info_client = Some(info_client);
+        self
+    }
+
+    pub fn vault_address(mut self, vault_address: Address) -> Self {
+        self.vault_address = Some(vault_address);
+        self
+    }
+
+    pub async fn build(self) -> Result<ExchangeClient> {
+        let http_client = HttpClient::build(self.client, self.api_url, self.mainnet, self.timeout)?;
+        let info_client = match self.info_client {
+            Some(info_client) => info_client,
+            None => Arc::new(InfoClient::from_http_client(
+                http_client.clone(),
+                None,
+                WsConfig::default(),
+                self.asset_registry.clone(),
+            )),
+        };
+
+        let asset_registry = match self.asset_registry {
+            Some(asset_registry) => asset_registry,
+            None => info_client.asset_registry().clone(),
+        };
+        match self.meta {
+            Some(meta) => asset_registry.seed(&meta, &info_client.spot_meta().await?),
+            None => asset_registry.ensure_loaded().await?,
+        }
+
+        Ok(ExchangeClient {
+            wallet: self.wallet,
+            vault_address: self.vault_address,
+            http_client,
+            asset_registry,
+            min_order_notional: self.min_order_notional,
+            info_client,
+        })
+    }
 }
 
 fn serialize_sig<S>(sig: &Signature, s: S) -> std::result::Result<S::Ok, S::Error>
@@ -100,6 +217,21 @@ impl Actions {
 }
 
 impl ExchangeClient {
+    pub fn builder(wallet: PrivateKeySigner) -> ExchangeClientBuilder {
+        ExchangeClientBuilder {
+            wallet,
+            client: None,
+            api_url: None,
+            mainnet: None,
+            timeout: None,
+            meta: None,
+            asset_registry: None,
+            info_client: None,
+            vault_address: None,
+            min_order_notional: MIN_ORDER_NOTIONAL,
+        }
+    }
+
     pub async fn new(
         client: Option<Client>,
         wallet: PrivateKeySigner,
@@ -107,36 +239,17 @@ impl ExchangeClient {
         meta: Option<Meta>,
         vault_address: Option<Address>,
     ) -> Result<ExchangeClient> {
-        let client = client.unwrap_or_default();
-        let base_url = base_url.unwrap_or(BaseUrl::Mainnet);
-
-        let info = InfoClient::new(None, Some(base_url)).await?;
-        let meta = if let Some(meta) = meta {
-            meta
-        } else {
-            info.meta().await?
-        };
-
-        let mut coin_to_asset = HashMap::new();
-        for (asset_ind, asset) in meta.universe.iter().enumerate() {
-            coin_to_asset.insert(asset.name.clone(), asset_ind as u32);
+        let mut builder = Self::builder(wallet).base_url(base_url.unwrap_or(BaseUrl::Mainnet));
+        if let Some(client) = client {
+            builder = builder.client(client);
         }
-
-        coin_to_asset = info
-            .spot_meta()
-            .await?
-            .add_pair_and_name_to_index_map(coin_to_asset);
-
-        Ok(ExchangeClient {
-            wallet,
-            meta,
-            vault_address,
-            http_client: HttpClient {
-                client,
-                base_url: base_url.get_url(),
-            },
-            coin_to_asset,
-        })
+        if let Some(meta) = meta {
+            builder = builder.meta(meta);
+        }
+        if let Some(vault_address) = vault_address {
+            builder = builder.vault_address(vault_address);
+        }
+        builder.build().await
     }
 
     async fn post(
@@ -314,8 +427,8 @@ impl ExchangeClient {
         &self,
         params: MarketOrderParams<'_>,
     ) -> Result<ExchangeResponseStatus> {
-        let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
-        let (px, sz_decimals) = self
+        let slippage = params.slippage.unwrap_or(dec!(0.05)); // Default 5% slippage
+        let (px, precision) = self
             .calculate_slippage_price(params.asset, params.is_buy, slippage, params.px)
             .await?;
 
@@ -324,7 +437,7 @@ impl ExchangeClient {
             is_buy: params.is_buy,
             reduce_only: false,
             limit_px: px,
-            sz: round_to_decimals(params.sz, sz_decimals),
+            sz: precision.round_size(params.sz),
             cloid: params.cloid,
             order_type: ClientOrder::Limit(ClientLimit {
                 tif: "Ioc".to_string(),
@@ -339,8 +452,8 @@ impl ExchangeClient {
         params: MarketOrderParams<'_>,
         builder: BuilderInfo,
     ) -> Result<ExchangeResponseStatus> {
-        let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
-        let (px, sz_decimals) = self
+        let slippage = params.slippage.unwrap_or(dec!(0.05)); // Default 5% slippage
+        let (px, precision) = self
             .calculate_slippage_price(params.asset, params.is_buy, slippage, params.px)
             .await?;
 
@@ -349,7 +462,7 @@ impl ExchangeClient {
             is_buy: params.is_buy,
             reduce_only: false,
             limit_px: px,
-            sz: round_to_decimals(params.sz, sz_decimals),
+            sz: precision.round_size(params.sz),
             cloid: params.cloid,
             order_type: ClientOrder::Limit(ClientLimit {
                 tif: "Ioc".to_string(),
@@ -363,16 +476,10 @@ impl ExchangeClient {
         &self,
         params: MarketCloseParams<'_>,
     ) -> Result<ExchangeResponseStatus> {
-        let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
+        let slippage = params.slippage.unwrap_or(dec!(0.05)); // Default 5% slippage
         let wallet = params.wallet.unwrap_or(&self.wallet);
 
-        let base_url = match self.http_client.base_url.as_str() {
-            "https://api.hyperliquid.xyz" => BaseUrl::Mainnet,
-            "https://api.hyperliquid-testnet.xyz" => BaseUrl::Testnet,
-            _ => return Err(Error::GenericRequest("Invalid base URL".to_string())),
-        };
-        let info_client = InfoClient::new(None, Some(base_url)).await?;
-        let user_state = info_client.user_state(wallet.address()).await?;
+        let user_state = self.info_client.user_state(wallet.address()).await?;
 
         let position = user_state
             .asset_positions
@@ -380,21 +487,17 @@ impl ExchangeClient {
             .find(|p| p.position.coin == params.asset)
             .ok_or(Error::AssetNotFound)?;
 
-        let szi = position
-            .position
-            .szi
-            .parse::<f64>()
-            .map_err(|_| Error::FloatStringParse)?;
+        let szi = position.position.szi;
 
-        let (px, sz_decimals) = self
-            .calculate_slippage_price(params.asset, szi < 0.0, slippage, params.px)
+        let (px, precision) = self
+            .calculate_slippage_price(params.asset, szi < Decimal::ZERO, slippage, params.px)
             .await?;
 
-        let sz = round_to_decimals(params.sz.unwrap_or_else(|| szi.abs()), sz_decimals);
+        let sz = precision.round_size(params.sz.unwrap_or_else(|| szi.abs()));
 
         let order = ClientOrderRequest {
             asset: params.asset.to_string(),
-            is_buy: szi < 0.0,
+            is_buy: szi < Decimal::ZERO,
             reduce_only: true,
             limit_px: px,
             sz,
@@ -411,55 +514,35 @@ impl ExchangeClient {
         &self,
         asset: &str,
         is_buy: bool,
-        slippage: f64,
-        px: Option<f64>,
-    ) -> Result<(f64, u32)> {
-        let base_url = match self.http_client.base_url.as_str() {
-            "https://api.hyperliquid.xyz" => BaseUrl::Mainnet,
-            "https://api.hyperliquid-testnet.xyz" => BaseUrl::Testnet,
-            _ => return Err(Error::GenericRequest("Invalid base URL".to_string())),
-        };
-        let info_client = InfoClient::new(None, Some(base_url)).await?;
-        let meta = info_client.meta().await?;
-
-        let asset_meta = meta
-            .universe
-            .iter()
-            .find(|a| a.name == asset)
-            .ok_or(Error::AssetNotFound)?;
-
-        let sz_decimals = asset_meta.sz_decimals;
-        let max_decimals: u32 = if self.coin_to_asset[asset] < 10000 {
-            6
-        } else {
-            8
-        };
-        let price_decimals = max_decimals.saturating_sub(sz_decimals);
+        slippage: Decimal,
+        px: Option<Decimal>,
+    ) -> Result<(Decimal, AssetPrecision)> {
+        let asset_info = self.asset_registry.resolve(asset).await?;
+        let precision = asset_info.precision();
 
         let px = if let Some(px) = px {
             px
         } else {
-            let all_mids = info_client.all_mids().await?;
-            all_mids
+            let all_mids = self.info_client.all_mids().await?;
+            *all_mids
                 .get(asset)
+                .or_else(|| all_mids.get(&asset_info.name))
                 .ok_or(Error::AssetNotFound)?
-                .parse::<f64>()
-                .map_err(|_| Error::FloatStringParse)?
         };
 
         debug!("px before slippage: {px:?}");
         let slippage_factor = if is_buy {
-            1.0 + slippage
+            Decimal::ONE + slippage
         } else {
-            1.0 - slippage
+            Decimal::ONE - slippage
         };
         let px = px * slippage_factor;
 
         // Round to the correct number of decimal places and significant figures
-        let px = round_to_significant_and_decimal(px, 5, price_decimals);
+        let px = precision.round_price(px);
 
         debug!("px after slippage: {px:?}");
-        Ok((px, sz_decimals))
+        Ok((px, precision))
     }
 
     pub async fn order(
@@ -491,7 +574,9 @@ impl ExchangeClient {
         let mut transformed_orders = Vec::new();
 
         for order in orders {
-            transformed_orders.push(order.convert(&self.coin_to_asset)?);
+            let asset = self.asset_registry.resolve(&order.asset).await?;
+            validate_order(asset.precision(), &order, self.min_order_notional)?;
+            transformed_orders.push(order.convert(asset.asset)?);
         }
 
         let action = Actions::Order(BulkOrder {
@@ -521,7 +606,9 @@ impl ExchangeClient {
         let mut transformed_orders = Vec::new();
 
         for order in orders {
-            transformed_orders.push(order.convert(&self.coin_to_asset)?);
+            let asset = self.asset_registry.resolve(&order.asset).await?;
+            validate_order(asset.precision(), &order, self.min_order_notional)?;
+            transformed_orders.push(order.convert(asset.asset)?);
         }
 
         let action = Actions::Order(BulkOrder {
@@ -555,10 +642,7 @@ impl ExchangeClient {
 
         let mut transformed_cancels = Vec::new();
         for cancel in cancels.into_iter() {
-            let &asset = self
-                .coin_to_asset
-                .get(&cancel.asset)
-                .ok_or(Error::AssetNotFound)?;
+            let asset = self.asset_registry.resolve(&cancel.asset).await?.asset;
             transformed_cancels.push(CancelRequest {
                 asset,
                 oid: cancel.oid,
@@ -595,9 +679,11 @@ impl ExchangeClient {
 
         let mut transformed_modifies = Vec::new();
         for modify in modifies.into_iter() {
+            let asset = self.asset_registry.resolve(&modify.order.asset).await?;
+            validate_order(asset.precision(), &modify.order, self.min_order_notional)?;
             transformed_modifies.push(ModifyRequest {
                 oid: modify.oid,
-                order: modify.order.convert(&self.coin_to_asset)?,
+                order: modify.order.convert(asset.asset)?,
             });
         }
 
@@ -631,10 +717,7 @@ impl ExchangeClient {
 
         let mut transformed_cancels: Vec<CancelRequestCloid> = Vec::new();
         for cancel in cancels.into_iter() {
-            let &asset = self
-                .coin_to_asset
-                .get(&cancel.asset)
-                .ok_or(Error::AssetNotFound)?;
+            let asset = self.asset_registry.resolve(&cancel.asset).await?.asset;
             transformed_cancels.push(CancelRequestCloid {
                 asset,
                 cloid: uuid_to_hex_string(cancel.cloid),
@@ -664,7 +747,7 @@ impl ExchangeClient {
 
         let timestamp = next_nonce();
 
-        let &asset_index = self.coin_to_asset.get(coin).ok_or(Error::AssetNotFound)?;
+        let asset_index = self.asset_registry.resolve(coin).await?.asset;
         let action = Actions::UpdateLeverage(UpdateLeverage {
             asset: asset_index,
             is_cross,
@@ -689,7 +772,7 @@ impl ExchangeClient {
         let amount = (amount * 1_000_000.0).round() as i64;
         let timestamp = next_nonce();
 
-        let &asset_index = self.coin_to_asset.get(coin).ok_or(Error::AssetNotFound)?;
+        let asset_index = self.asset_registry.resolve(coin).await?.asset;
         let action = Actions::UpdateIsolatedMargin(UpdateIsolatedMargin {
             asset: asset_index,
             is_buy: true,
@@ -869,19 +952,6 @@ impl ExchangeClient {
     }
 }
 
-fn round_to_decimals(value: f64, decimals: u32) -> f64 {
-    let factor = 10f64.powi(decimals as i32);
-    (value * factor).round() / factor
-}
-
-fn round_to_significant_and_decimal(value: f64, sig_figs: u32, max_decimals: u32) -> f64 {
-    let abs_value = value.abs();
-    let magnitude = abs_value.log10().floor() as i32;
-    let scale = 10f64.powi(sig_figs as i32 - magnitude - 1);
-    let rounded = (abs_value * scale).round() / scale;
-    round_to_decimals(rounded.copysign(value), max_decimals)
-}
-
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -891,6 +961,7 @@ mod tests {
     use super::*;
     use crate::{
         exchange::order::{Limit, OrderRequest, Trigger},
+        test_server::spawn_stand_in_server,
         Order,
     };
 
@@ -1164,4 +1235,109 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_limit_order_decimal_wire_format() -> Result<()> {
+        let order = ClientOrderRequest {
+            asset: "ETH".to_string(),
+            is_buy: true,
+            reduce_only: false,
+            limit_px: dec!(2000.00),
+            sz: dec!(3.50),
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Gtc".to_string(),
+            }),
+        }
+        .convert(1)?;
+        assert_eq!(order.limit_px, "2000");
+        assert_eq!(order.sz, "3.5");
+
+        let too_precise = ClientOrderRequest {
+            asset: "ETH".to_string(),
+            is_buy: true,
+            reduce_only: false,
+            limit_px: dec!(2000),
+            sz: dec!(0.000000001),
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Gtc".to_string(),
+            }),
+        };
+        assert!(matches!(
+            too_precise.convert(1),
+            Err(Error::DecimalPrecision(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_market_orders_against_custom_url() -> Result<()> {
+        let server = spawn_stand_in_server().await;
+        *server.meta.lock().unwrap() = serde_json::json!({"universe": [
+            {"name": "BTC", "szDecimals": 5, "maxLeverage": 40},
+            {"name": "ETH", "szDecimals": 4, "maxLeverage": 25}
+        ]});
+
+        let exchange_client = ExchangeClient::builder(get_wallet()?)
+            .api_url(server.url.clone())
+            .timeout(Duration::from_secs(5))
+            .build()
+            .await?;
+        assert!(!exchange_client.http_client.is_mainnet());
+        assert_eq!(exchange_client.asset_registry.get("ETH").unwrap().asset, 1);
+        // The InfoClient built alongside shares the registry
+        assert!(exchange_client
+            .info_client
+            .asset_registry()
+            .get("ETH")
+            .is_some());
+
+        let opened = exchange_client
+            .market_open(MarketOrderParams {
+                asset: "ETH",
+                is_buy: true,
+                sz: dec!(0.01),
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: None,
+            })
+            .await?;
+        assert!(matches!(opened, ExchangeResponseStatus::Ok(_)));
+
+        let closed = exchange_client
+            .market_close(MarketCloseParams {
+                asset: "ETH",
+                sz: None,
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: None,
+            })
+            .await?;
+        assert!(matches!(closed, ExchangeResponseStatus::Ok(_)));
+
+        let requests = server.exchange_requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        // 1800.5 plus 5% slippage, rounded to ETH's two price decimals
+        let open = &requests[0]["action"]["orders"][0];
+        assert_eq!(
+            (open["b"].as_bool(), open["p"].as_str(), open["s"].as_str()),
+            (Some(true), Some("1890.5"), Some("0.01"))
+        );
+        // Closing the short buys back the whole position as reduce-only
+        let close = &requests[1]["action"]["orders"][0];
+        assert_eq!(
+            (
+                close["b"].as_bool(),
+                close["r"].as_bool(),
+                close["s"].as_str()
+            ),
+            (Some(true), Some(true), Some("0.05"))
+        );
+
+        Ok(())
+    }
 }
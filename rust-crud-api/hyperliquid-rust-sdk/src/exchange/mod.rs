@@ -4,6 +4,7 @@ mod cancel;
 mod exchange_client;
 mod exchange_responses;
 mod modify;
+mod normalizer;
 mod order;
 
 pub use actions::*;
@@ -12,6 +13,7 @@ pub use cancel::{ClientCancelRequest, ClientCancelRequestCloid};
 pub use exchange_client::*;
 pub use exchange_responses::*;
 pub use modify::{ClientModifyRequest, ModifyRequest};
+pub use normalizer::*;
 pub use order::{
     ClientLimit, ClientOrder, ClientOrderRequest, ClientTrigger, MarketCloseParams,
     MarketOrderParams, Order,
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+use crate::{
+    errors::Error,
+    exchange::order::{ClientOrder, ClientOrderRequest},
+    meta::{Meta, SpotMeta},
+    prelude::*,
+};
+
+/// Prices may have at most this many significant figures (integer prices are always allowed).
+pub const PRICE_SIG_FIGS: u32 = 5;
+/// Perp prices may have at most `PERP_MAX_DECIMALS - sz_decimals` decimal places.
+pub const PERP_MAX_DECIMALS: u32 = 6;
+/// Spot prices may have at most `SPOT_MAX_DECIMALS - sz_decimals` decimal places.
+pub const SPOT_MAX_DECIMALS: u32 = 8;
+/// Smallest order value, in USDC, the exchange accepts for non reduce-only orders.
+pub const MIN_ORDER_NOTIONAL: Decimal = dec!(10);
+
+/// Price and size precision of a single asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetPrecision {
+    pub sz_decimals: u32,
+    pub is_spot: bool,
+}
+
+impl AssetPrecision {
+    pub fn perp(sz_decimals: u32) -> Self {
+        Self {
+            sz_decimals,
+            is_spot: false,
+        }
+    }
+
+    pub fn spot(sz_decimals: u32) -> Self {
+        Self {
+            sz_decimals,
+            is_spot: true,
+        }
+    }
+
+    /// Maximum number of decimal places allowed in a price.
+    pub fn price_decimals(&self) -> u32 {
+        let max_decimals = if self.is_spot {
+            SPOT_MAX_DECIMALS
+        } else {
+            PERP_MAX_DECIMALS
+        };
+        max_decimals.saturating_sub(self.sz_decimals)
+    }
+
+    /// Rounds a price to 5 significant figures and the allowed number of decimals.
+    /// Prices with 5 or more integer digits are rounded to an integer.
+    pub fn round_price(&self, px: Decimal) -> Decimal {
+        let px = if px.abs() >= Decimal::from(10u64.pow(PRICE_SIG_FIGS - 1)) {
+            px.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+        } else {
+            px.round_sf_with_strategy(PRICE_SIG_FIGS, RoundingStrategy::MidpointAwayFromZero)
+                .unwrap_or(px)
+        };
+        px.round_dp_with_strategy(
+            self.price_decimals(),
+            RoundingStrategy::MidpointAwayFromZero,
+        )
+        .normalize()
+    }
+
+    /// Rounds a size to the asset's `sz_decimals`.
+    pub fn round_size(&self, sz: Decimal) -> Decimal {
+        sz.round_dp_with_strategy(self.sz_decimals, RoundingStrategy::MidpointAwayFromZero)
+            .normalize()
+    }
+
+    /// Checks a price against the exchange rules without changing it.
+    pub fn validate_price(&self, px: Decimal) -> std::result::Result<(), String> {
+        if px <= Decimal::ZERO {
+            return Err(format!("price {px} must be positive"));
+        }
+        let px = px.normalize();
+        if px.scale() == 0 {
+            return Ok(());
+        }
+        if px.scale() > self.price_decimals() {
+            return Err(format!(
+                "price {px} has more than {} decimal places",
+                self.price_decimals()
+            ));
+        }
+        let sig_figs = px.mantissa().unsigned_abs().to_string().len() as u32;
+        if sig_figs > PRICE_SIG_FIGS {
+            return Err(format!(
+                "price {px} has more than {PRICE_SIG_FIGS} significant figures"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks a size against the asset's `sz_decimals` without changing it.
+    pub fn validate_size(&self, sz: Decimal) -> std::result::Result<(), String> {
+        if sz <= Decimal::ZERO {
+            return Err(format!("size {sz} must be positive"));
+        }
+        if sz.normalize().scale() > self.sz_decimals {
+            return Err(format!(
+                "size {sz} has more than {} decimal places",
+                self.sz_decimals
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rounds and validates order prices and sizes per asset, so that malformed
+/// orders fail locally with a descriptive error instead of being rejected by
+/// the exchange.
+#[derive(Debug, Clone)]
+pub struct OrderNormalizer {
+    assets: HashMap<String, AssetPrecision>,
+    min_notional: Decimal,
+}
+
+impl OrderNormalizer {
+    /// Builds the precision table from perp metadata and, for spot pairs, the
+    /// `sz_decimals` of the base token.
+    pub fn new(meta: &Meta, spot_meta: &SpotMeta) -> Self {
+        let mut assets: HashMap<String, AssetPrecision> = meta
+            .universe
+            .iter()
+            .map(|asset| (asset.name.clone(), AssetPrecision::perp(asset.sz_decimals)))
+            .collect();
+
+        let tokens: HashMap<usize, _> = spot_meta
+            .tokens
+            .iter()
+            .map(|token| (token.index, token))
+            .collect();
+        for pair in spot_meta.universe.iter() {
+            let (Some(base), Some(quote)) =
+                (tokens.get(&pair.tokens[0]), tokens.get(&pair.tokens[1]))
+            else {
+                continue;
+            };
+            let precision = AssetPrecision::spot(base.sz_decimals.into());
+            assets.insert(format!("{}/{}", base.name, quote.name), precision);
+            assets.insert(format!("@{}", pair.index), precision);
+            assets.insert(pair.name.clone(), precision);
+        }
+
+        Self {
+            assets,
+            min_notional: MIN_ORDER_NOTIONAL,
+        }
+    }
+
+    /// Overrides the minimum order value (defaults to `MIN_ORDER_NOTIONAL`).
+    pub fn with_min_notional(mut self, min_notional: Decimal) -> Self {
+        self.min_notional = min_notional;
+        self
+    }
+
+    pub fn precision(&self, coin: &str) -> Result<AssetPrecision> {
+        self.assets.get(coin).copied().ok_or(Error::AssetNotFound)
+    }
+
+    pub fn round_price(&self, coin: &str, px: Decimal) -> Result<Decimal> {
+        Ok(self.precision(coin)?.round_price(px))
+    }
+
+    pub fn round_size(&self, coin: &str, sz: Decimal) -> Result<Decimal> {
+        Ok(self.precision(coin)?.round_size(sz))
+    }
+
+    /// Validates an order as-is; see [`validate_order`].
+    pub fn validate(&self, order: &ClientOrderRequest) -> Result<()> {
+        validate_order(self.precision(&order.asset)?, order, self.min_notional)
+    }
+
+    /// Rounds and validates an order; see [`normalize_order`].
+    pub fn normalize(&self, order: ClientOrderRequest) -> Result<ClientOrderRequest> {
+        normalize_order(self.precision(&order.asset)?, order, self.min_notional)
+    }
+}
+
+/// Validates an order as-is: price, trigger price, size and, for orders that
+/// are not reduce-only, the minimum notional.
+pub fn validate_order(
+    precision: AssetPrecision,
+    order: &ClientOrderRequest,
+    min_notional: Decimal,
+) -> Result<()> {
+    let invalid = |reason: String| Error::InvalidOrder(format!("{}: {reason}", order.asset));
+
+    precision.validate_price(order.limit_px).map_err(invalid)?;
+    if let ClientOrder::Trigger(trigger) = &order.order_type {
+        precision
+            .validate_price(trigger.trigger_px)
+            .map_err(|reason| invalid(format!("trigger {reason}")))?;
+    }
+    precision.validate_size(order.sz).map_err(invalid)?;
+
+    let notional = order.limit_px * order.sz;
+    if !order.reduce_only && notional < min_notional {
+        return Err(invalid(format!(
+            "order value {notional} is below the minimum of {min_notional}"
+        )));
+    }
+    Ok(())
+}
+
+/// Rounds the price, trigger price and size of an order to the asset's
+/// precision, then validates the result.
+pub fn normalize_order(
+    precision: AssetPrecision,
+    mut order: ClientOrderRequest,
+    min_notional: Decimal,
+) -> Result<ClientOrderRequest> {
+    order.limit_px = precision.round_price(order.limit_px);
+    order.sz = precision.round_size(order.sz);
+    if let ClientOrder::Trigger(trigger) = &mut order.order_type {
+        trigger.trigger_px = precision.round_price(trigger.trigger_px);
+    }
+    validate_order(precision, &order, min_notional)?;
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exchange::order::ClientLimit, ClientTrigger};
+
+    fn normalizer() -> OrderNormalizer {
+        let meta: Meta = serde_json::from_value(serde_json::json!({
+            "universe": [
+                {"name": "BTC", "szDecimals": 5, "maxLeverage": 40},
+                {"name": "ETH", "szDecimals": 4, "maxLeverage": 25}
+            ]
+        }))
+        .unwrap();
+        let spot_meta: SpotMeta = serde_json::from_value(serde_json::json!({
+            "universe": [{"tokens": [1, 0], "name": "PURR/USDC", "index": 0, "isCanonical": true}],
+            "tokens": [
+                {"name": "USDC", "szDecimals": 8, "weiDecimals": 8, "index": 0,
+                 "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054", "isCanonical": true},
+                {"name": "PURR", "szDecimals": 0, "weiDecimals": 5, "index": 1,
+                 "tokenId": "0xc1fb593aeffbeb02f85e0308e9956a90", "isCanonical": true}
+            ]
+        }))
+        .unwrap();
+        OrderNormalizer::new(&meta, &spot_meta)
+    }
+
+    fn limit(asset: &str, limit_px: Decimal, sz: Decimal) -> ClientOrderRequest {
+        ClientOrderRequest {
+            asset: asset.to_string(),
+            is_buy: true,
+            reduce_only: false,
+            limit_px,
+            sz,
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Gtc".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_price_rounding() {
+        let eth = AssetPrecision::perp(4);
+        assert_eq!(eth.price_decimals(), 2);
+        assert_eq!(eth.round_price(dec!(1891.6749)), dec!(1891.7));
+        assert_eq!(eth.round_price(dec!(0.0125)), dec!(0.01));
+        assert_eq!(eth.round_price(dec!(-1891.65)), dec!(-1891.7));
+
+        let btc = AssetPrecision::perp(5);
+        assert_eq!(btc.round_price(dec!(123456.7)), dec!(123457));
+        assert_eq!(btc.round_price(dec!(98765.43)), dec!(98765));
+
+        let purr = AssetPrecision::spot(0);
+        assert_eq!(purr.round_price(dec!(0.000123456)), dec!(0.00012346));
+        assert_eq!(purr.round_size(dec!(12.5)), dec!(13));
+    }
+
+    #[test]
+    fn test_price_and_size_validation() {
+        let eth = AssetPrecision::perp(4);
+        assert!(eth.validate_price(dec!(1891.7)).is_ok());
+        assert!(eth.validate_price(dec!(123456)).is_ok());
+        assert!(eth.validate_price(dec!(1891.75)).is_err());
+        assert!(eth.validate_price(dec!(12.345)).is_err());
+        assert!(eth.validate_price(dec!(0)).is_err());
+        assert!(eth.validate_size(dec!(0.0001)).is_ok());
+        assert!(eth.validate_size(dec!(0.00015)).is_err());
+    }
+
+    #[test]
+    fn test_validate_order() {
+        let normalizer = normalizer();
+        assert!(normalizer
+            .validate(&limit("ETH", dec!(1800), dec!(0.01)))
+            .is_ok());
+        assert!(normalizer
+            .validate(&limit("PURR/USDC", dec!(0.21), dec!(100)))
+            .is_ok());
+
+        let err = normalizer
+            .validate(&limit("ETH", dec!(100), dec!(0.01)))
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidOrder(ref msg) if msg.contains("minimum")));
+
+        let mut reduce_only = limit("ETH", dec!(100), dec!(0.01));
+        reduce_only.reduce_only = true;
+        assert!(normalizer.validate(&reduce_only).is_ok());
+
+        assert!(matches!(
+            normalizer.validate(&limit("ETH", dec!(1800.123), dec!(0.01))),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(matches!(
+            normalizer.validate(&limit("DOGE", dec!(1), dec!(100))),
+            Err(Error::AssetNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_normalize_order() {
+        let normalizer = normalizer();
+        let mut order = limit("BTC", dec!(65432.1), dec!(0.000456789));
+        order.order_type = ClientOrder::Trigger(ClientTrigger {
+            is_market: true,
+            trigger_px: dec!(65000.55),
+            tpsl: "sl".to_string(),
+        });
+        let order = normalizer.normalize(order).unwrap();
+        assert_eq!(order.limit_px, dec!(65432));
+        assert_eq!(order.sz, dec!(0.00046));
+        let ClientOrder::Trigger(trigger) = order.order_type else {
+            panic!("expected trigger order");
+        };
+        assert_eq!(trigger.trigger_px, dec!(65001));
+
+        // A size that rounds to zero is still rejected
+        assert!(normalizer
+            .normalize(limit("BTC", dec!(65000), dec!(0.000001)))
+            .is_err());
+    }
+}
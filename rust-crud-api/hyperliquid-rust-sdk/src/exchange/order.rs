@@ -1,12 +1,10 @@
-use std::collections::HashMap;
-
 use alloy::signers::local::PrivateKeySigner;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    errors::Error,
-    helpers::{float_to_string_for_hashing, uuid_to_hex_string},
+    helpers::{decimal_to_wire, uuid_to_hex_string},
     prelude::*,
 };
 
@@ -49,15 +47,15 @@ pub struct OrderRequest {
     pub cloid: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClientLimit {
     pub tif: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClientTrigger {
     pub is_market: bool,
-    pub trigger_px: f64,
+    pub trigger_px: Decimal,
     pub tpsl: String,
 }
 
@@ -65,9 +63,9 @@ pub struct ClientTrigger {
 pub struct MarketOrderParams<'a> {
     pub asset: &'a str,
     pub is_buy: bool,
-    pub sz: f64,
-    pub px: Option<f64>,
-    pub slippage: Option<f64>,
+    pub sz: Decimal,
+    pub px: Option<Decimal>,
+    pub slippage: Option<Decimal>,
     pub cloid: Option<Uuid>,
     pub wallet: Option<&'a PrivateKeySigner>,
 }
@@ -75,50 +73,49 @@ pub struct MarketOrderParams<'a> {
 #[derive(Debug)]
 pub struct MarketCloseParams<'a> {
     pub asset: &'a str,
-    pub sz: Option<f64>,
-    pub px: Option<f64>,
-    pub slippage: Option<f64>,
+    pub sz: Option<Decimal>,
+    pub px: Option<Decimal>,
+    pub slippage: Option<Decimal>,
     pub cloid: Option<Uuid>,
     pub wallet: Option<&'a PrivateKeySigner>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ClientOrder {
     Limit(ClientLimit),
     Trigger(ClientTrigger),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClientOrderRequest {
     pub asset: String,
     pub is_buy: bool,
     pub reduce_only: bool,
-    pub limit_px: f64,
-    pub sz: f64,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
     pub cloid: Option<Uuid>,
     pub order_type: ClientOrder,
 }
 
 impl ClientOrderRequest {
-    pub(crate) fn convert(self, coin_to_asset: &HashMap<String, u32>) -> Result<OrderRequest> {
+    /// Converts to the wire format, with `asset` the index resolved for `self.asset`.
+    pub(crate) fn convert(self, asset: u32) -> Result<OrderRequest> {
         let order_type = match self.order_type {
             ClientOrder::Limit(limit) => Order::Limit(Limit { tif: limit.tif }),
             ClientOrder::Trigger(trigger) => Order::Trigger(Trigger {
-                trigger_px: float_to_string_for_hashing(trigger.trigger_px),
+                trigger_px: decimal_to_wire(trigger.trigger_px)?,
                 is_market: trigger.is_market,
                 tpsl: trigger.tpsl,
             }),
         };
-        let &asset = coin_to_asset.get(&self.asset).ok_or(Error::AssetNotFound)?;
-
         let cloid = self.cloid.map(uuid_to_hex_string);
 
         Ok(OrderRequest {
             asset,
             is_buy: self.is_buy,
             reduce_only: self.reduce_only,
-            limit_px: float_to_string_for_hashing(self.limit_px),
-            sz: float_to_string_for_hashing(self.sz),
+            limit_px: decimal_to_wire(self.limit_px)?,
+            sz: decimal_to_wire(self.sz)?,
             order_type,
             cloid,
         })
@@ -1,13 +1,18 @@
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    collections::{HashSet, VecDeque},
+    hash::Hash,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use chrono::prelude::Utc;
 use lazy_static::lazy_static;
 use log::info;
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use crate::consts::*;
+use crate::{consts::*, errors::Error, prelude::*};
 
-fn now_timestamp_ms() -> u64 {
+pub(crate) fn now_timestamp_ms() -> u64 {
     let now = Utc::now();
     now.timestamp_millis() as u64
 }
@@ -28,18 +33,19 @@ pub(crate) fn next_nonce() -> u64 {
 
 pub(crate) const WIRE_DECIMALS: u8 = 8;
 
-pub(crate) fn float_to_string_for_hashing(x: f64) -> String {
-    let mut x = format!("{:.*}", WIRE_DECIMALS.into(), x);
-    while x.ends_with('0') {
-        x.pop();
+/// Formats a decimal the way the exchange hashes it: no trailing zeros, no
+/// exponent, and never "-0". Values with more than `WIRE_DECIMALS` decimal
+/// places are rejected rather than rounded, so the signed payload always
+/// matches the number the caller asked for.
+pub(crate) fn decimal_to_wire(x: Decimal) -> Result<String> {
+    let x = x.normalize();
+    if x.scale() > WIRE_DECIMALS.into() {
+        return Err(Error::DecimalPrecision(x.to_string()));
     }
-    if x.ends_with('.') {
-        x.pop();
-    }
-    if x == "-0" {
-        "0".to_string()
+    if x.is_zero() {
+        Ok("0".to_string())
     } else {
-        x
+        Ok(x.to_string())
     }
 }
 
@@ -87,6 +93,56 @@ impl BaseUrl {
     }
 }
 
+/// How many ids [`RecentIds`] remembers. Well above the fills and fundings
+/// a subscription snapshot repeats, which is what the dedupe is for.
+pub(crate) const RECENT_IDS_CAPACITY: usize = 10_000;
+
+/// Ids seen lately, forgetting the oldest once `capacity` is reached so a
+/// long-running tracker doesn't grow without bound.
+#[derive(Debug)]
+pub(crate) struct RecentIds<T> {
+    ids: HashSet<T>,
+    order: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T: Eq + Hash + Clone> RecentIds<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Returns `false` if `id` was seen already.
+    pub(crate) fn insert(&mut self, id: T) -> bool {
+        self.insert_evicting(id).0
+    }
+
+    /// Like [`RecentIds::insert`], also returning the id forgotten to make room.
+    pub(crate) fn insert_evicting(&mut self, id: T) -> (bool, Option<T>) {
+        if !self.ids.insert(id.clone()) {
+            return (false, None);
+        }
+        self.order.push_back(id);
+        let mut evicted = None;
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+                evicted = Some(oldest);
+            }
+        }
+        (true, evicted)
+    }
+}
+
+impl<T: Eq + Hash + Clone> Default for RecentIds<T> {
+    fn default() -> Self {
+        Self::new(RECENT_IDS_CAPACITY)
+    }
+}
+
 lazy_static! {
     static ref CUR_NONCE: AtomicU64 = AtomicU64::new(now_timestamp_ms());
 }
@@ -95,44 +151,46 @@ lazy_static! {
 mod tests {
     use super::*;
 
+    fn wire(x: &str) -> String {
+        decimal_to_wire(x.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn decimal_to_wire_test() {
+        assert_eq!(wire("0"), "0");
+        assert_eq!(wire("-0"), "0");
+        assert_eq!(wire("-0.0000"), "0");
+        assert_eq!(wire("0.00076000"), "0.00076");
+        assert_eq!(wire("0.00000001"), "0.00000001");
+        assert_eq!(wire("0.12345678"), "0.12345678");
+        assert_eq!(wire("87654321.12345678"), "87654321.12345678");
+        assert_eq!(wire("987654321.00000000"), "987654321");
+        assert_eq!(wire("87654321.1234"), "87654321.1234");
+        assert_eq!(wire("0.000760"), "0.00076");
+        assert_eq!(wire("987654321.0"), "987654321");
+        assert_eq!(wire("1000.000"), "1000");
+        assert_eq!(wire("-1850.5"), "-1850.5");
+        assert_eq!(wire("0.123456780"), "0.12345678");
+    }
+
+    #[test]
+    fn recent_ids_forget_the_oldest() {
+        let mut ids = RecentIds::new(2);
+        assert!(ids.insert(1));
+        assert!(!ids.insert(1));
+        assert!(ids.insert(2));
+        assert!(ids.insert(3));
+        assert!(ids.insert(1));
+        assert!(!ids.insert(3));
+        assert_eq!(ids.order.len(), 2);
+    }
+
     #[test]
-    fn float_to_string_for_hashing_test() {
-        assert_eq!(float_to_string_for_hashing(0.), "0".to_string());
-        assert_eq!(float_to_string_for_hashing(-0.), "0".to_string());
-        assert_eq!(float_to_string_for_hashing(-0.0000), "0".to_string());
-        assert_eq!(
-            float_to_string_for_hashing(0.00076000),
-            "0.00076".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(0.00000001),
-            "0.00000001".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(0.12345678),
-            "0.12345678".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(87654321.12345678),
-            "87654321.12345678".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(987654321.00000000),
-            "987654321".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(87654321.1234),
-            "87654321.1234".to_string()
-        );
-        assert_eq!(float_to_string_for_hashing(0.000760), "0.00076".to_string());
-        assert_eq!(float_to_string_for_hashing(0.00076), "0.00076".to_string());
-        assert_eq!(
-            float_to_string_for_hashing(987654321.0),
-            "987654321".to_string()
-        );
-        assert_eq!(
-            float_to_string_for_hashing(987654321.),
-            "987654321".to_string()
-        );
+    fn decimal_to_wire_rejects_extra_precision() {
+        let x: Decimal = "0.123456789".parse().unwrap();
+        assert!(matches!(
+            decimal_to_wire(x),
+            Err(Error::DecimalPrecision(_))
+        ));
     }
 }
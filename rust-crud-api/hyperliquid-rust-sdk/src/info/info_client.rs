@@ -1,11 +1,13 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use alloy::primitives::Address;
 use reqwest::Client;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 
 use crate::{
+    asset_registry::AssetRegistry,
     info::{
         ActiveAssetDataResponse, CandlesSnapshotResponse, FundingHistoryResponse,
         L2SnapshotResponse, OpenOrdersResponse, OrderInfo, RecentTradesResponse, UserFillsResponse,
@@ -14,7 +16,10 @@ use crate::{
     meta::{AssetContext, Meta, SpotMeta, SpotMetaAndAssetCtxs},
     prelude::*,
     req::HttpClient,
-    ws::{Subscription, WsManager},
+    ws::{
+        ChannelData, StreamConfig, Subscription, SubscriptionQueue, SubscriptionSink,
+        SubscriptionStream, TypedStream, TypedSubscription, WsConfig, WsManager,
+    },
     BaseUrl, Error, Message, OrderStatusResponse, ReferralResponse, UserFeesResponse,
     UserFundingResponse, UserTokenBalanceResponse,
 };
@@ -96,14 +101,108 @@ pub enum InfoRequest {
     },
 }
 
+pub(crate) async fn info_request<T: for<'a> Deserialize<'a>>(
+    http_client: &HttpClient,
+    info_request: InfoRequest,
+) -> Result<T> {
+    let data = serde_json::to_string(&info_request).map_err(|e| Error::JsonParse(e.to_string()))?;
+
+    let return_data = http_client.post("/info", data).await?;
+    serde_json::from_str(&return_data).map_err(|e| Error::JsonParse(e.to_string()))
+}
+
 #[derive(Debug)]
 pub struct InfoClient {
     pub http_client: HttpClient,
     pub(crate) ws_manager: Option<WsManager>,
-    reconnect: bool,
+    ws_url: String,
+    ws_config: WsConfig,
+    asset_registry: AssetRegistry,
+}
+
+/// Builds an [`InfoClient`] against a named network or any API URL, e.g. a
+/// local stand-in server.
+#[derive(Debug, Default)]
+pub struct InfoClientBuilder {
+    client: Option<Client>,
+    api_url: Option<String>,
+    ws_url: Option<String>,
+    mainnet: Option<bool>,
+    timeout: Option<Duration>,
+    ws_config: WsConfig,
+    asset_registry: Option<AssetRegistry>,
+}
+
+impl InfoClientBuilder {
+    pub fn base_url(mut self, base_url: BaseUrl) -> Self {
+        self.api_url = Some(base_url.get_url());
+        self
+    }
+
+    /// Any HTTP(S) API URL; the websocket URL is derived from it unless set
+    /// with [`InfoClientBuilder::ws_url`].
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+
+    pub fn ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.ws_url = Some(ws_url.into());
+        self
+    }
+
+    /// Whether the URL serves mainnet; by default only `MAINNET_API_URL` does.
+    pub fn mainnet(mut self, mainnet: bool) -> Self {
+        self.mainnet = Some(mainnet);
+        self
+    }
+
+    /// Shares an existing HTTP client (and its connection pool).
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Request timeout for the HTTP client the builder creates. Ignored when
+    /// a client is passed with [`InfoClientBuilder::client`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> Self {
+        self.ws_config.reconnect = reconnect;
+        self
+    }
+
+    /// Backoff and ping settings for subscriptions. Replaces the `reconnect` flag too.
+    pub fn ws_config(mut self, ws_config: WsConfig) -> Self {
+        self.ws_config = ws_config;
+        self
+    }
+
+    /// Shares an asset registry with other clients instead of creating one.
+    pub fn asset_registry(mut self, asset_registry: AssetRegistry) -> Self {
+        self.asset_registry = Some(asset_registry);
+        self
+    }
+
+    pub fn build(self) -> Result<InfoClient> {
+        let http_client = HttpClient::build(self.client, self.api_url, self.mainnet, self.timeout)?;
+        Ok(InfoClient::from_http_client(
+            http_client,
+            self.ws_url,
+            self.ws_config,
+            self.asset_registry,
+        ))
+    }
 }
 
 impl InfoClient {
+    pub fn builder() -> InfoClientBuilder {
+        InfoClientBuilder::default()
+    }
+
     pub async fn new(client: Option<Client>, base_url: Option<BaseUrl>) -> Result<InfoClient> {
         Self::new_internal(client, base_url, false).await
     }
@@ -120,27 +219,101 @@ impl InfoClient {
         base_url: Option<BaseUrl>,
         reconnect: bool,
     ) -> Result<InfoClient> {
-        let client = client.unwrap_or_default();
-        let base_url = base_url.unwrap_or(BaseUrl::Mainnet).get_url();
+        let mut builder = Self::builder()
+            .base_url(base_url.unwrap_or(BaseUrl::Mainnet))
+            .reconnect(reconnect);
+        if let Some(client) = client {
+            builder = builder.client(client);
+        }
+        builder.build()
+    }
 
-        Ok(InfoClient {
-            http_client: HttpClient { client, base_url },
+    pub(crate) fn from_http_client(
+        http_client: HttpClient,
+        ws_url: Option<String>,
+        ws_config: WsConfig,
+        asset_registry: Option<AssetRegistry>,
+    ) -> InfoClient {
+        let ws_url = ws_url.unwrap_or_else(|| http_client.ws_url());
+        let asset_registry =
+            asset_registry.unwrap_or_else(|| AssetRegistry::new(http_client.clone()));
+        InfoClient {
+            http_client,
             ws_manager: None,
-            reconnect,
-        })
+            ws_url,
+            ws_config,
+            asset_registry,
+        }
     }
 
+    /// Registry of perps and spot pairs, shared with exchange clients built from this client.
+    pub fn asset_registry(&self) -> &AssetRegistry {
+        &self.asset_registry
+    }
+
+    /// Sends the subscription's messages to `sender_channel`, which is never
+    /// full; see [`InfoClient::subscribe_stream`] for a bounded alternative.
     pub async fn subscribe(
         &mut self,
         subscription: Subscription,
         sender_channel: UnboundedSender<Message>,
     ) -> Result<u32> {
-        if self.ws_manager.is_none() {
-            let ws_manager = WsManager::new(
-                format!("ws{}/ws", &self.http_client.base_url[4..]),
-                self.reconnect,
+        self.add_subscription(subscription, SubscriptionSink::Channel(sender_channel))
+            .await
+    }
+
+    /// Subscribes with a bounded buffer that applies `config.overflow` when
+    /// the consumer falls behind, instead of growing without limit.
+    pub async fn subscribe_stream(
+        &mut self,
+        subscription: Subscription,
+        config: StreamConfig,
+    ) -> Result<SubscriptionStream> {
+        let queue = Arc::new(SubscriptionQueue::new(config));
+        let subscription_id = self
+            .add_subscription(subscription, SubscriptionSink::Stream(queue.clone()))
+            .await?;
+        Ok(SubscriptionStream::new(subscription_id, queue))
+    }
+
+    /// Subscribes with a stream of `T`, the payload `subscription` delivers,
+    /// e.g. [`L2BookData`](crate::L2BookData) for `Subscription::L2Book`.
+    /// Connection events and errors arrive on the separate `events` channel.
+    pub async fn subscribe_typed<T: ChannelData>(
+        &mut self,
+        subscription: Subscription,
+        config: StreamConfig,
+    ) -> Result<TypedSubscription<T>> {
+        if !T::accepts(&subscription) {
+            return Err(Error::SubscriptionDataMismatch(format!(
+                "{subscription:?} does not deliver {}",
+                std::any::type_name::<T>()
+            )));
+        }
+        let data = Arc::new(SubscriptionQueue::new(config));
+        let (events_sender, events) = unbounded_channel();
+        let subscription_id = self
+            .add_subscription(
+                subscription,
+                SubscriptionSink::Typed {
+                    data: data.clone(),
+                    events: events_sender,
+                },
             )
             .await?;
+        Ok(TypedSubscription {
+            data: TypedStream::new(SubscriptionStream::new(subscription_id, data)),
+            events,
+        })
+    }
+
+    async fn add_subscription(
+        &mut self,
+        subscription: Subscription,
+        sink: SubscriptionSink,
+    ) -> Result<u32> {
+        if self.ws_manager.is_none() {
+            let ws_manager = WsManager::new(self.ws_url.clone(), self.ws_config.clone()).await?;
             self.ws_manager = Some(ws_manager);
         }
 
@@ -150,17 +323,13 @@ impl InfoClient {
         self.ws_manager
             .as_mut()
             .ok_or(Error::WsManagerNotFound)?
-            .add_subscription(identifier, sender_channel)
+            .add_subscription(identifier, sink)
             .await
     }
 
     pub async fn unsubscribe(&mut self, subscription_id: u32) -> Result<()> {
         if self.ws_manager.is_none() {
-            let ws_manager = WsManager::new(
-                format!("ws{}/ws", &self.http_client.base_url[4..]),
-                self.reconnect,
-            )
-            .await?;
+            let ws_manager = WsManager::new(self.ws_url.clone(), self.ws_config.clone()).await?;
             self.ws_manager = Some(ws_manager);
         }
 
@@ -171,15 +340,8 @@ impl InfoClient {
             .await
     }
 
-    async fn send_info_request<T: for<'a> Deserialize<'a>>(
-        &self,
-        info_request: InfoRequest,
-    ) -> Result<T> {
-        let data =
-            serde_json::to_string(&info_request).map_err(|e| Error::JsonParse(e.to_string()))?;
-
-        let return_data = self.http_client.post("/info", data).await?;
-        serde_json::from_str(&return_data).map_err(|e| Error::JsonParse(e.to_string()))
+    async fn send_info_request<T: for<'a> Deserialize<'a>>(&self, input: InfoRequest) -> Result<T> {
+        info_request(&self.http_client, input).await
     }
 
     pub async fn open_orders(&self, address: Address) -> Result<Vec<OpenOrdersResponse>> {
@@ -227,7 +389,7 @@ impl InfoClient {
         self.send_info_request(input).await
     }
 
-    pub async fn all_mids(&self) -> Result<HashMap<String, String>> {
+    pub async fn all_mids(&self) -> Result<HashMap<String, Decimal>> {
         let input = InfoRequest::AllMids;
         self.send_info_request(input).await
     }
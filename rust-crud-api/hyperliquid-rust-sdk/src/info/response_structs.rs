@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
 use alloy::primitives::Address;
@@ -14,7 +15,7 @@ pub struct UserStateResponse {
     pub asset_positions: Vec<AssetPosition>,
     pub cross_margin_summary: MarginSummary,
     pub margin_summary: MarginSummary,
-    pub withdrawable: String,
+    pub withdrawable: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,10 +37,10 @@ pub struct UserFeesResponse {
 #[serde(rename_all = "camelCase")]
 pub struct OpenOrdersResponse {
     pub coin: String,
-    pub limit_px: String,
+    pub limit_px: Decimal,
     pub oid: u64,
     pub side: String,
-    pub sz: String,
+    pub sz: Decimal,
     pub timestamp: u64,
     pub cloid: Option<String>,
 }
@@ -47,18 +48,18 @@ pub struct OpenOrdersResponse {
 #[derive(serde::Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserFillsResponse {
-    pub closed_pnl: String,
+    pub closed_pnl: Decimal,
     pub coin: String,
     pub crossed: bool,
     pub dir: String,
     pub hash: String,
     pub oid: u64,
-    pub px: String,
+    pub px: Decimal,
     pub side: String,
-    pub start_position: String,
-    pub sz: String,
+    pub start_position: Decimal,
+    pub sz: Decimal,
     pub time: u64,
-    pub fee: String,
+    pub fee: Decimal,
     pub tid: u64,
     pub fee_token: String,
     pub twap_id: Option<u64>,
@@ -68,8 +69,8 @@ pub struct UserFillsResponse {
 #[serde(rename_all = "camelCase")]
 pub struct FundingHistoryResponse {
     pub coin: String,
-    pub funding_rate: String,
-    pub premium: String,
+    pub funding_rate: Decimal,
+    pub premium: Decimal,
     pub time: u64,
 }
 
@@ -93,8 +94,8 @@ pub struct L2SnapshotResponse {
 pub struct RecentTradesResponse {
     pub coin: String,
     pub side: String,
-    pub px: String,
-    pub sz: String,
+    pub px: Decimal,
+    pub sz: Decimal,
     pub time: u64,
     pub hash: String,
 }
@@ -110,15 +111,15 @@ pub struct CandlesSnapshotResponse {
     #[serde(rename = "i")]
     pub candle_interval: String,
     #[serde(rename = "o")]
-    pub open: String,
+    pub open: Decimal,
     #[serde(rename = "c")]
-    pub close: String,
+    pub close: Decimal,
     #[serde(rename = "h")]
-    pub high: String,
+    pub high: Decimal,
     #[serde(rename = "l")]
-    pub low: String,
+    pub low: Decimal,
     #[serde(rename = "v")]
-    pub vlm: String,
+    pub vlm: Decimal,
     #[serde(rename = "n")]
     pub num_trades: u64,
 }
@@ -147,7 +148,7 @@ pub struct ActiveAssetDataResponse {
     pub user: Address,
     pub coin: String,
     pub leverage: Leverage,
-    pub max_trade_szs: Vec<String>,
-    pub available_to_trade: Vec<String>,
-    pub mark_px: String,
+    pub max_trade_szs: Vec<Decimal>,
+    pub available_to_trade: Vec<Decimal>,
+    pub mark_px: Decimal,
 }
@@ -1,4 +1,5 @@
 use alloy::primitives::Address;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -7,29 +8,29 @@ pub struct Leverage {
     #[serde(rename = "type")]
     pub type_string: String,
     pub value: u32,
-    pub raw_usd: Option<String>,
+    pub raw_usd: Option<Decimal>,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CumulativeFunding {
-    pub all_time: String,
-    pub since_open: String,
-    pub since_change: String,
+    pub all_time: Decimal,
+    pub since_open: Decimal,
+    pub since_change: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionData {
     pub coin: String,
-    pub entry_px: Option<String>,
+    pub entry_px: Option<Decimal>,
     pub leverage: Leverage,
-    pub liquidation_px: Option<String>,
-    pub margin_used: String,
-    pub position_value: String,
-    pub return_on_equity: String,
-    pub szi: String,
-    pub unrealized_pnl: String,
+    pub liquidation_px: Option<Decimal>,
+    pub margin_used: Decimal,
+    pub position_value: Decimal,
+    pub return_on_equity: Decimal,
+    pub szi: Decimal,
+    pub unrealized_pnl: Decimal,
     pub max_leverage: u32,
     pub cum_funding: CumulativeFunding,
 }
@@ -44,18 +45,18 @@ pub struct AssetPosition {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MarginSummary {
-    pub account_value: String,
-    pub total_margin_used: String,
-    pub total_ntl_pos: String,
-    pub total_raw_usd: String,
+    pub account_value: Decimal,
+    pub total_margin_used: Decimal,
+    pub total_ntl_pos: Decimal,
+    pub total_raw_usd: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Level {
     pub n: u64,
-    pub px: String,
-    pub sz: String,
+    pub px: Decimal,
+    pub sz: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
@@ -64,9 +65,9 @@ pub struct Delta {
     #[serde(rename = "type")]
     pub type_string: String,
     pub coin: String,
-    pub usdc: String,
-    pub szi: String,
-    pub funding_rate: String,
+    pub usdc: Decimal,
+    pub szi: Decimal,
+    pub funding_rate: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
@@ -112,9 +113,9 @@ pub struct Vip {
 #[serde(rename_all = "camelCase")]
 pub struct UserTokenBalance {
     pub coin: String,
-    pub hold: String,
-    pub total: String,
-    pub entry_ntl: String,
+    pub hold: Decimal,
+    pub total: Decimal,
+    pub entry_ntl: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -130,17 +131,17 @@ pub struct OrderInfo {
 pub struct BasicOrderInfo {
     pub coin: String,
     pub side: String,
-    pub limit_px: String,
-    pub sz: String,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
     pub oid: u64,
     pub timestamp: u64,
     pub trigger_condition: String,
     pub is_trigger: bool,
-    pub trigger_px: String,
+    pub trigger_px: Decimal,
     pub is_position_tpsl: bool,
     pub reduce_only: bool,
     pub order_type: String,
-    pub orig_sz: String,
+    pub orig_sz: Decimal,
     pub tif: Option<String>,
     pub cloid: Option<String>,
 }
@@ -1,4 +1,5 @@
 #![deny(unreachable_pub)]
+mod asset_registry;
 mod consts;
 mod eip712;
 mod errors;
@@ -7,10 +8,19 @@ mod helpers;
 mod info;
 mod market_maker;
 mod meta;
+mod order_book;
+mod order_manager;
+mod portfolio;
 mod prelude;
 mod req;
 mod signature;
+#[cfg(test)]
+mod test_server;
 mod ws;
+pub use asset_registry::{
+    AssetInfo, AssetRegistry, DEFAULT_MISS_REFRESH_INTERVAL, DEFAULT_REFRESH_INTERVAL,
+    SPOT_ASSET_OFFSET,
+};
 pub use consts::{EPSILON, LOCAL_API_URL, MAINNET_API_URL, TESTNET_API_URL};
 pub use eip712::Eip712;
 pub use errors::Error;
@@ -19,4 +29,8 @@ pub use helpers::{bps_diff, truncate_float, BaseUrl};
 pub use info::{info_client::*, *};
 pub use market_maker::{MarketMaker, MarketMakerInput, MarketMakerRestingOrder};
 pub use meta::{AssetContext, AssetMeta, Meta, MetaAndAssetCtxs, SpotAssetMeta, SpotMeta};
+pub use order_book::{BookDepth, MarketImpact, OrderBook, OrderBookConfig, OrderBooks};
+pub use order_manager::{OrderEvent, OrderManager, OrderState, OrderTracker, TrackedOrder};
+pub use portfolio::{AccountSummary, Portfolio, PortfolioTracker, Position, SpotBalance};
+pub use rust_decimal::Decimal;
 pub use ws::*;
@@ -1,12 +1,26 @@
-use alloy::{primitives::Address, signers::local::PrivateKeySigner};
-use log::{error, info};
-use tokio::sync::mpsc::unbounded_channel;
-
 use crate::{
-    bps_diff, truncate_float, BaseUrl, ClientCancelRequest, ClientLimit, ClientOrder,
-    ClientOrderRequest, ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
-    Message, Subscription, UserData, EPSILON,
+    bps_diff, helpers::WIRE_DECIMALS, truncate_float, AllMidsData, BaseUrl, ClientCancelRequest,
+    ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient, ExchangeDataStatus,
+    ExchangeResponseStatus, InfoClient, OrderEvent, OrderManager, OverflowPolicy, StreamConfig,
+    Subscription, SubscriptionEvent, EPSILON,
+};
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use futures_util::StreamExt;
+use log::{error, info, warn};
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
 };
+use tokio::sync::mpsc::UnboundedReceiver;
+
+// The strategy itself works in f64; prices and sizes are converted to decimals
+// only when talking to the exchange.
+fn to_decimal(x: f64) -> Decimal {
+    Decimal::from_f64(x)
+        .unwrap_or_default()
+        .round_dp(WIRE_DECIMALS.into())
+}
+
 #[derive(Debug)]
 pub struct MarketMakerRestingOrder {
     pub oid: u64,
@@ -40,13 +54,18 @@ pub struct MarketMaker {
     pub info_client: InfoClient,
     pub exchange_client: ExchangeClient,
     pub user_address: Address,
+    pub order_manager: OrderManager,
+    order_events: UnboundedReceiver<OrderEvent>,
 }
 
 impl MarketMaker {
     pub async fn new(input: MarketMakerInput) -> MarketMaker {
         let user_address = input.wallet.address();
 
-        let info_client = InfoClient::new(None, Some(BaseUrl::Testnet)).await.unwrap();
+        let mut info_client = InfoClient::new(None, Some(BaseUrl::Testnet)).await.unwrap();
+        let (order_manager, order_events) = OrderManager::start(&mut info_client, user_address)
+            .await
+            .unwrap();
         let exchange_client =
             ExchangeClient::new(None, input.wallet, Some(BaseUrl::Testnet), None, None)
                 .await
@@ -74,37 +93,33 @@ impl MarketMaker {
             info_client,
             exchange_client,
             user_address,
+            order_manager,
+            order_events,
         }
     }
 
     pub async fn start(&mut self) {
-        let (sender, mut receiver) = unbounded_channel();
-
-        // Subscribe to UserEvents for fills
-        self.info_client
-            .subscribe(
-                Subscription::UserEvents {
-                    user: self.user_address,
+        // Subscribe to AllMids so we can market make around the mid price; only
+        // the newest mids matter if we fall behind
+        let mut all_mids = self
+            .info_client
+            .subscribe_typed::<AllMidsData>(
+                Subscription::AllMids,
+                StreamConfig {
+                    capacity: 1,
+                    overflow: OverflowPolicy::KeepLatest,
                 },
-                sender.clone(),
             )
             .await
             .unwrap();
 
-        // Subscribe to AllMids so we can market make around the mid price
-        self.info_client
-            .subscribe(Subscription::AllMids, sender)
-            .await
-            .unwrap();
-
         loop {
-            let message = receiver.recv().await.unwrap();
-            match message {
-                Message::AllMids(all_mids) => {
-                    let all_mids = all_mids.data.mids;
+            tokio::select! {
+                Some(all_mids) = all_mids.data.next() => {
+                    let all_mids = all_mids.mids;
                     let mid = all_mids.get(&self.asset);
                     if let Some(mid) = mid {
-                        let mid: f64 = mid.parse().unwrap();
+                        let mid = mid.to_f64().unwrap();
                         self.latest_mid_price = mid;
                         // Check to see if we need to cancel or place any new orders
                         self.potentially_update().await;
@@ -115,33 +130,49 @@ impl MarketMaker {
                         );
                     }
                 }
-                Message::User(user_events) => {
+                Some(event) = self.order_events.recv() => {
+                    self.on_order_event(event);
                     // We haven't seen the first mid price event yet, so just continue
                     if self.latest_mid_price < 0.0 {
                         continue;
                     }
-                    let user_events = user_events.data;
-                    if let UserData::Fills(fills) = user_events {
-                        for fill in fills {
-                            let amount: f64 = fill.sz.parse().unwrap();
-                            // Update our resting positions whenever we see a fill
-                            if fill.side.eq("B") {
-                                self.cur_position += amount;
-                                self.lower_resting.position -= amount;
-                                info!("Fill: bought {amount} {}", self.asset.clone());
-                            } else {
-                                self.cur_position -= amount;
-                                self.upper_resting.position -= amount;
-                                info!("Fill: sold {amount} {}", self.asset.clone());
-                            }
-                        }
-                    }
                     // Check to see if we need to cancel or place any new orders
                     self.potentially_update().await;
                 }
-                _ => {
-                    panic!("Unsupported message type");
-                }
+                Some(event) = all_mids.events.recv() => match event {
+                    SubscriptionEvent::Disconnected(disconnected) => {
+                        warn!("Disconnected: {}", disconnected.reason)
+                    }
+                    // The order manager catches up on our orders by itself
+                    SubscriptionEvent::Reconnected(_) => info!("Reconnected"),
+                    SubscriptionEvent::Error(err) => error!("Subscription error: {err}"),
+                },
+                else => break,
+            }
+        }
+    }
+
+    fn on_order_event(&mut self, event: OrderEvent) {
+        let order = event.order;
+        if order.coin != self.asset {
+            return;
+        }
+        let amount = event.filled.to_f64().unwrap_or_default();
+        if amount > EPSILON {
+            if order.is_buy {
+                self.cur_position += amount;
+                info!("Fill: bought {amount} {}", self.asset.clone());
+            } else {
+                self.cur_position -= amount;
+                info!("Fill: sold {amount} {}", self.asset.clone());
+            }
+        }
+
+        // Whatever is left of a filled, partially filled or cancelled order
+        let remaining = order.remaining_sz().to_f64().unwrap_or_default();
+        for resting in [&mut self.lower_resting, &mut self.upper_resting] {
+            if order.oid == Some(resting.oid) {
+                resting.position = remaining;
             }
         }
     }
@@ -187,34 +218,38 @@ impl MarketMaker {
         price: f64,
         is_buy: bool,
     ) -> (f64, u64) {
-        let order = self
-            .exchange_client
-            .order(
-                ClientOrderRequest {
-                    asset,
-                    is_buy,
-                    reduce_only: false,
-                    limit_px: price,
-                    sz: amount,
-                    cloid: None,
-                    order_type: ClientOrder::Limit(ClientLimit {
-                        tif: "Gtc".to_string(),
-                    }),
-                },
-                None,
-            )
-            .await;
+        let request = ClientOrderRequest {
+            asset,
+            is_buy,
+            reduce_only: false,
+            limit_px: to_decimal(price),
+            sz: to_decimal(amount),
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Gtc".to_string(),
+            }),
+        };
+        let order = self.exchange_client.order(request.clone(), None).await;
         match order {
             Ok(order) => match order {
                 ExchangeResponseStatus::Ok(order) => {
                     if let Some(order) = order.data {
                         if !order.statuses.is_empty() {
-                            match order.statuses[0].clone() {
+                            let status = &order.statuses[0];
+                            self.order_manager.track(&request, status);
+                            match status {
                                 ExchangeDataStatus::Filled(order) => {
-                                    return (amount, order.oid);
+                                    // Nothing rests; the fill arrives as an order event
+                                    return (0.0, order.oid);
                                 }
                                 ExchangeDataStatus::Resting(order) => {
-                                    return (amount, order.oid);
+                                    let resting = self
+                                        .order_manager
+                                        .order(order.oid)
+                                        .map_or(amount, |order| {
+                                            order.remaining_sz().to_f64().unwrap_or_default()
+                                        });
+                                    return (resting, order.oid);
                                 }
                                 ExchangeDataStatus::Error(e) => {
                                     error!("Error with placing order: {e}")
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use alloy::primitives::B128;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -62,26 +63,26 @@ pub enum MetaAndAssetCtxs {
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SpotAssetContext {
-    pub day_ntl_vlm: String,
-    pub mark_px: String,
-    pub mid_px: Option<String>,
-    pub prev_day_px: String,
-    pub circulating_supply: String,
+    pub day_ntl_vlm: Decimal,
+    pub mark_px: Decimal,
+    pub mid_px: Option<Decimal>,
+    pub prev_day_px: Decimal,
+    pub circulating_supply: Decimal,
     pub coin: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetContext {
-    pub day_ntl_vlm: String,
-    pub funding: String,
-    pub impact_pxs: Option<Vec<String>>,
-    pub mark_px: String,
-    pub mid_px: Option<String>,
-    pub open_interest: String,
-    pub oracle_px: String,
-    pub premium: Option<String>,
-    pub prev_day_px: String,
+    pub day_ntl_vlm: Decimal,
+    pub funding: Decimal,
+    pub impact_pxs: Option<Vec<Decimal>>,
+    pub mark_px: Decimal,
+    pub mid_px: Option<Decimal>,
+    pub open_interest: Decimal,
+    pub oracle_px: Decimal,
+    pub premium: Option<Decimal>,
+    pub prev_day_px: Decimal,
 }
 
 #[derive(Deserialize, Debug, Clone)]
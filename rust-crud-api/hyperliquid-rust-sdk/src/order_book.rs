@@ -0,0 +1,518 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures_util::StreamExt;
+use log::warn;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::{task::JoinHandle, time};
+
+use crate::{
+    errors::Error, info::info_client::InfoClient, prelude::*, BookLevel, L2BookData,
+    L2SnapshotResponse, OverflowPolicy, StreamConfig, Subscription, SubscriptionEvent,
+    TypedSubscription, WsConfig,
+};
+
+const BPS: Decimal = dec!(10000);
+
+/// Size resting on each side within a distance of the mid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookDepth {
+    pub bid_sz: Decimal,
+    pub ask_sz: Decimal,
+}
+
+/// What filling a market order of a given size against the book would cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketImpact {
+    /// Average fill price.
+    pub vwap: Decimal,
+    /// Price of the last level the order reaches.
+    pub worst_px: Decimal,
+    /// How far `vwap` is from the mid, against the taker, in bps.
+    pub impact_bps: Decimal,
+    /// Quote paid on top of (buy) or missed out on (sell) filling at the mid.
+    pub cost: Decimal,
+}
+
+/// L2 book of one coin, built from an `l2_snapshot` or `L2Book` message.
+///
+/// Hyperliquid sends the whole top of book with every update, so an update
+/// replaces both sides instead of patching levels.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub coin: String,
+    /// Best (highest) first.
+    pub bids: Vec<BookLevel>,
+    /// Best (lowest) first.
+    pub asks: Vec<BookLevel>,
+    /// Exchange time of the book, in milliseconds.
+    pub time: u64,
+    received_at: Instant,
+}
+
+impl OrderBook {
+    pub fn from_l2_book(data: L2BookData) -> Result<Self> {
+        let (bids, asks) = split_levels(&data.coin, data.levels)?;
+        Ok(Self {
+            coin: data.coin,
+            bids,
+            asks,
+            time: data.time,
+            received_at: Instant::now(),
+        })
+    }
+
+    pub fn from_snapshot(snapshot: L2SnapshotResponse) -> Result<Self> {
+        let levels = snapshot
+            .levels
+            .into_iter()
+            .map(|side| {
+                side.into_iter()
+                    .map(|level| BookLevel {
+                        px: level.px,
+                        sz: level.sz,
+                        n: level.n,
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::from_l2_book(L2BookData {
+            coin: snapshot.coin,
+            time: snapshot.time,
+            levels,
+        })
+    }
+
+    /// Replaces the book with `data`. Returns `false` for an update older than
+    /// the book, which is ignored, and an error for an inconsistent one, which
+    /// leaves the book unchanged.
+    pub fn apply(&mut self, data: L2BookData) -> Result<bool> {
+        if data.coin != self.coin {
+            return Err(Error::InconsistentBook(format!(
+                "update for {} applied to the {} book",
+                data.coin, self.coin
+            )));
+        }
+        if data.time < self.time {
+            return Ok(false);
+        }
+        *self = Self::from_l2_book(data)?;
+        Ok(true)
+    }
+
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks.first()
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_bid()?.px + self.best_ask()?.px) / dec!(2))
+    }
+
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        let mid = self.mid()?;
+        Some((self.best_ask()?.px - self.best_bid()?.px) / mid * BPS)
+    }
+
+    /// Size resting within `bps` of the mid on each side.
+    pub fn depth_within_bps(&self, bps: Decimal) -> Option<BookDepth> {
+        let mid = self.mid()?;
+        let distance = mid * bps / BPS;
+        let size_within = |levels: &[BookLevel], within: &dyn Fn(Decimal) -> bool| {
+            levels
+                .iter()
+                .take_while(|level| within(level.px))
+                .map(|level| level.sz)
+                .sum()
+        };
+        Some(BookDepth {
+            bid_sz: size_within(&self.bids, &|px| px >= mid - distance),
+            ask_sz: size_within(&self.asks, &|px| px <= mid + distance),
+        })
+    }
+
+    /// Average price of a market order for `sz`, or `None` if the book is
+    /// too thin to fill it.
+    pub fn vwap(&self, is_buy: bool, sz: Decimal) -> Option<Decimal> {
+        self.walk(is_buy, sz).map(|(notional, _)| notional / sz)
+    }
+
+    /// `(bid - ask) / (bid + ask)` of the size in the top `levels` levels, from
+    /// -1 (only asks) to 1 (only bids).
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid: Decimal = self.bids.iter().take(levels).map(|level| level.sz).sum();
+        let ask: Decimal = self.asks.iter().take(levels).map(|level| level.sz).sum();
+        let total = bid + ask;
+        (!total.is_zero()).then(|| (bid - ask) / total)
+    }
+
+    /// Simulates a market order for `sz` against the book.
+    pub fn market_impact(&self, is_buy: bool, sz: Decimal) -> Option<MarketImpact> {
+        let mid = self.mid()?;
+        let (notional, worst_px) = self.walk(is_buy, sz)?;
+        let cost = if is_buy {
+            notional - mid * sz
+        } else {
+            mid * sz - notional
+        };
+        Some(MarketImpact {
+            vwap: notional / sz,
+            worst_px,
+            impact_bps: cost / (mid * sz) * BPS,
+            cost,
+        })
+    }
+
+    /// Time since the book was received.
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+
+    /// Returns the notional and last price of filling `sz` from the best level on.
+    fn walk(&self, is_buy: bool, sz: Decimal) -> Option<(Decimal, Decimal)> {
+        if sz <= Decimal::ZERO {
+            return None;
+        }
+        let levels = if is_buy { &self.asks } else { &self.bids };
+        let mut remaining = sz;
+        let mut notional = Decimal::ZERO;
+        for level in levels {
+            let filled = remaining.min(level.sz);
+            notional += filled * level.px;
+            remaining -= filled;
+            if remaining.is_zero() {
+                return Some((notional, level.px));
+            }
+        }
+        None
+    }
+}
+
+/// Splits `[bids, asks]` and checks that both sides are sorted best first,
+/// have positive prices and sizes, and don't cross.
+fn split_levels(
+    coin: &str,
+    levels: Vec<Vec<BookLevel>>,
+) -> Result<(Vec<BookLevel>, Vec<BookLevel>)> {
+    let inconsistent = |reason: String| Error::InconsistentBook(format!("{coin}: {reason}"));
+    let Ok([bids, asks]) = <[Vec<BookLevel>; 2]>::try_from(levels) else {
+        return Err(inconsistent("expected bid and ask sides".to_string()));
+    };
+
+    for (side, levels, better) in [
+        ("bid", &bids, Decimal::gt as fn(&Decimal, &Decimal) -> bool),
+        ("ask", &asks, Decimal::lt),
+    ] {
+        if let Some(level) = levels
+            .iter()
+            .find(|level| level.px <= Decimal::ZERO || level.sz <= Decimal::ZERO)
+        {
+            return Err(inconsistent(format!(
+                "{side} level {} x {} is not positive",
+                level.px, level.sz
+            )));
+        }
+        if let Some(pair) = levels
+            .windows(2)
+            .find(|pair| !better(&pair[0].px, &pair[1].px))
+        {
+            return Err(inconsistent(format!(
+                "{side} levels out of order at {} then {}",
+                pair[0].px, pair[1].px
+            )));
+        }
+    }
+    if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+        if bid.px >= ask.px {
+            return Err(inconsistent(format!(
+                "crossed book, bid {} >= ask {}",
+                bid.px, ask.px
+            )));
+        }
+    }
+    Ok((bids, asks))
+}
+
+/// Settings for [`OrderBooks`].
+#[derive(Debug, Clone)]
+pub struct OrderBookConfig {
+    /// A book without updates for this long is reloaded from a snapshot.
+    pub max_age: Duration,
+}
+
+impl Default for OrderBookConfig {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Local books for a set of coins, kept current from `L2Book` subscriptions.
+///
+/// Each book is loaded from `l2_snapshot` when added, and reloaded when an
+/// update is inconsistent, when it goes stale and after the websocket
+/// reconnects. Dropping this stops the updates and ends the subscriptions.
+#[derive(Debug)]
+pub struct OrderBooks {
+    books: Arc<RwLock<HashMap<String, OrderBook>>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl OrderBooks {
+    pub async fn subscribe(
+        info_client: &mut InfoClient,
+        coins: impl IntoIterator<Item = impl Into<String>>,
+        config: OrderBookConfig,
+    ) -> Result<Self> {
+        let books = Arc::new(RwLock::new(HashMap::new()));
+        // Snapshots go through a client of its own so the tasks don't borrow `info_client`
+        let snapshots = Arc::new(InfoClient::from_http_client(
+            info_client.http_client.clone(),
+            None,
+            WsConfig::default(),
+            Some(info_client.asset_registry().clone()),
+        ));
+
+        // Built up front so that an error below drops it and aborts the tasks already spawned
+        let mut this = Self {
+            books: books.clone(),
+            tasks: Vec::new(),
+        };
+        for coin in coins {
+            let coin = coin.into();
+            // Every update is a full book, so only the newest one matters
+            let subscription = info_client
+                .subscribe_typed::<L2BookData>(
+                    Subscription::L2Book { coin: coin.clone() },
+                    StreamConfig {
+                        capacity: 1,
+                        overflow: OverflowPolicy::KeepLatest,
+                    },
+                )
+                .await?;
+            let tracker = BookTracker {
+                coin,
+                books: books.clone(),
+                snapshots: snapshots.clone(),
+                max_age: config.max_age,
+            };
+            tracker.resync().await?;
+            this.tasks.push(tokio::spawn(tracker.run(subscription)));
+        }
+
+        Ok(this)
+    }
+
+    /// Copy of the current book of `coin`.
+    pub fn book(&self, coin: &str) -> Option<OrderBook> {
+        self.books
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(coin)
+            .cloned()
+    }
+}
+
+impl Drop for OrderBooks {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+struct BookTracker {
+    coin: String,
+    books: Arc<RwLock<HashMap<String, OrderBook>>>,
+    snapshots: Arc<InfoClient>,
+    max_age: Duration,
+}
+
+impl BookTracker {
+    async fn run(self, mut subscription: TypedSubscription<L2BookData>) {
+        let mut staleness_check = time::interval((self.max_age / 2).max(Duration::from_millis(1)));
+        loop {
+            tokio::select! {
+                update = subscription.data.next() => {
+                    let Some(update) = update else {
+                        warn!("{} book subscription ended", self.coin);
+                        break;
+                    };
+                    if let Err(err) = self.apply(update) {
+                        warn!("Reloading {} book: {err}", self.coin);
+                        self.resync_or_warn().await;
+                    }
+                }
+                Some(event) = subscription.events.recv() => {
+                    // Updates were missed while disconnected
+                    if let SubscriptionEvent::Reconnected(_) = event {
+                        self.resync_or_warn().await;
+                    }
+                }
+                _ = staleness_check.tick() => {
+                    if self.is_stale() {
+                        warn!("{} book is stale, reloading", self.coin);
+                        self.resync_or_warn().await;
+                    }
+                }
+            }
+        }
+        self.write().remove(&self.coin);
+    }
+
+    fn apply(&self, update: L2BookData) -> Result<()> {
+        let mut books = self.write();
+        match books.get_mut(&self.coin) {
+            Some(book) => book.apply(update).map(|_| ()),
+            None => {
+                books.insert(self.coin.clone(), OrderBook::from_l2_book(update)?);
+                Ok(())
+            }
+        }
+    }
+
+    async fn resync(&self) -> Result<()> {
+        let snapshot = self.snapshots.l2_snapshot(self.coin.clone()).await?;
+        let book = OrderBook::from_snapshot(snapshot)?;
+        let mut books = self.write();
+        // A stream update may have landed while the snapshot was in flight
+        if books
+            .get(&self.coin)
+            .is_none_or(|current| current.time <= book.time || current.age() > self.max_age)
+        {
+            books.insert(self.coin.clone(), book);
+        }
+        Ok(())
+    }
+
+    async fn resync_or_warn(&self) {
+        if let Err(err) = self.resync().await {
+            warn!("Could not reload {} book: {err}", self.coin);
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.books
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&self.coin)
+            .is_none_or(|book| book.age() > self.max_age)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, OrderBook>> {
+        self.books.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(px: Decimal, sz: Decimal) -> BookLevel {
+        BookLevel { px, sz, n: 1 }
+    }
+
+    fn book() -> OrderBook {
+        OrderBook::from_l2_book(L2BookData {
+            coin: "ETH".to_string(),
+            time: 100,
+            levels: vec![
+                vec![
+                    level(dec!(1999), dec!(1)),
+                    level(dec!(1998), dec!(2)),
+                    level(dec!(1990), dec!(5)),
+                ],
+                vec![
+                    level(dec!(2001), dec!(1)),
+                    level(dec!(2002), dec!(1)),
+                    level(dec!(2010), dec!(4)),
+                ],
+            ],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_top_of_book_and_depth() {
+        let book = book();
+        assert_eq!(book.best_bid().unwrap().px, dec!(1999));
+        assert_eq!(book.best_ask().unwrap().px, dec!(2001));
+        assert_eq!(book.mid(), Some(dec!(2000)));
+        assert_eq!(book.spread_bps(), Some(dec!(10)));
+        // 10 bps of 2000 is 2, so 1998..=2002
+        assert_eq!(
+            book.depth_within_bps(dec!(10)),
+            Some(BookDepth {
+                bid_sz: dec!(3),
+                ask_sz: dec!(2)
+            })
+        );
+        // Top two levels: 3 bid against 2 ask
+        assert_eq!(book.imbalance(2), Some(dec!(0.2)));
+    }
+
+    #[test]
+    fn test_vwap_and_market_impact() {
+        let book = book();
+        assert_eq!(book.vwap(true, dec!(1)), Some(dec!(2001)));
+        assert_eq!(book.vwap(true, dec!(2)), Some(dec!(2001.5)));
+        assert_eq!(book.vwap(true, dec!(100)), None);
+
+        // Sell 3: 1 @ 1999 and 2 @ 1998
+        let impact = book.market_impact(false, dec!(3)).unwrap();
+        assert_eq!(impact.worst_px, dec!(1998));
+        assert_eq!(impact.cost, dec!(5));
+        assert_eq!(impact.vwap.round_dp(4), dec!(1998.3333));
+        assert_eq!(impact.impact_bps.round_dp(4), dec!(8.3333));
+    }
+
+    #[test]
+    fn test_apply_ignores_older_and_rejects_inconsistent_updates() -> Result<()> {
+        let mut book = book();
+        let update = |time, bid, ask| L2BookData {
+            coin: "ETH".to_string(),
+            time,
+            levels: vec![vec![level(bid, dec!(1))], vec![level(ask, dec!(1))]],
+        };
+
+        assert!(!book.apply(update(99, dec!(1990), dec!(1995)))?);
+        assert_eq!(book.best_bid().unwrap().px, dec!(1999));
+        assert!(book.apply(update(101, dec!(1990), dec!(1995)))?);
+        assert_eq!(book.mid(), Some(dec!(1992.5)));
+
+        assert!(matches!(
+            book.apply(update(102, dec!(2000), dec!(1995))),
+            Err(Error::InconsistentBook(_))
+        ));
+        let mut unsorted = update(102, dec!(1990), dec!(1995));
+        unsorted.levels[0].push(level(dec!(1991), dec!(1)));
+        assert!(book.apply(unsorted).is_err());
+        // Rejected updates leave the book as it was
+        assert_eq!((book.time, book.mid()), (101, Some(dec!(1992.5))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_snapshot() -> Result<()> {
+        let snapshot: L2SnapshotResponse = serde_json::from_value(serde_json::json!({
+            "coin": "ETH", "time": 5,
+            "levels": [
+                [{"px": "1999", "sz": "1", "n": 2}],
+                [{"px": "2001", "sz": "3", "n": 1}]
+            ]
+        }))
+        .unwrap();
+        let book = OrderBook::from_snapshot(snapshot)?;
+        assert_eq!((book.time, book.mid()), (5, Some(dec!(2000))));
+        assert_eq!(book.best_ask().unwrap().sz, dec!(3));
+        Ok(())
+    }
+}
@@ -0,0 +1,829 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::Address;
+use futures_util::StreamExt;
+use log::{debug, warn};
+use rust_decimal::Decimal;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{
+    helpers::{uuid_to_hex_string, RecentIds},
+    info::info_client::InfoClient,
+    prelude::*,
+    ClientOrderRequest, ExchangeDataStatus, ExchangeResponseStatus, OpenOrdersResponse, OrderInfo,
+    OrderUpdate, StreamConfig, Subscription, SubscriptionEvent, TradeInfo, TypedSubscription,
+    UserFillsData, WsConfig,
+};
+
+/// Where an order is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderState {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    /// A trigger order whose trigger price was hit; it now works like any other order.
+    Triggered,
+}
+
+impl OrderState {
+    /// Whether the order can no longer change.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected
+        )
+    }
+
+    /// Maps an order status from `orderUpdates` or `orderStatus`, e.g. `open`,
+    /// `filled`, `marginCanceled` or `tickRejected`.
+    fn from_status(status: &str) -> Option<Self> {
+        match status {
+            "open" => Some(OrderState::Open),
+            "filled" => Some(OrderState::Filled),
+            "triggered" => Some(OrderState::Triggered),
+            "scheduledCancel" => Some(OrderState::Cancelled),
+            status if status.ends_with("anceled") => Some(OrderState::Cancelled),
+            status if status.ends_with("ejected") => Some(OrderState::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// An order as last seen through placement responses, order updates and fills.
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    /// `None` only for orders rejected when placed.
+    pub oid: Option<u64>,
+    /// Hex string as sent on the wire, e.g. `0x1234...`.
+    pub cloid: Option<String>,
+    pub coin: String,
+    pub is_buy: bool,
+    pub limit_px: Decimal,
+    pub orig_sz: Decimal,
+    pub state: OrderState,
+    /// Raw status behind `state`, e.g. `reduceOnlyCanceled`, or the placement error.
+    pub status: String,
+    fill_sz: Decimal,
+    fill_notional: Decimal,
+    reported_filled_sz: Decimal,
+    reported_avg_px: Option<Decimal>,
+}
+
+impl TrackedOrder {
+    /// Size filled so far, from fills or, if further along, from order updates.
+    pub fn filled_sz(&self) -> Decimal {
+        self.fill_sz.max(self.reported_filled_sz)
+    }
+
+    /// Size still working on the book; zero once the order is done.
+    pub fn remaining_sz(&self) -> Decimal {
+        if self.state.is_terminal() {
+            Decimal::ZERO
+        } else {
+            (self.orig_sz - self.filled_sz()).max(Decimal::ZERO)
+        }
+    }
+
+    pub fn avg_fill_px(&self) -> Option<Decimal> {
+        if self.fill_sz.is_zero() {
+            self.reported_avg_px
+        } else {
+            Some(self.fill_notional / self.fill_sz)
+        }
+    }
+}
+
+/// A change in an order's state or filled size.
+#[derive(Debug, Clone)]
+pub struct OrderEvent {
+    pub order: TrackedOrder,
+    /// `None` for an order seen for the first time.
+    pub previous: Option<OrderState>,
+    /// Size filled since the previous event.
+    pub filled: Decimal,
+}
+
+/// Fields shared by `orderUpdates`, `orderStatus` and `openOrders`.
+struct OrderFields<'a> {
+    coin: &'a str,
+    side: &'a str,
+    limit_px: Decimal,
+    sz: Decimal,
+    orig_sz: Decimal,
+    oid: u64,
+    cloid: Option<&'a String>,
+}
+
+/// How many filled, cancelled or rejected orders are kept before the oldest
+/// is forgotten.
+const FINISHED_ORDERS: usize = 1_000;
+/// How many orders fills are held for before anything else about them
+/// arrives, e.g. fills of orders placed elsewhere that never show up.
+const PENDING_FILL_ORDERS: usize = 1_000;
+
+/// Order states by oid and cloid. Does no I/O, so [`OrderManager`] feeds it
+/// from the exchange and the websocket.
+///
+/// Only the latest 1,000 finished orders are kept, so a
+/// long-running tracker doesn't grow without bound.
+#[derive(Debug)]
+pub struct OrderTracker {
+    orders: HashMap<u64, TrackedOrder>,
+    by_cloid: HashMap<String, u64>,
+    seen_fills: RecentIds<u64>,
+    /// Oids of finished orders, oldest first.
+    finished: RecentIds<u64>,
+    /// Fills that arrived before anything else about their order.
+    pending_fills: HashMap<u64, Vec<TradeInfo>>,
+    pending_oids: RecentIds<u64>,
+}
+
+impl Default for OrderTracker {
+    fn default() -> Self {
+        Self {
+            orders: HashMap::new(),
+            by_cloid: HashMap::new(),
+            seen_fills: RecentIds::default(),
+            finished: RecentIds::new(FINISHED_ORDERS),
+            pending_fills: HashMap::new(),
+            pending_oids: RecentIds::new(PENDING_FILL_ORDERS),
+        }
+    }
+}
+
+impl OrderTracker {
+    pub fn order(&self, oid: u64) -> Option<&TrackedOrder> {
+        self.orders.get(&oid)
+    }
+
+    pub fn order_by_cloid(&self, cloid: Uuid) -> Option<&TrackedOrder> {
+        self.orders
+            .get(self.by_cloid.get(&uuid_to_hex_string(cloid))?)
+    }
+
+    /// Orders that are not filled, cancelled or rejected.
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders
+            .values()
+            .filter(|order| !order.state.is_terminal())
+    }
+
+    /// Records an order from the status `bulk_order` returned for it.
+    pub fn on_placed(
+        &mut self,
+        request: &ClientOrderRequest,
+        status: &ExchangeDataStatus,
+    ) -> Vec<OrderEvent> {
+        let order = |oid, state, status: &str| TrackedOrder {
+            oid,
+            cloid: request.cloid.map(uuid_to_hex_string),
+            coin: request.asset.clone(),
+            is_buy: request.is_buy,
+            limit_px: request.limit_px,
+            orig_sz: request.sz,
+            state,
+            status: status.to_string(),
+            fill_sz: Decimal::ZERO,
+            fill_notional: Decimal::ZERO,
+            reported_filled_sz: Decimal::ZERO,
+            reported_avg_px: None,
+        };
+        match status {
+            ExchangeDataStatus::Resting(resting) => {
+                self.upsert(order(Some(resting.oid), OrderState::Open, "open"))
+            }
+            ExchangeDataStatus::Filled(filled) => {
+                let mut filled_order = order(Some(filled.oid), OrderState::Filled, "filled");
+                filled_order.reported_filled_sz = filled.total_sz.parse().unwrap_or(request.sz);
+                filled_order.reported_avg_px = filled.avg_px.parse().ok();
+                // An IOC order that filled in part has its remainder cancelled
+                if filled_order.reported_filled_sz < request.sz {
+                    filled_order.state = OrderState::Cancelled;
+                }
+                self.upsert(filled_order)
+            }
+            ExchangeDataStatus::Error(err) => vec![OrderEvent {
+                order: order(None, OrderState::Rejected, err),
+                previous: None,
+                filled: Decimal::ZERO,
+            }],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn on_order_update(&mut self, update: &OrderUpdate) -> Vec<OrderEvent> {
+        let order = &update.order;
+        self.on_status(
+            OrderFields {
+                coin: &order.coin,
+                side: &order.side,
+                limit_px: order.limit_px,
+                sz: order.sz,
+                orig_sz: order.orig_sz,
+                oid: order.oid,
+                cloid: order.cloid.as_ref(),
+            },
+            &update.status,
+        )
+    }
+
+    /// Applies a status from `query_order_by_oid`.
+    pub fn on_order_info(&mut self, info: &OrderInfo) -> Vec<OrderEvent> {
+        let order = &info.order;
+        self.on_status(
+            OrderFields {
+                coin: &order.coin,
+                side: &order.side,
+                limit_px: order.limit_px,
+                sz: order.sz,
+                orig_sz: order.orig_sz,
+                oid: order.oid,
+                cloid: order.cloid.as_ref(),
+            },
+            &info.status,
+        )
+    }
+
+    pub fn on_fill(&mut self, fill: &TradeInfo) -> Vec<OrderEvent> {
+        if !self.seen_fills.insert(fill.tid) {
+            return Vec::new();
+        }
+        self.apply_fill(fill)
+    }
+
+    fn apply_fill(&mut self, fill: &TradeInfo) -> Vec<OrderEvent> {
+        let Some(order) = self.orders.get(&fill.oid) else {
+            if let (_, Some(evicted)) = self.pending_oids.insert_evicting(fill.oid) {
+                self.pending_fills.remove(&evicted);
+            }
+            self.pending_fills
+                .entry(fill.oid)
+                .or_default()
+                .push(fill.clone());
+            return Vec::new();
+        };
+
+        let mut updated = order.clone();
+        updated.fill_sz += fill.sz;
+        updated.fill_notional += fill.sz * fill.px;
+        if !updated.state.is_terminal() {
+            updated.state = if updated.filled_sz() >= updated.orig_sz {
+                OrderState::Filled
+            } else {
+                OrderState::PartiallyFilled
+            };
+        }
+        self.upsert(updated)
+    }
+
+    /// Compares tracked orders with the exchange's open orders. Returns the
+    /// resulting events and the oids of orders that are no longer open, whose
+    /// final status has to be queried.
+    pub fn reconcile_open_orders(
+        &mut self,
+        open_orders: &[OpenOrdersResponse],
+    ) -> (Vec<OrderEvent>, Vec<u64>) {
+        let mut events = Vec::new();
+        let open: HashSet<u64> = open_orders.iter().map(|order| order.oid).collect();
+        for order in open_orders {
+            let orig_sz = self
+                .orders
+                .get(&order.oid)
+                .map_or(order.sz, |tracked| tracked.orig_sz);
+            events.extend(self.on_status(
+                OrderFields {
+                    coin: &order.coin,
+                    side: &order.side,
+                    limit_px: order.limit_px,
+                    sz: order.sz,
+                    orig_sz,
+                    oid: order.oid,
+                    cloid: order.cloid.as_ref(),
+                },
+                "open",
+            ));
+        }
+        let closed = self
+            .open_orders()
+            .filter_map(|order| order.oid)
+            .filter(|oid| !open.contains(oid))
+            .collect();
+        (events, closed)
+    }
+
+    fn on_status(&mut self, fields: OrderFields<'_>, status: &str) -> Vec<OrderEvent> {
+        let Some(state) = OrderState::from_status(status) else {
+            warn!("Unknown status {status} for order {}", fields.oid);
+            return Vec::new();
+        };
+        let mut order = self
+            .orders
+            .get(&fields.oid)
+            .cloned()
+            .unwrap_or_else(|| TrackedOrder {
+                oid: Some(fields.oid),
+                cloid: fields.cloid.cloned(),
+                coin: fields.coin.to_string(),
+                is_buy: fields.side == "B",
+                limit_px: fields.limit_px,
+                orig_sz: fields.orig_sz,
+                state,
+                status: status.to_string(),
+                fill_sz: Decimal::ZERO,
+                fill_notional: Decimal::ZERO,
+                reported_filled_sz: Decimal::ZERO,
+                reported_avg_px: None,
+            });
+        // Updates can arrive out of order; a finished order stays finished
+        if order.state.is_terminal() && !state.is_terminal() {
+            return Vec::new();
+        }
+
+        order.limit_px = fields.limit_px;
+        order.reported_filled_sz = order
+            .reported_filled_sz
+            .max(order.orig_sz - fields.sz)
+            .max(Decimal::ZERO);
+        if state == OrderState::Filled {
+            order.reported_filled_sz = order.orig_sz;
+        }
+        order.state = match state {
+            OrderState::Open if !order.filled_sz().is_zero() => OrderState::PartiallyFilled,
+            state => state,
+        };
+        order.status = status.to_string();
+        self.upsert(order)
+    }
+
+    /// Stores `order` and returns an event if its state or filled size changed.
+    fn upsert(&mut self, mut order: TrackedOrder) -> Vec<OrderEvent> {
+        let Some(oid) = order.oid else {
+            return Vec::new();
+        };
+        let previous = self.orders.get(&oid);
+        let (previous_state, previous_filled) =
+            previous.map_or((None, Decimal::ZERO), |p| (Some(p.state), p.filled_sz()));
+        if let Some(previous) = previous {
+            // Placement responses don't know about fills that came first
+            order.fill_sz = order.fill_sz.max(previous.fill_sz);
+            order.fill_notional = order.fill_notional.max(previous.fill_notional);
+            order.reported_filled_sz = order.reported_filled_sz.max(previous.reported_filled_sz);
+            order.reported_avg_px = order.reported_avg_px.or(previous.reported_avg_px);
+        }
+        if let Some(cloid) = &order.cloid {
+            self.by_cloid.insert(cloid.clone(), oid);
+        }
+
+        let filled = order.filled_sz() - previous_filled;
+        let changed = previous_state != Some(order.state) || !filled.is_zero();
+        if order.state.is_terminal() {
+            if let (_, Some(evicted)) = self.finished.insert_evicting(oid) {
+                self.forget(evicted);
+            }
+        }
+        self.orders.insert(oid, order.clone());
+        let mut events = Vec::new();
+        if changed {
+            events.push(OrderEvent {
+                order,
+                previous: previous_state,
+                filled,
+            });
+        }
+
+        for fill in self.pending_fills.remove(&oid).unwrap_or_default() {
+            events.extend(self.apply_fill(&fill));
+        }
+        events
+    }
+
+    fn forget(&mut self, oid: u64) {
+        if let Some(cloid) = self.orders.remove(&oid).and_then(|order| order.cloid) {
+            if self.by_cloid.get(&cloid) == Some(&oid) {
+                self.by_cloid.remove(&cloid);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    user: Address,
+    tracker: Mutex<OrderTracker>,
+    events: UnboundedSender<OrderEvent>,
+    info_client: InfoClient,
+}
+
+impl Inner {
+    fn update(&self, apply: impl FnOnce(&mut OrderTracker) -> Vec<OrderEvent>) {
+        let events = apply(&mut self.tracker.lock().unwrap_or_else(|e| e.into_inner()));
+        for event in events {
+            debug!(
+                "Order {:?} {:?} -> {:?}",
+                event.order.oid, event.previous, event.order.state
+            );
+            // Nobody listening for events is fine
+            let _ = self.events.send(event);
+        }
+    }
+
+    async fn reconcile(&self) -> Result<()> {
+        let open_orders = self.info_client.open_orders(self.user).await?;
+        let mut closed = Vec::new();
+        self.update(|tracker| {
+            let (events, no_longer_open) = tracker.reconcile_open_orders(&open_orders);
+            closed = no_longer_open;
+            events
+        });
+        for oid in closed {
+            let status = self.info_client.query_order_by_oid(self.user, oid).await?;
+            if let Some(info) = status.order {
+                self.update(|tracker| tracker.on_order_info(&info));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tracks a user's orders through their life from `OrderUpdates` and
+/// `UserFills` subscriptions, emitting an [`OrderEvent`] whenever one changes.
+///
+/// Register orders placed through [`ExchangeClient`](crate::ExchangeClient)
+/// with [`OrderManager::track`] so rejections and immediate fills are seen
+/// too. After the websocket reconnects, or when updates were dropped because
+/// the manager fell behind, orders are reconciled with `open_orders` and the
+/// final status of orders that closed meanwhile is queried.
+#[derive(Debug)]
+pub struct OrderManager {
+    inner: Arc<Inner>,
+    task: JoinHandle<()>,
+}
+
+impl OrderManager {
+    pub async fn start(
+        info_client: &mut InfoClient,
+        user: Address,
+    ) -> Result<(Self, UnboundedReceiver<OrderEvent>)> {
+        let order_updates = info_client
+            .subscribe_typed::<Vec<OrderUpdate>>(
+                Subscription::OrderUpdates { user },
+                StreamConfig::default(),
+            )
+            .await?;
+        let fills = info_client
+            .subscribe_typed::<UserFillsData>(
+                Subscription::UserFills { user },
+                StreamConfig::default(),
+            )
+            .await?;
+
+        let (events, receiver) = unbounded_channel();
+        let inner = Arc::new(Inner {
+            user,
+            tracker: Mutex::default(),
+            events,
+            // Queries go through a client of its own so the task doesn't borrow `info_client`
+            info_client: InfoClient::from_http_client(
+                info_client.http_client.clone(),
+                None,
+                WsConfig::default(),
+                Some(info_client.asset_registry().clone()),
+            ),
+        });
+        inner.reconcile().await?;
+        let task = tokio::spawn(Self::run(inner.clone(), order_updates, fills));
+        Ok((Self { inner, task }, receiver))
+    }
+
+    /// Records an order from its placement status.
+    pub fn track(&self, request: &ClientOrderRequest, status: &ExchangeDataStatus) {
+        self.inner
+            .update(|tracker| tracker.on_placed(request, status));
+    }
+
+    /// Records the orders of a `bulk_order` call from its response, matching
+    /// statuses to requests by position.
+    pub fn track_bulk(&self, requests: &[ClientOrderRequest], response: &ExchangeResponseStatus) {
+        let ExchangeResponseStatus::Ok(response) = response else {
+            return;
+        };
+        let Some(data) = &response.data else {
+            return;
+        };
+        for (request, status) in requests.iter().zip(&data.statuses) {
+            self.track(request, status);
+        }
+    }
+
+    pub fn order(&self, oid: u64) -> Option<TrackedOrder> {
+        self.tracker().order(oid).cloned()
+    }
+
+    pub fn order_by_cloid(&self, cloid: Uuid) -> Option<TrackedOrder> {
+        self.tracker().order_by_cloid(cloid).cloned()
+    }
+
+    pub fn open_orders(&self) -> Vec<TrackedOrder> {
+        self.tracker().open_orders().cloned().collect()
+    }
+
+    /// Brings tracked orders in line with the exchange.
+    pub async fn reconcile(&self) -> Result<()> {
+        self.inner.reconcile().await
+    }
+
+    fn tracker(&self) -> std::sync::MutexGuard<'_, OrderTracker> {
+        self.inner.tracker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn run(
+        inner: Arc<Inner>,
+        mut order_updates: TypedSubscription<Vec<OrderUpdate>>,
+        mut fills: TypedSubscription<UserFillsData>,
+    ) {
+        let mut lagged = 0;
+        loop {
+            tokio::select! {
+                Some(updates) = order_updates.data.next() => {
+                    inner.update(|tracker| {
+                        updates
+                            .iter()
+                            .flat_map(|update| tracker.on_order_update(update))
+                            .collect()
+                    });
+                }
+                Some(fills) = fills.data.next() => {
+                    // The first message repeats recent history
+                    if fills.is_snapshot != Some(true) {
+                        inner.update(|tracker| {
+                            fills.fills.iter().flat_map(|fill| tracker.on_fill(fill)).collect()
+                        });
+                    }
+                }
+                Some(event) = order_updates.events.recv() => {
+                    // Connection events reach both subscriptions, so only this one reacts
+                    if let SubscriptionEvent::Reconnected(_) = event {
+                        if let Err(err) = inner.reconcile().await {
+                            warn!("Could not reconcile orders after reconnecting: {err}");
+                        }
+                    }
+                }
+                Some(_) = fills.events.recv() => {}
+                else => break,
+            }
+            // Dropped updates can't be replayed, so catch up from the exchange instead
+            let dropped = order_updates.data.lagged() + fills.data.lagged();
+            if dropped > lagged {
+                lagged = dropped;
+                warn!("Order manager fell behind its subscriptions, reconciling");
+                if let Err(err) = inner.reconcile().await {
+                    warn!("Could not reconcile orders after falling behind: {err}");
+                }
+            }
+        }
+        warn!("Order manager subscriptions ended");
+    }
+}
+
+impl Drop for OrderManager {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{BasicOrder, ClientLimit, ClientOrder, FilledOrder, RestingOrder};
+
+    fn request(cloid: Option<Uuid>) -> ClientOrderRequest {
+        ClientOrderRequest {
+            asset: "ETH".to_string(),
+            is_buy: true,
+            reduce_only: false,
+            limit_px: dec!(2000),
+            sz: dec!(3),
+            cloid,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Gtc".to_string(),
+            }),
+        }
+    }
+
+    fn fill(tid: u64, oid: u64, sz: Decimal, px: Decimal) -> TradeInfo {
+        serde_json::from_value(serde_json::json!({
+            "coin": "ETH", "side": "B", "px": px, "sz": sz, "time": 1, "hash": "0x0",
+            "startPosition": "0", "dir": "Open Long", "closedPnl": "0", "oid": oid,
+            "cloid": null, "crossed": false, "fee": "0", "feeToken": "USDC", "tid": tid
+        }))
+        .unwrap()
+    }
+
+    fn update(oid: u64, status: &str, sz: Decimal) -> OrderUpdate {
+        OrderUpdate {
+            order: BasicOrder {
+                coin: "ETH".to_string(),
+                side: "B".to_string(),
+                limit_px: dec!(2000),
+                sz,
+                oid,
+                timestamp: 1,
+                orig_sz: dec!(3),
+                cloid: None,
+            },
+            status: status.to_string(),
+            status_timestamp: 2,
+        }
+    }
+
+    fn states(events: &[OrderEvent]) -> Vec<(Option<OrderState>, OrderState, Decimal)> {
+        events
+            .iter()
+            .map(|event| (event.previous, event.order.state, event.filled))
+            .collect()
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(OrderState::from_status("open"), Some(OrderState::Open));
+        assert_eq!(
+            OrderState::from_status("reduceOnlyCanceled"),
+            Some(OrderState::Cancelled)
+        );
+        assert_eq!(
+            OrderState::from_status("badAloPxRejected"),
+            Some(OrderState::Rejected)
+        );
+        assert_eq!(OrderState::from_status("unheardOf"), None);
+    }
+
+    #[test]
+    fn test_partial_fills_then_fill() {
+        let cloid = Uuid::new_v4();
+        let mut tracker = OrderTracker::default();
+        let events = tracker.on_placed(
+            &request(Some(cloid)),
+            &ExchangeDataStatus::Resting(RestingOrder { oid: 7 }),
+        );
+        assert_eq!(states(&events), [(None, OrderState::Open, dec!(0))]);
+
+        let events = tracker.on_fill(&fill(1, 7, dec!(1), dec!(2000)));
+        assert_eq!(
+            states(&events),
+            [(Some(OrderState::Open), OrderState::PartiallyFilled, dec!(1))]
+        );
+        // A repeated fill is ignored, and the order update agreeing with it changes nothing
+        assert!(tracker.on_fill(&fill(1, 7, dec!(1), dec!(2000))).is_empty());
+        assert!(tracker
+            .on_order_update(&update(7, "open", dec!(2)))
+            .is_empty());
+
+        let events = tracker.on_fill(&fill(2, 7, dec!(2), dec!(1999)));
+        assert_eq!(
+            states(&events),
+            [(
+                Some(OrderState::PartiallyFilled),
+                OrderState::Filled,
+                dec!(2)
+            )]
+        );
+        let order = tracker.order_by_cloid(cloid).unwrap();
+        assert_eq!(order.remaining_sz(), dec!(0));
+        assert_eq!(order.avg_fill_px().unwrap().round_dp(4), dec!(1999.3333));
+        // The filled update arriving late doesn't emit again
+        assert!(tracker
+            .on_order_update(&update(7, "filled", dec!(0)))
+            .is_empty());
+        assert_eq!(tracker.open_orders().count(), 0);
+    }
+
+    #[test]
+    fn test_fill_before_placement_response_and_cancel() {
+        let mut tracker = OrderTracker::default();
+        assert!(tracker.on_fill(&fill(1, 8, dec!(1), dec!(2000))).is_empty());
+        let events = tracker.on_placed(
+            &request(None),
+            &ExchangeDataStatus::Resting(RestingOrder { oid: 8 }),
+        );
+        assert_eq!(
+            states(&events),
+            [
+                (None, OrderState::Open, dec!(0)),
+                (Some(OrderState::Open), OrderState::PartiallyFilled, dec!(1))
+            ]
+        );
+
+        let events = tracker.on_order_update(&update(8, "canceled", dec!(2)));
+        assert_eq!(
+            states(&events),
+            [(
+                Some(OrderState::PartiallyFilled),
+                OrderState::Cancelled,
+                dec!(0)
+            )]
+        );
+        assert_eq!(tracker.order(8).unwrap().remaining_sz(), dec!(0));
+        // Out of order update for a finished order
+        assert!(tracker
+            .on_order_update(&update(8, "open", dec!(2)))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_placement_rejections_and_immediate_fills() {
+        let mut tracker = OrderTracker::default();
+        let events = tracker.on_placed(
+            &request(None),
+            &ExchangeDataStatus::Error("Order has invalid price.".to_string()),
+        );
+        assert_eq!(states(&events), [(None, OrderState::Rejected, dec!(0))]);
+        assert_eq!(events[0].order.oid, None);
+
+        let events = tracker.on_placed(
+            &request(None),
+            &ExchangeDataStatus::Filled(FilledOrder {
+                total_sz: "3".to_string(),
+                avg_px: "1999.5".to_string(),
+                oid: 9,
+            }),
+        );
+        assert_eq!(states(&events), [(None, OrderState::Filled, dec!(3))]);
+        assert_eq!(tracker.order(9).unwrap().avg_fill_px(), Some(dec!(1999.5)));
+        // Its fills arriving afterwards don't count twice
+        assert!(tracker
+            .on_fill(&fill(5, 9, dec!(3), dec!(1999.5)))
+            .is_empty());
+        assert_eq!(tracker.order(9).unwrap().filled_sz(), dec!(3));
+    }
+
+    #[test]
+    fn test_finished_orders_and_pending_fills_are_bounded() {
+        let mut tracker = OrderTracker::default();
+        let cloid = Uuid::new_v4();
+        tracker.on_placed(
+            &request(Some(cloid)),
+            &ExchangeDataStatus::Resting(RestingOrder { oid: 0 }),
+        );
+        tracker.on_order_update(&update(0, "canceled", dec!(3)));
+        for oid in 1..=FINISHED_ORDERS as u64 {
+            tracker.on_order_update(&update(oid, "filled", dec!(0)));
+        }
+        // The oldest finished order made room for the newest
+        assert!(tracker.order(0).is_none());
+        assert!(tracker.order_by_cloid(cloid).is_none());
+        assert!(tracker.order(1).is_some());
+        assert_eq!(tracker.orders.len(), FINISHED_ORDERS);
+
+        // Open orders are never forgotten
+        tracker.on_order_update(&update(5_000, "open", dec!(3)));
+        for oid in 10_000..10_000 + FINISHED_ORDERS as u64 {
+            tracker.on_order_update(&update(oid, "filled", dec!(0)));
+        }
+        assert_eq!(tracker.open_orders().count(), 1);
+
+        for oid in 0..=PENDING_FILL_ORDERS as u64 {
+            tracker.on_fill(&fill(100_000 + oid, 20_000 + oid, dec!(1), dec!(2000)));
+        }
+        assert_eq!(tracker.pending_fills.len(), PENDING_FILL_ORDERS);
+        assert!(!tracker.pending_fills.contains_key(&20_000));
+    }
+
+    #[test]
+    fn test_reconcile_open_orders() {
+        let mut tracker = OrderTracker::default();
+        for oid in [1, 2] {
+            tracker.on_placed(
+                &request(None),
+                &ExchangeDataStatus::Resting(RestingOrder { oid }),
+            );
+        }
+        let open_orders: Vec<OpenOrdersResponse> = serde_json::from_value(serde_json::json!([
+            {"coin": "ETH", "limitPx": "2000", "oid": 1, "side": "B", "sz": "1", "timestamp": 1, "cloid": null},
+            {"coin": "BTC", "limitPx": "60000", "oid": 3, "side": "A", "sz": "0.1", "timestamp": 1, "cloid": null}
+        ]))
+        .unwrap();
+
+        let (events, closed) = tracker.reconcile_open_orders(&open_orders);
+        // Order 1 filled in part while disconnected, order 3 was placed elsewhere
+        assert_eq!(
+            states(&events),
+            [
+                (Some(OrderState::Open), OrderState::PartiallyFilled, dec!(2)),
+                (None, OrderState::Open, dec!(0))
+            ]
+        );
+        assert_eq!(closed, [2]);
+        assert!(!tracker.order(3).unwrap().is_buy);
+    }
+}
@@ -0,0 +1,1164 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::Address;
+use futures_util::StreamExt;
+use log::warn;
+use rust_decimal::{prelude::Signed, Decimal};
+use tokio::task::JoinHandle;
+
+use crate::{
+    helpers::{now_timestamp_ms, RecentIds},
+    info::info_client::InfoClient,
+    prelude::*,
+    ActiveAssetCtxData, AllMidsData, AssetCtx, AssetInfo, Leverage, OverflowPolicy, StreamConfig,
+    Subscription, SubscriptionEvent, TradeInfo, TypedSubscription, UserFillsData, UserFunding,
+    UserFundingsData, UserStateResponse, UserTokenBalanceResponse, WsConfig,
+};
+
+/// Quote token that spot balances are valued in.
+const USDC: &str = "USDC";
+
+/// Maintenance margin is half the initial margin at max leverage.
+fn maintenance_fraction(max_leverage: u32) -> Decimal {
+    Decimal::ONE / Decimal::from(2 * max_leverage.max(1))
+}
+
+/// A perp position, kept current from fills, fundings and mark prices.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub coin: String,
+    /// Signed size; negative for shorts.
+    pub szi: Decimal,
+    /// Average entry price of the open size.
+    pub entry_px: Decimal,
+    pub mark_px: Decimal,
+    pub leverage: Leverage,
+    pub max_leverage: u32,
+    /// Margin set aside for an isolated position, excluding unrealized PnL.
+    pub isolated_margin: Option<Decimal>,
+    /// Closed PnL since tracking started, before fees and funding.
+    pub realized_pnl: Decimal,
+    /// Fees paid since tracking started; negative for rebates.
+    pub fees: Decimal,
+    /// Funding received since tracking started; negative when paid.
+    pub funding: Decimal,
+    /// Estimated at current mark prices, see [`PortfolioTracker::liquidation_px`].
+    pub liquidation_px: Option<Decimal>,
+}
+
+impl Position {
+    pub fn is_isolated(&self) -> bool {
+        self.isolated_margin.is_some()
+    }
+
+    pub fn notional(&self) -> Decimal {
+        self.szi.abs() * self.mark_px
+    }
+
+    pub fn unrealized_pnl(&self) -> Decimal {
+        self.szi * (self.mark_px - self.entry_px)
+    }
+
+    pub fn margin_used(&self) -> Decimal {
+        match self.isolated_margin {
+            Some(margin) => margin + self.unrealized_pnl(),
+            None => self.notional() / Decimal::from(self.leverage.value.max(1)),
+        }
+    }
+
+    /// Margin below which the position is liquidated.
+    pub fn maintenance_margin(&self) -> Decimal {
+        self.notional() * maintenance_fraction(self.max_leverage)
+    }
+
+    /// Unrealized PnL over the margin the position was opened with.
+    pub fn return_on_equity(&self) -> Decimal {
+        let initial_margin =
+            self.szi.abs() * self.entry_px / Decimal::from(self.leverage.value.max(1));
+        if initial_margin.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.unrealized_pnl() / initial_margin
+        }
+    }
+}
+
+/// A spot token balance.
+#[derive(Debug, Clone)]
+pub struct SpotBalance {
+    pub coin: String,
+    pub total: Decimal,
+    /// Part of `total` held by open orders.
+    pub hold: Decimal,
+    /// What the balance cost in USDC.
+    pub entry_ntl: Decimal,
+    /// USDC price, once seen; always one for USDC itself.
+    pub mark_px: Option<Decimal>,
+    /// PnL of sales since tracking started, in USDC.
+    pub realized_pnl: Decimal,
+}
+
+impl SpotBalance {
+    /// Value in USDC, if the token has a price.
+    pub fn value(&self) -> Option<Decimal> {
+        Some(self.total * self.mark_px?)
+    }
+
+    pub fn unrealized_pnl(&self) -> Option<Decimal> {
+        if self.coin == USDC {
+            return Some(Decimal::ZERO);
+        }
+        Some(self.value()? - self.entry_ntl)
+    }
+}
+
+/// Account-level totals of a [`PortfolioTracker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSummary {
+    /// Perp account value, including isolated margin.
+    pub account_value: Decimal,
+    /// Account value backing cross positions.
+    pub cross_account_value: Decimal,
+    pub total_notional: Decimal,
+    pub margin_used: Decimal,
+    pub maintenance_margin: Decimal,
+    pub unrealized_pnl: Decimal,
+    /// Perp closed PnL and spot sale PnL since tracking started.
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub funding: Decimal,
+    /// Total notional over account value.
+    pub leverage: Decimal,
+    /// USDC value of spot balances that have a price.
+    pub spot_value: Decimal,
+}
+
+/// Positions, balances and PnL of one account. Does no I/O, so [`Portfolio`]
+/// feeds it from `user_state`, `user_token_balances` and the websocket.
+///
+/// Between snapshots everything is estimated locally: fills move sizes, entry
+/// prices and margin, fundings move margin, and marks move unrealized PnL.
+#[derive(Debug, Default)]
+pub struct PortfolioTracker {
+    positions: HashMap<String, Position>,
+    balances: HashMap<String, SpotBalance>,
+    /// Cross account value less the unrealized PnL of cross positions.
+    cross_cash: Decimal,
+    max_leverage: HashMap<String, u32>,
+    /// Base and quote tokens by spot pair name, e.g. `@107` or `PURR/USDC`.
+    spot_pairs: HashMap<String, (String, String)>,
+    /// Coins whose marks come from `ActiveAssetCtx` rather than mids.
+    ctx_marks: HashSet<String>,
+    seen_fills: RecentIds<u64>,
+    seen_fundings: RecentIds<(String, u64)>,
+    realized_pnl: Decimal,
+    fees: Decimal,
+    funding: Decimal,
+    resync_needed: bool,
+    /// Fills and fundings applied since [`PortfolioTracker::begin_resync`].
+    in_flight: Option<Vec<Applied>>,
+    /// When the snapshots in flight were requested, in ms since the epoch.
+    resync_started: u64,
+}
+
+/// An update to replay over a snapshot that may predate it.
+#[derive(Debug)]
+enum Applied {
+    Fill(TradeInfo),
+    Funding(UserFunding),
+}
+
+impl PortfolioTracker {
+    pub fn new(assets: &[AssetInfo]) -> Self {
+        let mut tracker = Self::default();
+        for asset in assets {
+            match (&asset.spot_tokens, asset.max_leverage) {
+                (Some(tokens), _) => {
+                    tracker
+                        .spot_pairs
+                        .insert(asset.name.clone(), tokens.clone());
+                }
+                (None, Some(max_leverage)) => {
+                    tracker
+                        .max_leverage
+                        .insert(asset.name.clone(), max_leverage as u32);
+                }
+                (None, None) => {}
+            }
+        }
+        tracker
+    }
+
+    /// Replaces perp positions and margin with `state`. PnL, fees and funding
+    /// counted so far are kept.
+    pub fn on_user_state(&mut self, state: &UserStateResponse) {
+        let mut previous = std::mem::take(&mut self.positions);
+        let mut cross_upnl = Decimal::ZERO;
+        for asset_position in &state.asset_positions {
+            let data = &asset_position.position;
+            let (Some(entry_px), false) = (data.entry_px, data.szi.is_zero()) else {
+                continue;
+            };
+            let isolated = data.leverage.type_string == "isolated";
+            if !isolated {
+                cross_upnl += data.unrealized_pnl;
+            }
+            let earlier = previous.remove(&data.coin);
+            self.max_leverage
+                .insert(data.coin.clone(), data.max_leverage);
+            self.positions.insert(
+                data.coin.clone(),
+                Position {
+                    coin: data.coin.clone(),
+                    szi: data.szi,
+                    entry_px,
+                    mark_px: data.position_value / data.szi.abs(),
+                    leverage: data.leverage.clone(),
+                    max_leverage: data.max_leverage,
+                    isolated_margin: isolated.then(|| data.margin_used - data.unrealized_pnl),
+                    realized_pnl: earlier.as_ref().map_or(Decimal::ZERO, |p| p.realized_pnl),
+                    fees: earlier.as_ref().map_or(Decimal::ZERO, |p| p.fees),
+                    funding: earlier.as_ref().map_or(Decimal::ZERO, |p| p.funding),
+                    liquidation_px: None,
+                },
+            );
+        }
+        // Closed positions stay around, flat, for their totals
+        for (coin, mut position) in previous {
+            position.szi = Decimal::ZERO;
+            position.entry_px = Decimal::ZERO;
+            position.isolated_margin = position.isolated_margin.map(|_| Decimal::ZERO);
+            self.positions.insert(coin, position);
+        }
+        self.cross_cash = state.cross_margin_summary.account_value - cross_upnl;
+        self.resync_needed = false;
+    }
+
+    /// Replaces spot balances with `balances`, keeping prices and realized PnL.
+    pub fn on_token_balances(&mut self, balances: &UserTokenBalanceResponse) {
+        let mut previous = std::mem::take(&mut self.balances);
+        for balance in &balances.balances {
+            let earlier = previous.remove(&balance.coin);
+            self.balances.insert(
+                balance.coin.clone(),
+                SpotBalance {
+                    coin: balance.coin.clone(),
+                    total: balance.total,
+                    hold: balance.hold,
+                    entry_ntl: balance.entry_ntl,
+                    mark_px: earlier
+                        .as_ref()
+                        .and_then(|b| b.mark_px)
+                        .or((balance.coin == USDC).then_some(Decimal::ONE)),
+                    realized_pnl: earlier.map_or(Decimal::ZERO, |b| b.realized_pnl),
+                },
+            );
+        }
+    }
+
+    /// Applies a fill. Returns `false` for fills already applied.
+    pub fn on_fill(&mut self, fill: &TradeInfo) -> bool {
+        if !self.seen_fills.insert(fill.tid) {
+            return false;
+        }
+        self.apply_fill(fill, true);
+        if let Some(in_flight) = &mut self.in_flight {
+            in_flight.push(Applied::Fill(fill.clone()));
+        }
+        true
+    }
+
+    pub fn on_funding(&mut self, funding: &UserFunding) -> bool {
+        if !self
+            .seen_fundings
+            .insert((funding.coin.clone(), funding.time))
+        {
+            return false;
+        }
+        self.apply_funding(funding, true);
+        if let Some(in_flight) = &mut self.in_flight {
+            in_flight.push(Applied::Funding(funding.clone()));
+        }
+        true
+    }
+
+    /// Starts recording fills and fundings to replay once the snapshots
+    /// requested now have been applied.
+    pub fn begin_resync(&mut self) {
+        self.in_flight = Some(Vec::new());
+        self.resync_started = now_timestamp_ms();
+    }
+
+    /// Replays what was applied while the snapshots were in flight, since
+    /// they may have been taken before it. Sizes, balances and margin move
+    /// again; PnL, fees and funding were counted already.
+    ///
+    /// A perp fill the snapshot turns out to have is skipped, like any later
+    /// fill. Spot fills and fundings leave nothing in the snapshots to tell,
+    /// so only those that happened after the snapshots were requested are
+    /// replayed.
+    pub fn finish_resync(&mut self) {
+        let started = self.resync_started;
+        for applied in self.in_flight.take().unwrap_or_default() {
+            match applied {
+                Applied::Fill(fill)
+                    if self.spot_pairs.contains_key(&fill.coin) && fill.time < started => {}
+                Applied::Fill(fill) => self.apply_fill(&fill, false),
+                Applied::Funding(funding) if funding.time < started => {}
+                Applied::Funding(funding) => self.apply_funding(&funding, false),
+            }
+        }
+    }
+
+    /// Sets the mark price of a perp or spot pair from `ActiveAssetCtx`.
+    /// Mids are ignored for the coin from then on.
+    pub fn on_mark_px(&mut self, coin: &str, mark_px: Decimal) {
+        self.ctx_marks.insert(coin.to_string());
+        self.set_mark(coin, mark_px);
+    }
+
+    /// Uses mids as marks for coins without an `ActiveAssetCtx` mark.
+    pub fn on_mids(&mut self, mids: &AllMidsData) {
+        for (coin, mid) in &mids.mids {
+            if !self.ctx_marks.contains(coin) {
+                self.set_mark(coin, *mid);
+            }
+        }
+    }
+
+    /// Whether a fill opened a position the last `user_state` didn't have, so
+    /// its leverage is a guess until the next one. Clears the flag.
+    pub fn take_resync_needed(&mut self) -> bool {
+        std::mem::take(&mut self.resync_needed)
+    }
+
+    pub fn position(&self, coin: &str) -> Option<Position> {
+        self.positions
+            .get(coin)
+            .map(|position| self.with_liquidation_px(position))
+    }
+
+    /// Open perp positions.
+    pub fn positions(&self) -> Vec<Position> {
+        self.positions
+            .values()
+            .filter(|position| !position.szi.is_zero())
+            .map(|position| self.with_liquidation_px(position))
+            .collect()
+    }
+
+    pub fn balance(&self, coin: &str) -> Option<SpotBalance> {
+        self.balances.get(coin).cloned()
+    }
+
+    pub fn balances(&self) -> Vec<SpotBalance> {
+        self.balances.values().cloned().collect()
+    }
+
+    pub fn summary(&self) -> AccountSummary {
+        let cross_account_value = self.cross_account_value();
+        let mut summary = AccountSummary {
+            account_value: cross_account_value,
+            cross_account_value,
+            total_notional: Decimal::ZERO,
+            margin_used: Decimal::ZERO,
+            maintenance_margin: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            realized_pnl: self.realized_pnl,
+            fees: self.fees,
+            funding: self.funding,
+            leverage: Decimal::ZERO,
+            spot_value: self.balances.values().filter_map(SpotBalance::value).sum(),
+        };
+        for position in self.positions.values() {
+            if let Some(margin) = position.isolated_margin {
+                summary.account_value += margin + position.unrealized_pnl();
+            }
+            summary.total_notional += position.notional();
+            summary.margin_used += position.margin_used();
+            summary.maintenance_margin += position.maintenance_margin();
+            summary.unrealized_pnl += position.unrealized_pnl();
+        }
+        if summary.account_value > Decimal::ZERO {
+            summary.leverage = summary.total_notional / summary.account_value;
+        }
+        summary
+    }
+
+    /// Price at which the position would be liquidated if the marks of all
+    /// other positions stayed where they are. `None` for a flat position, or
+    /// when it would take a price of zero or less.
+    pub fn liquidation_px(&self, position: &Position) -> Option<Decimal> {
+        let size = position.szi.abs();
+        if size.is_zero() {
+            return None;
+        }
+        let side = position.szi.signum();
+        let margin_available = match position.isolated_margin {
+            Some(margin) => margin + position.unrealized_pnl() - position.maintenance_margin(),
+            None => {
+                self.cross_account_value()
+                    - self
+                        .cross_positions()
+                        .map(Position::maintenance_margin)
+                        .sum::<Decimal>()
+            }
+        };
+        let fraction = maintenance_fraction(position.max_leverage);
+        let liquidation_px =
+            position.mark_px - side * margin_available / size / (Decimal::ONE - fraction * side);
+        (liquidation_px > Decimal::ZERO).then_some(liquidation_px)
+    }
+
+    fn cross_positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions
+            .values()
+            .filter(|position| !position.is_isolated())
+    }
+
+    fn cross_account_value(&self) -> Decimal {
+        self.cross_cash
+            + self
+                .cross_positions()
+                .map(Position::unrealized_pnl)
+                .sum::<Decimal>()
+    }
+
+    fn with_liquidation_px(&self, position: &Position) -> Position {
+        Position {
+            liquidation_px: self.liquidation_px(position),
+            ..position.clone()
+        }
+    }
+
+    fn set_mark(&mut self, coin: &str, mark_px: Decimal) {
+        if let Some(position) = self.positions.get_mut(coin) {
+            position.mark_px = mark_px;
+        } else if let Some((base, quote)) = self.spot_pairs.get(coin) {
+            if quote == USDC {
+                if let Some(balance) = self.balances.get_mut(base) {
+                    balance.mark_px = Some(mark_px);
+                }
+            }
+        }
+    }
+
+    fn apply_fill(&mut self, fill: &TradeInfo, count: bool) {
+        match self.spot_pairs.get(&fill.coin).cloned() {
+            Some((base, quote)) => self.apply_spot_fill(fill, &base, &quote, count),
+            None => self.apply_perp_fill(fill, count),
+        }
+    }
+
+    /// Moves margin by `funding`, and adds it to the totals if `count`.
+    fn apply_funding(&mut self, funding: &UserFunding, count: bool) {
+        if count {
+            self.funding += funding.usdc;
+        }
+        match self.positions.get_mut(&funding.coin) {
+            Some(position) => {
+                if count {
+                    position.funding += funding.usdc;
+                }
+                match &mut position.isolated_margin {
+                    Some(margin) => *margin += funding.usdc,
+                    None => self.cross_cash += funding.usdc,
+                }
+            }
+            None => self.cross_cash += funding.usdc,
+        }
+    }
+
+    fn apply_perp_fill(&mut self, fill: &TradeInfo, count: bool) {
+        let delta = if fill.side == "B" { fill.sz } else { -fill.sz };
+        let before = fill.start_position;
+        let after = before + delta;
+
+        if !self.positions.contains_key(&fill.coin) {
+            self.resync_needed = true;
+        }
+        let max_leverage = self.max_leverage.get(&fill.coin).copied().unwrap_or(1);
+        let position = self
+            .positions
+            .entry(fill.coin.clone())
+            .or_insert_with(|| Position {
+                coin: fill.coin.clone(),
+                szi: Decimal::ZERO,
+                entry_px: Decimal::ZERO,
+                mark_px: fill.px,
+                // Until `user_state` says otherwise
+                leverage: Leverage {
+                    type_string: "cross".to_string(),
+                    value: max_leverage,
+                    raw_usd: None,
+                },
+                max_leverage,
+                isolated_margin: None,
+                realized_pnl: Decimal::ZERO,
+                fees: Decimal::ZERO,
+                funding: Decimal::ZERO,
+                liquidation_px: None,
+            });
+        if count {
+            position.realized_pnl += fill.closed_pnl;
+            position.fees += fill.fee;
+            self.realized_pnl += fill.closed_pnl;
+            self.fees += fill.fee;
+        }
+
+        // A snapshot taken after the fill already has it
+        if position.szi == after && position.szi != before {
+            return;
+        }
+
+        let increasing = before.is_zero() || before.signum() == delta.signum();
+        let reduced = if increasing {
+            Decimal::ZERO
+        } else {
+            fill.sz.min(before.abs())
+        };
+        let opened = fill.sz - reduced;
+        if increasing {
+            position.entry_px =
+                (position.entry_px * before.abs() + fill.px * fill.sz) / after.abs();
+        } else if after.is_zero() {
+            position.entry_px = Decimal::ZERO;
+        } else if after.signum() != before.signum() {
+            // Flipped; what is left was opened by this fill
+            position.entry_px = fill.px;
+        }
+
+        if let Some(margin) = &mut position.isolated_margin {
+            let released = if before.is_zero() {
+                Decimal::ZERO
+            } else {
+                *margin * reduced / before.abs()
+            };
+            let added = opened * fill.px / Decimal::from(position.leverage.value.max(1));
+            *margin += added - released;
+            self.cross_cash += released - added;
+        }
+        self.cross_cash += fill.closed_pnl - fill.fee;
+        position.szi = after;
+    }
+
+    fn apply_spot_fill(&mut self, fill: &TradeInfo, base: &str, quote: &str, count: bool) {
+        let notional = fill.sz * fill.px;
+        let base_balance = self.balance_mut(base);
+        if fill.side == "B" {
+            base_balance.total += fill.sz;
+            base_balance.entry_ntl += notional;
+            self.balance_mut(quote).total -= notional;
+        } else {
+            let cost = if base_balance.total > Decimal::ZERO {
+                base_balance.entry_ntl * fill.sz.min(base_balance.total) / base_balance.total
+            } else {
+                Decimal::ZERO
+            };
+            base_balance.total -= fill.sz;
+            base_balance.entry_ntl -= cost;
+            if count {
+                base_balance.realized_pnl += notional - cost;
+                self.realized_pnl += notional - cost;
+            }
+            self.balance_mut(quote).total += notional;
+        }
+
+        self.balance_mut(&fill.fee_token).total -= fill.fee;
+        if count {
+            self.fees += if fill.fee_token == base {
+                fill.fee * fill.px
+            } else {
+                fill.fee
+            };
+        }
+    }
+
+    fn balance_mut(&mut self, coin: &str) -> &mut SpotBalance {
+        self.balances
+            .entry(coin.to_string())
+            .or_insert_with(|| SpotBalance {
+                coin: coin.to_string(),
+                total: Decimal::ZERO,
+                hold: Decimal::ZERO,
+                entry_ntl: Decimal::ZERO,
+                mark_px: (coin == USDC).then_some(Decimal::ONE),
+                realized_pnl: Decimal::ZERO,
+            })
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    user: Address,
+    tracker: Mutex<PortfolioTracker>,
+    info_client: InfoClient,
+    /// One snapshot at a time, so each replays what arrived during its own request.
+    resync_lock: tokio::sync::Mutex<()>,
+}
+
+impl Inner {
+    fn update<R>(&self, apply: impl FnOnce(&mut PortfolioTracker) -> R) -> R {
+        apply(&mut self.tracker.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    async fn resync(&self) -> Result<()> {
+        let _resync = self.resync_lock.lock().await;
+        self.update(PortfolioTracker::begin_resync);
+        let snapshots = tokio::try_join!(
+            self.info_client.user_state(self.user),
+            self.info_client.user_token_balances(self.user)
+        );
+        let (state, balances) = match snapshots {
+            Ok(snapshots) => snapshots,
+            Err(err) => {
+                // Nothing was overwritten, so nothing is replayed
+                self.update(|tracker| tracker.in_flight = None);
+                return Err(err);
+            }
+        };
+        self.update(|tracker| {
+            tracker.on_user_state(&state);
+            tracker.on_token_balances(&balances);
+            tracker.finish_resync();
+        });
+        Ok(())
+    }
+
+    async fn resync_or_warn(&self) {
+        if let Err(err) = self.resync().await {
+            warn!("Could not reload portfolio: {err}");
+        }
+    }
+}
+
+/// Live positions, spot balances, PnL and liquidation estimates of a user.
+///
+/// Starts from `user_state` and `user_token_balances`, then follows the
+/// `UserFills` and `UserFundings` subscriptions, with marks from `AllMids`,
+/// or from `ActiveAssetCtx` for coins passed to [`Portfolio::watch_mark`].
+/// Snapshots are reloaded after the websocket reconnects, when fills or
+/// fundings were dropped because it fell behind, and when a fill opens a
+/// position in a new coin. Dropping this stops the updates.
+#[derive(Debug)]
+pub struct Portfolio {
+    inner: Arc<Inner>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Portfolio {
+    /// Loads the portfolio of `user` and follows the marks of its open
+    /// positions from `ActiveAssetCtx`.
+    pub async fn start(info_client: &mut InfoClient, user: Address) -> Result<Self> {
+        let assets = info_client.asset_registry().assets().await?;
+        let fills = info_client
+            .subscribe_typed::<UserFillsData>(
+                Subscription::UserFills { user },
+                StreamConfig::default(),
+            )
+            .await?;
+        let fundings = info_client
+            .subscribe_typed::<UserFundingsData>(
+                Subscription::UserFundings { user },
+                StreamConfig::default(),
+            )
+            .await?;
+        let mids = info_client
+            .subscribe_typed::<AllMidsData>(
+                Subscription::AllMids,
+                StreamConfig {
+                    capacity: 1,
+                    overflow: OverflowPolicy::KeepLatest,
+                },
+            )
+            .await?;
+
+        let inner = Arc::new(Inner {
+            user,
+            tracker: Mutex::new(PortfolioTracker::new(&assets)),
+            // Snapshots go through a client of its own so the tasks don't borrow `info_client`
+            info_client: InfoClient::from_http_client(
+                info_client.http_client.clone(),
+                None,
+                WsConfig::default(),
+                Some(info_client.asset_registry().clone()),
+            ),
+            resync_lock: tokio::sync::Mutex::new(()),
+        });
+        inner.resync().await?;
+
+        let mut portfolio = Self {
+            tasks: vec![tokio::spawn(Self::run(
+                inner.clone(),
+                fills,
+                fundings,
+                mids,
+            ))],
+            inner,
+        };
+        for position in portfolio.positions() {
+            portfolio.watch_mark(info_client, position.coin).await?;
+        }
+        Ok(portfolio)
+    }
+
+    /// Takes the mark price of a perp or spot pair from `ActiveAssetCtx`
+    /// instead of its mid.
+    pub async fn watch_mark(
+        &mut self,
+        info_client: &mut InfoClient,
+        coin: impl Into<String>,
+    ) -> Result<()> {
+        let coin = coin.into();
+        let subscription = info_client
+            .subscribe_typed::<ActiveAssetCtxData>(
+                Subscription::ActiveAssetCtx { coin: coin.clone() },
+                StreamConfig {
+                    capacity: 1,
+                    overflow: OverflowPolicy::KeepLatest,
+                },
+            )
+            .await?;
+        self.tasks.push(tokio::spawn(Self::run_marks(
+            self.inner.clone(),
+            subscription,
+        )));
+        Ok(())
+    }
+
+    pub fn position(&self, coin: &str) -> Option<Position> {
+        self.inner.update(|tracker| tracker.position(coin))
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.inner.update(|tracker| tracker.positions())
+    }
+
+    pub fn balance(&self, coin: &str) -> Option<SpotBalance> {
+        self.inner.update(|tracker| tracker.balance(coin))
+    }
+
+    pub fn balances(&self) -> Vec<SpotBalance> {
+        self.inner.update(|tracker| tracker.balances())
+    }
+
+    pub fn summary(&self) -> AccountSummary {
+        self.inner.update(|tracker| tracker.summary())
+    }
+
+    /// Reloads positions and balances from `user_state` and `user_token_balances`.
+    pub async fn resync(&self) -> Result<()> {
+        self.inner.resync().await
+    }
+
+    async fn run(
+        inner: Arc<Inner>,
+        mut fills: TypedSubscription<UserFillsData>,
+        mut fundings: TypedSubscription<UserFundingsData>,
+        mut mids: TypedSubscription<AllMidsData>,
+    ) {
+        let mut lagged = 0;
+        // The first snapshot repeats history the starting snapshots have. Later
+        // ones follow a reconnect and may hold updates made while disconnected;
+        // those already applied are skipped by `on_fill` and `on_funding`.
+        let (mut fills_history, mut fundings_history) = (true, true);
+        loop {
+            tokio::select! {
+                Some(fills) = fills.data.next() => {
+                    if !(fills.is_snapshot == Some(true) && std::mem::take(&mut fills_history)) {
+                        let resync_needed = inner.update(|tracker| {
+                            for fill in &fills.fills {
+                                tracker.on_fill(fill);
+                            }
+                            tracker.take_resync_needed()
+                        });
+                        if resync_needed {
+                            inner.resync_or_warn().await;
+                        }
+                    }
+                }
+                Some(fundings) = fundings.data.next() => {
+                    if !(fundings.is_snapshot == Some(true) && std::mem::take(&mut fundings_history)) {
+                        inner.update(|tracker| {
+                            for funding in &fundings.fundings {
+                                tracker.on_funding(funding);
+                            }
+                        });
+                    }
+                }
+                Some(mids) = mids.data.next() => {
+                    inner.update(|tracker| tracker.on_mids(&mids));
+                }
+                Some(event) = fills.events.recv() => {
+                    // Connection events reach every subscription, so only this one reacts
+                    if let SubscriptionEvent::Reconnected(_) = event {
+                        inner.resync_or_warn().await;
+                    }
+                }
+                Some(_) = fundings.events.recv() => {}
+                Some(_) = mids.events.recv() => {}
+                else => break,
+            }
+            // Dropped fills and fundings can't be replayed, so reload the snapshots
+            // instead; dropped mids don't matter as only the latest one counts
+            let dropped = fills.data.lagged() + fundings.data.lagged();
+            if dropped > lagged {
+                lagged = dropped;
+                warn!("Portfolio fell behind its subscriptions, reloading");
+                inner.resync_or_warn().await;
+            }
+        }
+        warn!("Portfolio subscriptions ended");
+    }
+
+    async fn run_marks(inner: Arc<Inner>, mut subscription: TypedSubscription<ActiveAssetCtxData>) {
+        while let Some(data) = subscription.data.next().await {
+            let mark_px = match &data.ctx {
+                AssetCtx::Perps(ctx) => ctx.shared.mark_px,
+                AssetCtx::Spot(ctx) => ctx.shared.mark_px,
+            };
+            inner.update(|tracker| tracker.on_mark_px(&data.coin, mark_px));
+        }
+    }
+}
+
+impl Drop for Portfolio {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn assets() -> Vec<AssetInfo> {
+        vec![
+            AssetInfo {
+                name: "ETH".to_string(),
+                asset: 1,
+                sz_decimals: 4,
+                max_leverage: Some(20),
+                is_spot: false,
+                spot_tokens: None,
+            },
+            AssetInfo {
+                name: "@107".to_string(),
+                asset: 10107,
+                sz_decimals: 2,
+                max_leverage: None,
+                is_spot: true,
+                spot_tokens: Some(("HYPE".to_string(), "USDC".to_string())),
+            },
+        ]
+    }
+
+    fn user_state(leverage_type: &str) -> UserStateResponse {
+        serde_json::from_value(serde_json::json!({
+            "assetPositions": [{
+                "type": "oneWay",
+                "position": {
+                    "coin": "ETH", "entryPx": "100", "liquidationPx": null,
+                    "leverage": {"type": leverage_type, "value": 10, "rawUsd": null},
+                    "marginUsed": "10", "positionValue": "100", "returnOnEquity": "0",
+                    "szi": "1", "unrealizedPnl": "0", "maxLeverage": 20,
+                    "cumFunding": {"allTime": "0", "sinceOpen": "0", "sinceChange": "0"}
+                }
+            }],
+            "crossMarginSummary": {
+                "accountValue": "15", "totalMarginUsed": "10", "totalNtlPos": "100", "totalRawUsd": "-85"
+            },
+            "marginSummary": {
+                "accountValue": "15", "totalMarginUsed": "10", "totalNtlPos": "100", "totalRawUsd": "-85"
+            },
+            "withdrawable": "5"
+        }))
+        .unwrap()
+    }
+
+    fn fill(tid: u64, coin: &str, side: &str, sz: Decimal, px: Decimal) -> TradeInfo {
+        fill_from(tid, coin, side, sz, px, Decimal::ZERO, Decimal::ZERO)
+    }
+
+    fn fill_from(
+        tid: u64,
+        coin: &str,
+        side: &str,
+        sz: Decimal,
+        px: Decimal,
+        start_position: Decimal,
+        closed_pnl: Decimal,
+    ) -> TradeInfo {
+        serde_json::from_value(serde_json::json!({
+            "coin": coin, "side": side, "px": px, "sz": sz, "time": 1, "hash": "0x0",
+            "startPosition": start_position, "dir": "", "closedPnl": closed_pnl, "oid": 1,
+            "cloid": null, "crossed": true, "fee": "0.1", "feeToken": "USDC", "tid": tid
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cross_position_pnl_and_liquidation() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("cross"));
+        tracker.on_mark_px("ETH", dec!(110));
+
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!(position.unrealized_pnl(), dec!(10));
+        assert_eq!(position.margin_used(), dec!(11));
+        assert_eq!(position.return_on_equity(), dec!(1));
+        // (mark * size - account value) / (size * (1 - 1 / 40))
+        assert_eq!(
+            position.liquidation_px.unwrap().round_dp(4),
+            ((dec!(110) - dec!(25)) / dec!(0.975)).round_dp(4)
+        );
+
+        let summary = tracker.summary();
+        assert_eq!(summary.account_value, dec!(25));
+        assert_eq!(summary.total_notional, dec!(110));
+        assert_eq!(summary.leverage, dec!(4.4));
+        assert_eq!(summary.maintenance_margin, dec!(2.75));
+
+        // Mids don't override a mark from `ActiveAssetCtx`
+        let mids = AllMidsData {
+            mids: HashMap::from([("ETH".to_string(), dec!(90))]),
+        };
+        tracker.on_mids(&mids);
+        assert_eq!(tracker.position("ETH").unwrap().mark_px, dec!(110));
+    }
+
+    #[test]
+    fn test_fills_move_entry_and_realize_pnl() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("cross"));
+
+        // Adding 1 at 120 averages the entry to 110
+        assert!(tracker.on_fill(&fill_from(
+            1,
+            "ETH",
+            "B",
+            dec!(1),
+            dec!(120),
+            dec!(1),
+            dec!(0)
+        )));
+        assert!(!tracker.on_fill(&fill_from(
+            1,
+            "ETH",
+            "B",
+            dec!(1),
+            dec!(120),
+            dec!(1),
+            dec!(0)
+        )));
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!((position.szi, position.entry_px), (dec!(2), dec!(110)));
+
+        // Selling 3 closes the long at a profit of 10 and opens a short at 115
+        tracker.on_fill(&fill_from(
+            2,
+            "ETH",
+            "A",
+            dec!(3),
+            dec!(115),
+            dec!(2),
+            dec!(10),
+        ));
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!((position.szi, position.entry_px), (dec!(-1), dec!(115)));
+        assert_eq!(position.realized_pnl, dec!(10));
+        assert_eq!(position.fees, dec!(0.2));
+
+        tracker.on_mark_px("ETH", dec!(115));
+        let summary = tracker.summary();
+        assert_eq!(summary.realized_pnl, dec!(10));
+        // Starting cash of 15, plus closed PnL, less two fees
+        assert_eq!(summary.account_value, dec!(24.8));
+        assert!(!tracker.take_resync_needed());
+
+        // A fill in a new coin asks for a snapshot to learn its leverage
+        tracker.on_fill(&fill(3, "BTC", "B", dec!(0.1), dec!(60000)));
+        assert!(tracker.take_resync_needed());
+    }
+
+    #[test]
+    fn test_isolated_margin_and_funding() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("isolated"));
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!(position.isolated_margin, Some(dec!(10)));
+        // (mark * size - margin) / (size * (1 - 1 / 40))
+        assert_eq!(
+            position.liquidation_px.unwrap().round_dp(4),
+            (dec!(90) / dec!(0.975)).round_dp(4)
+        );
+
+        // Closing half returns half the margin to cross
+        tracker.on_fill(&fill_from(
+            1,
+            "ETH",
+            "A",
+            dec!(0.5),
+            dec!(100),
+            dec!(1),
+            dec!(0),
+        ));
+        let funding = UserFunding {
+            time: 5,
+            coin: "ETH".to_string(),
+            usdc: dec!(-0.5),
+            szi: dec!(0.5),
+            funding_rate: dec!(0.0001),
+        };
+        assert!(tracker.on_funding(&funding));
+        assert!(!tracker.on_funding(&funding));
+
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!(position.isolated_margin, Some(dec!(4.5)));
+        assert_eq!(position.funding, dec!(-0.5));
+        let summary = tracker.summary();
+        // Cash of 15, plus the released margin, less the fee
+        assert_eq!(summary.cross_account_value, dec!(19.9));
+        assert_eq!(summary.account_value, dec!(24.4));
+    }
+
+    #[test]
+    fn test_snapshot_fills_are_not_applied_twice() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("cross"));
+        // The snapshot's 1 ETH came from this fill
+        tracker.on_fill(&fill_from(
+            1,
+            "ETH",
+            "B",
+            dec!(1),
+            dec!(100),
+            dec!(0),
+            dec!(0),
+        ));
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!((position.szi, position.entry_px), (dec!(1), dec!(100)));
+        assert_eq!(position.fees, dec!(0.1));
+    }
+
+    #[test]
+    fn test_resync_replays_updates_applied_while_in_flight() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("cross"));
+
+        // The snapshot comes back without the fill that arrived meanwhile
+        tracker.begin_resync();
+        tracker.on_fill(&fill_from(
+            1,
+            "ETH",
+            "B",
+            dec!(1),
+            dec!(120),
+            dec!(1),
+            dec!(0),
+        ));
+        tracker.on_user_state(&user_state("cross"));
+        tracker.finish_resync();
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!((position.szi, position.entry_px), (dec!(2), dec!(110)));
+        assert_eq!(position.fees, dec!(0.1));
+        assert_eq!(tracker.summary().fees, dec!(0.1));
+
+        // A snapshot that has the fill already is left as it is
+        tracker.begin_resync();
+        tracker.on_fill(&fill_from(
+            2,
+            "ETH",
+            "A",
+            dec!(1),
+            dec!(120),
+            dec!(2),
+            dec!(10),
+        ));
+        tracker.on_user_state(&user_state("cross"));
+        tracker.finish_resync();
+        let position = tracker.position("ETH").unwrap();
+        assert_eq!(position.szi, dec!(1));
+        assert_eq!(position.realized_pnl, dec!(10));
+    }
+
+    fn balances() -> UserTokenBalanceResponse {
+        serde_json::from_value(serde_json::json!({
+            "balances": [
+                {"coin": "USDC", "hold": "0", "total": "1000", "entryNtl": "0"},
+                {"coin": "HYPE", "hold": "0", "total": "10", "entryNtl": "200"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resync_replays_spot_fills_and_fundings_after_the_request() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_user_state(&user_state("cross"));
+        tracker.on_token_balances(&balances());
+        let funding = |time| UserFunding {
+            time,
+            coin: "ETH".to_string(),
+            usdc: dec!(-0.5),
+            szi: dec!(1),
+            funding_rate: dec!(0.0001),
+        };
+
+        // Made before the request, so the snapshots have them
+        tracker.begin_resync();
+        tracker.on_fill(&fill(1, "@107", "A", dec!(5), dec!(30)));
+        tracker.on_funding(&funding(1));
+        tracker.on_user_state(&user_state("cross"));
+        tracker.on_token_balances(&balances());
+        tracker.finish_resync();
+        assert_eq!(tracker.balance("HYPE").unwrap().total, dec!(10));
+        assert_eq!(tracker.balance("USDC").unwrap().total, dec!(1000));
+        assert_eq!(tracker.summary().cross_account_value, dec!(15));
+        assert_eq!(tracker.summary().funding, dec!(-0.5));
+
+        // Made while the request was in flight, so the snapshots may lack them
+        tracker.begin_resync();
+        let mut sold = fill(2, "@107", "A", dec!(5), dec!(30));
+        sold.time = tracker.resync_started + 1;
+        tracker.on_fill(&sold);
+        tracker.on_funding(&funding(sold.time));
+        tracker.on_user_state(&user_state("cross"));
+        tracker.on_token_balances(&balances());
+        tracker.finish_resync();
+        let hype = tracker.balance("HYPE").unwrap();
+        assert_eq!((hype.total, hype.entry_ntl), (dec!(5), dec!(100)));
+        assert_eq!(tracker.balance("USDC").unwrap().total, dec!(1149.9));
+        assert_eq!(tracker.summary().cross_account_value, dec!(14.5));
+        // Counted once each, when they arrived
+        assert_eq!(tracker.summary().funding, dec!(-1));
+        assert_eq!(hype.realized_pnl, dec!(100));
+    }
+
+    #[test]
+    fn test_spot_fills_and_marks() {
+        let mut tracker = PortfolioTracker::new(&assets());
+        tracker.on_token_balances(&balances());
+
+        tracker.on_fill(&fill(1, "@107", "A", dec!(5), dec!(30)));
+        let hype = tracker.balance("HYPE").unwrap();
+        assert_eq!((hype.total, hype.entry_ntl), (dec!(5), dec!(100)));
+        assert_eq!(hype.realized_pnl, dec!(50));
+        assert_eq!(tracker.balance("USDC").unwrap().total, dec!(1149.9));
+
+        let mids = AllMidsData {
+            mids: HashMap::from([("@107".to_string(), dec!(25))]),
+        };
+        tracker.on_mids(&mids);
+        let hype = tracker.balance("HYPE").unwrap();
+        assert_eq!(hype.unrealized_pnl(), Some(dec!(25)));
+        assert_eq!(tracker.summary().spot_value, dec!(1274.9));
+    }
+}
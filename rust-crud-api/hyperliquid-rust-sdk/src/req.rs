@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use reqwest::{Client, Response};
 use serde::Deserialize;
 
@@ -10,10 +12,11 @@ struct ErrorData {
     msg: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HttpClient {
     pub client: Client,
     pub base_url: String,
+    pub mainnet: bool,
 }
 
 async fn parse_response(response: Response) -> Result<String> {
@@ -52,6 +55,45 @@ async fn parse_response(response: Response) -> Result<String> {
 }
 
 impl HttpClient {
+    pub(crate) fn build(
+        client: Option<Client>,
+        base_url: Option<String>,
+        mainnet: Option<bool>,
+        timeout: Option<Duration>,
+    ) -> Result<HttpClient> {
+        let client = match client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = timeout {
+                    builder = builder.timeout(timeout);
+                }
+                builder
+                    .build()
+                    .map_err(|e| Error::GenericRequest(e.to_string()))?
+            }
+        };
+        let base_url = base_url
+            .unwrap_or_else(|| BaseUrl::Mainnet.get_url())
+            .trim_end_matches('/')
+            .to_string();
+        let mainnet = mainnet.unwrap_or(base_url == BaseUrl::Mainnet.get_url());
+
+        Ok(HttpClient {
+            client,
+            base_url,
+            mainnet,
+        })
+    }
+
+    /// Websocket endpoint served next to the HTTP API (http -> ws, https -> wss).
+    pub(crate) fn ws_url(&self) -> String {
+        match self.base_url.strip_prefix("http") {
+            Some(rest) => format!("ws{rest}/ws"),
+            None => format!("{}/ws", self.base_url),
+        }
+    }
+
     pub async fn post(&self, url_path: &'static str, data: String) -> Result<String> {
         let full_url = format!("{}{url_path}", self.base_url);
         let request = self
@@ -70,6 +112,6 @@ impl HttpClient {
     }
 
     pub fn is_mainnet(&self) -> bool {
-        self.base_url == BaseUrl::Mainnet.get_url()
+        self.mainnet
     }
 }
@@ -0,0 +1,105 @@
+//! Minimal stand-in for the API used by tests: answers `/info` by request
+//! type and accepts every `/exchange` action, recording its body.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[derive(Clone)]
+pub(crate) struct StandInServer {
+    pub(crate) url: String,
+    pub(crate) exchange_requests: Arc<Mutex<Vec<Value>>>,
+    /// Served for `meta`; tests may change it to list new assets.
+    pub(crate) meta: Arc<Mutex<Value>>,
+    /// Served for `spotMeta`.
+    pub(crate) spot_meta: Arc<Mutex<Value>>,
+    pub(crate) meta_requests: Arc<AtomicUsize>,
+}
+
+/// Starts a stand-in on a local port.
+pub(crate) async fn spawn_stand_in_server() -> StandInServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = StandInServer {
+        url,
+        exchange_requests: Arc::default(),
+        meta: Arc::new(Mutex::new(json!({"universe": []}))),
+        spot_meta: Arc::new(Mutex::new(json!({"universe": [], "tokens": []}))),
+        meta_requests: Arc::default(),
+    };
+    let state = server.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let state = state.clone();
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&buf[..end]).to_lowercase();
+                    let len: usize = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |len| len.trim().parse().unwrap());
+                    if buf.len() >= end + 4 + len {
+                        let body: Value =
+                            serde_json::from_slice(&buf[end + 4..end + 4 + len]).unwrap();
+                        break (head, body);
+                    }
+                };
+
+                let response = if head.starts_with("post /exchange") {
+                    state.exchange_requests.lock().unwrap().push(body);
+                    json!({"status": "ok", "response": {"type": "order", "data": {
+                        "statuses": [{"filled": {"totalSz": "0.01", "avgPx": "1890.4", "oid": 1}}]
+                    }}})
+                } else {
+                    match body["type"].as_str() {
+                        Some("meta") => {
+                            state.meta_requests.fetch_add(1, Ordering::SeqCst);
+                            state.meta.lock().unwrap().clone()
+                        }
+                        Some("spotMeta") => state.spot_meta.lock().unwrap().clone(),
+                        Some("allMids") => json!({"ETH": "1800.5"}),
+                        Some("clearinghouseState") => json!({
+                            "assetPositions": [{"type": "oneWay", "position": {
+                                "coin": "ETH", "entryPx": "1850.0", "liquidationPx": null,
+                                "leverage": {"type": "cross", "value": 20},
+                                "marginUsed": "4.6", "maxLeverage": 50, "positionValue": "90.0",
+                                "returnOnEquity": "0.0", "szi": "-0.05", "unrealizedPnl": "0.0",
+                                "cumFunding": {"allTime": "0.0", "sinceChange": "0.0", "sinceOpen": "0.0"}
+                            }}],
+                            "crossMarginSummary": {"accountValue": "100.0", "totalMarginUsed": "4.6", "totalNtlPos": "90.0", "totalRawUsd": "10.0"},
+                            "marginSummary": {"accountValue": "100.0", "totalMarginUsed": "4.6", "totalNtlPos": "90.0", "totalRawUsd": "10.0"},
+                            "withdrawable": "95.4"
+                        }),
+                        _ => Value::Null,
+                    }
+                };
+                let response = response.to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+                    response.len()
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    server
+}
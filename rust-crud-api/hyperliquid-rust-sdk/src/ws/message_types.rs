@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use serde::Deserialize;
 
 use crate::ws::sub_structs::*;
@@ -76,3 +78,37 @@ pub struct ActiveAssetData {
 pub struct Bbo {
     pub data: BboData,
 }
+
+/// Sent to every subscriber when the websocket connection is lost.
+#[derive(Clone, Debug)]
+pub struct Disconnected {
+    pub reason: String,
+    /// Whether the manager will try to reconnect.
+    pub reconnecting: bool,
+}
+
+/// Sent to every subscriber once a new connection is up, with the outcome of
+/// sending each subscription again. Anything published while disconnected
+/// was missed, so state built from the stream should be resynced.
+#[derive(Clone, Debug)]
+pub struct Reconnected {
+    /// Connection attempts it took, starting at 1.
+    pub attempts: u32,
+    /// Time since the connection was found lost.
+    pub downtime: Duration,
+    pub resubscriptions: Vec<Resubscription>,
+}
+
+impl Reconnected {
+    pub fn all_resubscribed(&self) -> bool {
+        self.resubscriptions.iter().all(|r| r.error.is_none())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Resubscription {
+    /// The subscription, as sent to the server.
+    pub subscription: String,
+    /// Why it could not be sent, if it failed.
+    pub error: Option<String>,
+}
@@ -1,7 +1,12 @@
 mod message_types;
 mod sub_structs;
+mod subscription_stream;
+mod typed_subscription;
 mod ws_manager;
 pub use message_types::*;
 pub use sub_structs::*;
-pub(crate) use ws_manager::WsManager;
-pub use ws_manager::{Message, Subscription};
+pub(crate) use subscription_stream::SubscriptionQueue;
+pub use subscription_stream::{OverflowPolicy, StreamConfig, SubscriptionStream};
+pub use typed_subscription::{ChannelData, SubscriptionEvent, TypedStream, TypedSubscription};
+pub use ws_manager::{Message, Subscription, WsConfig};
+pub(crate) use ws_manager::{SubscriptionSink, WsManager};
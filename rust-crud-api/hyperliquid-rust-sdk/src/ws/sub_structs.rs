@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use alloy::primitives::Address;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::Leverage;
@@ -9,8 +10,8 @@ use crate::Leverage;
 pub struct Trade {
     pub coin: String,
     pub side: String,
-    pub px: String,
-    pub sz: String,
+    pub px: Decimal,
+    pub sz: Decimal,
     pub time: u64,
     pub hash: String,
     pub tid: u64,
@@ -19,8 +20,8 @@ pub struct Trade {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct BookLevel {
-    pub px: String,
-    pub sz: String,
+    pub px: Decimal,
+    pub sz: Decimal,
     pub n: u64,
 }
 
@@ -33,7 +34,7 @@ pub struct L2BookData {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct AllMidsData {
-    pub mids: HashMap<String, String>,
+    pub mids: HashMap<String, Decimal>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -41,17 +42,17 @@ pub struct AllMidsData {
 pub struct TradeInfo {
     pub coin: String,
     pub side: String,
-    pub px: String,
-    pub sz: String,
+    pub px: Decimal,
+    pub sz: Decimal,
     pub time: u64,
     pub hash: String,
-    pub start_position: String,
+    pub start_position: Decimal,
     pub dir: String,
-    pub closed_pnl: String,
+    pub closed_pnl: Decimal,
     pub oid: u64,
     pub cloid: Option<String>,
     pub crossed: bool,
-    pub fee: String,
+    pub fee: Decimal,
     pub fee_token: String,
     pub tid: u64,
 }
@@ -78,8 +79,8 @@ pub struct Liquidation {
     pub lid: u64,
     pub liquidator: String,
     pub liquidated_user: String,
-    pub liquidated_ntl_pos: String,
-    pub liquidated_account_value: String,
+    pub liquidated_ntl_pos: Decimal,
+    pub liquidated_account_value: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -93,23 +94,23 @@ pub struct CandleData {
     #[serde(rename = "T")]
     pub time_close: u64,
     #[serde(rename = "c")]
-    pub close: String,
+    pub close: Decimal,
     #[serde(rename = "h")]
-    pub high: String,
+    pub high: Decimal,
     #[serde(rename = "i")]
     pub interval: String,
     #[serde(rename = "l")]
-    pub low: String,
+    pub low: Decimal,
     #[serde(rename = "n")]
     pub num_trades: u64,
     #[serde(rename = "o")]
-    pub open: String,
+    pub open: Decimal,
     #[serde(rename = "s")]
     pub coin: String,
     #[serde(rename = "t")]
     pub time_open: u64,
     #[serde(rename = "v")]
-    pub volume: String,
+    pub volume: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -125,11 +126,11 @@ pub struct OrderUpdate {
 pub struct BasicOrder {
     pub coin: String,
     pub side: String,
-    pub limit_px: String,
-    pub sz: String,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
     pub oid: u64,
     pub timestamp: u64,
-    pub orig_sz: String,
+    pub orig_sz: Decimal,
     pub cloid: Option<String>,
 }
 
@@ -146,9 +147,9 @@ pub struct UserFundingsData {
 pub struct UserFunding {
     pub time: u64,
     pub coin: String,
-    pub usdc: String,
-    pub szi: String,
-    pub funding_rate: String,
+    pub usdc: Decimal,
+    pub szi: Decimal,
+    pub funding_rate: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -187,27 +188,27 @@ pub enum LedgerUpdate {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Deposit {
-    pub usdc: String,
+    pub usdc: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Withdraw {
-    pub usdc: String,
+    pub usdc: Decimal,
     pub nonce: u64,
-    pub fee: String,
+    pub fee: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct InternalTransfer {
-    pub usdc: String,
+    pub usdc: Decimal,
     pub user: Address,
     pub destination: Address,
-    pub fee: String,
+    pub fee: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SubAccountTransfer {
-    pub usdc: String,
+    pub usdc: Decimal,
     pub user: Address,
     pub destination: Address,
 }
@@ -223,13 +224,13 @@ pub struct LedgerLiquidation {
 #[derive(Deserialize, Clone, Debug)]
 pub struct LiquidatedPosition {
     pub coin: String,
-    pub szi: String,
+    pub szi: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct VaultDelta {
     pub vault: Address,
-    pub usdc: String,
+    pub usdc: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -237,23 +238,23 @@ pub struct VaultDelta {
 pub struct VaultWithdraw {
     pub vault: Address,
     pub user: Address,
-    pub requested_usd: String,
-    pub commission: String,
-    pub closing_cost: String,
-    pub basis: String,
-    pub net_withdrawn_usd: String,
+    pub requested_usd: Decimal,
+    pub commission: Decimal,
+    pub closing_cost: Decimal,
+    pub basis: Decimal,
+    pub net_withdrawn_usd: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct VaultLeaderCommission {
     pub user: Address,
-    pub usdc: String,
+    pub usdc: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountClassTransfer {
-    pub usdc: String,
+    pub usdc: Decimal,
     pub to_perp: bool,
 }
 
@@ -261,17 +262,17 @@ pub struct AccountClassTransfer {
 #[serde(rename_all = "camelCase")]
 pub struct SpotTransfer {
     pub token: String,
-    pub amount: String,
-    pub usdc_value: String,
+    pub amount: Decimal,
+    pub usdc_value: Decimal,
     pub user: Address,
     pub destination: Address,
-    pub fee: String,
+    pub fee: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SpotGenesis {
     pub token: String,
-    pub amount: String,
+    pub amount: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -303,10 +304,10 @@ pub enum AssetCtx {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SharedAssetCtx {
-    pub day_ntl_vlm: String,
-    pub prev_day_px: String,
-    pub mark_px: String,
-    pub mid_px: Option<String>,
+    pub day_ntl_vlm: Decimal,
+    pub prev_day_px: Decimal,
+    pub mark_px: Decimal,
+    pub mid_px: Option<Decimal>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -314,9 +315,9 @@ pub struct SharedAssetCtx {
 pub struct PerpsAssetCtx {
     #[serde(flatten)]
     pub shared: SharedAssetCtx,
-    pub funding: String,
-    pub open_interest: String,
-    pub oracle_px: String,
+    pub funding: Decimal,
+    pub open_interest: Decimal,
+    pub oracle_px: Decimal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -331,7 +332,7 @@ pub struct ActiveSpotAssetCtxData {
 pub struct SpotAssetCtx {
     #[serde(flatten)]
     pub shared: SharedAssetCtx,
-    pub circulating_supply: String,
+    pub circulating_supply: Decimal,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -340,8 +341,8 @@ pub struct ActiveAssetDataData {
     pub user: Address,
     pub coin: String,
     pub leverage: Leverage,
-    pub max_trade_szs: Vec<String>,
-    pub available_to_trade: Vec<String>,
+    pub max_trade_szs: Vec<Decimal>,
+    pub available_to_trade: Vec<Decimal>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
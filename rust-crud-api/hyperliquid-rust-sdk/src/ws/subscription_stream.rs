@@ -0,0 +1,247 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use futures_util::{task::AtomicWaker, Stream};
+
+use crate::Message;
+
+/// What a subscription does with a message that arrives while its buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Drop the oldest buffered message to make room.
+    #[default]
+    DropOldest,
+    /// Drop everything buffered and keep only the new message. Suits channels
+    /// where each message supersedes the last, such as `L2Book` or `AllMids`.
+    KeepLatest,
+    /// End the stream and remove the subscription.
+    Disconnect,
+}
+
+/// Buffer settings for [`InfoClient::subscribe_stream`](crate::InfoClient::subscribe_stream).
+#[derive(Debug, Clone, Copy)]
+pub struct StreamConfig {
+    /// Messages buffered before `overflow` applies; at least 1.
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buffer {
+    messages: VecDeque<Message>,
+    closed: bool,
+}
+
+/// Bounded buffer between the websocket reader and one subscriber. Pushing
+/// never waits, so a slow subscriber can't hold up the others.
+#[derive(Debug)]
+pub(crate) struct SubscriptionQueue {
+    config: StreamConfig,
+    buffer: Mutex<Buffer>,
+    waker: AtomicWaker,
+    lagged: AtomicU64,
+}
+
+impl SubscriptionQueue {
+    pub(crate) fn new(config: StreamConfig) -> Self {
+        let config = StreamConfig {
+            capacity: config.capacity.max(1),
+            ..config
+        };
+        Self {
+            buffer: Mutex::new(Buffer {
+                messages: VecDeque::with_capacity(config.capacity.min(64)),
+                closed: false,
+            }),
+            config,
+            waker: AtomicWaker::new(),
+            lagged: AtomicU64::new(0),
+        }
+    }
+
+    /// Buffers `message`, applying the overflow policy. Returns `false` once
+    /// the queue is closed and the subscription should be removed.
+    pub(crate) fn push(&self, message: Message) -> bool {
+        let mut buffer = self.lock();
+        if buffer.closed {
+            return false;
+        }
+        if buffer.messages.len() >= self.config.capacity {
+            match self.config.overflow {
+                OverflowPolicy::DropOldest => {
+                    buffer.messages.pop_front();
+                    self.lagged.fetch_add(1, Ordering::Relaxed);
+                }
+                OverflowPolicy::KeepLatest => {
+                    let dropped = buffer.messages.len() as u64;
+                    buffer.messages.clear();
+                    self.lagged.fetch_add(dropped, Ordering::Relaxed);
+                }
+                OverflowPolicy::Disconnect => {
+                    buffer.closed = true;
+                    self.lagged.fetch_add(1, Ordering::Relaxed);
+                    drop(buffer);
+                    self.waker.wake();
+                    return false;
+                }
+            }
+        }
+        buffer.messages.push_back(message);
+        drop(buffer);
+        self.waker.wake();
+        true
+    }
+
+    /// Ends the stream once the buffered messages are consumed.
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.waker.wake();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buffer> {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Messages of one subscription, from
+/// [`InfoClient::subscribe_stream`](crate::InfoClient::subscribe_stream).
+///
+/// Ends when the subscription is removed, or when it overflows under
+/// [`OverflowPolicy::Disconnect`]. Dropping the stream ends the subscription.
+#[derive(Debug)]
+pub struct SubscriptionStream {
+    subscription_id: u32,
+    queue: Arc<SubscriptionQueue>,
+}
+
+impl SubscriptionStream {
+    pub(crate) fn new(subscription_id: u32, queue: Arc<SubscriptionQueue>) -> Self {
+        Self {
+            subscription_id,
+            queue,
+        }
+    }
+
+    /// Id to pass to [`InfoClient::unsubscribe`](crate::InfoClient::unsubscribe).
+    pub fn subscription_id(&self) -> u32 {
+        self.subscription_id
+    }
+
+    /// Messages dropped so far because the subscriber fell behind.
+    pub fn lagged(&self) -> u64 {
+        self.queue.lagged.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for SubscriptionStream {
+    type Item = Message;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Message>> {
+        let queue = &self.queue;
+        // Registered before checking, so a push in between still wakes us
+        queue.waker.register(cx.waker());
+        let mut buffer = queue.lock();
+        match buffer.messages.pop_front() {
+            Some(message) => Poll::Ready(Some(message)),
+            None if buffer.closed => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for SubscriptionStream {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{FutureExt, StreamExt};
+
+    use super::*;
+
+    fn error(n: usize) -> Message {
+        Message::HyperliquidError(n.to_string())
+    }
+
+    fn drain(stream: &mut SubscriptionStream) -> Vec<String> {
+        let mut received = Vec::new();
+        while let Some(Some(message)) = stream.next().now_or_never() {
+            match message {
+                Message::HyperliquidError(n) => received.push(n),
+                message => panic!("unexpected {message:?}"),
+            }
+        }
+        received
+    }
+
+    fn stream_with(overflow: OverflowPolicy) -> (Arc<SubscriptionQueue>, SubscriptionStream) {
+        let queue = Arc::new(SubscriptionQueue::new(StreamConfig {
+            capacity: 2,
+            overflow,
+        }));
+        (queue.clone(), SubscriptionStream::new(7, queue))
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (queue, mut stream) = stream_with(OverflowPolicy::DropOldest);
+        assert!((0..5).all(|n| queue.push(error(n))));
+        assert_eq!(drain(&mut stream), ["3", "4"]);
+        assert_eq!(stream.lagged(), 3);
+    }
+
+    #[test]
+    fn test_keep_latest() {
+        let (queue, mut stream) = stream_with(OverflowPolicy::KeepLatest);
+        assert!((0..5).all(|n| queue.push(error(n))));
+        assert_eq!(drain(&mut stream), ["4"]);
+        assert_eq!(stream.lagged(), 4);
+    }
+
+    #[test]
+    fn test_disconnect_ends_stream_after_buffered_messages() {
+        let (queue, mut stream) = stream_with(OverflowPolicy::Disconnect);
+        assert!(queue.push(error(0)) && queue.push(error(1)));
+        assert!(!queue.push(error(2)));
+        assert!(queue.is_closed());
+        assert_eq!(drain(&mut stream), ["0", "1"]);
+        assert!(matches!(stream.next().now_or_never(), Some(None)));
+        assert_eq!(stream.lagged(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wakes_pending_consumer_and_closes_on_drop() {
+        let (queue, mut stream) = stream_with(OverflowPolicy::DropOldest);
+        let producer = queue.clone();
+        tokio::spawn(async move { producer.push(error(0)) });
+        assert!(matches!(
+            stream.next().await,
+            Some(Message::HyperliquidError(n)) if n == "0"
+        ));
+
+        drop(stream);
+        assert!(!queue.push(error(1)));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_util::Stream;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{
+    ws::sub_structs::*, Disconnected, Message, Reconnected, Subscription, SubscriptionStream,
+};
+
+/// Payload of a subscription channel, tied to the [`Subscription`] variant
+/// that delivers it.
+pub trait ChannelData: Sized + Send + 'static {
+    /// Whether `subscription` delivers this type.
+    fn accepts(subscription: &Subscription) -> bool;
+
+    /// The payload, if `message` carries this type.
+    fn from_message(message: Message) -> Option<Self>;
+}
+
+macro_rules! channel_data {
+    ($data:ty, $subscription:pat, $message:ident) => {
+        impl ChannelData for $data {
+            fn accepts(subscription: &Subscription) -> bool {
+                matches!(subscription, $subscription)
+            }
+
+            fn from_message(message: Message) -> Option<Self> {
+                match message {
+                    Message::$message(message) => Some(message.data),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+channel_data!(AllMidsData, Subscription::AllMids, AllMids);
+channel_data!(
+    NotificationData,
+    Subscription::Notification { .. },
+    Notification
+);
+channel_data!(WebData2Data, Subscription::WebData2 { .. }, WebData2);
+channel_data!(CandleData, Subscription::Candle { .. }, Candle);
+channel_data!(L2BookData, Subscription::L2Book { .. }, L2Book);
+channel_data!(Vec<Trade>, Subscription::Trades { .. }, Trades);
+channel_data!(
+    Vec<OrderUpdate>,
+    Subscription::OrderUpdates { .. },
+    OrderUpdates
+);
+channel_data!(UserData, Subscription::UserEvents { .. }, User);
+channel_data!(UserFillsData, Subscription::UserFills { .. }, UserFills);
+channel_data!(
+    UserFundingsData,
+    Subscription::UserFundings { .. },
+    UserFundings
+);
+channel_data!(
+    UserNonFundingLedgerUpdatesData,
+    Subscription::UserNonFundingLedgerUpdates { .. },
+    UserNonFundingLedgerUpdates
+);
+// Perp and spot contexts share the subscription; the coin decides which arrives
+channel_data!(
+    ActiveAssetCtxData,
+    Subscription::ActiveAssetCtx { .. },
+    ActiveAssetCtx
+);
+channel_data!(
+    ActiveSpotAssetCtxData,
+    Subscription::ActiveAssetCtx { .. },
+    ActiveSpotAssetCtx
+);
+channel_data!(
+    ActiveAssetDataData,
+    Subscription::ActiveAssetData { .. },
+    ActiveAssetData
+);
+channel_data!(BboData, Subscription::Bbo { .. }, Bbo);
+
+/// Connection events and errors of a typed subscription, kept apart from its data.
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent {
+    Disconnected(Disconnected),
+    Reconnected(Reconnected),
+    Error(String),
+}
+
+impl SubscriptionEvent {
+    /// The event `message` carries, or `None` for data messages.
+    pub(crate) fn from_message(message: &Message) -> Option<Self> {
+        match message {
+            Message::Disconnected(disconnected) => Some(Self::Disconnected(disconnected.clone())),
+            Message::Reconnected(reconnected) => Some(Self::Reconnected(reconnected.clone())),
+            Message::HyperliquidError(err) => Some(Self::Error(err.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Payloads of one subscription, e.g. [`L2BookData`] for `Subscription::L2Book`.
+/// Buffers and ends like [`SubscriptionStream`].
+#[derive(Debug)]
+pub struct TypedStream<T> {
+    inner: SubscriptionStream,
+    _data: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedStream<T> {
+    pub(crate) fn new(inner: SubscriptionStream) -> Self {
+        Self {
+            inner,
+            _data: PhantomData,
+        }
+    }
+
+    /// Id to pass to [`InfoClient::unsubscribe`](crate::InfoClient::unsubscribe).
+    pub fn subscription_id(&self) -> u32 {
+        self.inner.subscription_id()
+    }
+
+    /// Messages dropped so far because the subscriber fell behind.
+    pub fn lagged(&self) -> u64 {
+        self.inner.lagged()
+    }
+}
+
+impl<T: ChannelData> Stream for TypedStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+                Some(message) => {
+                    // Anything else shares the channel identifier but not the type,
+                    // e.g. a spot context on a perp `ActiveAssetCtx` stream
+                    if let Some(data) = T::from_message(message) {
+                        return Poll::Ready(Some(data));
+                    }
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A subscription from [`InfoClient::subscribe_typed`](crate::InfoClient::subscribe_typed).
+#[derive(Debug)]
+pub struct TypedSubscription<T> {
+    pub data: TypedStream<T>,
+    /// Closed once the subscription is removed.
+    pub events: UnboundedReceiver<SubscriptionEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_data_matches_subscription_and_message() {
+        let l2_book = Subscription::L2Book {
+            coin: "ETH".to_string(),
+        };
+        assert!(L2BookData::accepts(&l2_book));
+        assert!(!UserFillsData::accepts(&l2_book));
+        assert!(ActiveSpotAssetCtxData::accepts(
+            &Subscription::ActiveAssetCtx {
+                coin: "@107".to_string(),
+            }
+        ));
+
+        let message: Message = serde_json::from_value(serde_json::json!({
+            "channel": "l2Book",
+            "data": {"coin": "ETH", "time": 1, "levels": [[], []]}
+        }))
+        .unwrap();
+        assert_eq!(
+            L2BookData::from_message(message.clone()).unwrap().coin,
+            "ETH"
+        );
+        assert!(AllMidsData::from_message(message.clone()).is_none());
+        assert!(SubscriptionEvent::from_message(&message).is_none());
+        assert!(matches!(
+            SubscriptionEvent::from_message(&Message::HyperliquidError("oops".to_string())),
+            Some(SubscriptionEvent::Error(_))
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     borrow::BorrowMut,
-    collections::HashMap,
-    ops::DerefMut,
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, Hasher, RandomState},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -10,14 +10,17 @@ use std::{
 };
 
 use alloy::primitives::Address;
-use futures_util::{stream::SplitSink, SinkExt, StreamExt};
+use futures_util::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use tokio::{
     net::TcpStream,
     spawn,
     sync::{mpsc::UnboundedSender, Mutex},
-    time,
+    time::{self, Instant},
 };
 use tokio_tungstenite::{
     connect_async,
@@ -31,21 +34,115 @@ use crate::{
         ActiveAssetData, ActiveSpotAssetCtx, AllMids, Bbo, Candle, L2Book, OrderUpdates, Trades,
         User,
     },
-    ActiveAssetCtx, Error, Notification, UserFills, UserFundings, UserNonFundingLedgerUpdates,
-    WebData2,
+    ws::{SubscriptionEvent, SubscriptionQueue},
+    ActiveAssetCtx, Disconnected, Error, Notification, Reconnected, Resubscription, UserFills,
+    UserFundings, UserNonFundingLedgerUpdates, WebData2,
 };
 
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsWriter = SplitSink<WsStream, protocol::Message>;
+type WsReader = SplitStream<WsStream>;
+type Subscriptions = Arc<Mutex<HashMap<String, Vec<SubscriptionData>>>>;
+
+/// Reconnection and liveness settings for the websocket connection.
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+    /// Reconnect when the connection is lost instead of stopping.
+    pub reconnect: bool,
+    /// Delay before the first reconnection attempt, doubled after each failure.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub ping_interval: Duration,
+    /// How long a ping may go unanswered before the connection is treated as lost.
+    pub pong_timeout: Duration,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            reconnect: false,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            // The server closes connections that are idle for 60 seconds
+            ping_interval: Duration::from_secs(50),
+            pong_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Exponential backoff with equal jitter: each delay is drawn from the upper
+/// half of the current step, so clients that lost the same server don't all
+/// reconnect at once.
+#[derive(Debug)]
+struct Backoff {
+    step: Duration,
+    max: Duration,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Self { step: initial, max }
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let step = self.step.min(self.max);
+        self.step = step.saturating_mul(2);
+        let half = step / 2;
+        half + half.mul_f64(jitter())
+    }
+}
+
+/// Uniform in `[0, 1)`, seeded by the randomly keyed std hasher.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Where a subscription's messages go.
+#[derive(Debug, Clone)]
+pub(crate) enum SubscriptionSink {
+    Channel(UnboundedSender<Message>),
+    Stream(Arc<SubscriptionQueue>),
+    /// Data is buffered in `data`; events and errors go to `events`.
+    Typed {
+        data: Arc<SubscriptionQueue>,
+        events: UnboundedSender<SubscriptionEvent>,
+    },
+}
+
+impl SubscriptionSink {
+    fn is_closed(&self) -> bool {
+        match self {
+            SubscriptionSink::Channel(_) => false,
+            SubscriptionSink::Stream(queue) | SubscriptionSink::Typed { data: queue, .. } => {
+                queue.is_closed()
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct SubscriptionData {
-    sending_channel: UnboundedSender<Message>,
+    sink: SubscriptionSink,
     subscription_id: u32,
     id: String,
 }
+
+impl Drop for SubscriptionData {
+    fn drop(&mut self) {
+        // Ends the stream once the subscription is gone, like dropping a channel sender
+        if let SubscriptionSink::Stream(queue) | SubscriptionSink::Typed { data: queue, .. } =
+            &self.sink
+        {
+            queue.close();
+        }
+    }
+}
 #[derive(Debug)]
 pub(crate) struct WsManager {
     stop_flag: Arc<AtomicBool>,
-    writer: Arc<Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, protocol::Message>>>,
-    subscriptions: Arc<Mutex<HashMap<String, Vec<SubscriptionData>>>>,
+    writer: Arc<Mutex<WsWriter>>,
+    subscriptions: Subscriptions,
     subscription_id: u32,
     subscription_identifiers: HashMap<u32, String>,
 }
@@ -74,7 +171,12 @@ pub enum Subscription {
 #[serde(tag = "channel")]
 #[serde(rename_all = "camelCase")]
 pub enum Message {
-    NoData,
+    /// The connection was lost; messages may be missed until `Reconnected`.
+    #[serde(skip)]
+    Disconnected(Disconnected),
+    /// A new connection is up and subscriptions were sent again.
+    #[serde(skip)]
+    Reconnected(Reconnected),
     HyperliquidError(String),
     AllMids(AllMids),
     Trades(Trades),
@@ -107,109 +209,70 @@ pub(crate) struct Ping {
 }
 
 impl WsManager {
-    const SEND_PING_INTERVAL: u64 = 50;
-
-    pub(crate) async fn new(url: String, reconnect: bool) -> Result<WsManager> {
+    pub(crate) async fn new(url: String, config: WsConfig) -> Result<WsManager> {
         let stop_flag = Arc::new(AtomicBool::new(false));
 
-        let (writer, mut reader) = Self::connect(&url).await?.split();
+        let (writer, reader) = Self::connect(&url).await?.split();
         let writer = Arc::new(Mutex::new(writer));
 
         let subscriptions_map: HashMap<String, Vec<SubscriptionData>> = HashMap::new();
         let subscriptions = Arc::new(Mutex::new(subscriptions_map));
-        let subscriptions_copy = Arc::clone(&subscriptions);
 
         {
-            let writer = writer.clone();
+            let writer = Arc::clone(&writer);
+            let subscriptions = Arc::clone(&subscriptions);
             let stop_flag = Arc::clone(&stop_flag);
             let reader_fut = async move {
-                while !stop_flag.load(Ordering::Relaxed) {
-                    if let Some(data) = reader.next().await {
-                        if let Err(err) =
-                            WsManager::parse_and_send_data(data, &subscriptions_copy).await
-                        {
-                            error!("Error processing data received by WsManager reader: {err}");
-                        }
-                    } else {
-                        warn!("WsManager disconnected");
-                        if let Err(err) = WsManager::send_to_all_subscriptions(
-                            &subscriptions_copy,
-                            Message::NoData,
-                        )
-                        .await
-                        {
-                            warn!("Error sending disconnection notification err={err}");
-                        }
-                        if reconnect {
-                            // Always sleep for 1 second before attempting to reconnect so it does not spin during reconnecting. This could be enhanced with exponential backoff.
-                            tokio::time::sleep(Duration::from_secs(1)).await;
-                            info!("WsManager attempting to reconnect");
-                            match Self::connect(&url).await {
-                                Ok(ws) => {
-                                    let (new_writer, new_reader) = ws.split();
-                                    reader = new_reader;
-                                    let mut writer_guard = writer.lock().await;
-                                    *writer_guard = new_writer;
-                                    for (identifier, v) in subscriptions_copy.lock().await.iter() {
-                                        // TODO should these special keys be removed and instead use the simpler direct identifier mapping?
-                                        if identifier.eq("userEvents")
-                                            || identifier.eq("orderUpdates")
-                                        {
-                                            for subscription_data in v {
-                                                if let Err(err) = Self::subscribe(
-                                                    writer_guard.deref_mut(),
-                                                    &subscription_data.id,
-                                                )
-                                                .await
-                                                {
-                                                    error!(
-                                                        "Could not resubscribe {identifier}: {err}"
-                                                    );
-                                                }
-                                            }
-                                        } else if let Err(err) =
-                                            Self::subscribe(writer_guard.deref_mut(), identifier)
-                                                .await
-                                        {
-                                            error!("Could not resubscribe correctly {identifier}: {err}");
-                                        }
-                                    }
-                                    info!("WsManager reconnect finished");
-                                }
-                                Err(err) => error!("Could not connect to websocket {err}"),
-                            }
-                        } else {
-                            error!("WsManager reconnection disabled. Will not reconnect and exiting reader task.");
-                            break;
-                        }
+                let mut reader = reader;
+                while let Some(reason) = Self::read_until_disconnected(
+                    &mut reader,
+                    &writer,
+                    &subscriptions,
+                    &config,
+                    &stop_flag,
+                )
+                .await
+                {
+                    let disconnected_at = Instant::now();
+                    warn!("WsManager disconnected: {reason}");
+                    Self::notify_all(
+                        &subscriptions,
+                        &writer,
+                        Message::Disconnected(Disconnected {
+                            reason,
+                            reconnecting: config.reconnect,
+                        }),
+                    )
+                    .await;
+                    if !config.reconnect {
+                        error!("WsManager reconnection disabled. Will not reconnect and exiting reader task.");
+                        break;
                     }
+
+                    let Some((new_reader, attempts)) =
+                        Self::reconnect(&url, &writer, &config, &stop_flag).await
+                    else {
+                        break;
+                    };
+                    reader = new_reader;
+                    let resubscriptions = Self::resubscribe(&writer, &subscriptions).await;
+                    info!("WsManager reconnect finished");
+                    Self::notify_all(
+                        &subscriptions,
+                        &writer,
+                        Message::Reconnected(Reconnected {
+                            attempts,
+                            downtime: disconnected_at.elapsed(),
+                            resubscriptions,
+                        }),
+                    )
+                    .await;
                 }
                 warn!("ws message reader task stopped");
             };
             spawn(reader_fut);
         }
 
-        {
-            let stop_flag = Arc::clone(&stop_flag);
-            let writer = Arc::clone(&writer);
-            let ping_fut = async move {
-                while !stop_flag.load(Ordering::Relaxed) {
-                    match serde_json::to_string(&Ping { method: "ping" }) {
-                        Ok(payload) => {
-                            let mut writer = writer.lock().await;
-                            if let Err(err) = writer.send(protocol::Message::Text(payload)).await {
-                                error!("Error pinging server: {err}")
-                            }
-                        }
-                        Err(err) => error!("Error serializing ping message: {err}"),
-                    }
-                    time::sleep(Duration::from_secs(Self::SEND_PING_INTERVAL)).await;
-                }
-                warn!("ws ping task stopped");
-            };
-            spawn(ping_fut);
-        }
-
         Ok(WsManager {
             stop_flag,
             writer,
@@ -219,13 +282,146 @@ impl WsManager {
         })
     }
 
-    async fn connect(url: &str) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    async fn connect(url: &str) -> Result<WsStream> {
         Ok(connect_async(url)
             .await
             .map_err(|e| Error::Websocket(e.to_string()))?
             .0)
     }
 
+    /// Forwards messages to subscribers and pings the server until the
+    /// connection drops or stops answering. Returns why it was considered
+    /// lost, or `None` once the manager is dropped.
+    async fn read_until_disconnected(
+        reader: &mut WsReader,
+        writer: &Mutex<WsWriter>,
+        subscriptions: &Subscriptions,
+        config: &WsConfig,
+        stop_flag: &AtomicBool,
+    ) -> Option<String> {
+        let mut next_ping = Instant::now() + config.ping_interval;
+        let mut pong_deadline: Option<Instant> = None;
+
+        while !stop_flag.load(Ordering::Relaxed) {
+            let wake_at = pong_deadline.map_or(next_ping, |deadline| deadline.min(next_ping));
+            tokio::select! {
+                data = reader.next() => match data {
+                    None => return Some("connection closed".to_string()),
+                    Some(Err(
+                        err @ (tungstenite::Error::ConnectionClosed
+                        | tungstenite::Error::AlreadyClosed
+                        | tungstenite::Error::Io(_)
+                        | tungstenite::Error::Protocol(_)),
+                    )) => return Some(err.to_string()),
+                    Some(data) => {
+                        // Any frame shows the connection is alive, not only the pong
+                        pong_deadline = None;
+                        if let Err(err) =
+                            WsManager::parse_and_send_data(data, subscriptions, writer).await
+                        {
+                            error!("Error processing data received by WsManager reader: {err}");
+                        }
+                    }
+                },
+                _ = time::sleep_until(wake_at) => {
+                    let now = Instant::now();
+                    if pong_deadline.is_some_and(|deadline| now >= deadline) {
+                        return Some(format!("no pong within {:?}", config.pong_timeout));
+                    }
+                    if now >= next_ping {
+                        if let Err(err) = Self::ping(&mut *writer.lock().await).await {
+                            return Some(format!("could not ping server: {err}"));
+                        }
+                        pong_deadline.get_or_insert(now + config.pong_timeout);
+                        next_ping = now + config.ping_interval;
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    async fn ping(writer: &mut WsWriter) -> Result<()> {
+        let payload = serde_json::to_string(&Ping { method: "ping" })
+            .map_err(|e| Error::JsonParse(e.to_string()))?;
+        writer
+            .send(protocol::Message::Text(payload))
+            .await
+            .map_err(|e| Error::Websocket(e.to_string()))
+    }
+
+    /// Connects again with exponential backoff, swapping in the new writer.
+    /// Returns the new reader and the number of attempts it took, or `None`
+    /// if the manager was dropped meanwhile.
+    async fn reconnect(
+        url: &str,
+        writer: &Mutex<WsWriter>,
+        config: &WsConfig,
+        stop_flag: &AtomicBool,
+    ) -> Option<(WsReader, u32)> {
+        let mut backoff = Backoff::new(config.initial_backoff, config.max_backoff);
+        let mut attempts = 0;
+        while !stop_flag.load(Ordering::Relaxed) {
+            attempts += 1;
+            let delay = backoff.next_delay();
+            info!("WsManager attempting to reconnect in {delay:?} (attempt {attempts})");
+            time::sleep(delay).await;
+            match Self::connect(url).await {
+                Ok(ws) => {
+                    let (new_writer, new_reader) = ws.split();
+                    *writer.lock().await = new_writer;
+                    return Some((new_reader, attempts));
+                }
+                Err(err) => error!("Could not connect to websocket {err}"),
+            }
+        }
+        None
+    }
+
+    /// Sends every active subscription on the new connection.
+    async fn resubscribe(
+        writer: &Mutex<WsWriter>,
+        subscriptions: &Subscriptions,
+    ) -> Vec<Resubscription> {
+        // Collected first so the subscriptions lock is never held while
+        // waiting for the writer, which `add_subscription` takes in the other order
+        let mut identifiers = Vec::new();
+        for (identifier, v) in subscriptions.lock().await.iter() {
+            // TODO should these special keys be removed and instead use the simpler direct identifier mapping?
+            if identifier.eq("userEvents") || identifier.eq("orderUpdates") {
+                identifiers.extend(
+                    v.iter()
+                        .map(|subscription_data| subscription_data.id.clone()),
+                );
+            } else if !v.is_empty() {
+                identifiers.push(identifier.clone());
+            }
+        }
+
+        let mut writer = writer.lock().await;
+        let mut resubscriptions = Vec::with_capacity(identifiers.len());
+        for subscription in identifiers {
+            let error = match Self::subscribe(&mut writer, &subscription).await {
+                Ok(()) => None,
+                Err(err) => {
+                    error!("Could not resubscribe {subscription}: {err}");
+                    Some(err.to_string())
+                }
+            };
+            resubscriptions.push(Resubscription {
+                subscription,
+                error,
+            });
+        }
+        resubscriptions
+    }
+
+    async fn notify_all(subscriptions: &Subscriptions, writer: &Mutex<WsWriter>, message: Message) {
+        if let Err(err) = WsManager::deliver(subscriptions, writer, None, message).await {
+            warn!("Error sending connection event to subscribers err={err}");
+        }
+    }
+
     fn get_identifier(message: &Message) -> Result<String> {
         match message {
             Message::AllMids(_) => serde_json::to_string(&Subscription::AllMids)
@@ -294,14 +490,15 @@ impl WsManager {
             })
             .map_err(|e| Error::JsonParse(e.to_string())),
             Message::SubscriptionResponse | Message::Pong => Ok(String::default()),
-            Message::NoData => Ok("".to_string()),
+            Message::Disconnected(_) | Message::Reconnected(_) => Ok(String::default()),
             Message::HyperliquidError(err) => Ok(format!("hyperliquid error: {err:?}")),
         }
     }
 
     async fn parse_and_send_data(
         data: std::result::Result<protocol::Message, tungstenite::Error>,
-        subscriptions: &Arc<Mutex<HashMap<String, Vec<SubscriptionData>>>>,
+        subscriptions: &Subscriptions,
+        writer: &Mutex<WsWriter>,
     ) -> Result<()> {
         match data {
             Ok(data) => match data.into_text() {
@@ -315,65 +512,124 @@ impl WsManager {
                     if identifier.is_empty() {
                         return Ok(());
                     }
-
-                    let mut subscriptions = subscriptions.lock().await;
-                    let mut res = Ok(());
-                    if let Some(subscription_datas) = subscriptions.get_mut(&identifier) {
-                        for subscription_data in subscription_datas {
-                            if let Err(e) = subscription_data
-                                .sending_channel
-                                .send(message.clone())
-                                .map_err(|e| Error::WsSend(e.to_string()))
-                            {
-                                res = Err(e);
-                            }
-                        }
-                    }
-                    res
+                    WsManager::deliver(subscriptions, writer, Some(&identifier), message).await
                 }
                 Err(err) => {
                     let error = Error::ReaderTextConversion(err.to_string());
-                    Ok(WsManager::send_to_all_subscriptions(
+                    WsManager::deliver(
                         subscriptions,
+                        writer,
+                        None,
                         Message::HyperliquidError(error.to_string()),
                     )
-                    .await?)
+                    .await
                 }
             },
             Err(err) => {
                 let error = Error::GenericReader(err.to_string());
-                Ok(WsManager::send_to_all_subscriptions(
+                WsManager::deliver(
                     subscriptions,
+                    writer,
+                    None,
                     Message::HyperliquidError(error.to_string()),
                 )
-                .await?)
+                .await
             }
         }
     }
 
-    async fn send_to_all_subscriptions(
-        subscriptions: &Arc<Mutex<HashMap<String, Vec<SubscriptionData>>>>,
+    /// Sends `message` to the subscribers of `identifier`, or to all of them.
+    /// The sinks are cloned out so the lock isn't held while delivering, and
+    /// streams found closed are removed afterwards.
+    async fn deliver(
+        subscriptions: &Subscriptions,
+        writer: &Mutex<WsWriter>,
+        identifier: Option<&str>,
         message: Message,
     ) -> Result<()> {
-        let mut subscriptions = subscriptions.lock().await;
+        let sinks: Vec<SubscriptionSink> = {
+            let subscriptions = subscriptions.lock().await;
+            let subscription_datas: Vec<&SubscriptionData> = match identifier {
+                Some(identifier) => subscriptions
+                    .get(identifier)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                None => subscriptions.values().flatten().collect(),
+            };
+            subscription_datas
+                .into_iter()
+                .map(|subscription_data| subscription_data.sink.clone())
+                .collect()
+        };
+
         let mut res = Ok(());
-        for subscription_datas in subscriptions.values_mut() {
-            for subscription_data in subscription_datas {
-                if let Err(e) = subscription_data
-                    .sending_channel
-                    .send(message.clone())
-                    .map_err(|e| Error::WsSend(e.to_string()))
-                {
-                    res = Err(e);
+        let mut any_closed = false;
+        for sink in sinks {
+            match sink {
+                SubscriptionSink::Channel(sender) => {
+                    if let Err(e) = sender.send(message.clone()) {
+                        res = Err(Error::WsSend(e.to_string()));
+                    }
+                }
+                SubscriptionSink::Stream(queue) => any_closed |= !queue.push(message.clone()),
+                SubscriptionSink::Typed { data, events } => {
+                    match SubscriptionEvent::from_message(&message) {
+                        // Nobody listening for events is fine
+                        Some(event) => drop(events.send(event)),
+                        None => any_closed |= !data.push(message.clone()),
+                    }
                 }
             }
         }
+        if any_closed {
+            Self::remove_closed_streams(subscriptions, writer).await;
+        }
         res
     }
 
+    /// Drops subscriptions whose stream was dropped or disconnected for
+    /// falling behind, unsubscribing when no subscriber is left.
+    async fn remove_closed_streams(subscriptions: &Subscriptions, writer: &Mutex<WsWriter>) {
+        let mut unsubscribe = Vec::new();
+        for subscription_datas in subscriptions.lock().await.values_mut() {
+            let mut removed = None;
+            subscription_datas.retain(|subscription_data| {
+                let closed = subscription_data.sink.is_closed();
+                if closed {
+                    removed = Some(subscription_data.id.clone());
+                }
+                !closed
+            });
+            if let Some(identifier) = removed.filter(|_| subscription_datas.is_empty()) {
+                unsubscribe.push(identifier);
+            }
+        }
+
+        let mut writer = writer.lock().await;
+        for identifier in unsubscribe {
+            if let Err(err) = Self::unsubscribe(&mut writer, &identifier).await {
+                warn!("Could not unsubscribe closed stream {identifier}: {err}");
+            }
+        }
+    }
+
+    /// Forgets ids whose stream was closed and removed by the reader task.
+    fn prune_identifiers(
+        identifiers: &mut HashMap<u32, String>,
+        subscriptions: &HashMap<String, Vec<SubscriptionData>>,
+    ) {
+        let live: HashSet<u32> = subscriptions
+            .values()
+            .flatten()
+            .map(|subscription_data| subscription_data.subscription_id)
+            .collect();
+        identifiers.retain(|subscription_id, _| live.contains(subscription_id));
+    }
+
     async fn send_subscription_data(
         method: &'static str,
-        writer: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, protocol::Message>,
+        writer: &mut WsWriter,
         identifier: &str,
     ) -> Result<()> {
         let payload = serde_json::to_string(&SubscriptionSendData {
@@ -390,26 +646,21 @@ impl WsManager {
         Ok(())
     }
 
-    async fn subscribe(
-        writer: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, protocol::Message>,
-        identifier: &str,
-    ) -> Result<()> {
+    async fn subscribe(writer: &mut WsWriter, identifier: &str) -> Result<()> {
         Self::send_subscription_data("subscribe", writer, identifier).await
     }
 
-    async fn unsubscribe(
-        writer: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, protocol::Message>,
-        identifier: &str,
-    ) -> Result<()> {
+    async fn unsubscribe(writer: &mut WsWriter, identifier: &str) -> Result<()> {
         Self::send_subscription_data("unsubscribe", writer, identifier).await
     }
 
     pub(crate) async fn add_subscription(
         &mut self,
         identifier: String,
-        sending_channel: UnboundedSender<Message>,
+        sink: SubscriptionSink,
     ) -> Result<u32> {
         let mut subscriptions = self.subscriptions.lock().await;
+        Self::prune_identifiers(&mut self.subscription_identifiers, &subscriptions);
 
         let identifier_entry = if let Subscription::UserEvents { user: _ } =
             serde_json::from_str::<Subscription>(&identifier)
@@ -440,7 +691,7 @@ impl WsManager {
         self.subscription_identifiers
             .insert(subscription_id, identifier.clone());
         subscriptions.push(SubscriptionData {
-            sending_channel,
+            sink,
             subscription_id,
             id: identifier,
         });
@@ -474,13 +725,16 @@ impl WsManager {
 
         let mut subscriptions = self.subscriptions.lock().await;
 
-        let subscriptions = subscriptions
-            .get_mut(&identifier_entry)
-            .ok_or(Error::SubscriptionNotFound)?;
-        let index = subscriptions
+        let Some(subscriptions) = subscriptions.get_mut(&identifier_entry) else {
+            return Ok(());
+        };
+        // Closed streams are dropped by the reader task, which also unsubscribes
+        let Some(index) = subscriptions
             .iter()
             .position(|subscription_data| subscription_data.subscription_id == subscription_id)
-            .ok_or(Error::SubscriptionNotFound)?;
+        else {
+            return Ok(());
+        };
         subscriptions.remove(index);
 
         if subscriptions.is_empty() {
@@ -495,3 +749,182 @@ impl Drop for WsManager {
         self.stop_flag.store(true, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::{net::TcpListener, sync::mpsc::unbounded_channel, time::timeout};
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+    use crate::{OverflowPolicy, StreamConfig, SubscriptionStream};
+
+    fn test_config(reconnect: bool) -> WsConfig {
+        WsConfig {
+            reconnect,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            ping_interval: Duration::from_secs(60),
+            pong_timeout: Duration::from_secs(60),
+        }
+    }
+
+    async fn next_message(receiver: &mut tokio::sync::mpsc::UnboundedReceiver<Message>) -> Message {
+        timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("no message within 5s")
+            .expect("channel closed")
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(400));
+        for step in [100, 200, 400, 400] {
+            let delay = backoff.next_delay();
+            let step = Duration::from_millis(step);
+            assert!(
+                delay >= step / 2 && delay <= step,
+                "{delay:?} outside {step:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_and_reports_resubscriptions() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscribed_tx, mut subscribed) = unbounded_channel();
+        tokio::spawn(async move {
+            // The first connection is dropped right after the subscription
+            // arrives, the second one stays open
+            let mut connections = Vec::new();
+            for connection in 0.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                if let Some(Ok(frame)) = ws.next().await {
+                    subscribed_tx.send(frame.into_text().unwrap()).unwrap();
+                }
+                if connection > 0 {
+                    connections.push(ws);
+                }
+            }
+        });
+
+        let mut manager = WsManager::new(url, test_config(true)).await?;
+        let (sender, mut receiver) = unbounded_channel();
+        let subscription = serde_json::to_string(&Subscription::AllMids).unwrap();
+        manager
+            .add_subscription(subscription, SubscriptionSink::Channel(sender))
+            .await?;
+        assert!(subscribed.recv().await.unwrap().contains("allMids"));
+
+        match next_message(&mut receiver).await {
+            Message::Disconnected(disconnected) => assert!(disconnected.reconnecting),
+            message => panic!("expected Disconnected, got {message:?}"),
+        }
+        match next_message(&mut receiver).await {
+            Message::Reconnected(reconnected) => {
+                assert_eq!(reconnected.attempts, 1);
+                assert!(reconnected.all_resubscribed());
+                assert_eq!(reconnected.resubscriptions.len(), 1);
+            }
+            message => panic!("expected Reconnected, got {message:?}"),
+        }
+        assert!(subscribed.recv().await.unwrap().contains("allMids"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_pong_disconnects() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // Reads everything, answers nothing
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let config = WsConfig {
+            ping_interval: Duration::from_millis(50),
+            pong_timeout: Duration::from_millis(100),
+            ..test_config(false)
+        };
+        let mut manager = WsManager::new(url, config).await?;
+        let (sender, mut receiver) = unbounded_channel();
+        let subscription = serde_json::to_string(&Subscription::AllMids).unwrap();
+        manager
+            .add_subscription(subscription, SubscriptionSink::Channel(sender))
+            .await?;
+
+        match next_message(&mut receiver).await {
+            Message::Disconnected(disconnected) => {
+                assert!(disconnected.reason.contains("pong"));
+                assert!(!disconnected.reconnecting);
+            }
+            message => panic!("expected Disconnected, got {message:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_overflowing_stream_is_removed_and_unsubscribed() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (frames_tx, mut frames) = unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            // Answers the subscription with more mids than the stream buffers
+            while let Some(Ok(frame)) = ws.next().await {
+                let frame = frame.into_text().unwrap();
+                if frame.contains("\"subscribe\"") {
+                    for px in ["1800.5", "1801", "1802"] {
+                        let mids = serde_json::json!({"channel": "allMids", "data": {"mids": {"ETH": px}}});
+                        ws.send(protocol::Message::Text(mids.to_string()))
+                            .await
+                            .unwrap();
+                    }
+                }
+                frames_tx.send(frame).unwrap();
+            }
+        });
+
+        let mut manager = WsManager::new(url, test_config(false)).await?;
+        let queue = Arc::new(SubscriptionQueue::new(StreamConfig {
+            capacity: 1,
+            overflow: OverflowPolicy::Disconnect,
+        }));
+        let subscription = serde_json::to_string(&Subscription::AllMids).unwrap();
+        let subscription_id = manager
+            .add_subscription(subscription, SubscriptionSink::Stream(queue.clone()))
+            .await?;
+        let mut stream = SubscriptionStream::new(subscription_id, queue);
+
+        assert!(frames.recv().await.unwrap().contains("\"subscribe\""));
+        let unsubscribed = timeout(Duration::from_secs(5), frames.recv())
+            .await
+            .unwrap();
+        assert!(unsubscribed.unwrap().contains("\"unsubscribe\""));
+        assert!(matches!(stream.next().await, Some(Message::AllMids(_))));
+        assert!(stream.next().await.is_none());
+        assert_eq!(stream.lagged(), 1);
+
+        // The closed stream's id is forgotten on the next subscription
+        let (sender, _receiver) = unbounded_channel();
+        let trades = serde_json::to_string(&Subscription::Trades {
+            coin: "ETH".to_string(),
+        })
+        .unwrap();
+        manager
+            .add_subscription(trades, SubscriptionSink::Channel(sender))
+            .await?;
+        assert!(!manager
+            .subscription_identifiers
+            .contains_key(&subscription_id));
+        assert!(manager.remove_subscription(subscription_id).await.is_err());
+
+        Ok(())
+    }
+}
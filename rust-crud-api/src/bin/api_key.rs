@@ -0,0 +1,69 @@
+use rust_crud_api::config::Config;
+use rust_crud_api::database::create_pool;
+use rust_crud_api::models::CreateApiKeyRequest;
+use rust_crud_api::services::ApiKeyService;
+use std::env;
+use uuid::Uuid;
+
+fn print_usage() {
+    println!("❌ 用法：");
+    println!("  cargo run --bin api_key create <user_id> <name> <scope,scope,...>   # 创建 API Key（可授予 withdrawals:approve）");
+    println!("  cargo run --bin api_key revoke <user_id> <key_id>                   # 吊销 API Key");
+}
+
+fn parse_uuid(value: Option<&String>, name: &str) -> Result<Uuid, String> {
+    value
+        .ok_or_else(|| format!("❌ 缺少参数: {}", name))?
+        .parse()
+        .map_err(|_| format!("❌ 无效的 ID: {}", name))
+}
+
+/// 运维用的 API Key 管理命令：用户的第一个密钥和审批人的密钥只能从这里创建
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 加载环境变量
+    dotenv::dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        print_usage();
+        return Ok(());
+    }
+
+    // 创建配置和数据库连接池
+    let config = Config::from_env()?;
+    let pool = create_pool(&config)
+        .await
+        .map_err(|e| format!("❌ 数据库连接失败: {}", e))?;
+    let api_keys = ApiKeyService::new(pool, config.admin_user_ids.clone());
+
+    match args[1].as_str() {
+        "create" => {
+            let user_id = parse_uuid(args.get(2), "user_id")?;
+            let name = args.get(3).ok_or("❌ 缺少参数: name")?;
+            let scopes = args
+                .get(4)
+                .ok_or("❌ 缺少参数: scopes")?
+                .split(',')
+                .map(|scope| scope.trim().to_string())
+                .filter(|scope| !scope.is_empty())
+                .collect();
+
+            let request = CreateApiKeyRequest { name: name.clone(), scopes, expires_at: None };
+            let created = api_keys.create_key(user_id, request).await?;
+            println!("🔑 已创建 API Key {}（{}）:", created.api_key.prefix, created.api_key.scopes.join(","));
+            println!("  {}", created.key);
+            println!("💡 完整密钥只显示这一次，请妥善保存");
+        }
+        "revoke" => {
+            let user_id = parse_uuid(args.get(2), "user_id")?;
+            let key_id = parse_uuid(args.get(3), "key_id")?;
+            let api_key = api_keys.revoke_key(user_id, key_id).await?;
+            println!("🚫 已吊销 API Key {}", api_key.prefix);
+        }
+        _ => print_usage(),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use rust_crud_api::config::Config;
+use rust_crud_api::database::create_pool;
+use rust_crud_api::listeners::arbitrum_vault::{VAULT_SOURCE, VaultTarget, get_last_block, reset_progress, update_last_block};
+use rust_crud_api::listeners::indexer_admin::{
+    BackfillProgress, BackfillRun, VerifyOutcome, create_run, get_run, list_progress, list_runs, run_backfill,
+    verify_deposit,
+};
+use rust_crud_api::listeners::rpc_pool::RpcPool;
+use rust_crud_api::models::DepositQuery;
+use rust_crud_api::models::deposit::MAX_PAGE_SIZE;
+use rust_crud_api::services::{DepositNotifier, DepositService};
+use std::env;
+
+fn print_usage() {
+    println!("❌ 用法：");
+    println!("  cargo run --bin indexer status                    # 查看各来源进度与最近的补扫任务");
+    println!("  cargo run --bin indexer backfill <from> <to>      # 补扫区块范围（不移动实时进度）");
+    println!("  cargo run --bin indexer resume <run_id>           # 续跑中断或失败的补扫任务");
+    println!("  cargo run --bin indexer rewind <source> <block>   # 把来源进度回退到指定区块");
+    println!("  cargo run --bin indexer reset <source>            # 删除来源进度（重启后按 VAULT_START_BLOCK 开始）");
+    println!("  cargo run --bin indexer verify [from] [to]        # 按链上回执核对已入库的入金");
+}
+
+fn parse_block(value: Option<&String>, name: &str) -> Result<i64, String> {
+    value
+        .ok_or_else(|| format!("❌ 缺少参数: {}", name))?
+        .parse()
+        .map_err(|_| format!("❌ 无效的区块号: {}", name))
+}
+
+fn print_run(run: &BackfillRun) {
+    println!(
+        "  #{:<5} {:<16} {} -> {}  {:>6.2}%  入金 {:<6} {:<10} {}",
+        run.id,
+        run.source,
+        run.from_block,
+        run.to_block,
+        run.percent(),
+        run.deposits_found,
+        run.status,
+        run.last_error.as_deref().unwrap_or("")
+    );
+}
+
+fn print_progress(progress: &BackfillProgress) {
+    let eta = progress
+        .eta()
+        .map(|d| format!("{}s", d.as_secs()))
+        .unwrap_or_else(|| "-".to_string());
+    println!(
+        "⏳ [#{}] {}/{} 区块 ({:.2}%)，下一个区块 {}，新入金 {}，预计剩余 {}",
+        progress.run.id,
+        progress.run.processed_blocks(),
+        progress.run.total_blocks(),
+        progress.run.percent(),
+        progress.run.next_block,
+        progress.run.deposits_found,
+        eta
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 加载环境变量
+    dotenv::dotenv().ok();
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        print_usage();
+        return Ok(());
+    }
+
+    // 创建配置和数据库连接池
+    let config = Config::from_env()?;
+    let pool = create_pool(&config)
+        .await
+        .map_err(|e| format!("❌ 数据库连接失败: {}", e))?;
+
+    match args[1].as_str() {
+        "status" => {
+            let head = match RpcPool::from_config(&config) {
+                Ok(rpc) => rpc.get_block_number().await.ok().map(|b| b as i64),
+                Err(_) => None,
+            };
+            match head {
+                Some(head) => println!("⛓️ 链上最新区块: {}", head),
+                None => println!("⛓️ 链上最新区块: 未知（RPC 不可用）"),
+            }
+
+            println!("📍 实时进度:");
+            let progress = list_progress(&pool).await?;
+            if progress.is_empty() {
+                println!("  （暂无）");
+            }
+            for p in progress {
+                let lag = head
+                    .map(|h| format!("落后 {} 个区块", (h - p.last_block_number).max(0)))
+                    .unwrap_or_default();
+                println!("  {:<16} 区块 {:<12} 更新于 {}  {}", p.source, p.last_block_number, p.updated_at, lag);
+            }
+
+            println!("📦 最近的补扫任务:");
+            let runs = list_runs(&pool, 10).await?;
+            if runs.is_empty() {
+                println!("  （暂无）");
+            }
+            runs.iter().for_each(print_run);
+        }
+        "backfill" | "resume" => {
+            let run = if args[1] == "backfill" {
+                let from_block = parse_block(args.get(2), "from")?;
+                let to_block = parse_block(args.get(3), "to")?;
+                if from_block < 0 || from_block > to_block {
+                    return Err("❌ 区块范围无效，需要 0 <= from <= to".into());
+                }
+                let run = create_run(&pool, VAULT_SOURCE, from_block, to_block).await?;
+                println!("📦 已创建补扫任务 #{}: {} -> {}", run.id, from_block, to_block);
+                run
+            } else {
+                let id: i64 = args
+                    .get(2)
+                    .ok_or("❌ 缺少参数: run_id")?
+                    .parse()
+                    .map_err(|_| "❌ 无效的任务 ID")?;
+                let run = get_run(&pool, id).await?.ok_or("❌ 补扫任务不存在")?;
+                if run.status == "completed" {
+                    println!("✅ 补扫任务 #{} 已完成", id);
+                    return Ok(());
+                }
+                println!("▶️ 续跑补扫任务 #{}: 从区块 {} 继续", id, run.next_block);
+                run
+            };
+
+            let target = VaultTarget::from_config(&config)?;
+            let rpc = RpcPool::from_config(&config)?;
+            let notifier = DepositNotifier::new();
+
+            match run_backfill(&rpc, &pool, target, run, &notifier, print_progress).await {
+                Ok(run) => println!("✅ 补扫任务 #{} 完成，新入金 {} 笔", run.id, run.deposits_found),
+                Err(e) => {
+                    println!("❌ 补扫中断: {}", e);
+                    println!("💡 使用 `cargo run --bin indexer resume <run_id>` 从断点续跑");
+                    std::process::exit(1);
+                }
+            }
+        }
+        "rewind" => {
+            let source = args.get(2).ok_or("❌ 缺少参数: source")?;
+            let block = parse_block(args.get(3), "block")?;
+            let current = get_last_block(&pool, source).await?;
+            if let Some(current) = current
+                && block > current
+            {
+                return Err(format!("❌ 只能回退进度，当前进度为 {}", current).into());
+            }
+
+            update_last_block(&pool, source, block).await?;
+            println!("⏪ {} 进度已从 {:?} 回退到 {}，运行中的监听器将从区块 {} 重新扫描", source, current, block, block + 1);
+        }
+        "reset" => {
+            let source = args.get(2).ok_or("❌ 缺少参数: source")?;
+            if reset_progress(&pool, source).await? {
+                println!("🗑️ 已删除 {} 的进度，监听器重启后将按 VAULT_START_BLOCK 重新开始", source);
+            } else {
+                println!("ℹ️ {} 没有进度记录", source);
+            }
+        }
+        "verify" => {
+            let from_block = args.get(2).map(|_| parse_block(args.get(2), "from")).transpose()?;
+            let to_block = args.get(3).map(|_| parse_block(args.get(3), "to")).transpose()?;
+
+            let target = VaultTarget::from_config(&config)?;
+            let rpc = RpcPool::from_config(&config)?;
+            let deposits = DepositService::new(pool.clone(), DepositNotifier::new());
+
+            let mut page = 1;
+            let mut checked = 0;
+            let mut problems = 0;
+            loop {
+                let query = DepositQuery {
+                    sender: None,
+                    status: None,
+                    from_block,
+                    to_block,
+                    page: Some(page),
+                    page_size: Some(MAX_PAGE_SIZE),
+                    user_id: None,
+                };
+                let result = deposits.list_deposits(&query).await?;
+                if result.items.is_empty() {
+                    break;
+                }
+
+                for deposit in &result.items {
+                    checked += 1;
+                    match verify_deposit(&rpc, target, deposit).await {
+                        Ok(VerifyOutcome::Verified) => {}
+                        Ok(outcome) => {
+                            problems += 1;
+                            println!("🚨 区块 {} tx {}: {:?}", deposit.block_number, deposit.tx_hash, outcome);
+                        }
+                        Err(e) => {
+                            problems += 1;
+                            println!("❌ 区块 {} tx {}: 核对失败 {}", deposit.block_number, deposit.tx_hash, e);
+                        }
+                    }
+                }
+                println!("⏳ 已核对 {}/{} 笔入金，异常 {} 笔", checked, result.total, problems);
+                page += 1;
+            }
+
+            if problems > 0 {
+                println!("🚨 核对完成：{} 笔入金中有 {} 笔与链上不一致", checked, problems);
+                std::process::exit(1);
+            }
+            println!("✅ 核对完成：{} 笔入金全部与链上回执一致", checked);
+        }
+        _ => {
+            println!("❌ 未知命令: {}", args[1]);
+            print_usage();
+        }
+    }
+
+    Ok(())
+}
@@ -1,49 +1,130 @@
-use rust_crud_api::database::{create_pool, run_migrations, rollback_migrations};
+use rust_crud_api::database::migrations::{create_migration, migrations_dir};
+use rust_crud_api::database::{
+    MigrationState, create_pool, migration_status, pending_migrations, rollback_migrations, rollback_plan,
+    run_migrations,
+};
 use rust_crud_api::config::Config;
+use sqlx::migrate::Migration;
 use std::env;
 
+fn print_usage() {
+    println!("❌ 用法：");
+    println!("  cargo run --bin migrate up [--dry-run]       # 运行迁移（--dry-run 只打印待执行的 SQL）");
+    println!("  cargo run --bin migrate down <n> [--dry-run] # 回滚n步迁移");
+    println!("  cargo run --bin migrate redo                 # 回滚并重新执行最近一步迁移");
+    println!("  cargo run --bin migrate status               # 查看每个迁移的执行状态");
+    println!("  cargo run --bin migrate new <name>           # 创建一对 up/down 迁移文件");
+}
+
+fn print_sql(migrations: &[&Migration]) {
+    for migration in migrations {
+        println!("-- ==== {} {} ({}) ====", migration.version, migration.description, migration.migration_type.label());
+        println!("{}", migration.sql.trim_end());
+        println!();
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 加载环境变量
     dotenv::dotenv().ok();
-    
+
     let args: Vec<String> = env::args().collect();
-    
+
     if args.len() < 2 {
-        println!("❌ 用法：");
-        println!("  cargo run --bin migrate up       # 运行迁移");
-        println!("  cargo run --bin migrate down <n> # 回滚n步迁移");
+        print_usage();
+        return Ok(());
+    }
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+
+    // 创建迁移文件不需要连接数据库
+    if args[1] == "new" {
+        let name = args.get(2).ok_or("❌ 请指定迁移名: cargo run --bin migrate new <name>")?;
+        let (up, down) = create_migration(&migrations_dir(), name, chrono::Local::now().date_naive())?;
+        println!("📝 已创建迁移文件:");
+        println!("  {}", up.display());
+        println!("  {}", down.display());
         return Ok(());
     }
-    
+
     // 创建配置和数据库连接池
     let config = Config::from_env()?;
     let pool = create_pool(&config)
         .await
         .map_err(|e| format!("❌ 数据库连接失败: {}", e))?;
-    
+
     match args[1].as_str() {
         "up" => {
+            let pending = pending_migrations(&pool).await?;
+            if pending.is_empty() {
+                println!("✅ 没有待执行的迁移");
+                return Ok(());
+            }
+            if dry_run {
+                println!("🔍 以下 {} 个迁移待执行（未修改数据库）:", pending.len());
+                print_sql(&pending);
+                return Ok(());
+            }
+
             println!("🚀 开始运行数据库迁移...");
             run_migrations(&pool).await?;
         }
         "down" => {
-            if args.len() < 3 {
+            let Some(steps) = args.get(2).filter(|a| *a != "--dry-run") else {
                 println!("❌ 请指定回滚步数: cargo run --bin migrate down <steps>");
                 return Ok(());
+            };
+            let steps: usize = steps.parse().map_err(|_| "❌ 无效的步数")?;
+
+            if dry_run {
+                let plan = rollback_plan(&pool, steps).await?;
+                println!("🔍 回滚 {} 步将执行以下 SQL（未修改数据库）:", plan.len());
+                print_sql(&plan);
+                return Ok(());
             }
-            
-            let steps: i64 = args[2].parse()
-                .map_err(|_| "❌ 无效的步数")?;
-            
+
             println!("⏪ 开始回滚 {} 步迁移...", steps);
             rollback_migrations(&pool, steps).await?;
         }
+        "redo" => {
+            // 存在待执行的迁移时，回滚后的 up 会把它们一并执行，不再只是重做最近一步
+            let pending = pending_migrations(&pool).await?;
+            if !pending.is_empty() {
+                println!("❌ 存在 {} 个待执行的迁移，请先运行 `cargo run --bin migrate up` 再 redo", pending.len());
+                return Ok(());
+            }
+
+            let reverted = rollback_migrations(&pool, 1).await?;
+            if reverted.is_empty() {
+                println!("ℹ️ 没有已执行的迁移");
+                return Ok(());
+            }
+            println!("🔁 重新执行迁移 {}...", reverted[0]);
+            run_migrations(&pool).await?;
+        }
+        "status" => {
+            let status = migration_status(&pool).await?;
+            let mut pending = 0;
+            for s in &status {
+                let state = match s.state {
+                    MigrationState::Applied => "✅ 已执行",
+                    MigrationState::Pending => {
+                        pending += 1;
+                        "⏳ 待执行"
+                    }
+                    MigrationState::ChecksumMismatch => "⚠️ 文件已修改",
+                    MigrationState::Missing => "❓ 缺少文件",
+                };
+                let reversible = if s.reversible { "" } else { "（不可回滚）" };
+                println!("  {:<16} {:<14} {}{}", s.version, state, s.description, reversible);
+            }
+            println!("📋 共 {} 个迁移，{} 个待执行", status.len(), pending);
+        }
         _ => {
             println!("❌ 未知命令: {}", args[1]);
-            println!("支持的命令: up, down");
+            print_usage();
         }
     }
-    
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -9,6 +9,8 @@ pub struct Config {
     pub jwt_secret: Option<String>,
     pub redis_url: String,
     pub cache_ttl_seconds: u64,
+    // API Key 配置：可以访问全局接口的管理员
+    pub admin_user_ids: Vec<uuid::Uuid>,
     // 区块链监听配置
     pub arbitrum_ws_url: Option<String>,
     pub arbitrum_http_url: Option<String>,
@@ -18,21 +20,70 @@ pub struct Config {
     pub watcher_confirmations: u64,
     // 区块链扫描起始块高度配置
     pub vault_start_block: Option<u64>,
+    // RPC 节点故障转移与重试配置
+    pub arbitrum_http_fallback_urls: Vec<String>,
+    pub rpc_request_timeout_ms: u64,
+    pub rpc_max_retries: u32,
+    pub rpc_backoff_base_ms: u64,
+    pub rpc_backoff_max_ms: u64,
+    pub rpc_cross_check: bool,
+    pub rpc_cross_check_min_agree: usize,
+    // 钱包绑定（Sign-In with Ethereum）配置
+    pub siwe_domain: String,
+    pub siwe_uri: String,
+    pub siwe_chain_id: u64,
+    pub siwe_nonce_ttl_seconds: u64,
+    // 账本任务配置
+    pub ledger_posting_interval_secs: u64,
+    pub reconciliation_interval_secs: u64,
+    // 提现配置
+    pub withdrawal_executor: Option<String>,
+    pub withdrawal_private_key: Option<String>,
+    pub withdrawal_confirmations: u64,
+    pub withdrawal_worker_interval_secs: u64,
+    pub withdrawal_min_amount: i128,
+    pub withdrawal_max_amount: Option<i128>,
+    pub withdrawal_daily_limit: Option<i128>,
+    pub withdrawal_approval_threshold: Option<i128>,
+    pub withdrawal_required_approvals: i32,
+    pub withdrawal_approver_ids: Vec<uuid::Uuid>,
+    pub hyperliquid_network: String,
+    pub hyperliquid_bridge_address: Option<String>,
+    /// Hyperliquid 出金手续费（USDC 最小单位），用于按到账金额匹配 Bridge2 事件
+    pub hyperliquid_withdraw_fee: i128,
+    // Hyperliquid 成交与资金费入库配置
+    pub enable_hyperliquid_ingester: bool,
+    pub hyperliquid_backfill_interval_secs: u64,
+    pub hyperliquid_funding_lookback_days: u64,
+    // Webhook 投递配置
+    pub webhook_worker_interval_secs: u64,
+    pub webhook_max_attempts: i32,
+    pub webhook_timeout_secs: u64,
+    pub webhook_backoff_base_secs: u64,
+    pub webhook_backoff_max_secs: u64,
 }
 
 impl Config {
     /// 从环境变量加载配置
     pub fn from_env() -> Result<Self, env::VarError> {
+        let server_host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+        let server_port = env::var("SERVER_PORT").unwrap_or_else(|_| "8080".to_string());
+
         Ok(Config {
             database_url: env::var("DATABASE_URL")?,
-            server_host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            server_port: env::var("SERVER_PORT").unwrap_or_else(|_| "8080".to_string()),
+            siwe_domain: env::var("SIWE_DOMAIN").unwrap_or_else(|_| format!("{}:{}", server_host, server_port)),
+            siwe_uri: env::var("SIWE_URI").unwrap_or_else(|_| format!("http://{}:{}", server_host, server_port)),
+            server_host,
+            server_port,
             jwt_secret: env::var("JWT_SECRET").ok(),
             redis_url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
             cache_ttl_seconds: env::var("CACHE_TTL_SECONDS")
                 .unwrap_or_else(|_| "120".to_string()) // 默认2分钟
                 .parse()
                 .unwrap_or(120),
+            admin_user_ids: env::var("ADMIN_USER_IDS")
+                .map(|v| v.split(',').filter_map(|id| id.trim().parse().ok()).collect())
+                .unwrap_or_default(),
             arbitrum_ws_url: env::var("ARBITRUM_WS_URL").ok(),
             arbitrum_http_url: env::var("ARBITRUM_HTTP_URL").ok(),
             vault_contract_address: env::var("VAULT_CONTRACT_ADDRESS").ok(),
@@ -45,9 +96,130 @@ impl Config {
             vault_start_block: env::var("VAULT_START_BLOCK")
                 .ok()
                 .and_then(|v| v.parse().ok()),
+            arbitrum_http_fallback_urls: env::var("ARBITRUM_HTTP_FALLBACK_URLS")
+                .map(|v| {
+                    v.split(',')
+                        .map(|u| u.trim().to_string())
+                        .filter(|u| !u.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            rpc_request_timeout_ms: env::var("RPC_REQUEST_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            rpc_max_retries: env::var("RPC_MAX_RETRIES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            rpc_backoff_base_ms: env::var("RPC_BACKOFF_BASE_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            rpc_backoff_max_ms: env::var("RPC_BACKOFF_MAX_MS")
+                .unwrap_or_else(|_| "30000".to_string())
+                .parse()
+                .unwrap_or(30000),
+            rpc_cross_check: env::var("RPC_CROSS_CHECK").map(|v| v == "true").unwrap_or(false),
+            rpc_cross_check_min_agree: env::var("RPC_CROSS_CHECK_MIN_AGREE")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1),
+            siwe_chain_id: env::var("SIWE_CHAIN_ID")
+                .unwrap_or_else(|_| "421614".to_string()) // 默认 Arbitrum Sepolia
+                .parse()
+                .unwrap_or(421614),
+            siwe_nonce_ttl_seconds: env::var("SIWE_NONCE_TTL_SECONDS")
+                .unwrap_or_else(|_| "600".to_string()) // 默认10分钟
+                .parse()
+                .unwrap_or(600),
+            ledger_posting_interval_secs: env::var("LEDGER_POSTING_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            reconciliation_interval_secs: env::var("RECONCILIATION_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string()) // 默认5分钟
+                .parse()
+                .unwrap_or(300),
+            withdrawal_executor: env::var("WITHDRAWAL_EXECUTOR").ok().filter(|v| !v.is_empty()),
+            withdrawal_private_key: env::var("WITHDRAWAL_PRIVATE_KEY").ok(),
+            withdrawal_confirmations: env::var("WITHDRAWAL_CONFIRMATIONS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            withdrawal_worker_interval_secs: env::var("WITHDRAWAL_WORKER_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            withdrawal_min_amount: env::var("WITHDRAWAL_MIN_AMOUNT")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1),
+            withdrawal_max_amount: env::var("WITHDRAWAL_MAX_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            withdrawal_daily_limit: env::var("WITHDRAWAL_DAILY_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            withdrawal_approval_threshold: env::var("WITHDRAWAL_APPROVAL_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            withdrawal_required_approvals: env::var("WITHDRAWAL_REQUIRED_APPROVALS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            withdrawal_approver_ids: env::var("WITHDRAWAL_APPROVER_IDS")
+                .map(|v| v.split(',').filter_map(|id| id.trim().parse().ok()).collect())
+                .unwrap_or_default(),
+            hyperliquid_network: env::var("HYPERLIQUID_NETWORK").unwrap_or_else(|_| "testnet".to_string()),
+            hyperliquid_bridge_address: env::var("HYPERLIQUID_BRIDGE_ADDRESS").ok(),
+            hyperliquid_withdraw_fee: env::var("HYPERLIQUID_WITHDRAW_FEE")
+                .unwrap_or_else(|_| "1000000".to_string()) // 默认 1 USDC
+                .parse()
+                .unwrap_or(1_000_000),
+            enable_hyperliquid_ingester: env::var("ENABLE_HYPERLIQUID_INGESTER").map(|v| v == "true").unwrap_or(false),
+            hyperliquid_backfill_interval_secs: env::var("HYPERLIQUID_BACKFILL_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            hyperliquid_funding_lookback_days: env::var("HYPERLIQUID_FUNDING_LOOKBACK_DAYS")
+                .unwrap_or_else(|_| "90".to_string())
+                .parse()
+                .unwrap_or(90),
+            webhook_worker_interval_secs: env::var("WEBHOOK_WORKER_INTERVAL_SECS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            webhook_max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()
+                .unwrap_or(8),
+            webhook_timeout_secs: env::var("WEBHOOK_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            webhook_backoff_base_secs: env::var("WEBHOOK_BACKOFF_BASE_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            webhook_backoff_max_secs: env::var("WEBHOOK_BACKOFF_MAX_SECS")
+                .unwrap_or_else(|_| "21600".to_string()) // 默认最长6小时
+                .parse()
+                .unwrap_or(21600),
         })
     }
 
+    /// 获取所有 Arbitrum HTTP RPC 地址（主节点在前，备用节点在后，已去重）
+    pub fn arbitrum_http_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for url in self.arbitrum_http_url.iter().chain(self.arbitrum_http_fallback_urls.iter()) {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
     /// 获取服务器绑定地址
     pub fn bind_address(&self) -> String {
         format!("{}:{}", self.server_host, self.server_port)
@@ -1,7 +1,121 @@
 use crate::database::DatabasePool;
 use crate::errors::AppError;
-use sqlx::migrate::Migrator;
-use std::path::Path;
+use chrono::NaiveDate;
+use sqlx::migrate::{AppliedMigration, Migrate, Migration, Migrator};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 编译期嵌入的迁移文件（src/migrations 下成对的 .up.sql / .down.sql）
+/// 二进制不再依赖运行时的工作目录，新增迁移后由 build.rs 触发重新编译
+pub static MIGRATOR: Migrator = sqlx::migrate!("./src/migrations");
+
+/// 单个迁移的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// 已执行，但文件内容在执行后被修改过
+    ChecksumMismatch,
+    /// 数据库中已执行，但当前二进制中没有对应文件（通常来自更新的版本）
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    /// 是否有 down 脚本
+    pub reversible: bool,
+}
+
+fn up_migrations() -> impl Iterator<Item = &'static Migration> {
+    MIGRATOR.iter().filter(|m| !m.migration_type.is_down_migration())
+}
+
+fn down_migration(version: i64) -> Option<&'static Migration> {
+    MIGRATOR
+        .iter()
+        .find(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+/// 已执行的迁移（按版本升序）；上次迁移中途失败时返回错误
+async fn applied_migrations(pool: &DatabasePool) -> Result<Vec<AppliedMigration>, AppError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(AppError::Conflict(format!("迁移 {} 上次执行失败，请手动修复数据库后再试", version)));
+    }
+
+    let mut applied = conn.list_applied_migrations().await?;
+    applied.sort_by_key(|m| m.version);
+    Ok(applied)
+}
+
+/// 合并嵌入的迁移与数据库中的执行记录
+fn build_status(applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let mut status: Vec<MigrationStatus> = up_migrations()
+        .map(|migration| {
+            let state = match applied.iter().find(|a| a.version == migration.version) {
+                Some(a) if a.checksum != migration.checksum => MigrationState::ChecksumMismatch,
+                Some(_) => MigrationState::Applied,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+                reversible: down_migration(migration.version).is_some(),
+            }
+        })
+        .collect();
+
+    for a in applied {
+        if !status.iter().any(|s| s.version == a.version) {
+            status.push(MigrationStatus {
+                version: a.version,
+                description: String::new(),
+                state: MigrationState::Missing,
+                reversible: false,
+            });
+        }
+    }
+
+    status.sort_by_key(|s| s.version);
+    status
+}
+
+/// 所有迁移的执行状态
+pub async fn migration_status(pool: &DatabasePool) -> Result<Vec<MigrationStatus>, AppError> {
+    Ok(build_status(&applied_migrations(pool).await?))
+}
+
+/// 待执行的迁移（按执行顺序）
+pub async fn pending_migrations(pool: &DatabasePool) -> Result<Vec<&'static Migration>, AppError> {
+    let applied = applied_migrations(pool).await?;
+    Ok(up_migrations()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .collect())
+}
+
+/// 服务启动前检查：存在待执行的迁移时返回错误
+pub async fn ensure_migrations_applied(pool: &DatabasePool) -> Result<(), AppError> {
+    let pending = pending_migrations(pool).await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let versions: Vec<String> = pending
+        .iter()
+        .map(|m| format!("{}_{}", m.version, m.description.replace(' ', "_")))
+        .collect();
+    Err(AppError::Conflict(format!(
+        "存在 {} 个未执行的迁移: {}，请先运行 `cargo run --bin migrate up`",
+        pending.len(),
+        versions.join(", ")
+    )))
+}
 
 /// 使用SQLx的迁移系统运行数据库迁移
 /// 这是生产环境推荐的方式：
@@ -10,45 +124,161 @@ use std::path::Path;
 /// 3. 可回滚
 /// 4. 原子性操作
 pub async fn run_migrations(pool: &DatabasePool) -> Result<(), AppError> {
-    // 指定迁移文件目录
-    let migrator = Migrator::new(Path::new("./src/migrations"))
-        .await
-        .map_err(|e| {
-            eprintln!("❌ 无法加载迁移文件: {}", e);
-            AppError::from(e)
-        })?;
-    
     // 运行迁移
-    migrator
+    MIGRATOR
         .run(pool)
         .await
         .map_err(|e| {
             eprintln!("❌ 迁移执行失败: {}", e);
             AppError::from(e)
         })?;
-    
+
     println!("✅ 数据库迁移完成");
     Ok(())
 }
 
-/// 回滚迁移（生产环境重要功能）
-pub async fn rollback_migrations(pool: &DatabasePool, steps: i64) -> Result<(), AppError> {
-    let migrator = Migrator::new(Path::new("./src/migrations"))
-        .await
-        .map_err(|e| {
-            eprintln!("❌ 无法加载迁移文件: {}", e);
-            AppError::from(e)
-        })?;
-    
+/// 回滚 steps 步后应保留的最高版本；undo 会回滚所有高于该版本的迁移
+fn rollback_target(applied: &[i64], steps: usize) -> i64 {
+    if steps >= applied.len() {
+        return 0;
+    }
+    applied[applied.len() - steps - 1]
+}
+
+/// 回滚最近 steps 步需要执行的 down 脚本（按执行顺序）
+pub async fn rollback_plan(pool: &DatabasePool, steps: usize) -> Result<Vec<&'static Migration>, AppError> {
+    let applied = applied_migrations(pool).await?;
+    applied
+        .iter()
+        .rev()
+        .take(steps)
+        .map(|a| {
+            down_migration(a.version)
+                .ok_or_else(|| AppError::BadRequest(format!("迁移 {} 没有 down 脚本，无法回滚", a.version)))
+        })
+        .collect()
+}
+
+/// 回滚迁移（生产环境重要功能），返回已回滚的版本
+pub async fn rollback_migrations(pool: &DatabasePool, steps: usize) -> Result<Vec<i64>, AppError> {
+    // 先确认每一步都有 down 脚本，避免回滚到一半才失败
+    let plan = rollback_plan(pool, steps).await?;
+    let applied: Vec<i64> = applied_migrations(pool).await?.iter().map(|a| a.version).collect();
+
     // 回滚指定步数的迁移
-    migrator
-        .undo(pool, steps)
+    MIGRATOR
+        .undo(pool, rollback_target(&applied, steps))
         .await
         .map_err(|e| {
             eprintln!("❌ 迁移回滚失败: {}", e);
             AppError::from(e)
         })?;
-    
-    println!("✅ 成功回滚 {} 步迁移", steps);
-    Ok(())
-}
\ No newline at end of file
+
+    println!("✅ 成功回滚 {} 步迁移", plan.len());
+    Ok(plan.iter().map(|m| m.version).collect())
+}
+
+/// 源码中的迁移目录（`migrate new` 在这里创建文件）
+pub fn migrations_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/migrations")
+}
+
+/// 迁移名转换为文件名片段：小写字母、数字与下划线
+fn migration_slug(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    slug.split('_').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+/// 新迁移的版本号：日期（YYYYMMDD）+ 6 位全局序号，并保证大于已有的所有版本
+fn next_migration_version(existing: &[i64], today: NaiveDate) -> i64 {
+    let latest = existing.iter().copied().max().unwrap_or(0);
+    let date: i64 = today.format("%Y%m%d").to_string().parse().unwrap_or(0);
+    let version = date * 1_000_000 + latest % 1_000_000 + 1;
+    version.max(latest + 1)
+}
+
+/// 在 dir 下创建一对空的 up/down 迁移文件，返回两个文件的路径
+pub fn create_migration(dir: &Path, name: &str, today: NaiveDate) -> io::Result<(PathBuf, PathBuf)> {
+    let slug = migration_slug(name);
+    if slug.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "迁移名不能为空"));
+    }
+
+    let existing: Vec<i64> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            file_name.split('_').next()?.parse().ok()
+        })
+        .collect();
+
+    let version = next_migration_version(&existing, today);
+    let up = dir.join(format!("{}_{}.up.sql", version, slug));
+    let down = dir.join(format!("{}_{}.down.sql", version, slug));
+    fs::write(&up, format!("-- {}\n", name.trim()))?;
+    fs::write(&down, format!("-- 回滚：{}\n", name.trim()))?;
+    Ok((up, down))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_migrations_are_reversible() {
+        let ups: Vec<_> = up_migrations().collect();
+        assert!(!ups.is_empty());
+        for migration in &ups {
+            assert!(down_migration(migration.version).is_some(), "迁移 {} 缺少 down 脚本", migration.version);
+        }
+
+        // 版本严格递增
+        assert!(ups.windows(2).all(|w| w[0].version < w[1].version));
+    }
+
+    #[test]
+    fn test_build_status() {
+        let first = up_migrations().next().unwrap();
+        let second = up_migrations().nth(1).unwrap();
+        let applied = vec![
+            AppliedMigration { version: first.version, checksum: first.checksum.clone() },
+            AppliedMigration { version: second.version, checksum: vec![0u8; 4].into() },
+            AppliedMigration { version: 99990101000001, checksum: vec![].into() },
+        ];
+
+        let status = build_status(&applied);
+        assert_eq!(status[0].state, MigrationState::Applied);
+        assert_eq!(status[1].state, MigrationState::ChecksumMismatch);
+        assert_eq!(status[2].state, MigrationState::Pending);
+        assert_eq!(status.last().unwrap().state, MigrationState::Missing);
+    }
+
+    #[test]
+    fn test_rollback_target() {
+        let applied = [1, 2, 3];
+        assert_eq!(rollback_target(&applied, 1), 2);
+        assert_eq!(rollback_target(&applied, 2), 1);
+        assert_eq!(rollback_target(&applied, 3), 0);
+        assert_eq!(rollback_target(&applied, 10), 0);
+    }
+
+    #[test]
+    fn test_next_migration_version() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        assert_eq!(next_migration_version(&[20261019000008], today), 20261020000009);
+        assert_eq!(next_migration_version(&[], today), 20261020000001);
+        // 本地时钟落后时仍然保证递增
+        assert_eq!(next_migration_version(&[20301019000008], today), 20301019000009);
+    }
+
+    #[test]
+    fn test_migration_slug() {
+        assert_eq!(migration_slug("Add API keys"), "add_api_keys");
+        assert_eq!(migration_slug("  users: add-version  "), "users_add_version");
+        assert_eq!(migration_slug("!!!"), "");
+    }
+}
@@ -3,5 +3,8 @@ pub mod migrations;
 pub mod redis;
 
 pub use connection::{DatabasePool, create_pool};
-pub use migrations::{run_migrations, rollback_migrations};
+pub use migrations::{
+    MIGRATOR, MigrationState, MigrationStatus, ensure_migrations_applied, migration_status, pending_migrations,
+    rollback_migrations, rollback_plan, run_migrations,
+};
 pub use redis::{RedisPool, create_redis_pool, test_redis_connection};
\ No newline at end of file
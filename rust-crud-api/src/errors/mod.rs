@@ -12,6 +12,13 @@ pub enum AppError {
     Conflict(String),
     InternalServerError(String),
     BadRequest(String),
+    Unauthorized(String),
+    /// 已认证但没有访问权限
+    Forbidden(String),
+    /// If-Match 等前置条件不满足
+    PreconditionFailed(String),
+    /// 上游服务（如 Hyperliquid API）不可用或返回异常
+    UpstreamError(String),
 }
 
 impl fmt::Display for AppError {
@@ -23,6 +30,10 @@ impl fmt::Display for AppError {
             AppError::Conflict(msg) => write!(f, "冲突: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "内部服务器错误: {}", msg),
             AppError::BadRequest(msg) => write!(f, "请求错误: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "未授权: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "禁止访问: {}", msg),
+            AppError::PreconditionFailed(msg) => write!(f, "前置条件不满足: {}", msg),
+            AppError::UpstreamError(msg) => write!(f, "上游服务错误: {}", msg),
         }
     }
 }
@@ -54,6 +65,22 @@ impl ResponseError for AppError {
                 let response = ApiResponse::<()>::error(msg);
                 HttpResponse::BadRequest().json(response)
             }
+            AppError::Unauthorized(msg) => {
+                let response = ApiResponse::<()>::error(msg);
+                HttpResponse::Unauthorized().json(response)
+            }
+            AppError::Forbidden(msg) => {
+                let response = ApiResponse::<()>::error(msg);
+                HttpResponse::Forbidden().json(response)
+            }
+            AppError::PreconditionFailed(msg) => {
+                let response = ApiResponse::<()>::error(msg);
+                HttpResponse::PreconditionFailed().json(response)
+            }
+            AppError::UpstreamError(msg) => {
+                let response = ApiResponse::<()>::error(msg);
+                HttpResponse::BadGateway().json(response)
+            }
         }
     }
 }
@@ -89,4 +116,20 @@ impl Error for AppError {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<hyperliquid_rust_sdk::Error> for AppError {
+    fn from(error: hyperliquid_rust_sdk::Error) -> Self {
+        use hyperliquid_rust_sdk::Error as HlError;
+        match error {
+            // 限流按上游故障处理，其余 4xx 说明请求参数有误
+            HlError::ClientRequest { status_code: 429, .. } => {
+                AppError::UpstreamError("Hyperliquid API 请求过于频繁，请稍后再试".to_string())
+            }
+            HlError::ClientRequest { error_message, .. } => {
+                AppError::BadRequest(format!("Hyperliquid 拒绝了请求: {}", error_message))
+            }
+            other => AppError::UpstreamError(format!("Hyperliquid API 请求失败: {}", other)),
+        }
+    }
+}
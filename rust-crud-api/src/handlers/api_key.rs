@@ -0,0 +1,75 @@
+use crate::errors::AppError;
+use crate::models::{ApiKeyPrincipal, ApiResponse, CreateApiKeyRequest};
+use crate::services::ApiKeyService;
+use actix_web::{HttpResponse, Result, web};
+use uuid::Uuid;
+
+/// 操作用户自己的资金或密钥必须通过该用户的 API Key 认证
+pub(crate) fn require_owner(
+    principal: &Option<web::ReqData<ApiKeyPrincipal>>,
+    user_id: Uuid,
+) -> Result<&ApiKeyPrincipal, AppError> {
+    match principal.as_deref() {
+        None => Err(AppError::Unauthorized("该操作需要使用 API Key 认证".to_string())),
+        Some(principal) if principal.user_id != user_id => {
+            Err(AppError::Forbidden("只能操作 API Key 所属用户的资源".to_string()))
+        }
+        Some(principal) => Ok(principal),
+    }
+}
+
+/// 为用户创建 API Key
+pub async fn create_api_key(
+    api_key_service: web::Data<ApiKeyService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    let principal = require_owner(&principal, user_id)?;
+
+    let request = request.into_inner();
+    request.validate().map_err(AppError::ValidationError)?;
+    if let Some(scope) = request.scopes.iter().find(|scope| !principal.can_grant(scope)) {
+        return Err(AppError::Forbidden(format!("无权授予权限: {}", scope)));
+    }
+
+    let created = api_key_service.create_key(user_id, request).await?;
+
+    log::info!("🔑 API Key 已创建: user_id={}, prefix={}", user_id, created.api_key.prefix);
+
+    let response = ApiResponse::success(created, "API Key 创建成功，请妥善保存 key，之后将无法再次查看");
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// 获取用户的 API Key 列表
+pub async fn list_api_keys(
+    api_key_service: web::Data<ApiKeyService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    require_owner(&principal, user_id)?;
+
+    let keys = api_key_service.list_keys(user_id).await?;
+
+    let response = ApiResponse::success(keys, "获取 API Key 列表成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 吊销 API Key
+pub async fn revoke_api_key(
+    api_key_service: web::Data<ApiKeyService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, key_id) = path.into_inner();
+    require_owner(&principal, user_id)?;
+
+    let api_key = api_key_service.revoke_key(user_id, key_id).await?;
+
+    log::info!("🚫 API Key 已吊销: user_id={}, prefix={}", user_id, api_key.prefix);
+
+    let response = ApiResponse::success(api_key, "API Key 已吊销");
+    Ok(HttpResponse::Ok().json(response))
+}
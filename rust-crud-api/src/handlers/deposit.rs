@@ -0,0 +1,108 @@
+use crate::errors::AppError;
+use crate::models::{ApiResponse, DepositQuery, DepositStreamQuery};
+use crate::services::DepositService;
+use crate::utils::is_valid_tx_hash;
+use actix_web::{HttpResponse, Result, web};
+use futures_util::stream;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time;
+
+/// SSE 心跳间隔，防止代理因空闲断开连接
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// 分页查询入金记录
+pub async fn list_deposits(
+    deposit_service: web::Data<DepositService>,
+    query: web::Query<DepositQuery>,
+) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+
+    log::info!(
+        "📋 查询入金列表: sender={:?}, status={:?}, blocks={:?}..{:?}, page={}, page_size={}",
+        query.sender,
+        query.status,
+        query.from_block,
+        query.to_block,
+        query.page(),
+        query.page_size()
+    );
+
+    let deposits = deposit_service.list_deposits(&query).await?;
+
+    let response = ApiResponse::success(deposits, "获取入金列表成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 根据交易哈希获取入金记录
+pub async fn get_deposit_by_tx_hash(
+    deposit_service: web::Data<DepositService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let tx_hash = path.into_inner();
+
+    if !is_valid_tx_hash(&tx_hash) {
+        return Err(AppError::ValidationError("交易哈希格式无效".to_string()));
+    }
+
+    match deposit_service.get_deposit_by_tx_hash(&tx_hash).await? {
+        Some(deposit) => {
+            let response = ApiResponse::success(deposit, "获取入金记录成功");
+            Ok(HttpResponse::Ok().json(response))
+        }
+        None => Err(AppError::NotFound("入金记录不存在".to_string())),
+    }
+}
+
+/// 通过 Server-Sent Events 实时推送新入金
+/// 每条事件格式为 `event: deposit`，data 为入金记录 JSON；可通过 sender 参数只订阅某个地址
+pub async fn stream_deposits(
+    deposit_service: web::Data<DepositService>,
+    query: web::Query<DepositStreamQuery>,
+) -> Result<HttpResponse, AppError> {
+    let sender = query.into_inner().sender.map(|s| s.to_lowercase());
+    let receiver = deposit_service.subscribe();
+
+    log::info!("📡 新的入金推送订阅: sender={:?}", sender);
+
+    // 心跳计时器在整个连接期间只创建一次，被过滤掉的事件不会推迟心跳
+    let mut keepalive = time::interval_at(time::Instant::now() + SSE_KEEPALIVE, SSE_KEEPALIVE);
+    keepalive.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+    let events = stream::unfold((receiver, keepalive), move |(mut receiver, mut keepalive)| {
+        let sender = sender.clone();
+        async move {
+            loop {
+                let event = tokio::select! {
+                    received = receiver.recv() => match received {
+                        Ok(deposit) => {
+                            if sender.as_ref().is_some_and(|s| s != &deposit.sender) {
+                                continue;
+                            }
+                            match serde_json::to_string(&deposit) {
+                                Ok(data) => format!("event: deposit\nid: {}\ndata: {}\n\n", deposit.id, data),
+                                Err(e) => {
+                                    log::error!("❌ 入金事件序列化失败: {}", e);
+                                    continue;
+                                }
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("⚠️ 入金推送订阅者落后，丢弃 {} 条事件", skipped);
+                            format!("event: lagged\ndata: {}\n\n", skipped)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    },
+                    _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+                };
+                return Some((Ok::<_, actix_web::Error>(web::Bytes::from(event)), (receiver, keepalive)));
+            }
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events))
+}
@@ -0,0 +1,57 @@
+use crate::errors::AppError;
+use crate::models::ApiResponse;
+use crate::models::hyperliquid::HyperliquidFundingQuery;
+use crate::services::HyperliquidService;
+use actix_web::{HttpResponse, Result, web};
+use uuid::Uuid;
+
+/// 获取已绑定钱包在 Hyperliquid 上的账户概览与持仓
+pub async fn get_hyperliquid_positions(
+    hyperliquid_service: web::Data<HyperliquidService>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, address) = path.into_inner();
+    let state = hyperliquid_service.account_state(user_id, &address).await?;
+
+    let response = ApiResponse::success(state, "获取 Hyperliquid 持仓成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 获取已绑定钱包在 Hyperliquid 上的挂单
+pub async fn get_hyperliquid_open_orders(
+    hyperliquid_service: web::Data<HyperliquidService>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, address) = path.into_inner();
+    let orders = hyperliquid_service.open_orders(user_id, &address).await?;
+
+    let response = ApiResponse::success(orders, "获取 Hyperliquid 挂单成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 获取已绑定钱包在 Hyperliquid 上的成交记录
+pub async fn get_hyperliquid_fills(
+    hyperliquid_service: web::Data<HyperliquidService>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, address) = path.into_inner();
+    let fills = hyperliquid_service.fills(user_id, &address).await?;
+
+    let response = ApiResponse::success(fills, "获取 Hyperliquid 成交记录成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 获取已绑定钱包在 Hyperliquid 上的资金费历史
+pub async fn get_hyperliquid_funding(
+    hyperliquid_service: web::Data<HyperliquidService>,
+    path: web::Path<(Uuid, String)>,
+    query: web::Query<HyperliquidFundingQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, address) = path.into_inner();
+    let history = hyperliquid_service
+        .funding_history(user_id, &address, query.into_inner())
+        .await?;
+
+    let response = ApiResponse::success(history, "获取 Hyperliquid 资金费历史成功");
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,70 @@
+use crate::errors::AppError;
+use crate::models::{AccountQuery, ApiResponse};
+use crate::services::{LedgerService, WalletService};
+use actix_web::{HttpResponse, Result, web};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// 对账记录查询参数
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationQuery {
+    pub limit: Option<i64>,
+}
+
+/// 查询账户余额列表
+pub async fn list_account_balances(
+    ledger_service: web::Data<LedgerService>,
+    query: web::Query<AccountQuery>,
+) -> Result<HttpResponse, AppError> {
+    let balances = ledger_service.list_balances(&query.into_inner()).await?;
+
+    let response = ApiResponse::success(balances, "获取账户余额成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 查询单个账户余额
+pub async fn get_account_balance(
+    ledger_service: web::Data<LedgerService>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let account_id = path.into_inner();
+
+    match ledger_service.get_balance(account_id).await? {
+        Some(balance) => {
+            let response = ApiResponse::success(balance, "获取账户余额成功");
+            Ok(HttpResponse::Ok().json(response))
+        }
+        None => Err(AppError::NotFound("账户不存在".to_string())),
+    }
+}
+
+/// 查询用户的各资产余额
+pub async fn get_user_balances(
+    ledger_service: web::Data<LedgerService>,
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+
+    wallet_service.ensure_user_exists(user_id).await?;
+    let balances = ledger_service.user_balances(user_id).await?;
+
+    let response = ApiResponse::success(balances, "获取用户余额成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 查询最近的对账记录
+pub async fn list_reconciliations(
+    ledger_service: web::Data<LedgerService>,
+    query: web::Query<ReconciliationQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(20);
+    if !(1..=100).contains(&limit) {
+        return Err(AppError::ValidationError("limit 必须在 1-100 之间".to_string()));
+    }
+
+    let reconciliations = ledger_service.list_reconciliations(limit).await?;
+
+    let response = ApiResponse::success(reconciliations, "获取对账记录成功");
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -1,5 +1,19 @@
 pub mod user;
+pub mod deposit;
+pub mod wallet;
+pub mod ledger;
+pub mod withdrawal;
+pub mod webhook;
+pub mod api_key;
 pub mod health;
+pub mod hyperliquid;
 
 pub use user::*;
-pub use health::*;
\ No newline at end of file
+pub use deposit::*;
+pub use wallet::*;
+pub use ledger::*;
+pub use withdrawal::*;
+pub use webhook::*;
+pub use api_key::*;
+pub use health::*;
+pub use hyperliquid::*;
@@ -1,7 +1,14 @@
 use crate::errors::AppError;
-use crate::models::{ApiResponse, CreateUserRequest, UpdateUserRequest, UserResponse};
+use crate::models::{
+    ApiResponse, CreateUserRequest, UpdateUserRequest, User, UserBulkFormat, UserBulkQuery, UserImportReport,
+    UserResponse,
+};
 use crate::services::UserService;
-use actix_web::{HttpResponse, Result, web};
+use crate::services::user_bulk::{EXPORT_PAGE_SIZE, IMPORT_BATCH_SIZE, LineBuffer, RowParser, export_header, export_line};
+use crate::utils::{if_match, if_none_match_hits};
+use actix_web::http::header::ETAG;
+use actix_web::{HttpRequest, HttpResponse, Result, web};
+use futures_util::{StreamExt, stream};
 use uuid::Uuid;
 
 /// 创建用户 (用户注册)
@@ -26,13 +33,15 @@ pub async fn create_user(
         user.username
     );
 
+    let etag = user.etag();
     let response = ApiResponse::success_with_print(UserResponse::from(user), "用户注册成功");
-    Ok(HttpResponse::Created().json(response))
+    Ok(HttpResponse::Created().insert_header((ETAG, etag)).json(response))
 }
 
 /// 根据 ID 获取用户信息
 pub async fn get_user_by_id(
     user_service: web::Data<UserService>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
@@ -40,26 +49,32 @@ pub async fn get_user_by_id(
     log::info!("🔍 查询用户: id={}", user_id);
 
     match user_service.get_user_by_id(user_id).await? {
-        Some(user) => {
-            let response = ApiResponse::success_with_print(UserResponse::from(user), "获取用户信息成功");
-            Ok(HttpResponse::Ok().json(response))
-        }
+        Some(user) => Ok(user_response(&req, user)),
         None => Err(AppError::NotFound("用户不存在".to_string())),
     }
 }
 
+/// 带 ETag 的用户响应；If-None-Match 命中时返回 304，不重复传输内容
+fn user_response(req: &HttpRequest, user: User) -> HttpResponse {
+    let etag = user.etag();
+    if if_none_match_hits(req, &etag) {
+        return HttpResponse::NotModified().insert_header((ETAG, etag)).finish();
+    }
+
+    let response = ApiResponse::success_with_print(UserResponse::from(user), "获取用户信息成功");
+    HttpResponse::Ok().insert_header((ETAG, etag)).json(response)
+}
+
 /// 根据用户名获取用户信息
 pub async fn get_user_by_username(
     user_service: web::Data<UserService>,
+    req: HttpRequest,
     path: web::Path<String>,
 ) -> Result<HttpResponse, AppError> {
     let username = path.into_inner();
 
     match user_service.get_user_by_username(&username).await? {
-        Some(user) => {
-            let response = ApiResponse::success_with_print(UserResponse::from(user), "获取用户信息成功");
-            Ok(HttpResponse::Ok().json(response))
-        }
+        Some(user) => Ok(user_response(&req, user)),
         None => Err(AppError::NotFound("用户不存在".to_string())),
     }
 }
@@ -77,9 +92,10 @@ pub async fn get_all_users(user_service: web::Data<UserService>) -> Result<HttpR
     Ok(HttpResponse::Ok().json(response))
 }
 
-/// 更新用户信息
+/// 更新用户信息（支持 If-Match，版本不一致时返回 412）
 pub async fn update_user(
     user_service: web::Data<UserService>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
     request: web::Json<UpdateUserRequest>,
 ) -> Result<HttpResponse, AppError> {
@@ -94,7 +110,7 @@ pub async fn update_user(
         req_data.password.is_some()
     );
 
-    let user = user_service.update_user(user_id, req_data).await?;
+    let user = user_service.update_user(user_id, req_data, if_match(&req).as_ref()).await?;
 
     log::info!(
         "✅ 用户更新成功: id={}, username={}",
@@ -102,20 +118,22 @@ pub async fn update_user(
         user.username
     );
 
+    let etag = user.etag();
     let response = ApiResponse::success(UserResponse::from(user), "用户信息更新成功");
-    Ok(HttpResponse::Ok().json(response))
+    Ok(HttpResponse::Ok().insert_header((ETAG, etag)).json(response))
 }
 
-/// 删除用户
+/// 删除用户（支持 If-Match，版本不一致时返回 412）
 pub async fn delete_user(
     user_service: web::Data<UserService>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
 
     log::info!("🗑️ 删除用户请求: id={}", user_id);
 
-    if user_service.delete_user(user_id).await? {
+    if user_service.delete_user(user_id, if_match(&req).as_ref()).await? {
         log::info!("✅ 用户删除成功: id={}", user_id);
         let response = ApiResponse::success((), "用户删除成功");
         Ok(HttpResponse::Ok().json(response))
@@ -123,3 +141,135 @@ pub async fn delete_user(
         Err(AppError::NotFound("用户不存在".to_string()))
     }
 }
+
+/// 批量导入用户（CSV 或 NDJSON，流式读取请求体）
+/// 格式由 format 参数或 Content-Type 决定；每行按注册规则校验，返回逐行的错误报告
+pub async fn import_users(
+    user_service: web::Data<UserService>,
+    req: HttpRequest,
+    query: web::Query<UserBulkQuery>,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, AppError> {
+    let content_type = req
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let format = query
+        .format
+        .or_else(|| UserBulkFormat::from_content_type(content_type))
+        .ok_or_else(|| AppError::BadRequest("无法识别导入格式，请使用 format=csv|ndjson 或对应的 Content-Type".to_string()))?;
+
+    log::info!("📥 批量导入用户: format={:?}", format);
+
+    let mut parser = RowParser::new(format);
+    let mut buffer = LineBuffer::default();
+    let mut report = UserImportReport::default();
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut line_no = 0;
+    let mut finished = false;
+
+    while !finished {
+        let lines = match payload.next().await {
+            Some(chunk) => {
+                let chunk = chunk.map_err(|e| AppError::BadRequest(format!("读取请求体失败: {}", e)))?;
+                buffer.push(&chunk)
+            }
+            None => {
+                finished = true;
+                buffer.finish().into_iter().collect()
+            }
+        };
+
+        for line in lines {
+            line_no += 1;
+            let Ok(line) = String::from_utf8(line) else {
+                report.total += 1;
+                report.reject(line_no, None, "不是有效的 UTF-8 文本");
+                continue;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            if parser.needs_header() {
+                parser.parse_header(&line)?;
+                continue;
+            }
+
+            match parser.parse_row(&line) {
+                Ok(request) => batch.push((line_no, request)),
+                Err(e) => {
+                    report.total += 1;
+                    report.reject(line_no, None, e);
+                }
+            }
+            if batch.len() >= IMPORT_BATCH_SIZE {
+                user_service.import_users_batch(std::mem::take(&mut batch), &mut report).await?;
+            }
+        }
+    }
+    if !batch.is_empty() {
+        user_service.import_users_batch(batch, &mut report).await?;
+    }
+    report.errors.sort_by_key(|e| e.line);
+
+    log::info!(
+        "✅ 批量导入完成: 共{}行，成功{}，失败{}",
+        report.total,
+        report.created,
+        report.failed
+    );
+
+    let response = ApiResponse::success(report, "批量导入完成");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 流式导出所有用户（默认 NDJSON，format=csv 时为 CSV），不包含密码
+pub async fn export_users(
+    user_service: web::Data<UserService>,
+    query: web::Query<UserBulkQuery>,
+) -> Result<HttpResponse, AppError> {
+    let format = query.format.unwrap_or(UserBulkFormat::Ndjson);
+    log::info!("📤 导出用户: format={:?}", format);
+
+    let service = user_service.into_inner();
+    let header = stream::iter([Ok::<_, actix_web::Error>(web::Bytes::from(export_header(format)))]);
+
+    // 状态：(上一页最后一个用户的位置, 是否已读完)
+    let pages = stream::unfold((None, false), move |(after, done)| {
+        let service = service.clone();
+        async move {
+            if done {
+                return None;
+            }
+            let users = match service.export_users_page(after, EXPORT_PAGE_SIZE).await {
+                Ok(users) => users,
+                Err(e) => {
+                    // 响应头已经发出，只能截断输出
+                    log::error!("❌ 导出用户失败: {}", e);
+                    return None;
+                }
+            };
+
+            let done = (users.len() as i64) < EXPORT_PAGE_SIZE;
+            let next = users.last().map(|u| (u.created_at, u.id));
+            let mut body = String::new();
+            for user in users {
+                match export_line(format, &UserResponse::from(user)) {
+                    Ok(line) => body.push_str(&line),
+                    Err(e) => log::error!("❌ 用户导出序列化失败: {}", e),
+                }
+            }
+            Some((Ok::<_, actix_web::Error>(web::Bytes::from(body)), (next, done || next.is_none())))
+        }
+    });
+
+    let extension = match format {
+        UserBulkFormat::Csv => "csv",
+        UserBulkFormat::Ndjson => "ndjson",
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"users.{}\"", extension)))
+        .streaming(header.chain(pages)))
+}
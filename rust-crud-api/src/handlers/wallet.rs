@@ -0,0 +1,88 @@
+use crate::errors::AppError;
+use crate::models::{ApiResponse, DepositQuery, LinkWalletRequest, WalletNonceRequest};
+use crate::services::{DepositService, WalletService};
+use actix_web::{HttpResponse, Result, web};
+use uuid::Uuid;
+
+/// 申请钱包绑定挑战
+pub async fn create_wallet_nonce(
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<Uuid>,
+    request: web::Json<WalletNonceRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    let req_data = request.into_inner();
+
+    log::info!("🔐 申请钱包绑定 nonce: user_id={}, address={}", user_id, req_data.address);
+
+    let challenge = wallet_service.create_link_nonce(user_id, req_data).await?;
+
+    let response = ApiResponse::success(challenge, "请使用钱包签名返回的消息");
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// 提交签名绑定钱包
+pub async fn link_wallet(
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<Uuid>,
+    request: web::Json<LinkWalletRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+
+    let wallet = wallet_service.link_wallet(user_id, request.into_inner()).await?;
+
+    log::info!("✅ 钱包绑定成功: user_id={}, address={}", user_id, wallet.address);
+
+    let response = ApiResponse::success(wallet, "钱包绑定成功");
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// 获取用户绑定的钱包列表
+pub async fn list_user_wallets(
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+
+    let wallets = wallet_service.list_wallets(user_id).await?;
+
+    let response = ApiResponse::success(wallets, "获取钱包列表成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 解绑钱包
+pub async fn unlink_wallet(
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, address) = path.into_inner();
+
+    log::info!("🔓 解绑钱包: user_id={}, address={}", user_id, address);
+
+    if wallet_service.unlink_wallet(user_id, &address).await? {
+        let response = ApiResponse::success((), "钱包解绑成功");
+        Ok(HttpResponse::Ok().json(response))
+    } else {
+        Err(AppError::NotFound("钱包绑定不存在".to_string()))
+    }
+}
+
+/// 获取用户的入金历史（通过已绑定的钱包关联）
+pub async fn list_user_deposits(
+    wallet_service: web::Data<WalletService>,
+    deposit_service: web::Data<DepositService>,
+    path: web::Path<Uuid>,
+    query: web::Query<DepositQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    let mut query = query.into_inner();
+    query.user_id = Some(user_id);
+
+    // 确认用户存在，避免对不存在的用户返回空列表
+    wallet_service.ensure_user_exists(user_id).await?;
+
+    let deposits = deposit_service.list_deposits(&query).await?;
+
+    let response = ApiResponse::success(deposits, "获取用户入金历史成功");
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,116 @@
+use crate::errors::AppError;
+use crate::models::{ApiResponse, CreateWebhookEndpointRequest, UpdateWebhookEndpointRequest, WebhookDeliveryQuery};
+use crate::services::WebhookService;
+use actix_web::{HttpResponse, Result, web};
+use uuid::Uuid;
+
+/// 注册 Webhook 端点
+pub async fn create_webhook_endpoint(
+    webhook_service: web::Data<WebhookService>,
+    request: web::Json<CreateWebhookEndpointRequest>,
+) -> Result<HttpResponse, AppError> {
+    let created = webhook_service.create_endpoint(request.into_inner()).await?;
+
+    log::info!("🔗 Webhook 端点已注册: id={}, url={}", created.endpoint.id, created.endpoint.url);
+
+    let response = ApiResponse::success(created, "Webhook 端点注册成功，请妥善保存 secret");
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// 获取全部 Webhook 端点
+pub async fn list_webhook_endpoints(
+    webhook_service: web::Data<WebhookService>,
+) -> Result<HttpResponse, AppError> {
+    let endpoints = webhook_service.list_endpoints().await?;
+
+    let response = ApiResponse::success(endpoints, "获取 Webhook 端点成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 根据 ID 获取 Webhook 端点
+pub async fn get_webhook_endpoint(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let endpoint = webhook_service.get_endpoint(path.into_inner()).await?;
+
+    let response = ApiResponse::success(endpoint, "获取 Webhook 端点成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 更新 Webhook 端点
+pub async fn update_webhook_endpoint(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateWebhookEndpointRequest>,
+) -> Result<HttpResponse, AppError> {
+    let endpoint = webhook_service
+        .update_endpoint(path.into_inner(), request.into_inner())
+        .await?;
+
+    let response = ApiResponse::success(endpoint, "Webhook 端点更新成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 删除 Webhook 端点
+pub async fn delete_webhook_endpoint(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+
+    if webhook_service.delete_endpoint(id).await? {
+        let response = ApiResponse::success((), "Webhook 端点删除成功");
+        Ok(HttpResponse::Ok().json(response))
+    } else {
+        Err(AppError::NotFound("Webhook 端点不存在".to_string()))
+    }
+}
+
+/// 轮换 Webhook 签名密钥
+pub async fn rotate_webhook_secret(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let rotated = webhook_service.rotate_secret(path.into_inner()).await?;
+
+    let response = ApiResponse::success(rotated, "签名密钥已轮换，请妥善保存 secret");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 查询端点的投递日志
+pub async fn list_webhook_deliveries(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<Uuid>,
+    query: web::Query<WebhookDeliveryQuery>,
+) -> Result<HttpResponse, AppError> {
+    let endpoint_id = path.into_inner();
+
+    webhook_service.get_endpoint(endpoint_id).await?;
+    let deliveries = webhook_service.list_deliveries(endpoint_id, &query).await?;
+
+    let response = ApiResponse::success(deliveries, "获取投递日志成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 查询单条投递详情（含事件内容与每次尝试）
+pub async fn get_webhook_delivery(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let delivery = webhook_service.get_delivery(path.into_inner()).await?;
+
+    let response = ApiResponse::success(delivery, "获取投递详情成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 重新投递（死信重放）
+pub async fn retry_webhook_delivery(
+    webhook_service: web::Data<WebhookService>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let delivery = webhook_service.retry_delivery(path.into_inner()).await?;
+
+    let response = ApiResponse::success(delivery, "已重新加入投递队列");
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,113 @@
+use crate::errors::AppError;
+use crate::handlers::api_key::require_owner;
+use crate::models::{ApiKeyPrincipal, ApiResponse, CreateWithdrawalRequest, RejectWithdrawalRequest, WithdrawalQuery};
+use crate::services::{WalletService, WithdrawalService};
+use actix_web::{HttpResponse, Result, web};
+use uuid::Uuid;
+
+/// 用户申请提现
+pub async fn create_withdrawal(
+    withdrawal_service: web::Data<WithdrawalService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateWithdrawalRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    require_owner(&principal, user_id)?;
+
+    let withdrawal = withdrawal_service.create_withdrawal(user_id, request.into_inner()).await?;
+
+    let response = ApiResponse::success(withdrawal, "提现申请已提交");
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// 查询用户的提现记录
+pub async fn list_user_withdrawals(
+    withdrawal_service: web::Data<WithdrawalService>,
+    wallet_service: web::Data<WalletService>,
+    path: web::Path<Uuid>,
+    query: web::Query<WithdrawalQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+
+    wallet_service.ensure_user_exists(user_id).await?;
+    let withdrawals = withdrawal_service.list_withdrawals(Some(user_id), &query).await?;
+
+    let response = ApiResponse::success(withdrawals, "获取提现记录成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 用户取消尚未执行的提现
+pub async fn cancel_withdrawal(
+    withdrawal_service: web::Data<WithdrawalService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, withdrawal_id) = path.into_inner();
+    require_owner(&principal, user_id)?;
+
+    let withdrawal = withdrawal_service.cancel_withdrawal(user_id, withdrawal_id).await?;
+
+    let response = ApiResponse::success(withdrawal, "提现已取消");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 查询全部提现记录（审批台使用）
+pub async fn list_withdrawals(
+    withdrawal_service: web::Data<WithdrawalService>,
+    query: web::Query<WithdrawalQuery>,
+) -> Result<HttpResponse, AppError> {
+    let withdrawals = withdrawal_service.list_withdrawals(None, &query).await?;
+
+    let response = ApiResponse::success(withdrawals, "获取提现记录成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 根据 ID 获取提现
+pub async fn get_withdrawal(
+    withdrawal_service: web::Data<WithdrawalService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let withdrawal = withdrawal_service.get_withdrawal(path.into_inner()).await?;
+
+    let response = ApiResponse::success(withdrawal, "获取提现成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 审批操作必须通过 API Key 认证，审批人即密钥所属用户
+fn approver_id(principal: Option<web::ReqData<ApiKeyPrincipal>>) -> Result<Uuid, AppError> {
+    principal
+        .map(|principal| principal.user_id)
+        .ok_or_else(|| AppError::Unauthorized("审批提现需要使用 API Key 认证".to_string()))
+}
+
+/// 审批大额提现
+pub async fn approve_withdrawal(
+    withdrawal_service: web::Data<WithdrawalService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let approver_id = approver_id(principal)?;
+    let withdrawal = withdrawal_service
+        .approve_withdrawal(path.into_inner(), approver_id)
+        .await?;
+
+    let response = ApiResponse::success(withdrawal, "审批成功");
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 拒绝提现
+pub async fn reject_withdrawal(
+    withdrawal_service: web::Data<WithdrawalService>,
+    principal: Option<web::ReqData<ApiKeyPrincipal>>,
+    path: web::Path<Uuid>,
+    request: web::Json<RejectWithdrawalRequest>,
+) -> Result<HttpResponse, AppError> {
+    let approver_id = approver_id(principal)?;
+    let withdrawal = withdrawal_service
+        .reject_withdrawal(path.into_inner(), approver_id, request.into_inner())
+        .await?;
+
+    let response = ApiResponse::success(withdrawal, "提现已拒绝");
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -9,6 +9,7 @@ pub mod utils; // 工具函数模块
 pub mod errors; // 错误处理模块
 pub mod examples; // 示例模块
 pub mod listeners; // 区块链监听模块
+pub mod workers; // 后台任务模块
 
 pub use config::Config; // 导出配置模块
 pub use database::DatabasePool; // 导出数据库连接池模块
@@ -1,12 +1,23 @@
 use crate::{Config, database::DatabasePool};
+use crate::listeners::rpc_pool::{CrossCheckOutcome, RpcPool};
+use crate::models::Deposit;
+use crate::models::webhook::{EVENT_DEPOSIT_CREATED, EVENT_DEPOSIT_ORPHANED};
+use crate::listeners::chain_source::ChainSource;
+use crate::listeners::progress_store::{BatchCommit, ProgressStore, ReorgCheck, ReorgStore, VaultProgressStore};
+use crate::listeners::source_lock::{SourceLock, source_lock_key};
+use crate::services::{DepositNotifier, LedgerService, WebhookService};
 use ethers::prelude::*;
-use ethers::providers::{Provider, Http};
+use sqlx::{PgConnection, PgExecutor};
+use std::fmt;
 use std::time::Duration;
 use tokio::time;
 
+/// 进度表中 vault 监听器的来源标识
+pub const VAULT_SOURCE: &str = "arbitrum_vault";
+
 /// 获取ERC20 Transfer事件的签名哈希
 /// Transfer(address indexed from, address indexed to, uint256 value)
-fn get_transfer_event_signature() -> H256 {
+pub(crate) fn get_transfer_event_signature() -> H256 {
     use ethers::utils::keccak256;
     H256::from(keccak256("Transfer(address,address,uint256)"))
 }
@@ -14,26 +25,59 @@ fn get_transfer_event_signature() -> H256 {
 /// 将 U256 格式化为十进制字符串（wei）
 fn u256_to_string(v: U256) -> String { format!("{}", v) }
 
-/// 处理Transfer事件日志，如果是向vault的转账则插入数据库
-async fn process_transfer_log(
-    pool: &DatabasePool,
-    log: &Log,
-    vault_addr: Address,
-    usdc_addr: Address,
-    source: &str,
-) -> anyhow::Result<bool> {
+/// 每批扫描的区块数（Alchemy免费计划限制 eth_getLogs 最多10个区块）
+pub const BATCH_BLOCKS: i64 = 10;
+
+/// 每轮轮询重新核对的最近已处理区块数；更深的链重组无法被发现
+pub const REORG_CHECK_BLOCKS: i64 = BATCH_BLOCKS;
+
+/// 监听目标：vault 合约地址与入金代币地址
+#[derive(Debug, Clone, Copy)]
+pub struct VaultTarget {
+    pub vault: Address,
+    pub token: Address,
+}
+
+impl VaultTarget {
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let vault = match &config.vault_contract_address {
+            Some(a) => a.parse()?,
+            None => anyhow::bail!("VAULT_CONTRACT_ADDRESS 未设置"),
+        };
+        let token = match &config.usdc_token_address {
+            Some(a) => a.parse()?,
+            None => anyhow::bail!("USDC_TOKEN_ADDRESS 未设置"),
+        };
+        Ok(Self { vault, token })
+    }
+}
+
+/// 从日志中解码出的一笔向 vault 的转账
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultTransfer {
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub tx_index: Option<i64>,
+    pub sender: String,
+    pub to_address: String,
+    pub amount_wei: String,
+    pub token_address: String,
+}
+
+/// 解码 Transfer 事件日志；不是标准 Transfer 或不是向 vault 的转账时返回 None
+pub fn decode_transfer(log: &Log, target: VaultTarget) -> Option<VaultTransfer> {
     // 验证是否为标准 Transfer 事件
     if log.topics.len() != 3 || log.topics[0] != get_transfer_event_signature() {
-        return Ok(false);
+        return None;
     }
 
     // 主题2为 to 地址（indexed）
     let to_topic = log.topics[2];
     let to = Address::from_slice(&to_topic.as_bytes()[12..]);
-    
+
     // 只处理向vault的转账
-    if to != vault_addr {
-        return Ok(false);
+    if to != target.vault {
+        return None;
     }
 
     // 主题1为 from 地址
@@ -42,32 +86,104 @@ async fn process_transfer_log(
     // data 为 amount（uint256）
     let amount = U256::from_big_endian(log.data.as_ref());
 
-    let tx_hash = format!("0x{:x}", log.transaction_hash.unwrap_or_default());
-    let block_number = log.block_number.unwrap_or_default().as_u64() as i64;
-    let tx_index = log.transaction_index.map(|i| i.as_u64() as i64);
-    let sender = format!("0x{:x}", from);
-    let to_address = format!("0x{:x}", to);
-    let amount_raw = u256_to_string(amount);
-
-    insert_deposit(pool, &tx_hash, block_number, tx_index, &sender, &to_address, &amount_raw, &format!("0x{:x}", usdc_addr)).await?;
-    update_last_block(pool, source, block_number).await?;
-    
-    Ok(true)
+    Some(VaultTransfer {
+        tx_hash: format!("0x{:x}", log.transaction_hash.unwrap_or_default()),
+        block_number: log.block_number.unwrap_or_default().as_u64() as i64,
+        tx_index: log.transaction_index.map(|i| i.as_u64() as i64),
+        sender: format!("0x{:x}", from),
+        to_address: format!("0x{:x}", to),
+        amount_wei: u256_to_string(amount),
+        token_address: format!("0x{:x}", target.token),
+    })
+}
+
+/// 在调用方的事务中写入一批转账对应的入金，返回新入库的记录
+/// 入金与 webhook 事件写入同一事务，保证下游不会漏收或收到未落库的入金
+pub async fn index_transfers(conn: &mut PgConnection, transfers: &[VaultTransfer]) -> anyhow::Result<Vec<Deposit>> {
+    let mut inserted = Vec::new();
+    for transfer in transfers {
+        if let Some(deposit) = insert_deposit(&mut *conn, transfer).await? {
+            WebhookService::enqueue(&mut *conn, EVENT_DEPOSIT_CREATED, &deposit).await?;
+            inserted.push(deposit);
+        }
+    }
+    Ok(inserted)
+}
+
+/// 在调用方的事务中把 from_block 及之后、不在 keep 中的已确认入金标记为 orphaned，
+/// 同时冲销已记账的金额并发出 deposit.orphaned 事件，返回被丢弃的入金
+pub(crate) async fn orphan_deposits(
+    conn: &mut PgConnection,
+    token_address: &str,
+    from_block: i64,
+    keep: &[String],
+) -> anyhow::Result<Vec<Deposit>> {
+    let orphaned = sqlx::query_as::<_, Deposit>(
+        "WITH orphaned AS (
+             UPDATE vault_deposits SET status = 'orphaned'
+             WHERE status = 'confirmed' AND token_address = $1 AND block_number >= $2 AND tx_hash <> ALL($3)
+             RETURNING *
+         )
+         SELECT o.id, o.tx_hash, o.block_number, o.tx_index, o.sender, o.to_address, o.amount_wei,
+                o.token_address, o.status, o.created_at, w.user_id
+         FROM orphaned o LEFT JOIN user_wallets w ON w.address = o.sender
+         ORDER BY o.id"
+    )
+    .bind(token_address)
+    .bind(from_block)
+    .bind(keep)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for deposit in &orphaned {
+        LedgerService::reverse_deposit(&mut *conn, &deposit.tx_hash).await?;
+        WebhookService::enqueue(&mut *conn, EVENT_DEPOSIT_ORPHANED, deposit).await?;
+    }
+    Ok(orphaned)
+}
+
+/// 拉取一个区块范围（闭区间）内入金代币的 Transfer 事件
+pub async fn fetch_transfer_logs(
+    rpc: &RpcPool,
+    target: VaultTarget,
+    from_block: i64,
+    to_block: i64,
+) -> anyhow::Result<Vec<Log>> {
+    rpc.get_logs(&transfer_filter(target, from_block, to_block)).await
+}
+
+/// 入金代币在区块范围内的 Transfer 事件过滤器；入库时再过滤 to == vault
+fn transfer_filter(target: VaultTarget, from_block: i64, to_block: i64) -> Filter {
+    Filter::new()
+        .address(target.token)
+        .from_block(from_block as u64)
+        .to_block(to_block as u64)
+}
+
+/// 事务提交后推送新入金；重复扫描到的记录不会出现在这里
+pub fn publish_deposits(notifier: &DepositNotifier, deposits: Vec<Deposit>) {
+    for deposit in deposits {
+        log::info!(
+            "💰 检测到入金: {} -> {} amount: {} USDC (tx: {})",
+            deposit.sender, deposit.to_address, deposit.amount_wei, deposit.tx_hash
+        );
+        notifier.publish(deposit);
+    }
 }
 
 /// 获取进度（last_block_number）
-async fn get_last_block(pool: &DatabasePool, source: &str) -> Result<Option<i64>, sqlx::Error> {
+pub async fn get_last_block<'e, E: PgExecutor<'e>>(executor: E, source: &str) -> Result<Option<i64>, sqlx::Error> {
     let row: Option<(i64,)> = sqlx::query_as(
         "SELECT last_block_number FROM indexer_progress WHERE source = $1"
     )
     .bind(source)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await?;
     Ok(row.map(|r| r.0))
 }
 
 /// 更新进度表
-async fn update_last_block(pool: &DatabasePool, source: &str, last_block: i64) -> Result<(), sqlx::Error> {
+pub async fn update_last_block(pool: &DatabasePool, source: &str, last_block: i64) -> Result<(), sqlx::Error> {
     sqlx::query(
         "INSERT INTO indexer_progress (source, last_block_number, updated_at)
          VALUES ($1, $2, NOW())
@@ -80,70 +196,293 @@ async fn update_last_block(pool: &DatabasePool, source: &str, last_block: i64) -
     Ok(())
 }
 
-/// 幂等插入一条入金记录（ERC20，记录 token_address）
-async fn insert_deposit(
-    pool: &DatabasePool,
-    tx_hash: &str,
-    block_number: i64,
-    tx_index: Option<i64>,
-    sender: &str,
-    to_address: &str,
-    amount_wei: &str,
-    token_address: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query(
-        "INSERT INTO vault_deposits (tx_hash, block_number, tx_index, sender, to_address, amount_wei, token_address, status)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, 'confirmed')
-         ON CONFLICT (tx_hash) DO NOTHING"
+/// 幂等插入一条入金记录（ERC20，记录 token_address），返回新插入的记录；已存在时返回 None
+/// 此前被链重组丢弃（orphaned）的交易重新上链时恢复为 confirmed 并记录新区块，
+/// 清空记账凭证后由记账任务按新的幂等键重新记账，同样作为新入金返回
+async fn insert_deposit(conn: &mut PgConnection, transfer: &VaultTransfer) -> Result<Option<Deposit>, sqlx::Error> {
+    sqlx::query_as::<_, Deposit>(
+        "WITH inserted AS (
+             INSERT INTO vault_deposits (tx_hash, block_number, tx_index, sender, to_address, amount_wei, token_address, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, 'confirmed')
+             ON CONFLICT (tx_hash) DO UPDATE
+                 SET status = 'confirmed', block_number = EXCLUDED.block_number, tx_index = EXCLUDED.tx_index,
+                     ledger_entry_id = NULL, reinclusions = vault_deposits.reinclusions + 1
+                 WHERE vault_deposits.status = 'orphaned'
+             RETURNING *
+         )
+         SELECT i.id, i.tx_hash, i.block_number, i.tx_index, i.sender, i.to_address, i.amount_wei,
+                i.token_address, i.status, i.created_at, w.user_id
+         FROM inserted i LEFT JOIN user_wallets w ON w.address = i.sender"
     )
-    .bind(tx_hash)
-    .bind(block_number)
-    .bind(tx_index)
-    .bind(sender)
-    .bind(to_address)
-    .bind(amount_wei)
-    .bind(token_address)
-    .execute(pool)
-    .await?;
-    Ok(())
+    .bind(&transfer.tx_hash)
+    .bind(transfer.block_number)
+    .bind(transfer.tx_index)
+    .bind(&transfer.sender)
+    .bind(&transfer.to_address)
+    .bind(&transfer.amount_wei)
+    .bind(&transfer.token_address)
+    .fetch_optional(conn)
+    .await
 }
 
-/// 启动 Arbitrum 上的 Vault 监听器：
-/// - 补扫区块：从上次处理到的区块到最新区块
-/// - 轮询新区块：定期检查新区块中的USDC转账事件
-pub async fn start_vault_watcher(config: Config, pool: DatabasePool) -> anyhow::Result<()> {
-    if !config.enable_vault_watcher {
-        log::info!("🔕 Vault 监听已禁用（ENABLE_VAULT_WATCHER=false）");
-        return Ok(());
+/// 在批次事务中推进进度：仅当进度仍为 expected 时才更新（比较并交换）
+/// 返回 false 表示进度已被外部修改（例如管理工具回退了进度），调用方应回滚本批次
+pub(crate) async fn advance_cursor(
+    conn: &mut PgConnection,
+    source: &str,
+    expected: Option<i64>,
+    last_block: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = match expected {
+        Some(expected) => {
+            sqlx::query(
+                "UPDATE indexer_progress SET last_block_number = $2, updated_at = NOW()
+                 WHERE source = $1 AND last_block_number = $3"
+            )
+            .bind(source)
+            .bind(last_block)
+            .bind(expected)
+            .execute(&mut *conn)
+            .await?
+        }
+        None => {
+            sqlx::query(
+                "INSERT INTO indexer_progress (source, last_block_number, updated_at)
+                 VALUES ($1, $2, NOW())
+                 ON CONFLICT (source) DO NOTHING"
+            )
+            .bind(source)
+            .bind(last_block)
+            .execute(&mut *conn)
+            .await?
+        }
+    };
+    Ok(result.rows_affected() == 1)
+}
+
+/// 删除进度记录，监听器下次启动时按 VAULT_START_BLOCK 重新选择起点
+pub async fn reset_progress(pool: &DatabasePool, source: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM indexer_progress WHERE source = $1")
+        .bind(source)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 交叉校验批次的末尾区块，并只从哈希一致的节点拉取本批次日志
+///
+/// 无法达成多数时返回 None，调用方应停止推进进度；
+/// 返回的日志中落在末尾区块的必须带有一致的区块哈希，避免在校验与拉取之间发生重组
+pub(crate) async fn fetch_verified_transfer_logs(
+    rpc: &RpcPool,
+    target: VaultTarget,
+    from_block: i64,
+    to_block: i64,
+) -> anyhow::Result<Option<Vec<Log>>> {
+    if !rpc.cross_check_enabled() {
+        return fetch_transfer_logs(rpc, target, from_block, to_block).await.map(Some);
     }
 
-    let http_url = match &config.arbitrum_http_url { 
-        Some(u) => u.clone(), 
-        None => anyhow::bail!("ARBITRUM_HTTP_URL 未设置") 
-    };
-    let vault_addr_str = match &config.vault_contract_address { 
-        Some(a) => a.clone(), 
-        None => anyhow::bail!("VAULT_CONTRACT_ADDRESS 未设置") 
-    };
-    let usdc_addr_str = match &config.usdc_token_address { 
-        Some(a) => a.clone(), 
-        None => anyhow::bail!("USDC_TOKEN_ADDRESS 未设置") 
+    match rpc.cross_check_block(to_block as u64).await {
+        CrossCheckOutcome::Agreed { hash, agreeing, lagging, conflicting } => {
+            if !conflicting.is_empty() {
+                log::error!("🚨 区块 {} 哈希不一致，已降级节点: {:?} (多数哈希: 0x{:x})", to_block, conflicting, hash);
+            }
+            if !lagging.is_empty() {
+                log::warn!("🐢 以下节点尚未同步到区块 {}: {:?}", to_block, lagging);
+            }
+
+            let logs = rpc.get_logs_from(&agreeing, &transfer_filter(target, from_block, to_block)).await?;
+            let end = U64::from(to_block as u64);
+            if let Some(log) = logs.iter().find(|log| log.block_number == Some(end) && log.block_hash != Some(hash)) {
+                anyhow::bail!(
+                    "区块 {} 的日志哈希 {:?} 与交叉校验结果 0x{:x} 不一致，可能刚发生重组",
+                    to_block, log.block_hash, hash
+                );
+            }
+            Ok(Some(logs))
+        }
+        CrossCheckOutcome::Inconclusive { responses } => {
+            log::error!("🚨 区块 {} 交叉校验未达成多数，暂停推进: {:?}", to_block, responses);
+            Ok(None)
+        }
+    }
+}
+
+/// 扫描提前结束的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanStop {
+    /// 批次末尾区块交叉校验未通过
+    Unverified { block: i64 },
+    /// 拉取日志失败
+    Rpc { from: i64, to: i64, error: String },
+    /// 进度已被外部修改，本批次已放弃
+    CursorMoved { from: i64, to: i64 },
+}
+
+impl fmt::Display for ScanStop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanStop::Unverified { block } => write!(f, "区块 {} 交叉校验未通过", block),
+            ScanStop::Rpc { from, to, error } => write!(f, "区块范围 {} -> {} 获取日志失败: {}", from, to, error),
+            ScanStop::CursorMoved { from, to } => write!(f, "区块范围 {} -> {} 提交时进度已被外部修改", from, to),
+        }
+    }
+}
+
+/// 一次扫描的结果
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    /// 最后成功提交的区块
+    pub last_committed: Option<i64>,
+    /// 新入库的入金数量
+    pub deposits: usize,
+    /// 未扫描到 to_block 时的原因
+    pub stopped: Option<ScanStop>,
+}
+
+/// 分批扫描 [from_block, to_block]，每批的入金与进度原子提交；cursor 为扫描开始前的进度
+/// 某批失败时停止处理后续批次，避免跳过区块；每批提交后调用 on_batch(批次末尾区块, 新入金)
+#[allow(clippy::too_many_arguments)]
+pub async fn scan_range<C, S, F>(
+    chain: &C,
+    store: &mut S,
+    target: VaultTarget,
+    mut cursor: Option<i64>,
+    from_block: i64,
+    to_block: i64,
+    notifier: &DepositNotifier,
+    mut on_batch: F,
+) -> anyhow::Result<ScanReport>
+where
+    C: ChainSource + ?Sized,
+    S: ProgressStore + ?Sized,
+    F: FnMut(i64, &[Deposit]),
+{
+    let mut report = ScanReport::default();
+    let mut current_block = from_block;
+
+    while current_block <= to_block {
+        let end_block = (current_block + BATCH_BLOCKS - 1).min(to_block);
+        log::debug!("🔍 处理区块范围: {} -> {}", current_block, end_block);
+
+        // 先完成网络请求，再开启事务，避免长时间持有事务
+        let logs = match chain.verified_transfer_logs(target, current_block, end_block).await {
+            Ok(Some(logs)) => logs,
+            Ok(None) => {
+                report.stopped = Some(ScanStop::Unverified { block: end_block });
+                break;
+            }
+            Err(e) => {
+                log::error!("❌ 获取日志失败 (区块范围 {} -> {}): {}", current_block, end_block, e);
+                report.stopped = Some(ScanStop::Rpc { from: current_block, to: end_block, error: e.to_string() });
+                break;
+            }
+        };
+        let transfers: Vec<VaultTransfer> = logs.iter().filter_map(|log| decode_transfer(log, target)).collect();
+
+        let inserted = match store.commit_batch(cursor, end_block, &transfers).await? {
+            BatchCommit::Committed(inserted) => inserted,
+            BatchCommit::CursorMoved => {
+                log::warn!("⚠️ 进度已被外部修改，放弃区块 {} -> {} 并从新进度继续", current_block, end_block);
+                report.stopped = Some(ScanStop::CursorMoved { from: current_block, to: end_block });
+                break;
+            }
+        };
+
+        on_batch(end_block, &inserted);
+        report.deposits += inserted.len();
+        publish_deposits(notifier, inserted);
+        cursor = Some(end_block);
+        report.last_committed = Some(end_block);
+        current_block = end_block + 1;
+    }
+
+    Ok(report)
+}
+
+/// 链重组检测：按链上最新数据重新核对最近 REORG_CHECK_BLOCKS 个已处理区块
+/// 已不在链上的入金被标记为 orphaned 并冲销，窗口内新出现的入金补录；链头回退到进度之下时进度随之回退
+/// 返回核对后的进度
+async fn check_reorg<C, S>(
+    chain: &C,
+    store: &mut S,
+    target: VaultTarget,
+    last_processed: i64,
+    latest: i64,
+    notifier: &DepositNotifier,
+) -> anyhow::Result<i64>
+where
+    C: ChainSource + ?Sized,
+    S: ReorgStore + ?Sized,
+{
+    let to_block = last_processed.min(latest);
+    let from_block = (to_block - REORG_CHECK_BLOCKS + 1).max(0);
+
+    let logs = chain.transfer_logs(target, from_block, to_block).await?;
+    let transfers: Vec<VaultTransfer> = logs.iter().filter_map(|log| decode_transfer(log, target)).collect();
+    let token_address = format!("0x{:x}", target.token);
+
+    match store.reconcile_recent(last_processed, &token_address, from_block, to_block, &transfers).await? {
+        ReorgCheck::Checked { inserted, orphaned } => {
+            for deposit in &orphaned {
+                log::error!(
+                    "🚨 链重组丢弃了入金，已标记为 orphaned: tx={}, block={}, amount={}",
+                    deposit.tx_hash, deposit.block_number, deposit.amount_wei
+                );
+            }
+            if to_block < last_processed {
+                log::warn!("⚠️ 链头回退到 {}，进度从 {} 回退", to_block, last_processed);
+            }
+            publish_deposits(notifier, inserted);
+            Ok(to_block)
+        }
+        ReorgCheck::CursorMoved => anyhow::bail!("重组核对时进度已被外部修改"),
+    }
+}
+
+/// 轮询一次：先核对最近已处理的区块是否被重组，再从进度扫描到最新区块；没有新区块时返回 None
+pub async fn poll_once<C, S>(
+    chain: &C,
+    store: &mut S,
+    target: VaultTarget,
+    notifier: &DepositNotifier,
+) -> anyhow::Result<Option<ScanReport>>
+where
+    C: ChainSource + ?Sized,
+    S: ReorgStore + ?Sized,
+{
+    // 获取最新区块号
+    let latest = chain.block_number().await?;
+
+    // 获取上次处理的区块号
+    let Some(mut last_processed) = store.cursor().await? else {
+        anyhow::bail!("无法获取上次处理的区块号");
     };
 
-    let vault_addr: Address = vault_addr_str.parse()?;
-    let usdc_addr: Address = usdc_addr_str.parse()?;
-    let source = "arbitrum_vault";
+    // 核对失败不影响本轮扫描新区块，下一轮会再次核对
+    match check_reorg(chain, store, target, last_processed, latest, notifier).await {
+        Ok(cursor) => last_processed = cursor,
+        Err(e) => log::warn!("⚠️ 链重组核对失败，下轮重试: {}", e),
+    }
+
+    if latest <= last_processed {
+        return Ok(None);
+    }
 
-    // HTTP provider
-    let http = Provider::<Http>::try_from(http_url.clone())?;
-    let latest = http.get_block_number().await?.as_u64() as i64;
+    // 如果有新区块，检查其中的USDC转账事件
+    log::info!("🔍 检查新区块: {} -> {}", last_processed + 1, latest);
+    let report = scan_range(chain, store, target, Some(last_processed), last_processed + 1, latest, notifier, |_, _| {}).await?;
+    Ok(Some(report))
+}
 
-    // 选择起始区块：优先用进度表，否则使用配置的起始块，最后回退到最新往前回溯 10 个块
-    let start_block = match get_last_block(&pool, source).await? {
+/// 选择起始区块：优先用进度表，否则使用配置的起始块，最后回退到最新往前回溯 10 个块
+pub fn initial_start_block(last_processed: Option<i64>, configured_start: Option<u64>, latest: i64) -> i64 {
+    match last_processed {
         Some(last) => last + 1,
         None => {
             // 如果配置了起始块高度，使用配置值；否则从最新往前回溯 10 个块
-            match config.vault_start_block {
+            match configured_start {
                 Some(configured_start) => {
                     log::info!("🎯 使用配置的起始块高度: {}", configured_start);
                     configured_start as i64
@@ -154,140 +493,321 @@ pub async fn start_vault_watcher(config: Config, pool: DatabasePool) -> anyhow::
                 }
             }
         }
-    };
+    }
+}
+
+/// 启动 Arbitrum 上的 Vault 监听器：
+/// - 补扫区块：从上次处理到的区块到最新区块
+/// - 轮询新区块：定期检查新区块中的USDC转账事件
+/// - 每批区块的入金与进度在同一事务中提交，多实例部署时通过 advisory lock 保证只有一个实例在索引
+/// - 新入金通过 notifier 广播给实时推送的订阅者
+pub async fn start_vault_watcher(config: Config, pool: DatabasePool, notifier: DepositNotifier) -> anyhow::Result<()> {
+    if !config.enable_vault_watcher {
+        log::info!("🔕 Vault 监听已禁用（ENABLE_VAULT_WATCHER=false）");
+        return Ok(());
+    }
+
+    let target = VaultTarget::from_config(&config)?;
+    let source = VAULT_SOURCE;
+
+    // HTTP RPC 节点池（支持多节点故障转移、指数退避重试与请求超时）
+    let rpc = RpcPool::from_config(&config)?;
+    log::info!("🌐 已加载 {} 个 RPC 节点，交叉校验: {}", rpc.status().len(), rpc.cross_check_enabled());
+
+    // 同一来源只允许一个实例索引，其他实例在此等待
+    let lock = SourceLock::acquire(&pool, &source_lock_key(source)).await?;
+    let mut store = VaultProgressStore::new(lock, source);
+
+    let latest = rpc.block_number().await?;
+    let last_processed = store.cursor().await?;
+    let start_block = initial_start_block(last_processed, config.vault_start_block, latest);
 
     if start_block <= latest {
         log::info!("📦 开始补扫 USDC Transfer 事件: {} -> {}", start_block, latest);
-        
-        // 分批处理，每批最多10个区块（Alchemy免费计划限制）
-        let mut current_block = start_block;
-        while current_block <= latest {
-            let end_block = (current_block + 9).min(latest);
-            log::info!("🔍 处理区块范围: {} -> {}", current_block, end_block);
-            
-            // 通过 get_logs 拉取 USDC 的 Transfer 事件，再过滤 to == vault
-            let filter = Filter::new()
-                .address(usdc_addr)
-                .from_block(current_block as u64)
-                .to_block(end_block as u64);
-
-            match http.get_logs(&filter).await {
-                Ok(logs) => {
-                    for log in logs {
-                        if process_transfer_log(&pool, &log, vault_addr, usdc_addr, source).await? {
-                            let tx_hash = format!("0x{:x}", log.transaction_hash.unwrap_or_default());
-                            let from_topic = log.topics[1];
-                            let from = Address::from_slice(&from_topic.as_bytes()[12..]);
-                            let to_topic = log.topics[2];
-                            let to = Address::from_slice(&to_topic.as_bytes()[12..]);
-                            let amount = U256::from_big_endian(log.data.as_ref());
-                            let amount_raw = u256_to_string(amount);
-                            let sender = format!("0x{:x}", from);
-                            let to_address = format!("0x{:x}", to);
-                            
-                            log::info!("💰 检测到入金: {} -> {} amount: {} USDC (tx: {})", sender, to_address, amount_raw, tx_hash);
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("❌ 获取日志失败 (区块范围 {} -> {}): {}", current_block, end_block, e);
-                    // 如果失败，停止处理后续批次，避免跳过区块
-                    break;
-                }
-            }
-            
-            current_block = end_block + 1;
-        }
-        
-        // 最后更新到成功处理的区块，而不是latest，确保失败的区块可以在下次尝试中被处理
-        if current_block > start_block {
-            let last_successfully_processed = current_block - 1;
-            update_last_block(&pool, source, last_successfully_processed).await?;
-            log::info!("✅ 初始扫描完成，已处理到区块: {}", last_successfully_processed);
+        let report = scan_range(&rpc, &mut store, target, last_processed, start_block, latest, &notifier, |_, _| {}).await?;
+        if let Some(last) = report.last_committed {
+            log::info!("✅ 初始扫描完成，已处理到区块: {}", last);
         }
     }
 
     // 使用HTTP轮询新区块
     log::info!("🔄 开始轮询新区块，每5秒检查一次");
     let mut interval = time::interval(Duration::from_secs(5));
-    
+
     loop {
         interval.tick().await;
-        
-        // 获取最新区块号
-        let current_latest = match http.get_block_number().await {
-            Ok(block_number) => block_number.as_u64() as i64,
-            Err(e) => {
-                log::error!("❌ 获取最新区块号失败: {}", e);
-                continue;
-            }
-        };
-        
-        // 获取上次处理的区块号
-        let last_processed = match get_last_block(&pool, source).await {
-            Ok(Some(last)) => last,
-            Ok(None) => {
-                log::error!("❌ 无法获取上次处理的区块号");
-                continue;
-            }
-            Err(e) => {
-                log::error!("❌ 查询上次处理的区块号失败: {}", e);
-                continue;
-            }
-        };
-        
-        // 如果有新区块，检查其中的USDC转账事件
-        if current_latest > last_processed {
-            log::info!("🔍 检查新区块: {} -> {}", last_processed + 1, current_latest);
-            
-            // 分批处理，每批最多10个区块（Alchemy免费计划限制）
-            let mut current_block = last_processed + 1;
-            let mut last_successfully_processed = last_processed;
-            
-            while current_block <= current_latest {
-                let end_block = (current_block + 9).min(current_latest);
-                
-                let filter = Filter::new()
-                    .address(usdc_addr)
-                    .from_block(current_block as u64)
-                    .to_block(end_block as u64);
-                
-                match http.get_logs(&filter).await {
-                    Ok(logs) => {
-                        for log in logs {
-                            if process_transfer_log(&pool, &log, vault_addr, usdc_addr, source).await? {
-                                let tx_hash = format!("0x{:x}", log.transaction_hash.unwrap_or_default());
-                                let from_topic = log.topics[1];
-                                let from = Address::from_slice(&from_topic.as_bytes()[12..]);
-                                let to_topic = log.topics[2];
-                                let to = Address::from_slice(&to_topic.as_bytes()[12..]);
-                                let amount = U256::from_big_endian(log.data.as_ref());
-                                let amount_raw = u256_to_string(amount);
-                                let sender = format!("0x{:x}", from);
-                                let to_address = format!("0x{:x}", to);
-                                
-                                log::info!("🔔 实时检测到入金: {} -> {} amount: {} USDC (tx: {})", sender, to_address, amount_raw, tx_hash);
-                            }
-                        }
-                        // 更新成功处理的区块
-                        last_successfully_processed = end_block;
-                    }
-                    Err(e) => {
-                        log::error!("❌ 获取日志失败 (区块范围 {} -> {}): {}", current_block, end_block, e);
-                        // 如果失败，停止处理后续批次，避免跳过区块
-                        break;
-                    }
-                }
-                
-                current_block = end_block + 1;
-            }
-            
-            // 更新已处理的区块号
-            if last_successfully_processed > last_processed {
-                if let Err(e) = update_last_block(&pool, source, last_successfully_processed).await {
-                    log::error!("❌ 更新区块进度失败: {}", e);
-                }
-            }
+
+        // 持锁连接断开意味着锁已释放，重新排队获取
+        if !store.lock_mut().is_alive().await {
+            log::error!("❌ 索引锁连接已断开，重新获取锁");
+            let lock = SourceLock::acquire(&pool, &source_lock_key(source)).await?;
+            store = VaultProgressStore::new(lock, source);
+        }
+
+        if let Err(e) = poll_once(&rpc, &mut store, target, &notifier).await {
+            log::error!("❌ 处理新区块失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listeners::chain_source::FakeChain;
+    use crate::listeners::progress_store::MemoryProgressStore;
+
+    fn target() -> VaultTarget {
+        VaultTarget {
+            vault: Address::from_low_u64_be(0xa),
+            token: Address::from_low_u64_be(0xb),
         }
     }
-}
\ No newline at end of file
+
+    fn alice() -> Address {
+        Address::from_low_u64_be(0x1)
+    }
+
+    #[test]
+    fn test_decode_transfer() {
+        let chain = FakeChain::new(0);
+        let target = target();
+        chain.transfer(target.token, alice(), target.vault, 1_000);
+        chain.transfer(target.token, alice(), Address::from_low_u64_be(0x2), 500);
+
+        let logs = futures_util::FutureExt::now_or_never(chain.transfer_logs(target, 1, 2)).unwrap().unwrap();
+        let transfer = decode_transfer(&logs[0], target).unwrap();
+        assert_eq!(transfer.block_number, 1);
+        assert_eq!(transfer.amount_wei, "1000");
+        assert_eq!(transfer.sender, format!("0x{:x}", alice()));
+        assert_eq!(transfer.token_address, format!("0x{:x}", target.token));
+        assert!(decode_transfer(&logs[1], target).is_none()); // 不是转给 vault
+    }
+
+    #[test]
+    fn test_initial_start_block() {
+        assert_eq!(initial_start_block(Some(100), Some(5), 200), 101);
+        assert_eq!(initial_start_block(None, Some(5), 200), 5);
+        assert_eq!(initial_start_block(None, None, 200), 190);
+        assert_eq!(initial_start_block(None, None, 3), 0);
+    }
+
+    #[tokio::test]
+    async fn test_scan_range_commits_each_batch_with_cursor() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut rx = notifier.subscribe();
+
+        chain.mine(2);
+        chain.transfer(target.token, alice(), target.vault, 1_000); // 区块 103
+        chain.transfer(Address::from_low_u64_be(0xc), alice(), target.vault, 7); // 其他代币
+        chain.mine(14);
+        chain.transfer(target.token, alice(), target.vault, 2_000); // 区块 119
+        chain.mine(6);
+
+        let mut store = MemoryProgressStore::new(Some(99));
+        let mut batches = Vec::new();
+        let report = scan_range(&chain, &mut store, target, Some(99), 100, 125, &notifier, |end, inserted| {
+            batches.push((end, inserted.len()))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(chain.log_requests(), vec![(100, 109), (110, 119), (120, 125)]);
+        assert_eq!(batches, vec![(109, 1), (119, 1), (125, 0)]);
+        assert_eq!(store.commits, vec![109, 119, 125]);
+        assert_eq!(report.last_committed, Some(125));
+        assert_eq!(report.deposits, 2);
+        assert_eq!(report.stopped, None);
+
+        // 提交后才推送
+        assert_eq!(rx.try_recv().unwrap().amount_wei, "1000");
+        assert_eq!(rx.try_recv().unwrap().amount_wei, "2000");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_error_stops_without_skipping_blocks() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut store = MemoryProgressStore::new(Some(100));
+
+        chain.transfer(target.token, alice(), target.vault, 1_000);
+        chain.mine(20);
+        // 第一次失败发生在重组核对（只记录告警），第二次发生在扫描新区块
+        chain.fail_logs(2);
+
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert!(matches!(report.stopped, Some(ScanStop::Rpc { from: 101, to: 110, .. })));
+        assert_eq!(store.cursor, Some(100));
+        assert!(store.deposits.is_empty());
+
+        // 下一轮从同一位置重试
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.stopped, None);
+        assert_eq!(store.cursor, Some(121));
+        assert_eq!(store.deposits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unverified_block_halts_progress() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut store = MemoryProgressStore::new(Some(100));
+
+        chain.mine(20);
+        chain.set_verified(120, false);
+
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.stopped, Some(ScanStop::Unverified { block: 120 }));
+        assert_eq!(store.cursor, Some(110));
+
+        chain.set_verified(120, true);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        assert_eq!(store.cursor, Some(120));
+    }
+
+    #[tokio::test]
+    async fn test_polling_handles_idle_errors_and_gaps() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+
+        // 没有进度时不会猜测起点
+        let mut empty = MemoryProgressStore::new(None);
+        assert!(poll_once(&chain, &mut empty, target, &notifier).await.is_err());
+
+        let mut store = MemoryProgressStore::new(Some(100));
+        assert!(poll_once(&chain, &mut store, target, &notifier).await.unwrap().is_none());
+
+        chain.fail_block_number(1);
+        chain.mine(1);
+        assert!(poll_once(&chain, &mut store, target, &notifier).await.is_err());
+        assert_eq!(store.cursor, Some(100));
+
+        // 一次跳过 1000 个区块，按批次补齐
+        chain.mine(999);
+        chain.transfer(target.token, alice(), target.vault, 42);
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.last_committed, Some(1101));
+        assert_eq!(store.commits.len(), 101);
+        assert_eq!(store.deposits.len(), 1);
+        assert_eq!(store.deposits[0].block_number, 1101);
+    }
+
+    fn status_of(store: &MemoryProgressStore, tx_hash: H256) -> Option<String> {
+        let tx_hash = format!("0x{:x}", tx_hash);
+        store.deposits.iter().find(|d| d.tx_hash == tx_hash).map(|d| d.status.clone())
+    }
+
+    #[tokio::test]
+    async fn test_reorg_below_cursor_orphans_indexed_deposit() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut store = MemoryProgressStore::new(Some(100));
+
+        // 先索引区块 101 中的入金
+        let orphaned = chain.transfer(target.token, alice(), target.vault, 1_000);
+        chain.mine(2);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        assert_eq!(store.cursor, Some(103));
+        assert_eq!(status_of(&store, orphaned).as_deref(), Some("confirmed"));
+
+        // 区块 101 起被重组丢弃，新的链头低于已处理进度
+        chain.reorg(101);
+        let canonical = chain.transfer(target.token, alice(), target.vault, 2_000); // 新的区块 101
+        chain.mine(1);
+
+        let mut rx = notifier.subscribe();
+        assert!(poll_once(&chain, &mut store, target, &notifier).await.unwrap().is_none());
+        assert_eq!(status_of(&store, orphaned).as_deref(), Some("orphaned"));
+        assert_eq!(status_of(&store, canonical).as_deref(), Some("confirmed"));
+        assert_eq!(store.cursor, Some(102)); // 进度回退到新的链头
+        assert_eq!(rx.try_recv().unwrap().tx_hash, format!("0x{:x}", canonical));
+
+        // 链继续增长后从回退后的进度扫描，不会重复入金，也不会恢复被丢弃的入金
+        chain.mine(3);
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.deposits, 0);
+        assert_eq!(store.cursor, Some(105));
+        assert_eq!(store.deposits.len(), 2);
+        assert_eq!(status_of(&store, orphaned).as_deref(), Some("orphaned"));
+    }
+
+    #[tokio::test]
+    async fn test_reorg_at_same_height_orphans_indexed_deposit() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut store = MemoryProgressStore::new(Some(100));
+
+        chain.mine(3);
+        let orphaned = chain.transfer(target.token, alice(), target.vault, 1_000); // 区块 104
+        chain.mine(1);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        assert_eq!(store.cursor, Some(105));
+
+        // 重组替换了区块 104 之后的区块，新链头高于已处理进度
+        chain.reorg(104);
+        chain.mine(5);
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(status_of(&store, orphaned).as_deref(), Some("orphaned"));
+        assert_eq!(report.deposits, 0);
+        assert_eq!(store.cursor, Some(108));
+
+        // 回退进度后重新扫描不会产生重复入金
+        store.cursor = Some(100);
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.deposits, 0);
+        assert_eq!(store.cursor, Some(108));
+        assert_eq!(store.deposits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reincluded_deposit_is_confirmed_again() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        let mut store = MemoryProgressStore::new(Some(100));
+
+        let tx_hash = chain.transfer(target.token, alice(), target.vault, 1_000); // 区块 101
+        chain.mine(2);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        chain.reorg(101);
+        chain.mine(3);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        assert_eq!(status_of(&store, tx_hash).as_deref(), Some("orphaned"));
+
+        // 同一笔交易在核对窗口之外的新区块中重新上链
+        chain.mine(REORG_CHECK_BLOCKS * 3);
+        poll_once(&chain, &mut store, target, &notifier).await.unwrap();
+        let block = chain.mine(1);
+        chain.include_at(block, tx_hash, target.token, alice(), target.vault, 1_000);
+
+        let mut rx = notifier.subscribe();
+        let report = poll_once(&chain, &mut store, target, &notifier).await.unwrap().unwrap();
+        assert_eq!(report.deposits, 1);
+        assert_eq!(status_of(&store, tx_hash).as_deref(), Some("confirmed"));
+        assert_eq!(store.deposits.len(), 1);
+        assert_eq!(store.deposits[0].block_number, block);
+        assert_eq!(rx.try_recv().unwrap().tx_hash, format!("0x{:x}", tx_hash));
+    }
+
+    #[tokio::test]
+    async fn test_cursor_moved_abandons_batch() {
+        let chain = FakeChain::new(100);
+        let target = target();
+        let notifier = DepositNotifier::new();
+        chain.transfer(target.token, alice(), target.vault, 1_000);
+
+        // 扫描期间进度被管理工具回退到 90
+        let mut store = MemoryProgressStore::new(Some(90));
+        let report = scan_range(&chain, &mut store, target, Some(100), 101, 101, &notifier, |_, _| {}).await.unwrap();
+        assert_eq!(report.stopped, Some(ScanStop::CursorMoved { from: 101, to: 101 }));
+        assert_eq!(store.cursor, Some(90));
+        assert!(store.deposits.is_empty());
+    }
+}
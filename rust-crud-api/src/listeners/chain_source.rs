@@ -0,0 +1,188 @@
+use crate::listeners::arbitrum_vault::{VaultTarget, fetch_transfer_logs, fetch_verified_transfer_logs};
+use crate::listeners::rpc_pool::RpcPool;
+use ethers::prelude::*;
+use futures_util::future::BoxFuture;
+
+#[cfg(test)]
+pub use fake::FakeChain;
+
+/// 监听器读取链上数据的来源：生产环境为 RPC 节点池，测试中为脚本化的 FakeChain
+pub trait ChainSource: Send + Sync {
+    /// 最新区块号
+    fn block_number(&self) -> BoxFuture<'_, anyhow::Result<i64>>;
+
+    /// 拉取区块范围（闭区间）内入金代币的 Transfer 事件
+    fn transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Vec<Log>>>;
+
+    /// 交叉校验 to_block 后拉取区块范围内的 Transfer 事件；末尾区块不可信时返回 None，调用方应停止推进进度
+    fn verified_transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Option<Vec<Log>>>>;
+}
+
+impl ChainSource for RpcPool {
+    fn block_number(&self) -> BoxFuture<'_, anyhow::Result<i64>> {
+        Box::pin(async move { Ok(self.get_block_number().await? as i64) })
+    }
+
+    fn transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Vec<Log>>> {
+        Box::pin(fetch_transfer_logs(self, target, from_block, to_block))
+    }
+
+    fn verified_transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Option<Vec<Log>>>> {
+        Box::pin(fetch_verified_transfer_logs(self, target, from_block, to_block))
+    }
+}
+
+/// 测试用的脚本化链，只在测试中编译
+#[cfg(test)]
+mod fake {
+    use super::*;
+    use crate::listeners::arbitrum_vault::get_transfer_event_signature;
+    use std::collections::{BTreeMap, HashSet};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeChainState {
+        head: i64,
+        /// 每个区块中的日志（包含所有代币）
+        blocks: BTreeMap<i64, Vec<Log>>,
+        next_tx: u64,
+        head_failures: u32,
+        log_failures: u32,
+        unverified: HashSet<i64>,
+        log_requests: Vec<(i64, i64)>,
+    }
+
+    /// 进程内的脚本化链：用于在没有节点的情况下确定性地测试监听器
+    ///
+    /// 支持出块、转账、重组、RPC 错误与区块空洞（head 一次跳过大量空块），
+    /// 并记录监听器发出的每次日志请求
+    pub struct FakeChain {
+        state: Mutex<FakeChainState>,
+    }
+
+    impl FakeChain {
+        pub fn new(head: i64) -> Self {
+            Self {
+                state: Mutex::new(FakeChainState { head, ..Default::default() }),
+            }
+        }
+
+        pub fn head(&self) -> i64 {
+            self.state.lock().unwrap().head
+        }
+
+        /// 出 n 个空块；n 很大时即为区块空洞
+        pub fn mine(&self, n: i64) -> i64 {
+            let mut state = self.state.lock().unwrap();
+            state.head += n;
+            state.head
+        }
+
+        /// 出一个只包含这笔转账的新区块，返回交易哈希
+        pub fn transfer(&self, token: Address, from: Address, to: Address, amount: u64) -> H256 {
+            let block = self.mine(1);
+            self.transfer_at(block, token, from, to, amount)
+        }
+
+        /// 在指定区块中追加一笔转账（区块不存在时视为未来区块，需要出块后才可见）
+        pub fn transfer_at(&self, block_number: i64, token: Address, from: Address, to: Address, amount: u64) -> H256 {
+            let tx_hash = {
+                let mut state = self.state.lock().unwrap();
+                state.next_tx += 1;
+                H256::from_low_u64_be(state.next_tx)
+            };
+            self.include_at(block_number, tx_hash, token, from, to, amount);
+            tx_hash
+        }
+
+        /// 把指定哈希的转账打包进区块，用于模拟被重组丢弃的交易重新上链
+        pub fn include_at(&self, block_number: i64, tx_hash: H256, token: Address, from: Address, to: Address, amount: u64) {
+            let mut state = self.state.lock().unwrap();
+            let logs = state.blocks.entry(block_number).or_default();
+            logs.push(Log {
+                address: token,
+                topics: vec![get_transfer_event_signature(), H256::from(from), H256::from(to)],
+                data: Bytes::from(H256::from_low_u64_be(amount).as_bytes().to_vec()),
+                block_number: Some(U64::from(block_number as u64)),
+                transaction_hash: Some(tx_hash),
+                transaction_index: Some(U64::from(logs.len() as u64)),
+                ..Default::default()
+            });
+        }
+
+        /// 重组：丢弃 from_block 及之后的所有区块，链头回到 from_block - 1，之后可重新出块
+        pub fn reorg(&self, from_block: i64) {
+            let mut state = self.state.lock().unwrap();
+            state.blocks.retain(|block, _| *block < from_block);
+            state.head = state.head.min(from_block - 1);
+        }
+
+        /// 接下来 n 次查询最新区块号失败
+        pub fn fail_block_number(&self, n: u32) {
+            self.state.lock().unwrap().head_failures = n;
+        }
+
+        /// 接下来 n 次拉取日志失败
+        pub fn fail_logs(&self, n: u32) {
+            self.state.lock().unwrap().log_failures = n;
+        }
+
+        /// 设置区块交叉校验是否通过（模拟节点之间对区块哈希无法达成一致）
+        pub fn set_verified(&self, block_number: i64, verified: bool) {
+            let mut state = self.state.lock().unwrap();
+            if verified {
+                state.unverified.remove(&block_number);
+            } else {
+                state.unverified.insert(block_number);
+            }
+        }
+
+        /// 监听器发出的日志请求区间
+        pub fn log_requests(&self) -> Vec<(i64, i64)> {
+            self.state.lock().unwrap().log_requests.clone()
+        }
+    }
+
+    impl ChainSource for FakeChain {
+        fn block_number(&self) -> BoxFuture<'_, anyhow::Result<i64>> {
+            Box::pin(async move {
+                let mut state = self.state.lock().unwrap();
+                if state.head_failures > 0 {
+                    state.head_failures -= 1;
+                    anyhow::bail!("fake chain: eth_blockNumber 请求失败");
+                }
+                Ok(state.head)
+            })
+        }
+
+        fn transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Vec<Log>>> {
+            Box::pin(async move {
+                let mut state = self.state.lock().unwrap();
+                state.log_requests.push((from_block, to_block));
+                if state.log_failures > 0 {
+                    state.log_failures -= 1;
+                    anyhow::bail!("fake chain: eth_getLogs 请求失败");
+                }
+                if to_block > state.head {
+                    anyhow::bail!("fake chain: 区块 {} 尚未产生（最新区块 {}）", to_block, state.head);
+                }
+                Ok(state
+                    .blocks
+                    .range(from_block..=to_block)
+                    .flat_map(|(_, logs)| logs.iter())
+                    .filter(|log| log.address == target.token)
+                    .cloned()
+                    .collect())
+            })
+        }
+
+        fn verified_transfer_logs(&self, target: VaultTarget, from_block: i64, to_block: i64) -> BoxFuture<'_, anyhow::Result<Option<Vec<Log>>>> {
+            Box::pin(async move {
+                if self.state.lock().unwrap().unverified.contains(&to_block) {
+                    return Ok(None);
+                }
+                self.transfer_logs(target, from_block, to_block).await.map(Some)
+            })
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use crate::database::DatabasePool;
+use crate::listeners::arbitrum_vault::{
+    ScanStop, VaultTarget, VaultTransfer, get_transfer_event_signature, index_transfers, scan_range,
+};
+use crate::listeners::chain_source::ChainSource;
+use crate::listeners::progress_store::{BatchCommit, ProgressStore};
+use crate::listeners::rpc_pool::RpcPool;
+use crate::listeners::source_lock::{SourceLock, backfill_lock_key};
+use crate::models::Deposit;
+use crate::services::DepositNotifier;
+use chrono::{DateTime, Utc};
+use ethers::prelude::*;
+use futures_util::future::BoxFuture;
+use sqlx::{Connection, FromRow};
+use std::time::{Duration, Instant};
+
+/// 实时监听进度
+#[derive(Debug, Clone, FromRow)]
+pub struct IndexerProgress {
+    pub source: String,
+    pub last_block_number: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 手动补扫任务
+#[derive(Debug, Clone, FromRow)]
+pub struct BackfillRun {
+    pub id: i64,
+    pub source: String,
+    pub from_block: i64,
+    pub to_block: i64,
+    /// 下一个待扫描的区块，大于 to_block 时表示已完成
+    pub next_block: i64,
+    pub deposits_found: i64,
+    pub status: String,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BackfillRun {
+    pub fn total_blocks(&self) -> i64 {
+        self.to_block - self.from_block + 1
+    }
+
+    pub fn processed_blocks(&self) -> i64 {
+        (self.next_block - self.from_block).clamp(0, self.total_blocks())
+    }
+
+    /// 完成百分比
+    pub fn percent(&self) -> f64 {
+        self.processed_blocks() as f64 * 100.0 / self.total_blocks() as f64
+    }
+}
+
+/// 补扫过程中每批完成后的进度快照
+#[derive(Debug, Clone)]
+pub struct BackfillProgress {
+    pub run: BackfillRun,
+    /// 本次执行已扫描的区块数（不含之前中断前完成的部分）
+    pub blocks_this_session: i64,
+    pub elapsed: Duration,
+}
+
+impl BackfillProgress {
+    /// 按本次执行的速度估算剩余时间
+    pub fn eta(&self) -> Option<Duration> {
+        if self.blocks_this_session == 0 {
+            return None;
+        }
+        let remaining = self.run.total_blocks() - self.run.processed_blocks();
+        let per_block = self.elapsed.as_secs_f64() / self.blocks_this_session as f64;
+        Some(Duration::from_secs_f64(per_block * remaining as f64))
+    }
+}
+
+const RUN_COLUMNS: &str = "id, source, from_block, to_block, next_block, deposits_found, status, last_error, created_at, updated_at";
+
+/// 查询所有来源的实时进度
+pub async fn list_progress(pool: &DatabasePool) -> Result<Vec<IndexerProgress>, sqlx::Error> {
+    sqlx::query_as::<_, IndexerProgress>(
+        "SELECT source, last_block_number, updated_at FROM indexer_progress ORDER BY source"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// 创建补扫任务
+pub async fn create_run(pool: &DatabasePool, source: &str, from_block: i64, to_block: i64) -> Result<BackfillRun, sqlx::Error> {
+    sqlx::query_as::<_, BackfillRun>(&format!(
+        "INSERT INTO indexer_backfill_runs (source, from_block, to_block, next_block) VALUES ($1, $2, $3, $2) RETURNING {}",
+        RUN_COLUMNS
+    ))
+    .bind(source)
+    .bind(from_block)
+    .bind(to_block)
+    .fetch_one(pool)
+    .await
+}
+
+/// 根据 ID 获取补扫任务
+pub async fn get_run(pool: &DatabasePool, id: i64) -> Result<Option<BackfillRun>, sqlx::Error> {
+    sqlx::query_as::<_, BackfillRun>(&format!("SELECT {} FROM indexer_backfill_runs WHERE id = $1", RUN_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// 最近的补扫任务
+pub async fn list_runs(pool: &DatabasePool, limit: i64) -> Result<Vec<BackfillRun>, sqlx::Error> {
+    sqlx::query_as::<_, BackfillRun>(&format!(
+        "SELECT {} FROM indexer_backfill_runs ORDER BY id DESC LIMIT $1",
+        RUN_COLUMNS
+    ))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+async fn finish_run(pool: &DatabasePool, id: i64, status: &str, error: Option<&str>) -> Result<BackfillRun, sqlx::Error> {
+    sqlx::query_as::<_, BackfillRun>(&format!(
+        "UPDATE indexer_backfill_runs SET status = $2, last_error = $3 WHERE id = $1 RETURNING {}",
+        RUN_COLUMNS
+    ))
+    .bind(id)
+    .bind(status)
+    .bind(error)
+    .fetch_one(pool)
+    .await
+}
+
+/// 补扫任务的进度：indexer_backfill_runs 中的 next_block，写入在持有任务锁的连接上执行
+struct BackfillRunStore {
+    lock: SourceLock,
+    run_id: i64,
+}
+
+impl ProgressStore for BackfillRunStore {
+    fn cursor(&mut self) -> BoxFuture<'_, anyhow::Result<Option<i64>>> {
+        Box::pin(async move {
+            let next_block: i64 = sqlx::query_scalar("SELECT next_block FROM indexer_backfill_runs WHERE id = $1")
+                .bind(self.run_id)
+                .fetch_one(self.lock.conn())
+                .await?;
+            Ok(Some(next_block - 1))
+        })
+    }
+
+    fn commit_batch<'a>(
+        &'a mut self,
+        expected: Option<i64>,
+        last_block: i64,
+        transfers: &'a [VaultTransfer],
+    ) -> BoxFuture<'a, anyhow::Result<BatchCommit>> {
+        Box::pin(async move {
+            let mut tx = self.lock.conn().begin().await?;
+            let inserted = index_transfers(&mut tx, transfers).await?;
+            let result = sqlx::query(
+                r#"
+                UPDATE indexer_backfill_runs
+                SET next_block = $2, deposits_found = deposits_found + $3
+                WHERE id = $1 AND next_block = $4
+                "#,
+            )
+            .bind(self.run_id)
+            .bind(last_block + 1)
+            .bind(inserted.len() as i64)
+            .bind(expected.map(|b| b + 1))
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() != 1 {
+                return Ok(BatchCommit::CursorMoved);
+            }
+            tx.commit().await?;
+            Ok(BatchCommit::Committed(inserted))
+        })
+    }
+}
+
+/// 补扫循环：从 run.next_block 扫到 run.to_block，每批提交后回调进度
+/// 返回更新后的任务快照，以及未完成时的中断原因
+pub async fn drive_backfill<C, S, F>(
+    chain: &C,
+    store: &mut S,
+    target: VaultTarget,
+    mut run: BackfillRun,
+    notifier: &DepositNotifier,
+    mut on_progress: F,
+) -> anyhow::Result<(BackfillRun, Option<ScanStop>)>
+where
+    C: ChainSource + ?Sized,
+    S: ProgressStore + ?Sized,
+    F: FnMut(&BackfillProgress),
+{
+    let started = Instant::now();
+    let session_start = run.next_block;
+    let (from_block, to_block) = (run.next_block, run.to_block);
+
+    let report = scan_range(chain, store, target, Some(from_block - 1), from_block, to_block, notifier, |end_block, inserted| {
+        run.next_block = end_block + 1;
+        run.deposits_found += inserted.len() as i64;
+        on_progress(&BackfillProgress {
+            blocks_this_session: run.next_block - session_start,
+            elapsed: started.elapsed(),
+            run: run.clone(),
+        });
+    })
+    .await?;
+
+    Ok((run, report.stopped))
+}
+
+/// 执行（或继续执行）补扫任务：从 next_block 扫到 to_block，不修改实时进度
+/// 每批的入金与 next_block 在同一事务中提交，中断后可以从断点续跑
+pub async fn run_backfill<F>(
+    rpc: &RpcPool,
+    pool: &DatabasePool,
+    target: VaultTarget,
+    run: BackfillRun,
+    notifier: &DepositNotifier,
+    on_progress: F,
+) -> anyhow::Result<BackfillRun>
+where
+    F: FnMut(&BackfillProgress),
+{
+    // 同一任务只允许一个进程执行
+    let Some(lock) = SourceLock::try_acquire(pool, &backfill_lock_key(run.id)).await? else {
+        anyhow::bail!("补扫任务 #{} 正在其他进程中运行", run.id);
+    };
+    let mut store = BackfillRunStore { lock, run_id: run.id };
+
+    let run = finish_run(pool, run.id, "running", None).await?;
+    let (run, stopped) = drive_backfill(rpc, &mut store, target, run, notifier, on_progress).await?;
+
+    if let Some(stop) = stopped {
+        let message = stop.to_string();
+        finish_run(pool, run.id, "failed", Some(&message)).await?;
+        anyhow::bail!(message);
+    }
+
+    Ok(finish_run(pool, run.id, "completed", None).await?)
+}
+
+/// 入金与链上回执的核对结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// 回执中存在对应的 Transfer
+    Verified,
+    /// 链上查不到回执（交易被重组丢弃或哈希错误）
+    ReceiptMissing,
+    /// 交易执行失败
+    Reverted,
+    /// 回执所在区块与记录不一致（发生过重组）
+    BlockMismatch { stored: i64, actual: i64 },
+    /// 回执中没有与记录一致的 Transfer 事件
+    TransferMismatch,
+}
+
+/// 回执中是否包含与入金记录一致的 Transfer(sender -> vault, amount)
+pub fn receipt_contains_transfer(receipt: &TransactionReceipt, target: VaultTarget, sender: Address, amount: U256) -> bool {
+    let signature = get_transfer_event_signature();
+    receipt.logs.iter().any(|log| {
+        log.address == target.token
+            && log.topics.len() == 3
+            && log.topics[0] == signature
+            && Address::from_slice(&log.topics[1].as_bytes()[12..]) == sender
+            && Address::from_slice(&log.topics[2].as_bytes()[12..]) == target.vault
+            && U256::from_big_endian(log.data.as_ref()) == amount
+    })
+}
+
+/// 按链上回执重新核对一条入金记录
+pub async fn verify_deposit(rpc: &RpcPool, target: VaultTarget, deposit: &Deposit) -> anyhow::Result<VerifyOutcome> {
+    let tx_hash: H256 = deposit.tx_hash.parse()?;
+    let sender: Address = deposit.sender.parse()?;
+    let amount = U256::from_dec_str(&deposit.amount_wei)?;
+
+    let Some(receipt) = rpc.get_transaction_receipt(tx_hash).await? else {
+        return Ok(VerifyOutcome::ReceiptMissing);
+    };
+    if receipt.status == Some(U64::zero()) {
+        return Ok(VerifyOutcome::Reverted);
+    }
+
+    let actual = receipt.block_number.map(|b| b.as_u64() as i64).unwrap_or_default();
+    if actual != deposit.block_number {
+        return Ok(VerifyOutcome::BlockMismatch { stored: deposit.block_number, actual });
+    }
+
+    if receipt_contains_transfer(&receipt, target, sender, amount) {
+        Ok(VerifyOutcome::Verified)
+    } else {
+        Ok(VerifyOutcome::TransferMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listeners::chain_source::FakeChain;
+    use crate::listeners::progress_store::MemoryProgressStore;
+
+    fn transfer_log(token: Address, from: Address, to: Address, amount: u64) -> Log {
+        Log {
+            address: token,
+            topics: vec![get_transfer_event_signature(), H256::from(from), H256::from(to)],
+            data: Bytes::from(H256::from_low_u64_be(amount).as_bytes().to_vec()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_receipt_contains_transfer() {
+        let target = VaultTarget {
+            vault: Address::from_low_u64_be(1),
+            token: Address::from_low_u64_be(2),
+        };
+        let sender = Address::from_low_u64_be(3);
+        let other = Address::from_low_u64_be(4);
+
+        let receipt = TransactionReceipt {
+            logs: vec![
+                transfer_log(target.token, sender, other, 500),
+                transfer_log(target.token, sender, target.vault, 1_000),
+            ],
+            ..Default::default()
+        };
+
+        assert!(receipt_contains_transfer(&receipt, target, sender, U256::from(1_000)));
+        assert!(!receipt_contains_transfer(&receipt, target, sender, U256::from(500))); // 转给了其他地址
+        assert!(!receipt_contains_transfer(&receipt, target, other, U256::from(1_000))); // 发送方不一致
+
+        let other_token = TransactionReceipt {
+            logs: vec![transfer_log(other, sender, target.vault, 1_000)],
+            ..Default::default()
+        };
+        assert!(!receipt_contains_transfer(&other_token, target, sender, U256::from(1_000)));
+    }
+
+    #[test]
+    fn test_backfill_progress() {
+        let now = Utc::now();
+        let run = BackfillRun {
+            id: 1,
+            source: "arbitrum_vault".to_string(),
+            from_block: 100,
+            to_block: 199,
+            next_block: 150,
+            deposits_found: 3,
+            status: "running".to_string(),
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert_eq!(run.total_blocks(), 100);
+        assert_eq!(run.processed_blocks(), 50);
+        assert_eq!(run.percent(), 50.0);
+
+        // 续跑时只按本次执行的速度估算
+        let progress = BackfillProgress {
+            run,
+            blocks_this_session: 10,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.eta(), Some(Duration::from_secs(10)));
+    }
+
+    fn new_run(from_block: i64, to_block: i64) -> BackfillRun {
+        let now = Utc::now();
+        BackfillRun {
+            id: 1,
+            source: "arbitrum_vault".to_string(),
+            from_block,
+            to_block,
+            next_block: from_block,
+            deposits_found: 0,
+            status: "running".to_string(),
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drive_backfill_resumes_after_failure() {
+        let target = VaultTarget {
+            vault: Address::from_low_u64_be(1),
+            token: Address::from_low_u64_be(2),
+        };
+        let sender = Address::from_low_u64_be(3);
+        let chain = FakeChain::new(0);
+        chain.transfer_at(5, target.token, sender, target.vault, 100);
+        chain.transfer_at(17, target.token, sender, target.vault, 200);
+        chain.transfer_at(24, target.token, sender, target.vault, 300);
+        chain.mine(30);
+        let notifier = DepositNotifier::new();
+
+        // 第二批时交叉校验失败，任务中断
+        chain.set_verified(20, false);
+        let run = new_run(1, 25);
+        let mut store = MemoryProgressStore::new(Some(run.next_block - 1));
+        let mut snapshots = Vec::new();
+        let (run, stopped) = drive_backfill(&chain, &mut store, target, run, &notifier, |p| {
+            snapshots.push((p.run.next_block, p.run.deposits_found, p.blocks_this_session))
+        })
+        .await
+        .unwrap();
+        assert_eq!(stopped, Some(ScanStop::Unverified { block: 20 }));
+        assert_eq!(snapshots, vec![(11, 1, 10)]);
+        assert_eq!(run.next_block, 11);
+
+        // 续跑从断点继续，本次执行的区块数重新计算
+        chain.set_verified(20, true);
+        let mut snapshots = Vec::new();
+        let (run, stopped) = drive_backfill(&chain, &mut store, target, run, &notifier, |p| {
+            snapshots.push((p.run.next_block, p.run.deposits_found, p.blocks_this_session))
+        })
+        .await
+        .unwrap();
+        assert_eq!(stopped, None);
+        assert_eq!(snapshots, vec![(21, 2, 10), (26, 3, 15)]);
+        assert_eq!(run.processed_blocks(), run.total_blocks());
+        assert_eq!(chain.log_requests(), vec![(1, 10), (11, 20), (21, 25)]);
+        assert_eq!(store.deposits.len(), 3);
+    }
+}
@@ -1 +1,6 @@
-pub mod arbitrum_vault;
\ No newline at end of file
+pub mod arbitrum_vault;
+pub mod chain_source;
+pub mod indexer_admin;
+pub mod progress_store;
+pub mod rpc_pool;
+pub mod source_lock;
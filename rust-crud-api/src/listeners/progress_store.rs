@@ -0,0 +1,236 @@
+use crate::listeners::arbitrum_vault::{VaultTransfer, advance_cursor, get_last_block, index_transfers, orphan_deposits};
+use crate::listeners::source_lock::SourceLock;
+use crate::models::Deposit;
+use futures_util::future::BoxFuture;
+use sqlx::Connection;
+
+#[cfg(test)]
+pub use memory::MemoryProgressStore;
+
+/// 一批区块的提交结果
+#[derive(Debug)]
+pub enum BatchCommit {
+    /// 入金与进度已提交，返回新入库的入金（重复扫描到的记录不包含在内）
+    Committed(Vec<Deposit>),
+    /// 进度已被外部修改（例如管理工具回退了进度），本批次已放弃
+    CursorMoved,
+}
+
+/// 扫描进度与入金的存储：每批入金必须与进度原子地一起提交
+pub trait ProgressStore: Send {
+    /// 最后处理完成的区块；尚未开始时为 None
+    fn cursor(&mut self) -> BoxFuture<'_, anyhow::Result<Option<i64>>>;
+
+    /// 写入一批入金并把进度从 expected 推进到 last_block（比较并交换）
+    fn commit_batch<'a>(
+        &'a mut self,
+        expected: Option<i64>,
+        last_block: i64,
+        transfers: &'a [VaultTransfer],
+    ) -> BoxFuture<'a, anyhow::Result<BatchCommit>>;
+}
+
+/// 链重组核对的结果
+#[derive(Debug)]
+pub enum ReorgCheck {
+    /// 核对已提交：inserted 为窗口内新出现的入金，orphaned 为已不在链上、被标记为 orphaned 的入金
+    Checked { inserted: Vec<Deposit>, orphaned: Vec<Deposit> },
+    /// 进度已被外部修改，本次核对已放弃
+    CursorMoved,
+}
+
+/// 实时监听的存储还需要支持链重组核对
+pub trait ReorgStore: ProgressStore {
+    /// 按 [from_block, to_block] 的最新链上数据核对入金（与进度原子提交）：
+    /// 补录 transfers 中尚未入库的入金，把 from_block 及之后不在 transfers 中的已确认入金标记为 orphaned，
+    /// 进度从 expected 回退到 min(expected, to_block)
+    fn reconcile_recent<'a>(
+        &'a mut self,
+        expected: i64,
+        token_address: &'a str,
+        from_block: i64,
+        to_block: i64,
+        transfers: &'a [VaultTransfer],
+    ) -> BoxFuture<'a, anyhow::Result<ReorgCheck>>;
+}
+
+/// 实时监听的进度：indexer_progress 中的一行，写入都在持锁连接上执行
+pub struct VaultProgressStore {
+    lock: SourceLock,
+    source: String,
+}
+
+impl VaultProgressStore {
+    pub fn new(lock: SourceLock, source: &str) -> Self {
+        Self { lock, source: source.to_string() }
+    }
+
+    pub fn lock_mut(&mut self) -> &mut SourceLock {
+        &mut self.lock
+    }
+}
+
+impl ProgressStore for VaultProgressStore {
+    fn cursor(&mut self) -> BoxFuture<'_, anyhow::Result<Option<i64>>> {
+        Box::pin(async move { Ok(get_last_block(&mut *self.lock.conn(), &self.source).await?) })
+    }
+
+    fn commit_batch<'a>(
+        &'a mut self,
+        expected: Option<i64>,
+        last_block: i64,
+        transfers: &'a [VaultTransfer],
+    ) -> BoxFuture<'a, anyhow::Result<BatchCommit>> {
+        Box::pin(async move {
+            let mut tx = self.lock.conn().begin().await?;
+            let inserted = index_transfers(&mut tx, transfers).await?;
+            if !advance_cursor(&mut tx, &self.source, expected, last_block).await? {
+                // 事务在 drop 时回滚，本批入金不会提交
+                return Ok(BatchCommit::CursorMoved);
+            }
+            tx.commit().await?;
+            Ok(BatchCommit::Committed(inserted))
+        })
+    }
+}
+
+impl ReorgStore for VaultProgressStore {
+    fn reconcile_recent<'a>(
+        &'a mut self,
+        expected: i64,
+        token_address: &'a str,
+        from_block: i64,
+        to_block: i64,
+        transfers: &'a [VaultTransfer],
+    ) -> BoxFuture<'a, anyhow::Result<ReorgCheck>> {
+        Box::pin(async move {
+            let mut tx = self.lock.conn().begin().await?;
+            let inserted = index_transfers(&mut tx, transfers).await?;
+            let keep: Vec<String> = transfers.iter().map(|t| t.tx_hash.clone()).collect();
+            let orphaned = orphan_deposits(&mut tx, token_address, from_block, &keep).await?;
+            if !advance_cursor(&mut tx, &self.source, Some(expected), expected.min(to_block)).await? {
+                return Ok(ReorgCheck::CursorMoved);
+            }
+            tx.commit().await?;
+            Ok(ReorgCheck::Checked { inserted, orphaned })
+        })
+    }
+}
+
+/// 测试用的内存存储，只在测试中编译
+#[cfg(test)]
+mod memory {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashSet;
+
+    /// 内存中的进度与入金，用于配合 FakeChain 测试监听循环
+    #[derive(Debug, Default)]
+    pub struct MemoryProgressStore {
+        pub cursor: Option<i64>,
+        pub deposits: Vec<Deposit>,
+        /// 每次成功提交的进度
+        pub commits: Vec<i64>,
+        seen: HashSet<String>,
+    }
+
+    impl MemoryProgressStore {
+        pub fn new(cursor: Option<i64>) -> Self {
+            Self { cursor, ..Default::default() }
+        }
+
+        fn insert(&mut self, transfers: &[VaultTransfer]) -> Vec<Deposit> {
+            let mut inserted = Vec::new();
+            for transfer in transfers {
+                if !self.seen.insert(transfer.tx_hash.clone()) {
+                    // 与数据库实现一致：被重组丢弃的入金重新上链时恢复为 confirmed
+                    if let Some(deposit) = self
+                        .deposits
+                        .iter_mut()
+                        .find(|d| d.tx_hash == transfer.tx_hash && d.status == "orphaned")
+                    {
+                        deposit.status = "confirmed".to_string();
+                        deposit.block_number = transfer.block_number;
+                        deposit.tx_index = transfer.tx_index;
+                        inserted.push(deposit.clone());
+                    }
+                    continue;
+                }
+                let deposit = Deposit {
+                    id: self.deposits.len() as i64 + 1,
+                    tx_hash: transfer.tx_hash.clone(),
+                    block_number: transfer.block_number,
+                    tx_index: transfer.tx_index,
+                    sender: transfer.sender.clone(),
+                    to_address: transfer.to_address.clone(),
+                    amount_wei: transfer.amount_wei.clone(),
+                    token_address: Some(transfer.token_address.clone()),
+                    status: "confirmed".to_string(),
+                    created_at: Utc::now(),
+                    user_id: None,
+                };
+                self.deposits.push(deposit.clone());
+                inserted.push(deposit);
+            }
+            inserted
+        }
+    }
+
+    impl ProgressStore for MemoryProgressStore {
+        fn cursor(&mut self) -> BoxFuture<'_, anyhow::Result<Option<i64>>> {
+            Box::pin(async move { Ok(self.cursor) })
+        }
+
+        fn commit_batch<'a>(
+            &'a mut self,
+            expected: Option<i64>,
+            last_block: i64,
+            transfers: &'a [VaultTransfer],
+        ) -> BoxFuture<'a, anyhow::Result<BatchCommit>> {
+            Box::pin(async move {
+                // 与数据库实现一致：进度不符时整批放弃
+                if self.cursor != expected {
+                    return Ok(BatchCommit::CursorMoved);
+                }
+
+                let inserted = self.insert(transfers);
+                self.cursor = Some(last_block);
+                self.commits.push(last_block);
+                Ok(BatchCommit::Committed(inserted))
+            })
+        }
+    }
+
+    impl ReorgStore for MemoryProgressStore {
+        fn reconcile_recent<'a>(
+            &'a mut self,
+            expected: i64,
+            token_address: &'a str,
+            from_block: i64,
+            to_block: i64,
+            transfers: &'a [VaultTransfer],
+        ) -> BoxFuture<'a, anyhow::Result<ReorgCheck>> {
+            Box::pin(async move {
+                if self.cursor != Some(expected) {
+                    return Ok(ReorgCheck::CursorMoved);
+                }
+
+                let inserted = self.insert(transfers);
+                let mut orphaned = Vec::new();
+                for deposit in &mut self.deposits {
+                    if deposit.status == "confirmed"
+                        && deposit.token_address.as_deref() == Some(token_address)
+                        && deposit.block_number >= from_block
+                        && !transfers.iter().any(|t| t.tx_hash == deposit.tx_hash)
+                    {
+                        deposit.status = "orphaned".to_string();
+                        orphaned.push(deposit.clone());
+                    }
+                }
+
+                self.cursor = Some(expected.min(to_block));
+                Ok(ReorgCheck::Checked { inserted, orphaned })
+            })
+        }
+    }
+}
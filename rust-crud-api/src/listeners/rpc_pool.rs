@@ -0,0 +1,623 @@
+use crate::Config;
+use ethers::core::rand::Rng;
+use ethers::prelude::*;
+use ethers::providers::{Http, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use futures_util::future::join_all;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time;
+
+/// 健康分上限（新节点以满分开始）
+const MAX_HEALTH_SCORE: i32 = 100;
+/// 请求成功时恢复的分数
+const SUCCESS_REWARD: i32 = 5;
+/// 请求失败或超时扣除的分数
+const FAILURE_PENALTY: i32 = 25;
+/// 连续失败达到该次数后进入冷却期
+const COOLDOWN_AFTER_FAILURES: u32 = 3;
+/// 冷却时长，冷却期间该节点只作为最后的备选
+const COOLDOWN: Duration = Duration::from_secs(30);
+/// 交叉校验中返回与多数节点不一致的区块哈希时的冷却时长
+const MISMATCH_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// RPC 重试策略：指数退避 + 随机抖动，以及单次请求超时
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl RetryPolicy {
+    /// 从配置中创建重试策略
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_retries: config.rpc_max_retries,
+            base_delay: Duration::from_millis(config.rpc_backoff_base_ms),
+            max_delay: Duration::from_millis(config.rpc_backoff_max_ms),
+            request_timeout: Duration::from_millis(config.rpc_request_timeout_ms),
+        }
+    }
+
+    /// 计算第 attempt 轮重试前的等待时间（从 0 开始计数）
+    /// 基础值按 2 的指数增长并以 max_delay 封顶，实际等待时间在 [基础值/2, 基础值] 之间随机抖动，
+    /// 避免多个实例在同一时刻重试
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        let capped = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let capped_ms = capped.as_millis() as u64;
+        if capped_ms < 2 {
+            return capped;
+        }
+        let half = capped_ms / 2;
+        Duration::from_millis(half + ethers::core::rand::thread_rng().gen_range(0..=half))
+    }
+}
+
+/// 单个 RPC 节点的健康状态
+#[derive(Debug, Clone)]
+struct EndpointHealth {
+    score: i32,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    last_latency: Option<Duration>,
+}
+
+impl Default for EndpointHealth {
+    fn default() -> Self {
+        Self {
+            score: MAX_HEALTH_SCORE,
+            consecutive_failures: 0,
+            cooldown_until: None,
+            last_latency: None,
+        }
+    }
+}
+
+impl EndpointHealth {
+    fn is_cooling_down(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        self.score = (self.score + SUCCESS_REWARD).min(MAX_HEALTH_SCORE);
+        self.consecutive_failures = 0;
+        self.cooldown_until = None;
+        self.last_latency = Some(latency);
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.score = (self.score - FAILURE_PENALTY).max(0);
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= COOLDOWN_AFTER_FAILURES {
+            self.cooldown_until = Some(now + COOLDOWN);
+        }
+    }
+
+    fn record_mismatch(&mut self, now: Instant) {
+        self.score = 0;
+        self.cooldown_until = Some(now + MISMATCH_COOLDOWN);
+    }
+}
+
+/// 一个 RPC 节点
+struct RpcEndpoint {
+    url: String,
+    provider: Provider<Http>,
+    health: Mutex<EndpointHealth>,
+}
+
+impl RpcEndpoint {
+    fn health(&self) -> EndpointHealth {
+        self.health.lock().unwrap().clone()
+    }
+
+    fn record_success(&self, latency: Duration) {
+        self.health.lock().unwrap().record_success(latency);
+    }
+
+    fn record_failure(&self) {
+        self.health.lock().unwrap().record_failure(Instant::now());
+    }
+
+    fn record_mismatch(&self) {
+        self.health.lock().unwrap().record_mismatch(Instant::now());
+    }
+}
+
+/// 节点健康快照（用于日志与排查）
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub score: i32,
+    pub consecutive_failures: u32,
+    pub cooling_down: bool,
+    pub last_latency: Option<Duration>,
+}
+
+/// 区块哈希交叉校验结果
+#[derive(Debug, Clone)]
+pub enum CrossCheckOutcome {
+    /// 多数节点对区块哈希达成一致；agreeing 为返回该哈希的节点，lagging 为尚未同步到该区块的节点，
+    /// conflicting 为返回不同哈希的节点
+    Agreed {
+        hash: H256,
+        agreeing: Vec<String>,
+        lagging: Vec<String>,
+        conflicting: Vec<String>,
+    },
+    /// 无法形成多数（应答节点之间分歧，或应答节点数不足最小一致数），不应推进进度
+    Inconclusive { responses: Vec<(String, Option<H256>)> },
+}
+
+/// 带健康评分与故障转移的 RPC 节点池
+/// - 按健康分从高到低选择节点，失败时自动切换到下一个节点
+/// - 所有节点都失败后按指数退避 + 抖动等待，再进行下一轮
+/// - 每次请求都有独立的超时
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    policy: RetryPolicy,
+    cross_check: bool,
+    /// 交叉校验时至少需要多少个节点返回同一哈希
+    min_agree: usize,
+}
+
+impl RpcPool {
+    /// 根据 RPC 地址列表创建节点池，列表顺序即初始优先级
+    pub fn new(urls: &[String], policy: RetryPolicy, cross_check: bool, min_agree: usize) -> anyhow::Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("未配置任何 RPC 地址");
+        }
+
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                Ok(RpcEndpoint {
+                    url: url.clone(),
+                    provider: Provider::<Http>::try_from(url.as_str())?,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            endpoints,
+            policy,
+            cross_check,
+            min_agree: min_agree.max(1),
+        })
+    }
+
+    /// 从配置创建节点池（ARBITRUM_HTTP_URL + ARBITRUM_HTTP_FALLBACK_URLS）
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let urls = config.arbitrum_http_urls();
+        if urls.is_empty() {
+            anyhow::bail!("ARBITRUM_HTTP_URL 未设置");
+        }
+        Self::new(
+            &urls,
+            RetryPolicy::from_config(config),
+            config.rpc_cross_check,
+            config.rpc_cross_check_min_agree,
+        )
+    }
+
+    /// 是否启用了区块哈希交叉校验（只有一个节点时没有意义）
+    pub fn cross_check_enabled(&self) -> bool {
+        self.cross_check && self.endpoints.len() > 1
+    }
+
+    /// 当前所有节点的健康快照
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|e| {
+                let health = e.health();
+                EndpointStatus {
+                    url: e.url.clone(),
+                    score: health.score,
+                    consecutive_failures: health.consecutive_failures,
+                    cooling_down: health.is_cooling_down(now),
+                    last_latency: health.last_latency,
+                }
+            })
+            .collect()
+    }
+
+    /// 按优先级排序的节点下标：未冷却的节点按健康分从高到低排列，冷却中的节点排在最后
+    fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let snapshot: Vec<EndpointHealth> = self.endpoints.iter().map(|e| e.health()).collect();
+        rank_endpoints(&snapshot, now)
+    }
+
+    /// 以故障转移 + 重试的方式执行一次 RPC 请求
+    async fn request<T, F, Fut>(&self, op: &str, f: F) -> anyhow::Result<T>
+    where
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.request_from(op, None, f).await
+    }
+
+    /// 同 request，only 不为 None 时只在这些节点（按 URL）之间故障转移
+    async fn request_from<T, F, Fut>(&self, op: &str, only: Option<&[String]>, f: F) -> anyhow::Result<T>
+    where
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let mut last_error = None;
+
+        for attempt in 0..=self.policy.max_retries {
+            let allowed = |idx: &usize| only.is_none_or(|urls| urls.contains(&self.endpoints[*idx].url));
+            for idx in self.ranked().into_iter().filter(allowed) {
+                let endpoint = &self.endpoints[idx];
+                let started = Instant::now();
+
+                match time::timeout(self.policy.request_timeout, f(endpoint.provider.clone())).await {
+                    Ok(Ok(value)) => {
+                        endpoint.record_success(started.elapsed());
+                        return Ok(value);
+                    }
+                    Ok(Err(e)) => {
+                        endpoint.record_failure();
+                        log::warn!("⚠️ RPC 请求失败 [{}] {}: {}", op, endpoint.url, e);
+                        last_error = Some(anyhow::anyhow!("{} 失败 ({}): {}", op, endpoint.url, e));
+                    }
+                    Err(_) => {
+                        endpoint.record_failure();
+                        log::warn!(
+                            "⏱️ RPC 请求超时 [{}] {} (>{:?})",
+                            op,
+                            endpoint.url,
+                            self.policy.request_timeout
+                        );
+                        last_error = Some(anyhow::anyhow!("{} 超时 ({})", op, endpoint.url));
+                    }
+                }
+            }
+
+            if attempt < self.policy.max_retries {
+                let delay = self.policy.backoff_delay(attempt);
+                log::warn!(
+                    "🔁 所有 RPC 节点均失败 [{}]，{:?} 后进行第 {} 次重试",
+                    op,
+                    delay,
+                    attempt + 1
+                );
+                time::sleep(delay).await;
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("{} 失败: 没有可用的 RPC 节点", op)))
+    }
+
+    /// 获取最新区块号
+    pub async fn get_block_number(&self) -> anyhow::Result<u64> {
+        let number = self
+            .request("eth_blockNumber", |p| async move { p.get_block_number().await })
+            .await?;
+        Ok(number.as_u64())
+    }
+
+    /// 拉取日志
+    pub async fn get_logs(&self, filter: &Filter) -> anyhow::Result<Vec<Log>> {
+        self.request("eth_getLogs", |p| {
+            let filter = filter.clone();
+            async move { p.get_logs(&filter).await }
+        })
+        .await
+    }
+
+    /// 只从指定节点拉取日志，例如交叉校验中认可区块哈希的节点
+    pub async fn get_logs_from(&self, urls: &[String], filter: &Filter) -> anyhow::Result<Vec<Log>> {
+        self.request_from("eth_getLogs", Some(urls), |p| {
+            let filter = filter.clone();
+            async move { p.get_logs(&filter).await }
+        })
+        .await
+    }
+
+    /// 查询交易回执（交易未打包时返回 None）
+    pub async fn get_transaction_receipt(&self, tx_hash: H256) -> anyhow::Result<Option<TransactionReceipt>> {
+        self.request("eth_getTransactionReceipt", |p| async move { p.get_transaction_receipt(tx_hash).await })
+            .await
+    }
+
+    /// 查询 ERC20 余额（balanceOf），可指定区块高度
+    pub async fn erc20_balance_of(&self, token: Address, owner: Address, block: Option<u64>) -> anyhow::Result<U256> {
+        // balanceOf(address) 选择器 0x70a08231 + 左侧补零的地址参数
+        let mut calldata = vec![0x70, 0xa0, 0x82, 0x31];
+        calldata.extend_from_slice(H256::from(owner).as_bytes());
+        let tx: TypedTransaction = TransactionRequest::new().to(token).data(calldata).into();
+        let block_id = block.map(|b| BlockId::Number(BlockNumber::Number(b.into())));
+
+        let output = self
+            .request("eth_call(balanceOf)", |p| {
+                let tx = tx.clone();
+                async move { p.call(&tx, block_id).await }
+            })
+            .await?;
+
+        if output.len() != 32 {
+            anyhow::bail!("balanceOf 返回数据长度异常: {} 字节", output.len());
+        }
+        Ok(U256::from_big_endian(&output))
+    }
+
+    /// 向所有节点查询同一区块的哈希并比较，用于发现落后或返回错误数据的节点
+    /// 多数只在有应答的节点中计算，单个节点宕机或落后不会阻塞进度
+    /// 与多数不一致的节点会被清零健康分并长时间冷却；尚未同步到该区块的节点按普通失败扣分
+    pub async fn cross_check_block(&self, block_number: u64) -> CrossCheckOutcome {
+        let timeout = self.policy.request_timeout;
+        let queries = self.endpoints.iter().map(|endpoint| async move {
+            let result = time::timeout(timeout, endpoint.provider.get_block(block_number)).await;
+            let hash = match result {
+                Ok(Ok(block)) => block.and_then(|b| b.hash),
+                Ok(Err(e)) => {
+                    log::warn!("⚠️ 交叉校验查询区块 {} 失败 {}: {}", block_number, endpoint.url, e);
+                    None
+                }
+                Err(_) => {
+                    log::warn!("⏱️ 交叉校验查询区块 {} 超时 {}", block_number, endpoint.url);
+                    None
+                }
+            };
+            (endpoint.url.clone(), hash)
+        });
+        let responses: Vec<(String, Option<H256>)> = join_all(queries).await;
+
+        let Some(hash) = majority_hash(&responses, self.min_agree) else {
+            return CrossCheckOutcome::Inconclusive { responses };
+        };
+
+        let mut agreeing = Vec::new();
+        let mut lagging = Vec::new();
+        let mut conflicting = Vec::new();
+        for (endpoint, (url, response)) in self.endpoints.iter().zip(responses) {
+            match response {
+                Some(h) if h == hash => agreeing.push(url),
+                Some(_) => {
+                    endpoint.record_mismatch();
+                    conflicting.push(url);
+                }
+                None => {
+                    endpoint.record_failure();
+                    lagging.push(url);
+                }
+            }
+        }
+
+        CrossCheckOutcome::Agreed {
+            hash,
+            agreeing,
+            lagging,
+            conflicting,
+        }
+    }
+}
+
+/// 节点排序：未冷却优先，其次健康分高者优先，分数相同时保持配置顺序
+fn rank_endpoints(health: &[EndpointHealth], now: Instant) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..health.len()).collect();
+    order.sort_by_key(|&i| (health[i].is_cooling_down(now), -health[i].score, i));
+    order
+}
+
+/// 在有应答的节点中找出严格多数（超过应答节点数的一半）认可、且至少有 min_agree 个节点返回的区块哈希
+/// 未应答（宕机或尚未同步）的节点不计入分母
+fn majority_hash(responses: &[(String, Option<H256>)], min_agree: usize) -> Option<H256> {
+    let mut counts: HashMap<H256, usize> = HashMap::new();
+    for hash in responses.iter().filter_map(|(_, h)| *h) {
+        *counts.entry(hash).or_default() += 1;
+    }
+    let answered: usize = counts.values().sum();
+    counts
+        .into_iter()
+        .find(|(_, count)| *count * 2 > answered && *count >= min_agree)
+        .map(|(hash, _)| hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            request_timeout: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_grows_and_is_capped() {
+        let policy = policy();
+        for _ in 0..50 {
+            let first = policy.backoff_delay(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+            let third = policy.backoff_delay(2);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+
+            let capped = policy.backoff_delay(20);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_failing_endpoint_is_ranked_last() {
+        let now = Instant::now();
+        let mut health = vec![EndpointHealth::default(); 3];
+
+        health[0].record_failure(now);
+        assert_eq!(rank_endpoints(&health, now), vec![1, 2, 0]);
+
+        // 连续失败后进入冷却，即使其他节点分数更低也排在后面
+        health[1].record_failure(now);
+        health[1].record_failure(now);
+        health[1].record_failure(now);
+        health[2].score = 10;
+        assert_eq!(rank_endpoints(&health, now), vec![0, 2, 1]);
+
+        // 冷却结束后按分数重新参与排序
+        assert_eq!(rank_endpoints(&health, now + COOLDOWN + Duration::from_secs(1)), vec![0, 1, 2]);
+    }
+
+    /// 只会回答 eth_getBlockByNumber 的本地 JSON-RPC 节点，每个区块都返回 hash
+    async fn serve_blocks(hash: H256) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    // 读完请求头和 Content-Length 指定的请求体
+                    let body = loop {
+                        let n = socket.read(&mut chunk).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        buf.extend_from_slice(&chunk[..n]);
+                        let text = String::from_utf8_lossy(&buf).to_string();
+                        let Some(end) = text.find("\r\n\r\n") else { continue };
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0usize);
+                        if buf.len() >= end + 4 + length {
+                            break buf[end + 4..end + 4 + length].to_vec();
+                        }
+                    };
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let zero = format!("0x{}", "0".repeat(64));
+                    // eth_getLogs 返回一条带本节点区块哈希的日志，用来区分数据来自哪个节点
+                    let result = if request["method"] == "eth_getLogs" {
+                        serde_json::json!([{
+                            "address": format!("0x{}", "0".repeat(40)),
+                            "topics": [],
+                            "data": "0x",
+                            "blockHash": format!("0x{:x}", hash),
+                            "blockNumber": "0x1"
+                        }])
+                    } else {
+                        serde_json::json!({
+                            "hash": format!("0x{:x}", hash),
+                            "parentHash": zero,
+                            "sha3Uncles": zero,
+                            "miner": format!("0x{}", "0".repeat(40)),
+                            "stateRoot": zero,
+                            "transactionsRoot": zero,
+                            "receiptsRoot": zero,
+                            "number": request["params"][0],
+                            "gasUsed": "0x0",
+                            "gasLimit": "0x0",
+                            "extraData": "0x",
+                            "logsBloom": format!("0x{}", "0".repeat(512)),
+                            "timestamp": "0x0",
+                            "difficulty": "0x0",
+                            "totalDifficulty": "0x0",
+                            "uncles": [],
+                            "transactions": [],
+                            "size": "0x0",
+                            "mixHash": zero,
+                            "nonce": "0x0000000000000000"
+                        })
+                    };
+                    let response = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+                    let reply = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.len(),
+                        response
+                    );
+                    let _ = socket.write_all(reply.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    /// 一个已关闭端口的地址，连接会立即被拒绝
+    async fn down_endpoint() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        url
+    }
+
+    #[tokio::test]
+    async fn test_cross_check_with_one_of_two_endpoints_down() {
+        let hash = H256::repeat_byte(0xaa);
+        let urls = vec![serve_blocks(hash).await, down_endpoint().await];
+        let policy = RetryPolicy {
+            request_timeout: Duration::from_secs(2),
+            ..policy()
+        };
+
+        let pool = RpcPool::new(&urls, policy.clone(), true, 1).unwrap();
+        match pool.cross_check_block(5).await {
+            CrossCheckOutcome::Agreed { hash: agreed, agreeing, lagging, conflicting } => {
+                assert_eq!(agreed, hash);
+                assert_eq!(agreeing, vec![urls[0].clone()]);
+                assert_eq!(lagging, vec![urls[1].clone()]);
+                assert!(conflicting.is_empty());
+            }
+            other => panic!("一个节点宕机时应仍能达成一致: {:?}", other),
+        }
+
+        // 要求两个节点一致时，单个节点的应答不足以推进
+        let strict = RpcPool::new(&urls, policy, true, 2).unwrap();
+        assert!(matches!(strict.cross_check_block(5).await, CrossCheckOutcome::Inconclusive { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_from_only_queries_listed_endpoints() {
+        let forked = H256::repeat_byte(0xbb);
+        let agreed = H256::repeat_byte(0xaa);
+        // 排在前面的节点在分叉上，日志只能从达成一致的节点取
+        let urls = vec![serve_blocks(forked).await, serve_blocks(agreed).await];
+        let pool = RpcPool::new(&urls, policy(), true, 1).unwrap();
+
+        let logs = pool.get_logs_from(&urls[1..], &Filter::new()).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].block_hash, Some(agreed));
+
+        let logs = pool.get_logs(&Filter::new()).await.unwrap();
+        assert_eq!(logs[0].block_hash, Some(forked));
+    }
+
+    #[test]
+    fn test_one_of_two_down_still_agrees() {
+        let a = H256::repeat_byte(0xaa);
+        let responses = [("primary".to_string(), Some(a)), ("fallback".to_string(), None)];
+        assert_eq!(majority_hash(&responses, 1), Some(a));
+        assert_eq!(majority_hash(&responses, 2), None);
+    }
+
+    #[test]
+    fn test_majority_hash() {
+        let a = H256::repeat_byte(0xaa);
+        let b = H256::repeat_byte(0xbb);
+        let r = |h: Option<H256>| ("rpc".to_string(), h);
+
+        assert_eq!(majority_hash(&[r(Some(a)), r(Some(a)), r(Some(b))], 1), Some(a));
+        assert_eq!(majority_hash(&[r(Some(a)), r(None), r(Some(a))], 1), Some(a));
+        assert_eq!(majority_hash(&[r(Some(a)), r(Some(b))], 1), None);
+        assert_eq!(majority_hash(&[r(Some(a))], 1), Some(a));
+        assert_eq!(majority_hash(&[r(None), r(None)], 1), None);
+        // 分歧节点仍计入分母：2 对 1 是多数，1 对 1 不是
+        assert_eq!(majority_hash(&[r(Some(a)), r(Some(b)), r(None)], 1), None);
+        // 最小一致数限制：只有一个节点应答时不足 2
+        assert_eq!(majority_hash(&[r(Some(a)), r(None), r(None)], 2), None);
+        assert_eq!(majority_hash(&[r(Some(a)), r(Some(a)), r(None)], 2), Some(a));
+    }
+}
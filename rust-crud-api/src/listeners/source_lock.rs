@@ -0,0 +1,78 @@
+use crate::database::DatabasePool;
+use sqlx::{Connection, PgConnection};
+use std::time::Duration;
+use tokio::time;
+
+/// 未抢到锁时的重试间隔
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 基于 Postgres 会话级 advisory lock 的来源独占锁
+///
+/// 锁绑定在一条从连接池中分离出来的专用连接上：进程退出或连接断开时锁自动释放，
+/// 持锁期间的批次事务也在这条连接上执行，连接失效时写入随之失败，不会出现无锁写入
+pub struct SourceLock {
+    conn: PgConnection,
+    key: String,
+}
+
+impl SourceLock {
+    /// 尝试获取锁，已被其他会话持有时返回 None
+    pub async fn try_acquire(pool: &DatabasePool, key: &str) -> Result<Option<Self>, sqlx::Error> {
+        // 分离出的连接不会归还连接池，drop 时直接关闭，从而释放会话锁
+        let mut conn = pool.acquire().await?.detach();
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
+            .bind(key)
+            .fetch_one(&mut conn)
+            .await?;
+
+        if acquired {
+            Ok(Some(Self { conn, key: key.to_string() }))
+        } else {
+            conn.close().await?;
+            Ok(None)
+        }
+    }
+
+    /// 等待直到获取锁；用于多实例部署时让备用实例待命
+    pub async fn acquire(pool: &DatabasePool, key: &str) -> Result<Self, sqlx::Error> {
+        let mut waiting = false;
+        loop {
+            if let Some(lock) = Self::try_acquire(pool, key).await? {
+                if waiting {
+                    log::info!("🔒 已获取索引锁: {}", key);
+                }
+                return Ok(lock);
+            }
+
+            if !waiting {
+                log::warn!("⏸️ 索引锁 {} 被其他实例持有，每{}秒重试", key, LOCK_RETRY_INTERVAL.as_secs());
+                waiting = true;
+            }
+            time::sleep(LOCK_RETRY_INTERVAL).await;
+        }
+    }
+
+    /// 持锁的专用连接，批次事务应在该连接上开启
+    pub fn conn(&mut self) -> &mut PgConnection {
+        &mut self.conn
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 检查持锁连接是否仍然可用；连接断开意味着锁已丢失
+    pub async fn is_alive(&mut self) -> bool {
+        self.conn.ping().await.is_ok()
+    }
+}
+
+/// 实时监听来源的锁名
+pub fn source_lock_key(source: &str) -> String {
+    format!("indexer:{}", source)
+}
+
+/// 补扫任务的锁名，防止同一任务被多个进程同时续跑
+pub fn backfill_lock_key(run_id: i64) -> String {
+    format!("indexer_backfill:{}", run_id)
+}
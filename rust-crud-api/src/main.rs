@@ -25,6 +25,12 @@ async fn main() -> std::io::Result<()> {
     // 创建数据库连接池
     let pool = database::create_pool(&config).await
         .expect("Failed to create database pool");
+
+    // 存在未执行的迁移时拒绝启动，避免新代码访问不存在的表或字段
+    if let Err(e) = database::ensure_migrations_applied(&pool).await {
+        log::error!("❌ {}", e);
+        return Err(std::io::Error::other(e.to_string()));
+    }
     // 供监听器使用的克隆
     let pool_for_watcher = pool.clone();
 
@@ -40,36 +46,174 @@ async fn main() -> std::io::Result<()> {
     let cache_service = services::cache::CacheService::new(redis_pool, config.cache_ttl_seconds);
 
     // 创建用户服务
-    let user_service = services::UserService::new(pool, cache_service);
+    let user_service = services::UserService::new(pool.clone(), cache_service.clone());
+
+    // 创建入金服务（监听器与实时推送共享同一个广播器）
+    let deposit_notifier = services::DepositNotifier::new();
+    let deposit_service = services::DepositService::new(pool.clone(), deposit_notifier.clone());
+
+    // 创建钱包绑定服务
+    let wallet_service = services::WalletService::new(pool.clone(), services::SiweSettings::from_config(&config));
+
+    // 创建账本服务
+    let ledger_service = services::LedgerService::new(pool.clone());
+
+    // 创建 Webhook 服务
+    let webhook_service = services::WebhookService::new(pool.clone(), services::WebhookSettings::from_config(&config));
+
+    // 创建 API Key 服务
+    let api_key_service = services::ApiKeyService::new(pool.clone(), config.admin_user_ids.clone());
+
+    // 创建 Hyperliquid 账户查询服务
+    let hyperliquid_service = services::HyperliquidService::from_config(pool.clone(), cache_service, &config)
+        .await
+        .expect("Failed to create Hyperliquid client");
+
+    // 创建提现服务
+    let withdrawal_service = services::WithdrawalService::new(
+        pool.clone(),
+        services::WithdrawalPolicy::from_config(&config),
+        config.usdc_token_address.clone(),
+    );
 
     // 启动区块链监听（后台任务）
     {
         let config_clone = config.clone();
+        let notifier = deposit_notifier.clone();
         tokio::spawn(async move {
-            if let Err(e) = rust_crud_api::listeners::arbitrum_vault::start_vault_watcher(config_clone, pool_for_watcher).await {
+            if let Err(e) = rust_crud_api::listeners::arbitrum_vault::start_vault_watcher(config_clone, pool_for_watcher, notifier).await {
                 log::error!("❌ Vault 监听器启动失败: {}", e);
             }
         });
     }
 
+    // 启动入金记账与对账任务（后台任务）
+    {
+        let ledger = ledger_service.clone();
+        let interval_secs = config.ledger_posting_interval_secs;
+        tokio::spawn(async move {
+            rust_crud_api::workers::ledger::start_deposit_posting_worker(ledger, interval_secs).await;
+        });
+
+        let config_clone = config.clone();
+        let pool_clone = pool.clone();
+        let ledger = ledger_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = rust_crud_api::workers::ledger::start_reconciliation_worker(config_clone, pool_clone, ledger).await {
+                log::error!("❌ 对账任务启动失败: {}", e);
+            }
+        });
+    }
+
+    // 启动提现执行任务（后台任务，未配置执行器时只接受申请与审批）
+    {
+        let config_clone = config.clone();
+        let withdrawals = withdrawal_service.clone();
+        tokio::spawn(async move {
+            match services::withdrawal_executor::build_withdrawal_executor(&config_clone).await {
+                Ok(Some(executor)) => {
+                    rust_crud_api::workers::withdrawal::start_withdrawal_worker(
+                        withdrawals,
+                        executor,
+                        config_clone.withdrawal_worker_interval_secs,
+                    )
+                    .await;
+                }
+                Ok(None) => log::info!("🔕 提现执行任务已禁用（WITHDRAWAL_EXECUTOR 未设置）"),
+                Err(e) => log::error!("❌ 提现执行任务启动失败: {}", e),
+            }
+        });
+    }
+
+    // 启动 Hyperliquid 成交与资金费入库任务（后台任务）
+    if config.enable_hyperliquid_ingester {
+        let config_clone = config.clone();
+        let history = services::HyperliquidHistoryService::new(pool.clone());
+        tokio::spawn(async move {
+            if let Err(e) = rust_crud_api::workers::hyperliquid::start_hyperliquid_ingester(config_clone, history).await {
+                log::error!("❌ Hyperliquid 入库任务启动失败: {}", e);
+            }
+        });
+    } else {
+        log::info!("🔕 Hyperliquid 入库任务已禁用（ENABLE_HYPERLIQUID_INGESTER 未开启）");
+    }
+
+    // 启动 Webhook 投递任务（后台任务）
+    {
+        let webhooks = webhook_service.clone();
+        let interval_secs = config.webhook_worker_interval_secs;
+        tokio::spawn(async move {
+            rust_crud_api::workers::webhook::start_webhook_delivery_worker(webhooks, interval_secs).await;
+        });
+    }
+
     println!("🚀 服务器启动在 http://{}", config.bind_address());
     println!("💾 Redis缓存已启用，TTL: {}秒", config.cache_ttl_seconds);
     println!("📚 API 文档:");
     println!("  POST   /api/users          - 用户注册");
     println!("  GET    /api/users          - 获取所有用户 (缓存支持)");
+    println!("  POST   /api/users/import   - 批量导入用户 (CSV / NDJSON)");
+    println!("  GET    /api/users/export   - 流式导出用户 (?format=csv|ndjson)");
     println!("  GET    /api/users/{{id}}     - 根据 ID 获取用户 (缓存支持)");
     println!("  GET    /api/users/username/{{username}} - 根据用户名获取用户 (缓存支持)");
     println!("  PUT    /api/users/{{id}}     - 更新用户信息");
     println!("  DELETE /api/users/{{id}}     - 删除用户");
+    println!("  POST   /api/users/{{id}}/wallets/nonce - 申请钱包绑定签名挑战");
+    println!("  POST   /api/users/{{id}}/wallets - 提交签名绑定钱包");
+    println!("  GET    /api/users/{{id}}/wallets - 获取已绑定钱包");
+    println!("  DELETE /api/users/{{id}}/wallets/{{address}} - 解绑钱包");
+    println!("  GET    /api/users/{{id}}/deposits - 用户入金历史");
+    println!("  GET    /api/users/{{id}}/balances - 用户账本余额");
+    println!("  POST   /api/users/{{id}}/withdrawals - 申请提现");
+    println!("  GET    /api/users/{{id}}/withdrawals - 用户提现记录");
+    println!("  POST   /api/users/{{id}}/withdrawals/{{withdrawal_id}}/cancel - 取消提现");
+    println!("  GET    /api/users/{{id}}/hyperliquid/{{address}}/positions - Hyperliquid 账户与持仓");
+    println!("  GET    /api/users/{{id}}/hyperliquid/{{address}}/open-orders - Hyperliquid 挂单");
+    println!("  GET    /api/users/{{id}}/hyperliquid/{{address}}/fills - Hyperliquid 成交记录");
+    println!("  GET    /api/users/{{id}}/hyperliquid/{{address}}/funding - Hyperliquid 资金费历史 (start_time/end_time)");
+    println!("  POST   /api/users/{{id}}/api-keys - 创建 API Key (完整密钥只返回一次)");
+    println!("  GET    /api/users/{{id}}/api-keys - API Key 列表");
+    println!("  DELETE /api/users/{{id}}/api-keys/{{key_id}} - 吊销 API Key");
+    println!("  GET    /api/deposits       - 查询入金列表 (sender/status/from_block/to_block/page/page_size)");
+    println!("  GET    /api/deposits/stream - 入金实时推送 (SSE)");
+    println!("  GET    /api/deposits/{{tx_hash}} - 根据交易哈希获取入金");
+    println!("  GET    /api/ledger/accounts - 账户余额列表 (kind/asset)");
+    println!("  GET    /api/ledger/accounts/{{id}} - 账户余额");
+    println!("  GET    /api/ledger/reconciliations - 对账记录");
+    println!("  GET    /api/withdrawals    - 提现列表 (status/limit)");
+    println!("  GET    /api/withdrawals/{{id}} - 提现详情");
+    println!("  POST   /api/withdrawals/{{id}}/approve - 审批提现");
+    println!("  POST   /api/withdrawals/{{id}}/reject - 拒绝提现");
+    println!("  POST   /api/webhooks       - 注册 Webhook 端点");
+    println!("  GET    /api/webhooks       - Webhook 端点列表");
+    println!("  GET    /api/webhooks/{{id}} - Webhook 端点详情");
+    println!("  PUT    /api/webhooks/{{id}} - 更新 Webhook 端点");
+    println!("  DELETE /api/webhooks/{{id}} - 删除 Webhook 端点");
+    println!("  POST   /api/webhooks/{{id}}/rotate-secret - 轮换签名密钥");
+    println!("  GET    /api/webhooks/{{id}}/deliveries - 投递日志 (status/event_type/limit)");
+    println!("  GET    /api/webhooks/deliveries/{{delivery_id}} - 投递详情");
+    println!("  POST   /api/webhooks/deliveries/{{delivery_id}}/retry - 重新投递");
     println!("  GET    /health             - 健康检查");
 
     // 启动 HTTP 服务器
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(user_service.clone()))
+            .app_data(web::Data::new(deposit_service.clone()))
+            .app_data(web::Data::new(wallet_service.clone()))
+            .app_data(web::Data::new(ledger_service.clone()))
+            .app_data(web::Data::new(withdrawal_service.clone()))
+            .app_data(web::Data::new(webhook_service.clone()))
+            .app_data(web::Data::new(api_key_service.clone()))
+            .app_data(web::Data::new(hyperliquid_service.clone()))
+            .wrap(middleware::ApiKeyAuth)  // Authorization: Bearer rca_... 认证
             .wrap(middleware::RequestLogging::dev())
             .wrap(middleware::ResponsePrinter)  // 添加响应打印中间件
             .service(routes::user_routes())
+            .service(routes::deposit_routes())
+            .service(routes::ledger_routes())
+            .service(routes::withdrawal_routes())
+            .service(routes::webhook_routes())
             .configure(routes::health_routes())
     })
     .bind(config.bind_address())?
@@ -0,0 +1,96 @@
+use crate::errors::AppError;
+use crate::models::api_key::required_scope;
+use crate::services::ApiKeyService;
+use crate::services::api_key::is_api_key;
+use actix_web::{
+    Error, HttpMessage, Result, body::MessageBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header::AUTHORIZATION,
+    web,
+};
+use futures_util::future::LocalBoxFuture;
+use std::{
+    future::{Ready, ready},
+    rc::Rc,
+};
+
+/// API Key 认证中间件
+///
+/// 识别 `Authorization: Bearer rca_...`，校验通过后把 ApiKeyPrincipal 写入请求扩展，
+/// 并检查密钥是否拥有访问该接口所需的权限、/api/users/{id}/... 是否属于密钥所属用户，
+/// 以及跨用户的全局接口是否由管理员密钥调用。
+/// 其他形式的 Authorization（例如用户登录令牌）与未携带的请求原样放行，由用户认证处理。
+pub struct ApiKeyAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware { service: Rc::new(service) }))
+    }
+}
+
+pub struct ApiKeyAuthMiddleware<S> {
+    service: Rc<S>,
+}
+
+/// 从 Authorization 头中取出 API Key
+fn bearer_api_key(req: &ServiceRequest) -> Option<String> {
+    let value = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && is_api_key(token)).then(|| token.to_string())
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let Some(key) = bearer_api_key(&req) else {
+                return service.call(req).await;
+            };
+
+            let api_keys = req
+                .app_data::<web::Data<ApiKeyService>>()
+                .cloned()
+                .ok_or_else(|| AppError::InternalServerError("API Key 服务未配置".to_string()))?;
+            let principal = api_keys.authenticate(&key).await?;
+
+            if let Some(scope) = required_scope(req.method(), req.path())
+                && !principal.allows(&scope)
+            {
+                log::warn!("🔒 API Key {} 缺少权限 {}: {} {}", principal.key_id, scope, req.method(), req.path());
+                return Err(AppError::Forbidden(format!("API Key 缺少权限: {}", scope)).into());
+            }
+
+            if !principal.can_access(req.path()) {
+                log::warn!("🔒 API Key {} 访问其他用户或管理员的资源: {} {}", principal.key_id, req.method(), req.path());
+                return Err(AppError::Forbidden("API Key 只能访问所属用户的资源".to_string()).into());
+            }
+
+            req.extensions_mut().insert(principal);
+            service.call(req).await
+        })
+    }
+}
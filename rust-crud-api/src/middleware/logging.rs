@@ -5,6 +5,7 @@ pub struct RequestLogging;
 
 impl RequestLogging {
     /// 创建详细的日志中间件
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Logger {
         Logger::new(r#"🌐 %{r}a "%r" %s %b "%{Content-Type}i" %T ms"#)
             .log_target("actix_web::middleware::logger")
@@ -1,8 +1,10 @@
 // 这个模块用于存放自定义中间件
 // 例如：身份认证、请求日志、CORS 等
 
+pub mod api_key_auth;
 pub mod logging;
 pub mod response_printer;
 
+pub use api_key_auth::ApiKeyAuth;
 pub use logging::RequestLogging;
 pub use response_printer::ResponsePrinter;
\ No newline at end of file
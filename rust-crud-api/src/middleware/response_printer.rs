@@ -81,17 +81,17 @@ where
             );
             
             // 打印响应头信息（部分重要的）
-            if let Some(content_type) = headers.get("content-type") {
-                if let Ok(content_type_str) = content_type.to_str() {
-                    println!("📄 内容类型: {}", content_type_str);
-                }
+            if let Some(content_type) = headers.get("content-type")
+                && let Ok(content_type_str) = content_type.to_str()
+            {
+                println!("📄 内容类型: {}", content_type_str);
             }
             
             // 打印内容长度
-            if let Some(content_length) = headers.get("content-length") {
-                if let Ok(length_str) = content_length.to_str() {
-                    println!("📏 内容长度: {} 字节", length_str);
-                }
+            if let Some(content_length) = headers.get("content-length")
+                && let Ok(length_str) = content_length.to_str()
+            {
+                println!("📏 内容长度: {} 字节", length_str);
             }
             
             println!("💡 提示: 响应体内容已在处理程序中打印（如果启用）");
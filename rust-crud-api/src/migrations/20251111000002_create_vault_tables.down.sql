@@ -0,0 +1,3 @@
+-- 回滚：删除入金与索引进度表
+DROP TABLE IF EXISTS indexer_progress;
+DROP TABLE IF EXISTS vault_deposits;
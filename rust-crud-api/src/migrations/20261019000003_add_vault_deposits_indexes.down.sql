@@ -0,0 +1,4 @@
+-- 回滚：删除入金查询索引
+DROP INDEX IF EXISTS idx_vault_deposits_block_number;
+DROP INDEX IF EXISTS idx_vault_deposits_status;
+DROP INDEX IF EXISTS idx_vault_deposits_sender;
@@ -0,0 +1,4 @@
+-- 入金查询接口按 sender、status 和区块范围过滤
+CREATE INDEX IF NOT EXISTS idx_vault_deposits_sender ON vault_deposits(sender);
+CREATE INDEX IF NOT EXISTS idx_vault_deposits_status ON vault_deposits(status);
+CREATE INDEX IF NOT EXISTS idx_vault_deposits_block_number ON vault_deposits(block_number);
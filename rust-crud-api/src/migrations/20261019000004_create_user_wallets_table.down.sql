@@ -0,0 +1,3 @@
+-- 回滚：删除钱包绑定相关表
+DROP TABLE IF EXISTS wallet_link_nonces;
+DROP TABLE IF EXISTS user_wallets;
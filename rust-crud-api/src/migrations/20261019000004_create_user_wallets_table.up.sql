@@ -0,0 +1,22 @@
+-- 钱包绑定表：一个地址只能绑定到一个用户，用户删除时级联解绑
+CREATE TABLE IF NOT EXISTS user_wallets (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    address TEXT NOT NULL UNIQUE,
+    linked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_wallets_user_id ON user_wallets(user_id);
+
+-- 签名挑战表：服务端下发的一次性 nonce 及待签名消息
+CREATE TABLE IF NOT EXISTS wallet_link_nonces (
+    nonce TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_link_nonces_user_id ON wallet_link_nonces(user_id);
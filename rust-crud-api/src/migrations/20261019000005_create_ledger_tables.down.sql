@@ -0,0 +1,8 @@
+-- 回滚：删除账本相关表
+DROP TABLE IF EXISTS ledger_reconciliations;
+DROP INDEX IF EXISTS idx_vault_deposits_unposted;
+ALTER TABLE vault_deposits DROP COLUMN IF EXISTS ledger_entry_id;
+DROP TABLE IF EXISTS ledger_postings;
+DROP FUNCTION IF EXISTS check_journal_entry_balanced();
+DROP TABLE IF EXISTS ledger_journal_entries;
+DROP TABLE IF EXISTS ledger_accounts;
@@ -0,0 +1,64 @@
+-- 复式记账：账户表
+-- kind: asset（借方余额，如 vault 链上持仓）/ liability（贷方余额，如欠用户的余额）
+CREATE TABLE IF NOT EXISTS ledger_accounts (
+    id BIGSERIAL PRIMARY KEY,
+    code TEXT NOT NULL UNIQUE,
+    kind TEXT NOT NULL CHECK (kind IN ('asset', 'liability')),
+    user_id UUID REFERENCES users(id) ON DELETE RESTRICT,
+    asset TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_accounts_user_id ON ledger_accounts(user_id);
+
+-- 记账凭证：idempotency_key 保证同一业务事件只记账一次
+CREATE TABLE IF NOT EXISTS ledger_journal_entries (
+    id BIGSERIAL PRIMARY KEY,
+    idempotency_key TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 分录：amount 为最小单位的精确整数，借方为正、贷方为负，同一凭证的分录之和必须为 0
+CREATE TABLE IF NOT EXISTS ledger_postings (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id BIGINT NOT NULL REFERENCES ledger_journal_entries(id) ON DELETE RESTRICT,
+    account_id BIGINT NOT NULL REFERENCES ledger_accounts(id) ON DELETE RESTRICT,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount <> 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_postings_entry_id ON ledger_postings(entry_id);
+CREATE INDEX IF NOT EXISTS idx_ledger_postings_account_id ON ledger_postings(account_id);
+
+-- 在事务提交时校验凭证借贷平衡
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF (SELECT COALESCE(SUM(amount), 0) FROM ledger_postings WHERE entry_id = NEW.entry_id) <> 0 THEN
+        RAISE EXCEPTION 'journal entry % is not balanced', NEW.entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE CONSTRAINT TRIGGER ledger_postings_balanced AFTER INSERT OR UPDATE
+    ON ledger_postings DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+-- 入金记账标记：记录入金对应的凭证
+ALTER TABLE vault_deposits ADD COLUMN IF NOT EXISTS ledger_entry_id BIGINT REFERENCES ledger_journal_entries(id);
+
+CREATE INDEX IF NOT EXISTS idx_vault_deposits_unposted ON vault_deposits(id) WHERE ledger_entry_id IS NULL;
+
+-- 对账记录：账本中 vault 资产账户余额与链上 balanceOf 的比对结果
+CREATE TABLE IF NOT EXISTS ledger_reconciliations (
+    id BIGSERIAL PRIMARY KEY,
+    asset TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    ledger_balance NUMERIC(78, 0) NOT NULL,
+    onchain_balance NUMERIC(78, 0) NOT NULL,
+    difference NUMERIC(78, 0) NOT NULL,
+    matched BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
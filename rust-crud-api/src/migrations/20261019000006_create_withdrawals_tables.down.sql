@@ -0,0 +1,3 @@
+-- 回滚：删除提现相关表
+DROP TABLE IF EXISTS withdrawal_approvals;
+DROP TABLE IF EXISTS withdrawals;
@@ -0,0 +1,35 @@
+-- 提现申请
+-- status: pending_approval -> approved -> submitting -> submitted -> finalized
+--         以及终态 rejected / cancelled / failed（资金从冻结账户退回用户）
+CREATE TABLE IF NOT EXISTS withdrawals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    destination TEXT NOT NULL,
+    status TEXT NOT NULL,
+    approvals_required INT NOT NULL DEFAULT 0,
+    executor TEXT,
+    external_ref TEXT,
+    submitted_block BIGINT,
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMPTZ,
+    finalized_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_user_id ON withdrawals(user_id);
+CREATE INDEX IF NOT EXISTS idx_withdrawals_status ON withdrawals(status);
+
+CREATE TRIGGER update_withdrawals_updated_at BEFORE UPDATE
+    ON withdrawals FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- 大额提现审批记录：同一审批人只能审批一次
+CREATE TABLE IF NOT EXISTS withdrawal_approvals (
+    id BIGSERIAL PRIMARY KEY,
+    withdrawal_id UUID NOT NULL REFERENCES withdrawals(id) ON DELETE CASCADE,
+    approver_id UUID NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    approved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (withdrawal_id, approver_id)
+);
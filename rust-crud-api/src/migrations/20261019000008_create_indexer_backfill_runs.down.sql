@@ -0,0 +1,2 @@
+-- 回滚：删除补扫任务表
+DROP TABLE IF EXISTS indexer_backfill_runs;
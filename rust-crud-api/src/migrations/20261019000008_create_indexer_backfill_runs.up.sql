@@ -0,0 +1,20 @@
+-- 手动补扫任务：独立于监听器的实时进度（indexer_progress），可中断后续跑
+-- status: running -> completed，或 failed（可续跑）
+CREATE TABLE IF NOT EXISTS indexer_backfill_runs (
+    id BIGSERIAL PRIMARY KEY,
+    source TEXT NOT NULL,
+    from_block BIGINT NOT NULL,
+    to_block BIGINT NOT NULL,
+    next_block BIGINT NOT NULL,
+    deposits_found BIGINT NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'running',
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (from_block <= to_block)
+);
+
+CREATE INDEX IF NOT EXISTS idx_indexer_backfill_runs_source ON indexer_backfill_runs(source, id DESC);
+
+CREATE TRIGGER update_indexer_backfill_runs_updated_at BEFORE UPDATE
+    ON indexer_backfill_runs FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
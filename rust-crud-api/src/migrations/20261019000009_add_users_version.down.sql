@@ -0,0 +1,4 @@
+-- 回滚：删除用户版本号
+DROP TRIGGER IF EXISTS bump_users_version ON users;
+DROP FUNCTION IF EXISTS bump_version_column();
+ALTER TABLE users DROP COLUMN IF EXISTS version;
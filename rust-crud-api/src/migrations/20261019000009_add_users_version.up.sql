@@ -0,0 +1,13 @@
+-- 用户版本号：每次更新自动加 1，用于 ETag 与 If-Match 乐观并发控制
+ALTER TABLE users ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_version_column()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER bump_users_version BEFORE UPDATE
+    ON users FOR EACH ROW EXECUTE FUNCTION bump_version_column();
@@ -0,0 +1,2 @@
+-- 回滚：删除 API Key 表
+DROP TABLE IF EXISTS api_keys;
@@ -0,0 +1,4 @@
+-- 回滚：删除 Hyperliquid 成交与资金费历史表
+DROP TABLE IF EXISTS hyperliquid_sync_cursors;
+DROP TABLE IF EXISTS hyperliquid_fundings;
+DROP TABLE IF EXISTS hyperliquid_fills;
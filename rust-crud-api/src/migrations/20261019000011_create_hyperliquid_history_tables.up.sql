@@ -0,0 +1,49 @@
+-- Hyperliquid 成交历史：用于盈亏与税务报表
+-- 同一笔成交在 WebSocket 推送与 REST 补拉中都可能出现，按 (address, tid) 去重
+CREATE TABLE IF NOT EXISTS hyperliquid_fills (
+    id BIGSERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    tid BIGINT NOT NULL,
+    oid BIGINT NOT NULL,
+    coin TEXT NOT NULL,
+    side TEXT NOT NULL,
+    dir TEXT NOT NULL,
+    px NUMERIC NOT NULL,
+    size NUMERIC NOT NULL,
+    start_position NUMERIC NOT NULL,
+    closed_pnl NUMERIC NOT NULL,
+    fee NUMERIC NOT NULL,
+    fee_token TEXT NOT NULL,
+    crossed BOOLEAN NOT NULL,
+    hash TEXT NOT NULL,
+    filled_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (address, tid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_hyperliquid_fills_address_time ON hyperliquid_fills(address, filled_at);
+
+-- 资金费结算历史：每个币种每小时结算一次，按 (address, coin, funded_at) 去重
+-- WebSocket 推送不含交易哈希，hash 只在 REST 补拉时填充
+CREATE TABLE IF NOT EXISTS hyperliquid_fundings (
+    id BIGSERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    coin TEXT NOT NULL,
+    usdc NUMERIC NOT NULL,
+    size NUMERIC NOT NULL,
+    funding_rate NUMERIC NOT NULL,
+    hash TEXT,
+    funded_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (address, coin, funded_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_hyperliquid_fundings_address_time ON hyperliquid_fundings(address, funded_at);
+
+-- 补拉进度：资金费历史已通过 REST 完整同步到的时间点
+-- WebSocket 推送不推进进度，断线期间的缺口由下一次补拉覆盖
+CREATE TABLE IF NOT EXISTS hyperliquid_sync_cursors (
+    address TEXT PRIMARY KEY,
+    funding_synced_until TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
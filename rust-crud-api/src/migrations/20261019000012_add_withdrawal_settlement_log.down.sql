@@ -0,0 +1,4 @@
+-- 回滚：删除提现认领的链上事件字段
+ALTER TABLE withdrawals DROP CONSTRAINT IF EXISTS uq_withdrawals_settlement_log;
+ALTER TABLE withdrawals DROP COLUMN IF EXISTS settlement_log_index;
+ALTER TABLE withdrawals DROP COLUMN IF EXISTS settlement_tx_hash;
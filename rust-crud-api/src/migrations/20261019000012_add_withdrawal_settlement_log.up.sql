@@ -0,0 +1,6 @@
+-- 记录确认提现时认领的链上出金事件，同一事件只能对应一笔提现
+ALTER TABLE withdrawals ADD COLUMN IF NOT EXISTS settlement_tx_hash TEXT;
+ALTER TABLE withdrawals ADD COLUMN IF NOT EXISTS settlement_log_index BIGINT;
+
+ALTER TABLE withdrawals
+    ADD CONSTRAINT uq_withdrawals_settlement_log UNIQUE (settlement_tx_hash, settlement_log_index);
@@ -0,0 +1,2 @@
+-- 回滚：删除入金重新上链次数
+ALTER TABLE vault_deposits DROP COLUMN IF EXISTS reinclusions;
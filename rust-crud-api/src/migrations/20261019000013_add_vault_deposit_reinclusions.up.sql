@@ -0,0 +1,2 @@
+-- 被链重组丢弃（orphaned）后又重新上链的次数，用于区分每次上链对应的记账凭证
+ALTER TABLE vault_deposits ADD COLUMN IF NOT EXISTS reinclusions INT NOT NULL DEFAULT 0;
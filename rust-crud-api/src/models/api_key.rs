@@ -0,0 +1,289 @@
+use actix_web::http::Method;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 可授予 API Key 的权限（<资源>:<read|write>，审批提现单独使用 withdrawals:approve）
+pub const API_KEY_SCOPES: &[&str] = &[
+    "users:read",
+    "users:write",
+    "deposits:read",
+    "ledger:read",
+    "withdrawals:read",
+    "withdrawals:write",
+    "withdrawals:approve",
+    "hyperliquid:read",
+    "webhooks:read",
+    "webhooks:write",
+    "api_keys:read",
+    "api_keys:write",
+];
+
+/// 只能通过 `cargo run --bin api_key` 授予的权限，用户自助创建密钥时不能申请
+pub const CLI_ONLY_SCOPES: &[&str] = &["withdrawals:approve"];
+
+/// API Key（secret_hash 不会返回给客户端，完整密钥只在创建时返回一次）
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// 密钥的可见前缀，例如 rca_1a2b3c4d
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    /// 为空表示没有任何权限
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    /// 是否可以用于认证（未吊销且未过期）
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// 创建 API Key 的响应，包含完整密钥
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// 创建 API Key 的请求数据
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// 必须显式授予至少一个权限
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// 缺省永不过期
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CreateApiKeyRequest {
+    /// 验证请求数据
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("API Key 名称不能为空".to_string());
+        }
+        if name.chars().count() > 100 {
+            return Err("API Key 名称不能超过100个字符".to_string());
+        }
+        if self.scopes.is_empty() {
+            return Err("至少需要授予一个权限".to_string());
+        }
+        for scope in &self.scopes {
+            if !API_KEY_SCOPES.contains(&scope.as_str()) {
+                return Err(format!("未知的权限: {}", scope));
+            }
+        }
+        if let Some(expires_at) = self.expires_at
+            && expires_at <= Utc::now()
+        {
+            return Err("过期时间必须晚于当前时间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 通过 API Key 认证的调用方，由中间件写入请求扩展
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    pub key_id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<String>,
+    /// 密钥所属用户在 ADMIN_USER_IDS 中，可以访问全局接口
+    pub admin: bool,
+}
+
+impl ApiKeyPrincipal {
+    /// 是否拥有指定权限；没有授予任何权限的密钥不能访问受保护接口
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// 自助创建密钥时能否授予该权限：不能超出当前密钥的权限，也不能授予仅限命令行的权限
+    pub fn can_grant(&self, scope: &str) -> bool {
+        !CLI_ONLY_SCOPES.contains(&scope) && self.allows(scope)
+    }
+
+    /// 是否可以访问该路径：/api/users/{id}/... 只能访问密钥所属用户自己的资源，
+    /// 跨用户的全局接口（用户列表与导出、入金、账本、提现列表、Webhook）只对管理员开放
+    pub fn can_access(&self, path: &str) -> bool {
+        match path_user_id(path) {
+            Some(user_id) => user_id == self.user_id,
+            None => self.admin || !is_global_path(path),
+        }
+    }
+}
+
+/// 不属于单个用户的 /api 接口；审批与拒绝提现由审批人名单单独控制
+fn is_global_path(path: &str) -> bool {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    segments.first() == Some(&"api") && segments.len() > 1 && !is_approval_path(&segments)
+}
+
+/// /api/withdrawals/{id}/approve|reject
+fn is_approval_path(segments: &[&str]) -> bool {
+    segments.get(1) == Some(&"withdrawals") && matches!(segments.get(3).copied(), Some("approve") | Some("reject"))
+}
+
+/// 路径 /api/users/{id}[/...] 中的用户 ID；其他路径返回 None
+pub fn path_user_id(path: &str) -> Option<Uuid> {
+    let mut segments = path.trim_matches('/').split('/');
+    if segments.next() != Some("api") || segments.next() != Some("users") {
+        return None;
+    }
+    segments.next()?.parse().ok()
+}
+
+/// 访问某个接口需要的权限；不属于受保护资源的路径（如 /health）返回 None
+pub fn required_scope(method: &Method, path: &str) -> Option<String> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    if segments.first() != Some(&"api") {
+        return None;
+    }
+
+    let resource = match (segments.get(1).copied(), segments.get(3).copied()) {
+        (Some("users"), Some("deposits")) => "deposits",
+        (Some("users"), Some("balances")) => "ledger",
+        (Some("users"), Some("withdrawals")) => "withdrawals",
+        (Some("users"), Some("api-keys")) => "api_keys",
+        (Some("users"), Some("hyperliquid")) => "hyperliquid",
+        (Some("users"), _) => "users",
+        (Some("deposits"), _) => "deposits",
+        (Some("ledger"), _) => "ledger",
+        (Some("withdrawals"), _) => "withdrawals",
+        (Some("webhooks"), _) => "webhooks",
+        _ => return None,
+    };
+    // 审批与拒绝提现是管理操作，不随 withdrawals:write 一起授予
+    if is_approval_path(&segments) {
+        return Some("withdrawals:approve".to_string());
+    }
+    let action = if *method == Method::GET || *method == Method::HEAD { "read" } else { "write" };
+
+    Some(format!("{}:{}", resource, action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope(&Method::GET, "/api/users").as_deref(), Some("users:read"));
+        assert_eq!(required_scope(&Method::PUT, "/api/users/abc").as_deref(), Some("users:write"));
+        assert_eq!(required_scope(&Method::POST, "/api/users/abc/wallets").as_deref(), Some("users:write"));
+        assert_eq!(required_scope(&Method::GET, "/api/users/abc/deposits").as_deref(), Some("deposits:read"));
+        assert_eq!(required_scope(&Method::GET, "/api/users/abc/balances").as_deref(), Some("ledger:read"));
+        assert_eq!(
+            required_scope(&Method::POST, "/api/users/abc/withdrawals/x/cancel").as_deref(),
+            Some("withdrawals:write")
+        );
+        assert_eq!(required_scope(&Method::DELETE, "/api/users/abc/api-keys/k").as_deref(), Some("api_keys:write"));
+        assert_eq!(
+            required_scope(&Method::GET, "/api/users/abc/hyperliquid/0x1/fills").as_deref(),
+            Some("hyperliquid:read")
+        );
+        assert_eq!(required_scope(&Method::GET, "/api/deposits/stream").as_deref(), Some("deposits:read"));
+        assert_eq!(required_scope(&Method::GET, "/api/withdrawals/x").as_deref(), Some("withdrawals:read"));
+        assert_eq!(required_scope(&Method::POST, "/api/withdrawals/x/approve").as_deref(), Some("withdrawals:approve"));
+        assert_eq!(required_scope(&Method::POST, "/api/withdrawals/x/reject").as_deref(), Some("withdrawals:approve"));
+        assert_eq!(required_scope(&Method::POST, "/api/webhooks").as_deref(), Some("webhooks:write"));
+        assert_eq!(required_scope(&Method::GET, "/health"), None);
+
+        for scope in [
+            "users:read",
+            "deposits:read",
+            "ledger:read",
+            "hyperliquid:read",
+            "withdrawals:write",
+            "withdrawals:approve",
+            "api_keys:write",
+        ] {
+            assert!(API_KEY_SCOPES.contains(&scope));
+        }
+    }
+
+    #[test]
+    fn test_principal_allows() {
+        let mut principal = ApiKeyPrincipal { key_id: Uuid::nil(), user_id: Uuid::nil(), scopes: vec![], admin: false };
+        assert!(!principal.allows("webhooks:write")); // 空权限不代表全部权限
+
+        principal.scopes = vec!["users:read".to_string(), "withdrawals:write".to_string()];
+        assert!(principal.allows("users:read"));
+        assert!(!principal.allows("users:write"));
+        assert!(!principal.allows("withdrawals:approve"));
+    }
+
+    #[test]
+    fn test_principal_can_grant() {
+        let principal = ApiKeyPrincipal {
+            key_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            scopes: vec!["api_keys:write".to_string(), "users:read".to_string(), "withdrawals:approve".to_string()],
+            admin: false,
+        };
+        assert!(principal.can_grant("users:read"));
+        assert!(!principal.can_grant("withdrawals:write"));
+        assert!(!principal.can_grant("withdrawals:approve"));
+    }
+
+    #[test]
+    fn test_principal_can_access() {
+        let user_id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let mut principal = ApiKeyPrincipal { key_id: Uuid::nil(), user_id, scopes: vec![], admin: false };
+
+        assert_eq!(path_user_id(&format!("/api/users/{}/balances", user_id)), Some(user_id));
+        assert!(principal.can_access(&format!("/api/users/{}", user_id)));
+        assert!(principal.can_access(&format!("/api/users/{}/withdrawals", user_id)));
+        assert!(!principal.can_access(&format!("/api/users/{}/withdrawals", other)));
+        assert!(!principal.can_access(&format!("/api/users/{}/api-keys", other)));
+        assert!(principal.can_access("/health"));
+        assert!(principal.can_access("/api/withdrawals/x/approve"));
+
+        // 跨用户的全局接口只对管理员开放
+        for path in ["/api/users", "/api/users/export", "/api/deposits", "/api/withdrawals", "/api/webhooks"] {
+            assert!(!principal.can_access(path), "{}", path);
+        }
+        principal.admin = true;
+        assert!(principal.can_access("/api/users/export"));
+        assert!(principal.can_access("/api/webhooks"));
+        assert!(!principal.can_access(&format!("/api/users/{}/withdrawals", other)));
+    }
+
+    #[test]
+    fn test_create_request_validation() {
+        let mut request = CreateApiKeyRequest {
+            name: "billing-service".to_string(),
+            scopes: vec!["users:read".to_string(), "deposits:read".to_string()],
+            expires_at: Some(Utc::now() + Duration::days(30)),
+        };
+        assert!(request.validate().is_ok());
+
+        request.scopes = vec!["users:admin".to_string()];
+        assert_eq!(request.validate().unwrap_err(), "未知的权限: users:admin");
+
+        request.scopes = vec![];
+        assert_eq!(request.validate().unwrap_err(), "至少需要授予一个权限");
+
+        request.scopes = vec!["users:read".to_string()];
+        request.expires_at = Some(Utc::now() - Duration::minutes(1));
+        assert!(request.validate().is_err());
+
+        request.expires_at = None;
+        request.name = "  ".to_string();
+        assert!(request.validate().is_err());
+    }
+}
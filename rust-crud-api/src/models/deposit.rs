@@ -0,0 +1,117 @@
+use crate::utils::is_valid_eth_address;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// 默认分页大小
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+/// 最大分页大小
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Vault 入金记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Deposit {
+    pub id: i64,
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub tx_index: Option<i64>,
+    pub sender: String,
+    pub to_address: String,
+    pub amount_wei: String,
+    pub token_address: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    /// 通过钱包绑定关联到的用户（未绑定时为空）
+    #[sqlx(default)]
+    pub user_id: Option<Uuid>,
+}
+
+/// 入金列表查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct DepositQuery {
+    pub sender: Option<String>,
+    pub status: Option<String>,
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    /// 只查询该用户已绑定钱包的入金
+    pub user_id: Option<Uuid>,
+}
+
+impl DepositQuery {
+    /// 验证查询参数
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(sender) = &self.sender
+            && !is_valid_eth_address(sender)
+        {
+            return Err("sender 地址格式无效".to_string());
+        }
+
+        if let (Some(from), Some(to)) = (self.from_block, self.to_block)
+            && from > to
+        {
+            return Err("from_block 不能大于 to_block".to_string());
+        }
+
+        if self.from_block.is_some_and(|b| b < 0) || self.to_block.is_some_and(|b| b < 0) {
+            return Err("区块号不能为负数".to_string());
+        }
+
+        if self.page.is_some_and(|p| p < 1) {
+            return Err("page 必须大于等于1".to_string());
+        }
+
+        if self.page_size.is_some_and(|s| !(1..=MAX_PAGE_SIZE).contains(&s)) {
+            return Err(format!("page_size 必须在 1-{} 之间", MAX_PAGE_SIZE));
+        }
+
+        if (self.page() - 1).checked_mul(self.page_size()).is_none() {
+            return Err("page 超出范围".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// 当前页码（从1开始）
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1)
+    }
+
+    /// 每页条数
+    pub fn page_size(&self) -> i64 {
+        self.page_size.unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+
+    /// 分页偏移量；调用前需先通过 validate，保证不会溢出
+    pub fn offset(&self) -> i64 {
+        (self.page() - 1) * self.page_size()
+    }
+
+    /// 归一化后的 sender（监听器以小写形式入库）
+    pub fn normalized_sender(&self) -> Option<String> {
+        self.sender.as_ref().map(|s| s.to_lowercase())
+    }
+}
+
+/// 实时推送订阅参数
+#[derive(Debug, Default, Deserialize)]
+pub struct DepositStreamQuery {
+    pub sender: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_overflowing_page() {
+        let query = DepositQuery { page: Some(i64::MAX), page_size: Some(MAX_PAGE_SIZE), ..Default::default() };
+        assert!(query.validate().is_err());
+
+        let query = DepositQuery { page: Some(3), page_size: Some(10), ..Default::default() };
+        assert!(query.validate().is_ok());
+        assert_eq!(query.offset(), 20);
+    }
+}
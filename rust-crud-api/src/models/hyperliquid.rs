@@ -0,0 +1,309 @@
+use hyperliquid_rust_sdk::{
+    AssetPosition, OpenOrdersResponse, TradeInfo, UserFillsResponse, UserFunding, UserFundingResponse, UserStateResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Hyperliquid 的买卖方向：B（bid）为买入，A（ask）为卖出
+fn normalize_side(side: &str) -> String {
+    match side {
+        "B" => "buy".to_string(),
+        "A" => "sell".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+/// 永续合约持仓（数值保持上游返回的十进制字符串，避免精度损失）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyperliquidPosition {
+    pub coin: String,
+    /// 带符号的持仓数量，负数为空头
+    pub size: String,
+    pub entry_px: Option<String>,
+    pub position_value: String,
+    pub unrealized_pnl: String,
+    pub return_on_equity: String,
+    pub liquidation_px: Option<String>,
+    pub margin_used: String,
+    /// cross 或 isolated
+    pub leverage_type: String,
+    pub leverage: u32,
+    pub max_leverage: u32,
+    pub cum_funding_all_time: String,
+    pub cum_funding_since_open: String,
+}
+
+impl From<AssetPosition> for HyperliquidPosition {
+    fn from(asset_position: AssetPosition) -> Self {
+        let p = asset_position.position;
+        Self {
+            coin: p.coin,
+            size: p.szi.to_string(),
+            entry_px: p.entry_px.map(|px| px.to_string()),
+            position_value: p.position_value.to_string(),
+            unrealized_pnl: p.unrealized_pnl.to_string(),
+            return_on_equity: p.return_on_equity.to_string(),
+            liquidation_px: p.liquidation_px.map(|px| px.to_string()),
+            margin_used: p.margin_used.to_string(),
+            leverage_type: p.leverage.type_string,
+            leverage: p.leverage.value,
+            max_leverage: p.max_leverage,
+            cum_funding_all_time: p.cum_funding.all_time.to_string(),
+            cum_funding_since_open: p.cum_funding.since_open.to_string(),
+        }
+    }
+}
+
+/// 账户概览与全部持仓
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyperliquidAccountState {
+    pub address: String,
+    pub account_value: String,
+    pub total_margin_used: String,
+    pub total_notional_position: String,
+    pub withdrawable: String,
+    pub positions: Vec<HyperliquidPosition>,
+}
+
+impl HyperliquidAccountState {
+    pub fn from_response(address: &str, state: UserStateResponse) -> Self {
+        Self {
+            address: address.to_string(),
+            account_value: state.margin_summary.account_value.to_string(),
+            total_margin_used: state.margin_summary.total_margin_used.to_string(),
+            total_notional_position: state.margin_summary.total_ntl_pos.to_string(),
+            withdrawable: state.withdrawable.to_string(),
+            positions: state.asset_positions.into_iter().map(HyperliquidPosition::from).collect(),
+        }
+    }
+}
+
+/// 挂单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyperliquidOpenOrder {
+    pub oid: u64,
+    pub cloid: Option<String>,
+    pub coin: String,
+    pub side: String,
+    pub limit_px: String,
+    pub size: String,
+    /// 下单时间（毫秒）
+    pub timestamp: u64,
+}
+
+impl From<OpenOrdersResponse> for HyperliquidOpenOrder {
+    fn from(order: OpenOrdersResponse) -> Self {
+        Self {
+            oid: order.oid,
+            cloid: order.cloid,
+            coin: order.coin,
+            side: normalize_side(&order.side),
+            limit_px: order.limit_px.to_string(),
+            size: order.sz.to_string(),
+            timestamp: order.timestamp,
+        }
+    }
+}
+
+/// 成交记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyperliquidFill {
+    pub tid: u64,
+    pub oid: u64,
+    pub coin: String,
+    pub side: String,
+    /// 例如 Open Long、Close Short
+    pub dir: String,
+    pub px: String,
+    pub size: String,
+    pub start_position: String,
+    pub closed_pnl: String,
+    pub fee: String,
+    pub fee_token: String,
+    /// 是否为吃单
+    pub crossed: bool,
+    pub hash: String,
+    /// 成交时间（毫秒）
+    pub time: u64,
+}
+
+impl From<UserFillsResponse> for HyperliquidFill {
+    fn from(fill: UserFillsResponse) -> Self {
+        Self {
+            tid: fill.tid,
+            oid: fill.oid,
+            coin: fill.coin,
+            side: normalize_side(&fill.side),
+            dir: fill.dir,
+            px: fill.px.to_string(),
+            size: fill.sz.to_string(),
+            start_position: fill.start_position.to_string(),
+            closed_pnl: fill.closed_pnl.to_string(),
+            fee: fill.fee.to_string(),
+            fee_token: fill.fee_token,
+            crossed: fill.crossed,
+            hash: fill.hash,
+            time: fill.time,
+        }
+    }
+}
+
+/// WebSocket 推送的成交（与 REST 返回的字段相同）
+impl From<TradeInfo> for HyperliquidFill {
+    fn from(fill: TradeInfo) -> Self {
+        Self {
+            tid: fill.tid,
+            oid: fill.oid,
+            coin: fill.coin,
+            side: normalize_side(&fill.side),
+            dir: fill.dir,
+            px: fill.px.to_string(),
+            size: fill.sz.to_string(),
+            start_position: fill.start_position.to_string(),
+            closed_pnl: fill.closed_pnl.to_string(),
+            fee: fill.fee.to_string(),
+            fee_token: fill.fee_token,
+            crossed: fill.crossed,
+            hash: fill.hash,
+            time: fill.time,
+        }
+    }
+}
+
+/// 资金费结算记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyperliquidFunding {
+    pub time: u64,
+    /// WebSocket 推送的记录没有交易哈希
+    pub hash: Option<String>,
+    pub coin: String,
+    /// 本次结算的 USDC 金额，负数为支付
+    pub usdc: String,
+    /// 结算时的持仓数量
+    pub size: String,
+    pub funding_rate: String,
+}
+
+impl From<UserFundingResponse> for HyperliquidFunding {
+    fn from(funding: UserFundingResponse) -> Self {
+        Self {
+            time: funding.time,
+            hash: Some(funding.hash),
+            coin: funding.delta.coin,
+            usdc: funding.delta.usdc.to_string(),
+            size: funding.delta.szi.to_string(),
+            funding_rate: funding.delta.funding_rate.to_string(),
+        }
+    }
+}
+
+impl From<UserFunding> for HyperliquidFunding {
+    fn from(funding: UserFunding) -> Self {
+        Self {
+            time: funding.time,
+            hash: None,
+            coin: funding.coin,
+            usdc: funding.usdc.to_string(),
+            size: funding.szi.to_string(),
+            funding_rate: funding.funding_rate.to_string(),
+        }
+    }
+}
+
+/// 资金费历史的查询参数（毫秒时间戳）
+#[derive(Debug, Deserialize)]
+pub struct HyperliquidFundingQuery {
+    /// 缺省为最近 7 天
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+}
+
+impl HyperliquidFundingQuery {
+    /// 验证查询参数
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time)
+            && start > end
+        {
+            return Err("start_time 不能晚于 end_time".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_state_conversion() {
+        let state: UserStateResponse = serde_json::from_value(serde_json::json!({
+            "assetPositions": [{
+                "type": "oneWay",
+                "position": {
+                    "coin": "ETH",
+                    "entryPx": "2986.3",
+                    "leverage": {"type": "cross", "value": 20},
+                    "liquidationPx": "2866.26936529",
+                    "marginUsed": "4.967826",
+                    "maxLeverage": 50,
+                    "positionValue": "100.02765",
+                    "returnOnEquity": "-0.0026789",
+                    "szi": "-0.0335",
+                    "unrealizedPnl": "-0.0134",
+                    "cumFunding": {"allTime": "514.085417", "sinceChange": "0.0", "sinceOpen": "0.0"}
+                }
+            }],
+            "crossMarginSummary": {"accountValue": "13104.514502", "totalMarginUsed": "4.967826", "totalNtlPos": "100.02765", "totalRawUsd": "13204.542152"},
+            "marginSummary": {"accountValue": "13109.482328", "totalMarginUsed": "4.967826", "totalNtlPos": "100.02765", "totalRawUsd": "13209.510078"},
+            "withdrawable": "13104.514502"
+        }))
+        .unwrap();
+
+        let account = HyperliquidAccountState::from_response("0xabc", state);
+        assert_eq!(account.account_value, "13109.482328");
+        assert_eq!(account.positions.len(), 1);
+        let position = &account.positions[0];
+        assert_eq!(position.size, "-0.0335");
+        assert_eq!(position.leverage_type, "cross");
+        assert_eq!(position.leverage, 20);
+        assert_eq!(position.cum_funding_all_time, "514.085417");
+    }
+
+    #[test]
+    fn test_fill_and_funding_conversion() {
+        let fill: UserFillsResponse = serde_json::from_value(serde_json::json!({
+            "closedPnl": "0.0", "coin": "AVAX", "crossed": false, "dir": "Open Long",
+            "hash": "0xa166", "oid": 90542681, "px": "18.435", "side": "B",
+            "startPosition": "26.86", "sz": "93.53", "time": 1681222254710u64,
+            "fee": "0.01", "tid": 118906512037719u64, "feeToken": "USDC", "twapId": null
+        }))
+        .unwrap();
+        let fill = HyperliquidFill::from(fill);
+        assert_eq!(fill.side, "buy");
+        assert_eq!(fill.size, "93.53");
+
+        let funding: UserFundingResponse = serde_json::from_value(serde_json::json!({
+            "time": 1681222254710u64, "hash": "0x00",
+            "delta": {"type": "funding", "coin": "ETH", "usdc": "-3.625312", "szi": "49.1477", "fundingRate": "0.0000417"}
+        }))
+        .unwrap();
+        let funding = HyperliquidFunding::from(funding);
+        assert_eq!(funding.usdc, "-3.625312");
+        assert_eq!(funding.size, "49.1477");
+        assert_eq!(funding.hash.as_deref(), Some("0x00"));
+
+        let pushed: UserFunding = serde_json::from_value(serde_json::json!({
+            "time": 1681222254710u64, "coin": "ETH", "usdc": "-3.625312", "szi": "49.1477", "fundingRate": "0.0000417"
+        }))
+        .unwrap();
+        let pushed = HyperliquidFunding::from(pushed);
+        assert_eq!((pushed.coin.as_str(), pushed.time, pushed.hash), ("ETH", funding.time, None));
+    }
+
+    #[test]
+    fn test_funding_query_validation() {
+        let query = HyperliquidFundingQuery { start_time: Some(2), end_time: Some(1) };
+        assert!(query.validate().is_err());
+        let query = HyperliquidFundingQuery { start_time: None, end_time: Some(1) };
+        assert!(query.validate().is_ok());
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// 账户类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    /// 资产账户（借方余额），如 vault 在链上持有的代币
+    Asset,
+    /// 负债账户（贷方余额），如平台欠用户的余额
+    Liability,
+}
+
+impl AccountKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountKind::Asset => "asset",
+            AccountKind::Liability => "liability",
+        }
+    }
+}
+
+/// 账户余额（金额为最小单位的十进制字符串，避免前端精度丢失）
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account_id: i64,
+    pub code: String,
+    pub kind: String,
+    pub user_id: Option<Uuid>,
+    pub asset: String,
+    /// 按账户类型的正常方向计算的余额：资产账户为借方合计，负债账户为贷方合计
+    pub balance: String,
+}
+
+/// 账户余额查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct AccountQuery {
+    pub kind: Option<String>,
+    pub asset: Option<String>,
+}
+
+/// 对账记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Reconciliation {
+    pub id: i64,
+    pub asset: String,
+    pub block_number: i64,
+    pub ledger_balance: String,
+    pub onchain_balance: String,
+    /// 链上余额减去账本余额
+    pub difference: String,
+    pub matched: bool,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,5 +1,25 @@
 pub mod user;
+pub mod deposit;
+pub mod wallet;
+pub mod ledger;
+pub mod withdrawal;
+pub mod webhook;
+pub mod api_key;
+pub mod hyperliquid;
 pub mod response;
 
-pub use user::{User, CreateUserRequest, UpdateUserRequest, UserResponse};
-pub use response::ApiResponse;
\ No newline at end of file
+pub use user::{
+    CreateUserRequest, ImportRowError, UpdateUserRequest, User, UserBulkFormat, UserBulkQuery, UserImportReport,
+    UserResponse,
+};
+pub use deposit::{Deposit, DepositQuery, DepositStreamQuery};
+pub use wallet::{UserWallet, WalletNonceRequest, WalletNonceResponse, LinkWalletRequest};
+pub use ledger::{AccountBalance, AccountKind, AccountQuery, Reconciliation};
+pub use withdrawal::{
+    CreateWithdrawalRequest, RejectWithdrawalRequest, SettlementLog, Withdrawal, WithdrawalQuery, WithdrawalStatus,
+};
+pub use webhook::{
+    CreateWebhookEndpointRequest, UpdateWebhookEndpointRequest, WebhookDelivery, WebhookDeliveryQuery, WebhookEndpoint,
+};
+pub use api_key::{ApiKey, ApiKeyPrincipal, CreateApiKeyRequest, CreatedApiKey};
+pub use response::{ApiResponse, PaginatedData};
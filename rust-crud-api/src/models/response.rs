@@ -8,6 +8,15 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
 }
 
+/// 分页数据
+#[derive(Debug, Serialize)]
+pub struct PaginatedData<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
 impl<T> ApiResponse<T>
 where
     T: Serialize,
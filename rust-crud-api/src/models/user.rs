@@ -12,10 +12,19 @@ pub struct User {
     #[serde(skip_serializing, default)] // 密码不序列化到响应中，反序列化时使用默认值
     pub password_hash: String,
     pub full_name: String,
+    /// 每次更新自动加 1，用于 ETag 与 If-Match
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl User {
+    /// 强 ETag：由版本号生成，任何更新都会改变它
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.version)
+    }
+}
+
 /// 用户注册请求数据
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
@@ -76,16 +85,16 @@ impl UpdateUserRequest {
             }
         }
         
-        if let Some(password) = &self.password {
-            if password.len() < 6 {
-                return Err("密码至少需要6个字符".to_string());
-            }
+        if let Some(password) = &self.password
+            && password.len() < 6
+        {
+            return Err("密码至少需要6个字符".to_string());
         }
         
-        if let Some(full_name) = &self.full_name {
-            if full_name.is_empty() {
-                return Err("姓名不能为空".to_string());
-            }
+        if let Some(full_name) = &self.full_name
+            && full_name.is_empty()
+        {
+            return Err("姓名不能为空".to_string());
         }
         
         Ok(())
@@ -99,6 +108,7 @@ pub struct UserResponse {
     pub username: String,
     pub email: String,
     pub full_name: String,
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -110,8 +120,67 @@ impl From<User> for UserResponse {
             username: user.username,
             email: user.email,
             full_name: user.full_name,
+            version: user.version,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
     }
-}
\ No newline at end of file
+}
+/// 批量导入/导出的数据格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserBulkFormat {
+    Csv,
+    Ndjson,
+}
+
+impl UserBulkFormat {
+    /// 根据 Content-Type 推断格式
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "text/csv" => Some(UserBulkFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Some(UserBulkFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            UserBulkFormat::Csv => "text/csv; charset=utf-8",
+            UserBulkFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+/// 批量导入/导出查询参数；导入时未指定则按 Content-Type 判断
+#[derive(Debug, Default, Deserialize)]
+pub struct UserBulkQuery {
+    pub format: Option<UserBulkFormat>,
+}
+
+/// 导入失败的行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportRowError {
+    /// 行号（从 1 开始，CSV 包含表头行）
+    pub line: usize,
+    pub username: Option<String>,
+    pub error: String,
+}
+
+/// 批量导入结果
+#[derive(Debug, Default, Serialize)]
+pub struct UserImportReport {
+    /// 数据行总数（不含表头与空行）
+    pub total: usize,
+    pub created: usize,
+    pub failed: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+impl UserImportReport {
+    pub fn reject(&mut self, line: usize, username: Option<String>, error: impl Into<String>) {
+        self.failed += 1;
+        self.errors.push(ImportRowError { line, username, error: error.into() });
+    }
+}
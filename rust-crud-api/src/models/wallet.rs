@@ -0,0 +1,72 @@
+use crate::utils::is_valid_eth_address;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// 用户绑定的钱包地址
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UserWallet {
+    pub id: i64,
+    pub user_id: Uuid,
+    pub address: String,
+    pub linked_at: DateTime<Utc>,
+}
+
+/// 申请绑定 nonce 的请求数据
+#[derive(Debug, Deserialize)]
+pub struct WalletNonceRequest {
+    pub address: String,
+}
+
+impl WalletNonceRequest {
+    /// 验证请求数据
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_valid_eth_address(&self.address) {
+            return Err("钱包地址格式无效".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 待签名的绑定挑战
+#[derive(Debug, Serialize)]
+pub struct WalletNonceResponse {
+    pub address: String,
+    pub nonce: String,
+    /// 需要用户通过 personal_sign 签名的完整消息
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 提交签名完成绑定的请求数据
+#[derive(Debug, Deserialize)]
+pub struct LinkWalletRequest {
+    pub nonce: String,
+    /// 65 字节的签名（0x 开头的十六进制）
+    pub signature: String,
+}
+
+impl LinkWalletRequest {
+    /// 验证请求数据
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nonce.is_empty() {
+            return Err("nonce 不能为空".to_string());
+        }
+        if self.signature.is_empty() {
+            return Err("签名不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 绑定挑战记录
+#[derive(Debug, Clone, FromRow)]
+pub struct WalletLinkNonce {
+    pub nonce: String,
+    pub user_id: Uuid,
+    pub address: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
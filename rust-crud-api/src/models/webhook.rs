@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 入金事件：监听器写入新的 vault_deposits 记录
+pub const EVENT_DEPOSIT_CREATED: &str = "deposit.created";
+/// 入金被链重组丢弃：入金标记为 orphaned，已记账的金额已冲销
+pub const EVENT_DEPOSIT_ORPHANED: &str = "deposit.orphaned";
+/// 用户事件
+pub const EVENT_USER_CREATED: &str = "user.created";
+pub const EVENT_USER_UPDATED: &str = "user.updated";
+pub const EVENT_USER_DELETED: &str = "user.deleted";
+
+/// 支持订阅的事件类型（'*' 表示全部）
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    EVENT_DEPOSIT_CREATED,
+    EVENT_DEPOSIT_ORPHANED,
+    EVENT_USER_CREATED,
+    EVENT_USER_UPDATED,
+    EVENT_USER_DELETED,
+];
+
+/// Webhook 订阅端点（secret 只在创建时返回）
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 创建端点的响应，包含用于验签的 secret
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookEndpoint {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+/// 注册端点的请求数据
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookEndpointRequest {
+    pub url: String,
+    /// 缺省订阅全部事件
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+}
+
+/// 更新端点的请求数据
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookEndpointRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub active: Option<bool>,
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err("Webhook URL 必须以 http:// 或 https:// 开头".to_string());
+    }
+    Ok(())
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<(), String> {
+    if event_types.is_empty() {
+        return Err("至少需要订阅一种事件".to_string());
+    }
+    for event_type in event_types {
+        if event_type != "*" && !WEBHOOK_EVENT_TYPES.contains(&event_type.as_str()) {
+            return Err(format!("未知的事件类型: {}", event_type));
+        }
+    }
+    Ok(())
+}
+
+impl CreateWebhookEndpointRequest {
+    /// 验证端点注册数据
+    pub fn validate(&self) -> Result<(), String> {
+        validate_url(&self.url)?;
+        if let Some(event_types) = &self.event_types {
+            validate_event_types(event_types)?;
+        }
+        Ok(())
+    }
+}
+
+impl UpdateWebhookEndpointRequest {
+    /// 验证端点更新数据
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(url) = &self.url {
+            validate_url(url)?;
+        }
+        if let Some(event_types) = &self.event_types {
+            validate_event_types(event_types)?;
+        }
+        Ok(())
+    }
+}
+
+/// 投递状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    /// 超过最大重试次数（死信）
+    Dead,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(DeliveryStatus::Pending),
+            "delivered" => Some(DeliveryStatus::Delivered),
+            "dead" => Some(DeliveryStatus::Dead),
+            _ => None,
+        }
+    }
+}
+
+/// 投递记录
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub endpoint_id: Uuid,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 单次投递尝试日志
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookDeliveryAttempt {
+    pub id: i64,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub response_body: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 投递详情：投递记录 + 事件内容 + 全部尝试日志
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryDetail {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    pub payload: serde_json::Value,
+    pub attempts: Vec<WebhookDeliveryAttempt>,
+}
+
+/// 投递日志查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_request_validation() {
+        let request = |url: &str, event_types: Option<Vec<&str>>| CreateWebhookEndpointRequest {
+            url: url.to_string(),
+            event_types: event_types.map(|v| v.into_iter().map(String::from).collect()),
+            description: None,
+        };
+
+        assert!(request("https://example.com/hook", None).validate().is_ok());
+        assert!(request("https://example.com/hook", Some(vec!["*"])).validate().is_ok());
+        assert!(request("https://example.com/hook", Some(vec![EVENT_DEPOSIT_CREATED])).validate().is_ok());
+        assert!(request("ftp://example.com/hook", None).validate().is_err());
+        assert!(request("https://example.com/hook", Some(vec![])).validate().is_err());
+        assert!(request("https://example.com/hook", Some(vec!["order.created"])).validate().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::utils::is_valid_eth_address;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 提现状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawalStatus {
+    /// 等待审批（超过审批阈值）
+    PendingApproval,
+    /// 已通过风控/审批，等待执行
+    Approved,
+    /// 正在提交给执行器（进程在此状态崩溃时需要人工核对）
+    Submitting,
+    /// 已提交上链，等待最终确认
+    Submitted,
+    /// 已在链上最终确认
+    Finalized,
+    /// 审批被拒绝
+    Rejected,
+    /// 用户取消
+    Cancelled,
+    /// 执行失败
+    Failed,
+}
+
+impl WithdrawalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WithdrawalStatus::PendingApproval => "pending_approval",
+            WithdrawalStatus::Approved => "approved",
+            WithdrawalStatus::Submitting => "submitting",
+            WithdrawalStatus::Submitted => "submitted",
+            WithdrawalStatus::Finalized => "finalized",
+            WithdrawalStatus::Rejected => "rejected",
+            WithdrawalStatus::Cancelled => "cancelled",
+            WithdrawalStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending_approval" => Some(WithdrawalStatus::PendingApproval),
+            "approved" => Some(WithdrawalStatus::Approved),
+            "submitting" => Some(WithdrawalStatus::Submitting),
+            "submitted" => Some(WithdrawalStatus::Submitted),
+            "finalized" => Some(WithdrawalStatus::Finalized),
+            "rejected" => Some(WithdrawalStatus::Rejected),
+            "cancelled" => Some(WithdrawalStatus::Cancelled),
+            "failed" => Some(WithdrawalStatus::Failed),
+            _ => None,
+        }
+    }
+
+    /// 是否允许从当前状态流转到目标状态
+    pub fn can_transition_to(&self, next: WithdrawalStatus) -> bool {
+        use WithdrawalStatus::*;
+        matches!(
+            (self, next),
+            (PendingApproval, Approved)
+                | (PendingApproval, Rejected)
+                | (PendingApproval, Cancelled)
+                | (Approved, Submitting)
+                | (Approved, Cancelled)
+                | (Submitting, Submitted)
+                | (Submitting, Failed)
+                | (Submitted, Finalized)
+                | (Submitted, Failed)
+        )
+    }
+
+    /// 终态中资金需要从冻结账户退回给用户的状态
+    pub fn releases_hold(&self) -> bool {
+        matches!(
+            self,
+            WithdrawalStatus::Rejected | WithdrawalStatus::Cancelled | WithdrawalStatus::Failed
+        )
+    }
+}
+
+/// 提现记录（金额为最小单位的十进制字符串）
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub asset: String,
+    pub amount: String,
+    pub destination: String,
+    pub status: String,
+    pub approvals_required: i32,
+    pub approvals: i64,
+    pub executor: Option<String>,
+    pub external_ref: Option<String>,
+    pub submitted_block: Option<i64>,
+    pub settlement_tx_hash: Option<String>,
+    pub settlement_log_index: Option<i64>,
+    pub failure_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub finalized_at: Option<DateTime<Utc>>,
+}
+
+impl Withdrawal {
+    /// 解析当前状态
+    pub fn status(&self) -> Option<WithdrawalStatus> {
+        WithdrawalStatus::parse(&self.status)
+    }
+}
+
+/// 确认提现所认领的链上出金事件，同一事件只能对应一笔提现
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementLog {
+    pub tx_hash: String,
+    pub log_index: i64,
+}
+
+/// 申请提现的请求数据
+#[derive(Debug, Deserialize)]
+pub struct CreateWithdrawalRequest {
+    /// 代币地址，缺省为配置的 USDC
+    pub asset: Option<String>,
+    /// 最小单位的整数金额（字符串，避免精度丢失）
+    pub amount: String,
+    /// 链上收款地址
+    pub destination: String,
+}
+
+impl CreateWithdrawalRequest {
+    /// 验证提现请求数据
+    pub fn validate(&self) -> Result<(), String> {
+        if self.amount.is_empty() || !self.amount.chars().all(|c| c.is_ascii_digit()) {
+            return Err("金额必须是正整数（最小单位）".to_string());
+        }
+
+        if !is_valid_eth_address(&self.destination) {
+            return Err("收款地址格式无效".to_string());
+        }
+
+        if let Some(asset) = &self.asset
+            && !is_valid_eth_address(asset)
+        {
+            return Err("代币地址格式无效".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// 拒绝请求数据；审批人取自认证的 API Key
+#[derive(Debug, Deserialize)]
+pub struct RejectWithdrawalRequest {
+    pub reason: String,
+}
+
+/// 提现列表查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct WithdrawalQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WithdrawalStatus::*;
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [PendingApproval, Approved, Submitting, Submitted, Finalized, Rejected, Cancelled, Failed] {
+            assert_eq!(WithdrawalStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(WithdrawalStatus::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(PendingApproval.can_transition_to(Approved));
+        assert!(Approved.can_transition_to(Submitting));
+        assert!(Submitting.can_transition_to(Submitted));
+        assert!(Submitted.can_transition_to(Finalized));
+        assert!(Submitted.can_transition_to(Failed));
+
+        // 已提交上链后不能再取消或拒绝
+        assert!(!Submitted.can_transition_to(Cancelled));
+        assert!(!Submitting.can_transition_to(Rejected));
+        // 终态不能再流转
+        assert!(!Finalized.can_transition_to(Failed));
+        assert!(!Rejected.can_transition_to(Approved));
+        // 未审批不能直接执行
+        assert!(!PendingApproval.can_transition_to(Submitting));
+    }
+}
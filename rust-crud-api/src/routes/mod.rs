@@ -6,6 +6,9 @@ pub fn user_routes() -> Scope {
     web::scope("/api/users")
         .route("", web::post().to(handlers::create_user))
         .route("", web::get().to(handlers::get_all_users))
+        // 必须注册在 /{id} 之前
+        .route("/import", web::post().to(handlers::import_users))
+        .route("/export", web::get().to(handlers::export_users))
         .route("/{id}", web::get().to(handlers::get_user_by_id))
         .route(
             "/username/{username}",
@@ -13,6 +16,73 @@ pub fn user_routes() -> Scope {
         )
         .route("/{id}", web::put().to(handlers::update_user))
         .route("/{id}", web::delete().to(handlers::delete_user))
+        .route("/{id}/wallets/nonce", web::post().to(handlers::create_wallet_nonce))
+        .route("/{id}/wallets", web::post().to(handlers::link_wallet))
+        .route("/{id}/wallets", web::get().to(handlers::list_user_wallets))
+        .route("/{id}/wallets/{address}", web::delete().to(handlers::unlink_wallet))
+        .route("/{id}/deposits", web::get().to(handlers::list_user_deposits))
+        .route("/{id}/balances", web::get().to(handlers::get_user_balances))
+        .route("/{id}/withdrawals", web::post().to(handlers::create_withdrawal))
+        .route("/{id}/withdrawals", web::get().to(handlers::list_user_withdrawals))
+        .route(
+            "/{id}/withdrawals/{withdrawal_id}/cancel",
+            web::post().to(handlers::cancel_withdrawal),
+        )
+        .route(
+            "/{id}/hyperliquid/{address}/positions",
+            web::get().to(handlers::get_hyperliquid_positions),
+        )
+        .route(
+            "/{id}/hyperliquid/{address}/open-orders",
+            web::get().to(handlers::get_hyperliquid_open_orders),
+        )
+        .route("/{id}/hyperliquid/{address}/fills", web::get().to(handlers::get_hyperliquid_fills))
+        .route("/{id}/hyperliquid/{address}/funding", web::get().to(handlers::get_hyperliquid_funding))
+        .route("/{id}/api-keys", web::post().to(handlers::create_api_key))
+        .route("/{id}/api-keys", web::get().to(handlers::list_api_keys))
+        .route("/{id}/api-keys/{key_id}", web::delete().to(handlers::revoke_api_key))
+}
+
+/// 配置入金相关路由
+pub fn deposit_routes() -> Scope {
+    web::scope("/api/deposits")
+        .route("", web::get().to(handlers::list_deposits))
+        .route("/stream", web::get().to(handlers::stream_deposits))
+        .route("/{tx_hash}", web::get().to(handlers::get_deposit_by_tx_hash))
+}
+
+/// 配置账本相关路由
+pub fn ledger_routes() -> Scope {
+    web::scope("/api/ledger")
+        .route("/accounts", web::get().to(handlers::list_account_balances))
+        .route("/accounts/{id}", web::get().to(handlers::get_account_balance))
+        .route("/reconciliations", web::get().to(handlers::list_reconciliations))
+}
+
+/// 配置提现相关路由
+pub fn withdrawal_routes() -> Scope {
+    web::scope("/api/withdrawals")
+        .route("", web::get().to(handlers::list_withdrawals))
+        .route("/{id}", web::get().to(handlers::get_withdrawal))
+        .route("/{id}/approve", web::post().to(handlers::approve_withdrawal))
+        .route("/{id}/reject", web::post().to(handlers::reject_withdrawal))
+}
+
+/// 配置 Webhook 相关路由
+pub fn webhook_routes() -> Scope {
+    web::scope("/api/webhooks")
+        .route("", web::post().to(handlers::create_webhook_endpoint))
+        .route("", web::get().to(handlers::list_webhook_endpoints))
+        .route("/deliveries/{delivery_id}", web::get().to(handlers::get_webhook_delivery))
+        .route(
+            "/deliveries/{delivery_id}/retry",
+            web::post().to(handlers::retry_webhook_delivery),
+        )
+        .route("/{id}", web::get().to(handlers::get_webhook_endpoint))
+        .route("/{id}", web::put().to(handlers::update_webhook_endpoint))
+        .route("/{id}", web::delete().to(handlers::delete_webhook_endpoint))
+        .route("/{id}/rotate-secret", web::post().to(handlers::rotate_webhook_secret))
+        .route("/{id}/deliveries", web::get().to(handlers::list_webhook_deliveries))
 }
 
 /// 配置健康检查路由
@@ -0,0 +1,217 @@
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::api_key::{ApiKey, ApiKeyPrincipal, CreateApiKeyRequest, CreatedApiKey};
+use chrono::Utc;
+use ethers::core::rand::{RngCore, thread_rng};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// API Key 查询字段
+const API_KEY_COLUMNS: &str =
+    "id, user_id, name, prefix, secret_hash, scopes, expires_at, last_used_at, revoked_at, created_at";
+
+/// 完整密钥格式：rca_<8 位十六进制前缀>_<64 位十六进制密钥>
+const KEY_PREFIX: &str = "rca_";
+
+/// last_used_at 的最小更新间隔（秒），避免每个请求都写数据库
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// 生成新密钥，返回 (可见前缀, 完整密钥)
+fn generate_api_key() -> (String, String) {
+    let mut id = [0u8; 4];
+    let mut secret = [0u8; 32];
+    thread_rng().fill_bytes(&mut id);
+    thread_rng().fill_bytes(&mut secret);
+
+    let prefix = format!("{}{}", KEY_PREFIX, hex::encode(id));
+    let key = format!("{}_{}", prefix, hex::encode(secret));
+    (prefix, key)
+}
+
+/// 完整密钥的摘要（数据库中只保存该值）
+fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// 解析密钥中的可见前缀；格式不符时返回 None
+fn parse_prefix(key: &str) -> Option<&str> {
+    let (prefix, secret) = key.rsplit_once('_')?;
+    let id = prefix.strip_prefix(KEY_PREFIX)?;
+    let is_hex = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit());
+    (is_hex(id, 8) && is_hex(secret, 64)).then_some(prefix)
+}
+
+/// Authorization 头中的值是否为 API Key（其余值留给用户认证处理）
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(KEY_PREFIX)
+}
+
+/// 常量时间比较，避免通过响应时间猜测摘要
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// API Key 管理与认证服务
+#[derive(Clone)]
+pub struct ApiKeyService {
+    pool: DatabasePool,
+    /// 可以访问全局接口的用户
+    admin_user_ids: Vec<Uuid>,
+}
+
+impl ApiKeyService {
+    pub fn new(pool: DatabasePool, admin_user_ids: Vec<Uuid>) -> Self {
+        Self { pool, admin_user_ids }
+    }
+
+    /// 为用户创建 API Key，完整密钥只在这里返回一次
+    pub async fn create_key(&self, user_id: Uuid, request: CreateApiKeyRequest) -> Result<CreatedApiKey, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+        self.ensure_user_exists(user_id).await?;
+
+        let (prefix, key) = generate_api_key();
+        let mut scopes = request.scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, secret_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            API_KEY_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(request.name.trim())
+        .bind(&prefix)
+        .bind(hash_api_key(&key))
+        .bind(&scopes)
+        .bind(request.expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    /// 获取用户的全部 API Key（包含已吊销的）
+    pub async fn list_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>, AppError> {
+        self.ensure_user_exists(user_id).await?;
+
+        let keys = sqlx::query_as::<_, ApiKey>(&format!(
+            "SELECT {} FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+            API_KEY_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// 吊销 API Key；重复吊销保留第一次的吊销时间
+    pub async fn revoke_key(&self, user_id: Uuid, key_id: Uuid) -> Result<ApiKey, AppError> {
+        let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+            r#"
+            UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            RETURNING {}
+            "#,
+            API_KEY_COLUMNS
+        ))
+        .bind(key_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("API Key {} 不存在", key_id)))?;
+
+        Ok(api_key)
+    }
+
+    /// 校验完整密钥并记录使用时间；密钥无效、已吊销或已过期时返回 Unauthorized
+    pub async fn authenticate(&self, key: &str) -> Result<ApiKeyPrincipal, AppError> {
+        let invalid = || AppError::Unauthorized("API Key 无效".to_string());
+        let prefix = parse_prefix(key).ok_or_else(invalid)?;
+
+        let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+            "SELECT {} FROM api_keys WHERE prefix = $1",
+            API_KEY_COLUMNS
+        ))
+        .bind(prefix)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(invalid)?;
+
+        if !constant_time_eq(hash_api_key(key).as_bytes(), api_key.secret_hash.as_bytes()) {
+            return Err(invalid());
+        }
+        if api_key.revoked_at.is_some() {
+            return Err(AppError::Unauthorized("API Key 已吊销".to_string()));
+        }
+        if !api_key.is_usable(Utc::now()) {
+            return Err(AppError::Unauthorized("API Key 已过期".to_string()));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE api_keys SET last_used_at = NOW()
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - make_interval(secs => $2))
+            "#,
+        )
+        .bind(api_key.id)
+        .bind(LAST_USED_RESOLUTION_SECS as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(ApiKeyPrincipal {
+            key_id: api_key.id,
+            user_id: api_key.user_id,
+            scopes: api_key.scopes,
+            admin: self.admin_user_ids.contains(&api_key.user_id),
+        })
+    }
+
+    async fn ensure_user_exists(&self, user_id: Uuid) -> Result<(), AppError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !exists {
+            return Err(AppError::NotFound(format!("用户 {} 不存在", user_id)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_key_round_trip() {
+        let (prefix, key) = generate_api_key();
+        assert!(prefix.starts_with("rca_") && prefix.len() == 12);
+        assert!(is_api_key(&key));
+        assert_eq!(parse_prefix(&key), Some(prefix.as_str()));
+        assert_eq!(hash_api_key(&key).len(), 64);
+        assert_ne!(hash_api_key(&key), hash_api_key(&generate_api_key().1));
+    }
+
+    #[test]
+    fn test_parse_prefix_rejects_malformed_keys() {
+        let secret = "a".repeat(64);
+        assert_eq!(parse_prefix(&format!("rca_0011aabb_{}", secret)), Some("rca_0011aabb"));
+        assert_eq!(parse_prefix(&format!("rca_0011aab_{}", secret)), None);
+        assert_eq!(parse_prefix(&format!("rca_0011aabg_{}", secret)), None);
+        assert_eq!(parse_prefix("rca_0011aabb_abc"), None);
+        assert_eq!(parse_prefix(&format!("xyz_0011aabb_{}", secret)), None);
+        assert!(!is_api_key("eyJhbGciOiJIUzI1NiJ9"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
@@ -54,6 +54,14 @@ impl CacheService {
 
     /// 设置缓存数据
     pub async fn set<T>(&self, key: &str, value: &T) -> Result<(), AppError>
+    where
+        T: Serialize + Debug,
+    {
+        self.set_with_ttl(key, value, self.ttl_seconds).await
+    }
+
+    /// 设置缓存数据并指定过期时间（用于需要比默认 TTL 更短的数据）
+    pub async fn set_with_ttl<T>(&self, key: &str, value: &T, ttl_seconds: u64) -> Result<(), AppError>
     where
         T: Serialize + Debug,
     {
@@ -61,9 +69,9 @@ impl CacheService {
         
         match serde_json::to_string(value) {
             Ok(serialized) => {
-                match conn.set_ex::<_, _, ()>(key, serialized, self.ttl_seconds) {
+                match conn.set_ex::<_, _, ()>(key, serialized, ttl_seconds) {
                     Ok(_) => {
-                        log::debug!("💾 缓存设置成功: {}, TTL: {}秒", key, self.ttl_seconds);
+                        log::debug!("💾 缓存设置成功: {}, TTL: {}秒", key, ttl_seconds);
                         Ok(())
                     }
                     Err(e) => {
@@ -0,0 +1,130 @@
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::deposit::{Deposit, DepositQuery};
+use crate::models::PaginatedData;
+use sqlx::{Postgres, QueryBuilder};
+use tokio::sync::broadcast;
+
+/// 实时推送通道容量，订阅者落后超过该数量时会丢弃最旧的事件
+const NOTIFIER_CAPACITY: usize = 1024;
+
+/// 入金查询字段（d 为 vault_deposits，w 为 user_wallets）
+const DEPOSIT_COLUMNS: &str = "d.id, d.tx_hash, d.block_number, d.tx_index, d.sender, d.to_address, \
+    d.amount_wei, d.token_address, d.status, d.created_at, w.user_id";
+
+/// 入金表关联钱包绑定表，用于把入金归属到用户
+const DEPOSIT_FROM: &str = "vault_deposits d LEFT JOIN user_wallets w ON w.address = d.sender";
+
+/// 入金事件广播器：监听器写入新入金后发布，SSE 连接订阅
+#[derive(Clone)]
+pub struct DepositNotifier {
+    sender: broadcast::Sender<Deposit>,
+}
+
+impl DepositNotifier {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(NOTIFIER_CAPACITY);
+        Self { sender }
+    }
+
+    /// 发布一条入金事件（没有订阅者时直接丢弃）
+    pub fn publish(&self, deposit: Deposit) {
+        let _ = self.sender.send(deposit);
+    }
+
+    /// 订阅入金事件
+    pub fn subscribe(&self) -> broadcast::Receiver<Deposit> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for DepositNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 入金服务
+#[derive(Clone)]
+pub struct DepositService {
+    pool: DatabasePool,
+    notifier: DepositNotifier,
+}
+
+impl DepositService {
+    pub fn new(pool: DatabasePool, notifier: DepositNotifier) -> Self {
+        Self { pool, notifier }
+    }
+
+    /// 按条件分页查询入金记录（按区块倒序）
+    pub async fn list_deposits(&self, query: &DepositQuery) -> Result<PaginatedData<Deposit>, AppError> {
+        query.validate().map_err(AppError::ValidationError)?;
+
+        let page = query.page();
+        let page_size = query.page_size();
+
+        let mut count_builder = QueryBuilder::<Postgres>::new(format!("SELECT COUNT(*) FROM {}", DEPOSIT_FROM));
+        push_filters(&mut count_builder, query);
+        let total: i64 = count_builder
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await?;
+
+        let mut builder = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM {}", DEPOSIT_COLUMNS, DEPOSIT_FROM));
+        push_filters(&mut builder, query);
+        builder
+            .push(" ORDER BY d.block_number DESC, d.tx_index DESC NULLS LAST, d.id DESC LIMIT ")
+            .push_bind(page_size)
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = builder
+            .build_query_as::<Deposit>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(PaginatedData {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// 根据交易哈希获取入金记录
+    pub async fn get_deposit_by_tx_hash(&self, tx_hash: &str) -> Result<Option<Deposit>, AppError> {
+        let deposit = sqlx::query_as::<_, Deposit>(
+            &format!("SELECT {} FROM {} WHERE d.tx_hash = $1", DEPOSIT_COLUMNS, DEPOSIT_FROM)
+        )
+        .bind(tx_hash.to_lowercase())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deposit)
+    }
+
+    /// 订阅新入金的实时推送
+    pub fn subscribe(&self) -> broadcast::Receiver<Deposit> {
+        self.notifier.subscribe()
+    }
+}
+
+/// 追加 WHERE 过滤条件
+fn push_filters(builder: &mut QueryBuilder<'_, Postgres>, query: &DepositQuery) {
+    builder.push(" WHERE 1 = 1");
+
+    if let Some(sender) = query.normalized_sender() {
+        builder.push(" AND d.sender = ").push_bind(sender);
+    }
+    if let Some(status) = &query.status {
+        builder.push(" AND d.status = ").push_bind(status.clone());
+    }
+    if let Some(from_block) = query.from_block {
+        builder.push(" AND d.block_number >= ").push_bind(from_block);
+    }
+    if let Some(to_block) = query.to_block {
+        builder.push(" AND d.block_number <= ").push_bind(to_block);
+    }
+    if let Some(user_id) = query.user_id {
+        builder.push(" AND w.user_id = ").push_bind(user_id);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::config::Config;
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::hyperliquid::{
+    HyperliquidAccountState, HyperliquidFill, HyperliquidFunding, HyperliquidFundingQuery, HyperliquidOpenOrder,
+};
+use crate::services::cache::CacheService;
+use crate::utils::is_valid_eth_address;
+use alloy::primitives::Address;
+use hyperliquid_rust_sdk::{BaseUrl, InfoClient};
+use serde::{Serialize, de::DeserializeOwned};
+use std::fmt::Debug;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 各类数据的缓存时间（秒）：持仓与挂单变化快，资金费每小时结算一次
+const POSITIONS_CACHE_TTL_SECS: u64 = 5;
+const OPEN_ORDERS_CACHE_TTL_SECS: u64 = 5;
+const FILLS_CACHE_TTL_SECS: u64 = 15;
+const FUNDING_CACHE_TTL_SECS: u64 = 60;
+
+/// 未指定 start_time 时查询的资金费历史范围
+const DEFAULT_FUNDING_WINDOW_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Hyperliquid API 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HYPERLIQUID_NETWORK 对应的 API 地址
+pub fn hyperliquid_base_url(network: &str) -> anyhow::Result<BaseUrl> {
+    match network {
+        "mainnet" => Ok(BaseUrl::Mainnet),
+        "testnet" => Ok(BaseUrl::Testnet),
+        "localhost" => Ok(BaseUrl::Localhost),
+        other => anyhow::bail!("未知的 HYPERLIQUID_NETWORK: {}", other),
+    }
+}
+
+/// 按 HYPERLIQUID_NETWORK 创建带请求超时的 InfoClient
+pub async fn build_info_client(network: &str) -> anyhow::Result<InfoClient> {
+    let base_url = hyperliquid_base_url(network)?;
+    Ok(InfoClient::builder().base_url(base_url).timeout(REQUEST_TIMEOUT).build()?)
+}
+
+/// 校验并规范化地址，返回 (小写地址, SDK 使用的地址)
+fn parse_address(address: &str) -> Result<(String, Address), AppError> {
+    let address = address.trim().to_lowercase();
+    if !is_valid_eth_address(&address) {
+        return Err(AppError::ValidationError("钱包地址格式无效".to_string()));
+    }
+    let parsed = Address::from_str(&address).map_err(|_| AppError::ValidationError("钱包地址格式无效".to_string()))?;
+    Ok((address, parsed))
+}
+
+fn cache_key(kind: &str, address: &str) -> String {
+    format!("hyperliquid:{}:{}", kind, address)
+}
+
+fn funding_cache_key(address: &str, query: &HyperliquidFundingQuery) -> String {
+    let bound = |t: Option<u64>| t.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string());
+    format!("{}:{}:{}", cache_key("funding", address), bound(query.start_time), bound(query.end_time))
+}
+
+/// Hyperliquid 账户查询服务：只允许查询用户已绑定的钱包，结果按地址短时间缓存
+#[derive(Clone)]
+pub struct HyperliquidService {
+    pool: DatabasePool,
+    info: Arc<InfoClient>,
+    cache: CacheService,
+}
+
+impl HyperliquidService {
+    pub fn new(pool: DatabasePool, info: InfoClient, cache: CacheService) -> Self {
+        Self { pool, info: Arc::new(info), cache }
+    }
+
+    /// 按 HYPERLIQUID_NETWORK 创建 InfoClient
+    pub async fn from_config(pool: DatabasePool, cache: CacheService, config: &Config) -> anyhow::Result<Self> {
+        let info = build_info_client(&config.hyperliquid_network).await?;
+        Ok(Self::new(pool, info, cache))
+    }
+
+    /// 账户概览与持仓
+    pub async fn account_state(&self, user_id: Uuid, address: &str) -> Result<HyperliquidAccountState, AppError> {
+        let (address, parsed) = self.linked_address(user_id, address).await?;
+        self.cached(&cache_key("state", &address), POSITIONS_CACHE_TTL_SECS, async {
+            let state = self.info.user_state(parsed).await?;
+            Ok(HyperliquidAccountState::from_response(&address, state))
+        })
+        .await
+    }
+
+    /// 当前挂单
+    pub async fn open_orders(&self, user_id: Uuid, address: &str) -> Result<Vec<HyperliquidOpenOrder>, AppError> {
+        let (address, parsed) = self.linked_address(user_id, address).await?;
+        self.cached(&cache_key("open_orders", &address), OPEN_ORDERS_CACHE_TTL_SECS, async {
+            let orders = self.info.open_orders(parsed).await?;
+            Ok(orders.into_iter().map(HyperliquidOpenOrder::from).collect())
+        })
+        .await
+    }
+
+    /// 最近的成交记录（上游最多返回 2000 条）
+    pub async fn fills(&self, user_id: Uuid, address: &str) -> Result<Vec<HyperliquidFill>, AppError> {
+        let (address, parsed) = self.linked_address(user_id, address).await?;
+        self.cached(&cache_key("fills", &address), FILLS_CACHE_TTL_SECS, async {
+            let fills = self.info.user_fills(parsed).await?;
+            Ok(fills.into_iter().map(HyperliquidFill::from).collect())
+        })
+        .await
+    }
+
+    /// 资金费历史
+    pub async fn funding_history(
+        &self,
+        user_id: Uuid,
+        address: &str,
+        query: HyperliquidFundingQuery,
+    ) -> Result<Vec<HyperliquidFunding>, AppError> {
+        query.validate().map_err(AppError::ValidationError)?;
+        let (address, parsed) = self.linked_address(user_id, address).await?;
+
+        self.cached(&funding_cache_key(&address, &query), FUNDING_CACHE_TTL_SECS, async {
+            let start_time = query.start_time.unwrap_or_else(|| {
+                (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(DEFAULT_FUNDING_WINDOW_MS)
+            });
+            let history = self.info.user_funding_history(parsed, start_time, query.end_time).await?;
+            Ok(history.into_iter().map(HyperliquidFunding::from).collect())
+        })
+        .await
+    }
+
+    /// 先查缓存，未命中时请求上游并写入缓存
+    async fn cached<T, F>(&self, key: &str, ttl_seconds: u64, fetch: F) -> Result<T, AppError>
+    where
+        T: Serialize + DeserializeOwned + Debug,
+        F: Future<Output = Result<T, AppError>>,
+    {
+        if let Some(value) = self.cache.get::<T>(key).await? {
+            return Ok(value);
+        }
+
+        let value = fetch.await.inspect_err(|e| log::warn!("⚠️ Hyperliquid 查询失败: {}, key: {}", e, key))?;
+        self.cache.set_with_ttl(key, &value, ttl_seconds).await?;
+        Ok(value)
+    }
+
+    /// 地址必须是该用户已绑定的钱包
+    async fn linked_address(&self, user_id: Uuid, address: &str) -> Result<(String, Address), AppError> {
+        let (address, parsed) = parse_address(address)?;
+        let linked: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM user_wallets WHERE user_id = $1 AND address = $2)")
+                .bind(user_id)
+                .bind(&address)
+                .fetch_one(&self.pool)
+                .await?;
+        if !linked {
+            return Err(AppError::NotFound(format!("用户 {} 未绑定钱包 {}", user_id, address)));
+        }
+        Ok((address, parsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address_normalizes_case() {
+        let (address, parsed) = parse_address(" 0xAbCdEf0123456789abcdef0123456789ABCDEF01 ").unwrap();
+        assert_eq!(address, "0xabcdef0123456789abcdef0123456789abcdef01");
+        assert_eq!(parsed, Address::from_str(&address).unwrap());
+        assert!(matches!(parse_address("0x1234"), Err(AppError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_cache_keys() {
+        assert_eq!(cache_key("fills", "0xab"), "hyperliquid:fills:0xab");
+        let query = HyperliquidFundingQuery { start_time: Some(1000), end_time: None };
+        assert_eq!(funding_cache_key("0xab", &query), "hyperliquid:funding:0xab:1000:-");
+    }
+
+    #[test]
+    fn test_upstream_error_mapping() {
+        let rate_limited = hyperliquid_rust_sdk::Error::ClientRequest {
+            status_code: 429,
+            error_code: None,
+            error_message: "too many requests".to_string(),
+            error_data: None,
+        };
+        assert!(matches!(AppError::from(rate_limited), AppError::UpstreamError(_)));
+
+        let rejected = hyperliquid_rust_sdk::Error::ClientRequest {
+            status_code: 422,
+            error_code: None,
+            error_message: "bad user".to_string(),
+            error_data: None,
+        };
+        assert!(matches!(AppError::from(rejected), AppError::BadRequest(_)));
+
+        let server = hyperliquid_rust_sdk::Error::ServerRequest { status_code: 502, error_message: String::new() };
+        assert!(matches!(AppError::from(server), AppError::UpstreamError(_)));
+        assert!(hyperliquid_base_url("devnet").is_err());
+    }
+}
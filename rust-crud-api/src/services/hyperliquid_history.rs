@@ -0,0 +1,282 @@
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::hyperliquid::{HyperliquidFill, HyperliquidFunding};
+use alloy::primitives::Address;
+use chrono::{DateTime, Duration, Utc};
+use hyperliquid_rust_sdk::InfoClient;
+use std::str::FromStr;
+
+/// user_fills 最多返回的成交条数；达到该数量时更早的成交无法通过 REST 补齐
+const USER_FILLS_LIMIT: usize = 2000;
+/// user_funding_history 单次最多返回的条数，达到时继续翻页
+const FUNDING_PAGE_SIZE: usize = 500;
+/// 单次补拉最多翻页次数，避免异常数据导致死循环
+const MAX_FUNDING_PAGES: usize = 100;
+/// 补拉资金费时与上次进度重叠的时长，覆盖上游延迟写入的记录
+const FUNDING_OVERLAP_MINUTES: i64 = 60;
+
+/// 统一的地址格式：0x 开头的小写十六进制（与 user_wallets 一致）
+pub fn address_key(address: &Address) -> String {
+    format!("0x{}", hex::encode(address.as_slice()))
+}
+
+fn millis_to_datetime(ms: u64) -> Result<DateTime<Utc>, AppError> {
+    DateTime::from_timestamp_millis(ms as i64)
+        .ok_or_else(|| AppError::InternalServerError(format!("无效的时间戳: {}", ms)))
+}
+
+/// 本次资金费补拉的起点：上次进度往前重叠一段；没有进度时回溯 lookback
+fn funding_backfill_start(synced_until: Option<DateTime<Utc>>, now: DateTime<Utc>, lookback: Duration) -> DateTime<Utc> {
+    match synced_until {
+        Some(synced_until) => (synced_until - Duration::minutes(FUNDING_OVERLAP_MINUTES)).max(now - lookback),
+        None => now - lookback,
+    }
+}
+
+/// 资金费下一页的起点；返回 None 表示已经翻完
+/// 同一小时的多条资金费共享时间戳，可能被分在两页，因此从本页最后的时间戳重新开始，
+/// 重复的记录由 (address, coin, funded_at) 唯一约束去重；整页都是同一时间戳时只能跳过该时间戳
+fn next_funding_start(start: u64, page: &[u64]) -> Option<u64> {
+    let last = *page.iter().max()?;
+    if page.len() < FUNDING_PAGE_SIZE || last < start {
+        return None;
+    }
+    Some(if last > start { last } else { last + 1 })
+}
+
+/// 单个地址一次补拉的结果
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BackfillStats {
+    pub fills: u64,
+    pub fundings: u64,
+}
+
+/// Hyperliquid 成交与资金费历史的持久化
+#[derive(Clone)]
+pub struct HyperliquidHistoryService {
+    pool: DatabasePool,
+}
+
+impl HyperliquidHistoryService {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// 所有已绑定的钱包地址
+    pub async fn linked_addresses(&self) -> Result<Vec<String>, AppError> {
+        let addresses = sqlx::query_scalar("SELECT DISTINCT address FROM user_wallets ORDER BY address")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(addresses)
+    }
+
+    /// 写入成交，按 (address, tid) 去重，返回新写入的条数
+    pub async fn store_fills(&self, address: &str, fills: &[HyperliquidFill]) -> Result<u64, AppError> {
+        if fills.is_empty() {
+            return Ok(0);
+        }
+
+        let filled_at = fills
+            .iter()
+            .map(|f| millis_to_datetime(f.time))
+            .collect::<Result<Vec<_>, _>>()?;
+        let column = |f: fn(&HyperliquidFill) -> &String| fills.iter().map(f).cloned().collect::<Vec<String>>();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO hyperliquid_fills
+                (address, tid, oid, coin, side, dir, px, size, start_position, closed_pnl, fee, fee_token, crossed, hash, filled_at)
+            SELECT $1, t.tid, t.oid, t.coin, t.side, t.dir, t.px::NUMERIC, t.size::NUMERIC, t.start_position::NUMERIC,
+                   t.closed_pnl::NUMERIC, t.fee::NUMERIC, t.fee_token, t.crossed, t.hash, t.filled_at
+            FROM UNNEST($2::BIGINT[], $3::BIGINT[], $4::TEXT[], $5::TEXT[], $6::TEXT[], $7::TEXT[], $8::TEXT[],
+                        $9::TEXT[], $10::TEXT[], $11::TEXT[], $12::TEXT[], $13::BOOLEAN[], $14::TEXT[], $15::TIMESTAMPTZ[])
+                AS t(tid, oid, coin, side, dir, px, size, start_position, closed_pnl, fee, fee_token, crossed, hash, filled_at)
+            ON CONFLICT (address, tid) DO NOTHING
+            "#,
+        )
+        .bind(address)
+        .bind(fills.iter().map(|f| f.tid as i64).collect::<Vec<_>>())
+        .bind(fills.iter().map(|f| f.oid as i64).collect::<Vec<_>>())
+        .bind(column(|f| &f.coin))
+        .bind(column(|f| &f.side))
+        .bind(column(|f| &f.dir))
+        .bind(column(|f| &f.px))
+        .bind(column(|f| &f.size))
+        .bind(column(|f| &f.start_position))
+        .bind(column(|f| &f.closed_pnl))
+        .bind(column(|f| &f.fee))
+        .bind(column(|f| &f.fee_token))
+        .bind(fills.iter().map(|f| f.crossed).collect::<Vec<_>>())
+        .bind(column(|f| &f.hash))
+        .bind(filled_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 写入资金费，按 (address, coin, funded_at) 去重；已有记录缺少交易哈希时用补拉结果补上
+    /// 返回写入或补全的条数
+    pub async fn store_fundings(&self, address: &str, fundings: &[HyperliquidFunding]) -> Result<u64, AppError> {
+        if fundings.is_empty() {
+            return Ok(0);
+        }
+
+        let funded_at = fundings
+            .iter()
+            .map(|f| millis_to_datetime(f.time))
+            .collect::<Result<Vec<_>, _>>()?;
+        let column =
+            |f: fn(&HyperliquidFunding) -> &String| fundings.iter().map(f).cloned().collect::<Vec<String>>();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO hyperliquid_fundings (address, coin, usdc, size, funding_rate, hash, funded_at)
+            SELECT $1, t.coin, t.usdc::NUMERIC, t.size::NUMERIC, t.funding_rate::NUMERIC, t.hash, t.funded_at
+            FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[], $6::TEXT[], $7::TIMESTAMPTZ[])
+                AS t(coin, usdc, size, funding_rate, hash, funded_at)
+            ON CONFLICT (address, coin, funded_at) DO UPDATE SET hash = EXCLUDED.hash
+            WHERE hyperliquid_fundings.hash IS NULL AND EXCLUDED.hash IS NOT NULL
+            "#,
+        )
+        .bind(address)
+        .bind(column(|f| &f.coin))
+        .bind(column(|f| &f.usdc))
+        .bind(column(|f| &f.size))
+        .bind(column(|f| &f.funding_rate))
+        .bind(fundings.iter().map(|f| f.hash.clone()).collect::<Vec<Option<String>>>())
+        .bind(funded_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 通过 REST 补齐一个地址的成交与资金费，完成后推进资金费进度
+    pub async fn backfill_address(
+        &self,
+        info: &InfoClient,
+        address: &str,
+        lookback: Duration,
+    ) -> Result<BackfillStats, AppError> {
+        let user = Address::from_str(address).map_err(|_| AppError::ValidationError(format!("无效的地址: {}", address)))?;
+        let started_at = Utc::now();
+        let mut stats = BackfillStats::default();
+
+        // 成交：上游只返回最近的成交，全部写入后依赖唯一约束去重
+        let latest_fill: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT MAX(filled_at) FROM hyperliquid_fills WHERE address = $1")
+                .bind(address)
+                .fetch_one(&self.pool)
+                .await?;
+        let fills: Vec<HyperliquidFill> = info.user_fills(user).await?.into_iter().map(HyperliquidFill::from).collect();
+        if fills.len() >= USER_FILLS_LIMIT
+            && let (Some(latest), Some(oldest)) = (latest_fill, fills.iter().map(|f| f.time).min())
+            && millis_to_datetime(oldest)? > latest
+        {
+            log::warn!(
+                "⚠️ {} 的成交超过 {} 条未同步，{} 至 {} 之间的成交无法通过 REST 补齐",
+                address,
+                USER_FILLS_LIMIT,
+                latest,
+                millis_to_datetime(oldest)?
+            );
+        }
+        stats.fills = self.store_fills(address, &fills).await?;
+
+        // 资金费：从上次进度开始按时间翻页
+        let synced_until: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT funding_synced_until FROM hyperliquid_sync_cursors WHERE address = $1")
+                .bind(address)
+                .fetch_optional(&self.pool)
+                .await?;
+        let mut start = funding_backfill_start(synced_until, started_at, lookback).timestamp_millis() as u64;
+        for _ in 0..MAX_FUNDING_PAGES {
+            let page: Vec<HyperliquidFunding> = info
+                .user_funding_history(user, start, None)
+                .await?
+                .into_iter()
+                .map(HyperliquidFunding::from)
+                .collect();
+            stats.fundings += self.store_fundings(address, &page).await?;
+
+            let times: Vec<u64> = page.iter().map(|f| f.time).collect();
+            match next_funding_start(start, &times) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO hyperliquid_sync_cursors (address, funding_synced_until)
+            VALUES ($1, $2)
+            ON CONFLICT (address) DO UPDATE
+            SET funding_synced_until = GREATEST(hyperliquid_sync_cursors.funding_synced_until, EXCLUDED.funding_synced_until),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(address)
+        .bind(started_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_key() {
+        let address = Address::from_str("0xAbCdEf0123456789abcdef0123456789ABCDEF01").unwrap();
+        assert_eq!(address_key(&address), "0xabcdef0123456789abcdef0123456789abcdef01");
+    }
+
+    #[test]
+    fn test_funding_backfill_start() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let lookback = Duration::days(90);
+
+        assert_eq!(funding_backfill_start(None, now, lookback), now - lookback);
+        let synced = now - Duration::hours(5);
+        assert_eq!(funding_backfill_start(Some(synced), now, lookback), synced - Duration::minutes(60));
+        // 进度过旧时不超过回溯范围
+        let stale = now - Duration::days(365);
+        assert_eq!(funding_backfill_start(Some(stale), now, lookback), now - lookback);
+    }
+
+    #[test]
+    fn test_funding_pages_across_shared_timestamp() {
+        // 每小时 3 个币种的资金费，按时间排序；页大小不是 3 的倍数，同一小时的记录会跨页
+        let hour = 3_600_000u64;
+        let rows: Vec<(u64, usize)> = (0..400u64).flat_map(|h| (0..3).map(move |coin| (h * hour, coin))).collect();
+        let fetch = |start: u64| -> Vec<(u64, usize)> {
+            rows.iter().filter(|(time, _)| *time >= start).take(FUNDING_PAGE_SIZE).copied().collect()
+        };
+
+        let mut stored = std::collections::HashSet::new();
+        let mut start = 0;
+        let mut pages = 0;
+        loop {
+            let page = fetch(start);
+            pages += 1;
+            stored.extend(page.iter().copied());
+            let times: Vec<u64> = page.iter().map(|(time, _)| *time).collect();
+            match next_funding_start(start, &times) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+
+        assert_eq!(stored.len(), rows.len());
+        assert!(pages <= MAX_FUNDING_PAGES);
+
+        // 整页都是同一时间戳时跳过该时间戳，避免原地翻页
+        assert_eq!(next_funding_start(5, &[5; FUNDING_PAGE_SIZE]), Some(6));
+        assert_eq!(next_funding_start(5, &[5, 6]), None);
+        assert_eq!(next_funding_start(5, &[]), None);
+    }
+}
@@ -0,0 +1,544 @@
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::ledger::{AccountBalance, AccountKind, AccountQuery, Reconciliation};
+use sqlx::{FromRow, PgConnection, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// 余额查询：资产账户取借方合计，负债账户取贷方合计
+const BALANCE_SELECT: &str = "SELECT a.id AS account_id, a.code, a.kind, a.user_id, a.asset, \
+    ((CASE WHEN a.kind = 'asset' THEN 1 ELSE -1 END) * COALESCE(SUM(p.amount), 0))::TEXT AS balance \
+    FROM ledger_accounts a LEFT JOIN ledger_postings p ON p.account_id = a.id";
+
+/// 对账记录查询字段
+const RECONCILIATION_COLUMNS: &str = "id, asset, block_number, ledger_balance::TEXT AS ledger_balance, \
+    onchain_balance::TEXT AS onchain_balance, difference::TEXT AS difference, matched, created_at";
+
+/// 待记账的入金
+#[derive(Debug, FromRow)]
+struct UnpostedDeposit {
+    tx_hash: String,
+    reinclusions: i32,
+    amount_wei: String,
+    token_address: Option<String>,
+    user_id: Option<Uuid>,
+}
+
+/// 一条分录：amount 为最小单位的整数，借方为正、贷方为负
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posting {
+    pub account_id: i64,
+    pub amount: i128,
+}
+
+/// vault 链上持仓（资产账户）
+pub fn vault_account_code(asset: &str) -> String {
+    format!("vault:{}", asset)
+}
+
+/// 用户余额（负债账户）
+pub fn user_account_code(user_id: &Uuid, asset: &str) -> String {
+    format!("user:{}:{}", user_id, asset)
+}
+
+/// 尚未关联到用户的入金（负债挂账账户）
+pub fn unattributed_account_code(asset: &str) -> String {
+    format!("unattributed:{}", asset)
+}
+
+/// 提现在途冻结资金（负债挂账账户）
+pub fn withdrawal_hold_account_code(asset: &str) -> String {
+    format!("withdrawal_hold:{}", asset)
+}
+
+/// 入金记账凭证的幂等键；入金被链重组丢弃后重新上链时使用新的键，以便重新记账
+pub fn deposit_entry_key(tx_hash: &str, reinclusions: i32) -> String {
+    match reinclusions {
+        0 => format!("deposit:{}", tx_hash),
+        n => format!("deposit:{}:{}", tx_hash, n),
+    }
+}
+
+/// 挂账入金转入用户余额的凭证幂等键，规则同 deposit_entry_key
+pub fn deposit_attribution_key(tx_hash: &str, reinclusions: i32) -> String {
+    match reinclusions {
+        0 => format!("deposit_attribution:{}", tx_hash),
+        n => format!("deposit_attribution:{}:{}", tx_hash, n),
+    }
+}
+
+/// 校验凭证借贷平衡：至少两条分录、金额非零、合计为 0
+pub fn validate_postings(postings: &[Posting]) -> Result<(), String> {
+    if postings.len() < 2 {
+        return Err("凭证至少需要两条分录".to_string());
+    }
+
+    let mut total: i128 = 0;
+    for posting in postings {
+        if posting.amount == 0 {
+            return Err("分录金额不能为0".to_string());
+        }
+        total = total
+            .checked_add(posting.amount)
+            .ok_or_else(|| "分录金额溢出".to_string())?;
+    }
+
+    if total != 0 {
+        return Err(format!("凭证借贷不平衡，差额: {}", total));
+    }
+
+    Ok(())
+}
+
+/// 解析链上金额（十进制整数字符串）
+pub fn parse_amount(raw: &str) -> Result<i128, String> {
+    let amount: i128 = raw
+        .parse()
+        .map_err(|_| format!("金额无法解析为整数: {}", raw))?;
+    if amount <= 0 {
+        return Err(format!("金额必须大于0: {}", raw));
+    }
+    Ok(amount)
+}
+
+/// 复式记账服务
+#[derive(Clone)]
+pub struct LedgerService {
+    pool: DatabasePool,
+}
+
+impl LedgerService {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// 获取或创建账户，返回账户 ID
+    pub async fn ensure_account(
+        conn: &mut PgConnection,
+        code: &str,
+        kind: AccountKind,
+        user_id: Option<Uuid>,
+        asset: &str,
+    ) -> Result<i64, AppError> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO ledger_accounts (code, kind, user_id, asset)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (code) DO UPDATE SET code = EXCLUDED.code
+            RETURNING id
+            "#,
+        )
+        .bind(code)
+        .bind(kind.as_str())
+        .bind(user_id)
+        .bind(asset)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// 写入一张凭证及其分录，必须在调用方的事务中执行
+    /// 返回 (凭证 ID, 是否新写入)；idempotency_key 已存在时不重复记账
+    pub async fn post_entry(
+        conn: &mut PgConnection,
+        idempotency_key: &str,
+        description: &str,
+        postings: &[Posting],
+    ) -> Result<(i64, bool), AppError> {
+        validate_postings(postings).map_err(AppError::ValidationError)?;
+
+        let inserted: Option<i64> = sqlx::query_scalar(
+            r#"
+            INSERT INTO ledger_journal_entries (idempotency_key, description)
+            VALUES ($1, $2)
+            ON CONFLICT (idempotency_key) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(idempotency_key)
+        .bind(description)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some(entry_id) = inserted else {
+            let existing: i64 = sqlx::query_scalar(
+                "SELECT id FROM ledger_journal_entries WHERE idempotency_key = $1"
+            )
+            .bind(idempotency_key)
+            .fetch_one(&mut *conn)
+            .await?;
+            return Ok((existing, false));
+        };
+
+        for posting in postings {
+            sqlx::query(
+                "INSERT INTO ledger_postings (entry_id, account_id, amount) VALUES ($1, $2, $3::NUMERIC)"
+            )
+            .bind(entry_id)
+            .bind(posting.account_id)
+            .bind(posting.amount.to_string())
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok((entry_id, true))
+    }
+
+    /// 为尚未记账的已确认入金记账，返回本次新记账的条数
+    /// 借：vault 资产账户；贷：用户余额账户（未绑定钱包时记入挂账账户）
+    pub async fn post_pending_deposits(&self, limit: i64) -> Result<usize, AppError> {
+        let pending: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM vault_deposits WHERE status = 'confirmed' AND ledger_entry_id IS NULL ORDER BY id LIMIT $1"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut posted = 0;
+        for deposit_id in pending {
+            match self.post_deposit(deposit_id).await {
+                Ok(true) => posted += 1,
+                Ok(false) => {}
+                Err(e) => log::error!("❌ 入金记账失败: deposit_id={}, {}", deposit_id, e),
+            }
+        }
+
+        Ok(posted)
+    }
+
+    /// 为单笔入金记账；行锁 + 凭证幂等键保证每笔入金只记账一次
+    async fn post_deposit(&self, deposit_id: i64) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row: Option<UnpostedDeposit> = sqlx::query_as(
+            r#"
+            SELECT d.tx_hash, d.reinclusions, d.amount_wei, d.token_address, w.user_id
+            FROM vault_deposits d
+            LEFT JOIN user_wallets w ON w.address = d.sender
+            WHERE d.id = $1 AND d.status = 'confirmed' AND d.ledger_entry_id IS NULL
+            FOR UPDATE OF d SKIP LOCKED
+            "#,
+        )
+        .bind(deposit_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // 已被其他实例处理或已记账
+        let Some(UnpostedDeposit { tx_hash, reinclusions, amount_wei, token_address, user_id }) = row else {
+            return Ok(false);
+        };
+
+        let asset = token_address
+            .ok_or_else(|| AppError::ValidationError(format!("入金缺少 token_address: {}", tx_hash)))?;
+        let amount = parse_amount(&amount_wei).map_err(AppError::ValidationError)?;
+
+        let vault_account =
+            Self::ensure_account(&mut tx, &vault_account_code(&asset), AccountKind::Asset, None, &asset).await?;
+        let credit_account = match user_id {
+            Some(user_id) => {
+                Self::ensure_account(
+                    &mut tx,
+                    &user_account_code(&user_id, &asset),
+                    AccountKind::Liability,
+                    Some(user_id),
+                    &asset,
+                )
+                .await?
+            }
+            None => {
+                Self::ensure_account(
+                    &mut tx,
+                    &unattributed_account_code(&asset),
+                    AccountKind::Liability,
+                    None,
+                    &asset,
+                )
+                .await?
+            }
+        };
+
+        let postings = [
+            Posting { account_id: vault_account, amount },
+            Posting { account_id: credit_account, amount: -amount },
+        ];
+        let (entry_id, created) = Self::post_entry(
+            &mut tx,
+            &deposit_entry_key(&tx_hash, reinclusions),
+            &format!("Vault 入金 {}", tx_hash),
+            &postings,
+        )
+        .await?;
+
+        sqlx::query("UPDATE vault_deposits SET ledger_entry_id = $2 WHERE id = $1")
+            .bind(deposit_id)
+            .bind(entry_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        if created {
+            log::info!("📒 入金已记账: tx={}, amount={}, user_id={:?}", tx_hash, amount, user_id);
+        }
+
+        Ok(created)
+    }
+
+    /// 钱包绑定后，把该地址此前记入挂账账户的入金转入用户余额账户，返回本次转入的笔数
+    /// 必须在绑定钱包的事务中调用：先锁住该地址的入金行，与 post_deposit 的行锁互斥，
+    /// 保证绑定前后到达的入金要么已记入挂账（在此转出），要么在绑定提交后直接记给用户
+    pub async fn attribute_deposits(conn: &mut PgConnection, sender: &str, user_id: Uuid) -> Result<usize, AppError> {
+        sqlx::query("SELECT id FROM vault_deposits WHERE sender = $1 FOR UPDATE")
+            .bind(sender)
+            .execute(&mut *conn)
+            .await?;
+
+        // 已被链重组丢弃的入金已冲销，不再转入用户余额
+        let unattributed: Vec<(String, i32, String, String)> = sqlx::query_as(
+            r#"
+            SELECT d.tx_hash, d.reinclusions, d.amount_wei, d.token_address
+            FROM vault_deposits d
+            JOIN ledger_postings p ON p.entry_id = d.ledger_entry_id
+            JOIN ledger_accounts a ON a.id = p.account_id
+            WHERE d.sender = $1 AND d.status = 'confirmed' AND a.code LIKE 'unattributed:%'
+            ORDER BY d.id
+            "#,
+        )
+        .bind(sender)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut attributed = 0;
+        for (tx_hash, reinclusions, amount_wei, asset) in unattributed {
+            let amount = parse_amount(&amount_wei).map_err(AppError::ValidationError)?;
+            let unattributed_account =
+                Self::ensure_account(conn, &unattributed_account_code(&asset), AccountKind::Liability, None, &asset)
+                    .await?;
+            let user_account = Self::ensure_account(
+                conn,
+                &user_account_code(&user_id, &asset),
+                AccountKind::Liability,
+                Some(user_id),
+                &asset,
+            )
+            .await?;
+
+            // 幂等键保证同一笔入金只会被转出挂账一次（解绑后再绑定给他人也不会重复转）
+            let (_, created) = Self::post_entry(
+                conn,
+                &deposit_attribution_key(&tx_hash, reinclusions),
+                &format!("挂账入金归属用户 {}", tx_hash),
+                &[
+                    Posting { account_id: unattributed_account, amount },
+                    Posting { account_id: user_account, amount: -amount },
+                ],
+            )
+            .await?;
+            if created {
+                attributed += 1;
+            }
+        }
+
+        if attributed > 0 {
+            log::info!("📒 挂账入金已归属用户: sender={}, user_id={}, count={}", sender, user_id, attributed);
+        }
+        Ok(attributed)
+    }
+
+    /// 冲销被链重组丢弃的入金：把入金记账凭证与挂账归属凭证逐张反向记账，返回新写入的冲销凭证数
+    /// 必须在把入金标记为 orphaned 的事务中调用；尚未记账的入金没有需要冲销的凭证
+    pub async fn reverse_deposit(conn: &mut PgConnection, tx_hash: &str) -> Result<usize, AppError> {
+        let reinclusions: i32 = sqlx::query_scalar("SELECT reinclusions FROM vault_deposits WHERE tx_hash = $1")
+            .bind(tx_hash)
+            .fetch_optional(&mut *conn)
+            .await?
+            .unwrap_or(0);
+        let rows: Vec<(String, i64, String)> = sqlx::query_as(
+            r#"
+            SELECT e.idempotency_key, p.account_id, p.amount::TEXT
+            FROM ledger_journal_entries e
+            JOIN ledger_postings p ON p.entry_id = e.id
+            WHERE e.idempotency_key IN ($1, $2)
+            ORDER BY e.id, p.id
+            "#,
+        )
+        .bind(deposit_entry_key(tx_hash, reinclusions))
+        .bind(deposit_attribution_key(tx_hash, reinclusions))
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut entries: Vec<(String, Vec<Posting>)> = Vec::new();
+        for (key, account_id, amount) in rows {
+            let amount: i128 = amount
+                .parse()
+                .map_err(|_| AppError::InternalServerError(format!("分录金额无法解析: {}", amount)))?;
+            let posting = Posting { account_id, amount: -amount };
+            match entries.last_mut() {
+                Some((last_key, postings)) if *last_key == key => postings.push(posting),
+                _ => entries.push((key, vec![posting])),
+            }
+        }
+
+        let mut reversed = 0;
+        for (key, postings) in entries {
+            let (_, created) =
+                Self::post_entry(conn, &format!("reorg_reversal:{}", key), &format!("链重组冲销 {}", key), &postings)
+                    .await?;
+            if created {
+                reversed += 1;
+            }
+        }
+
+        if reversed > 0 {
+            log::warn!("📒 已冲销被链重组丢弃的入金: tx={}, entries={}", tx_hash, reversed);
+        }
+        Ok(reversed)
+    }
+
+    /// 查询账户余额列表
+    pub async fn list_balances(&self, query: &AccountQuery) -> Result<Vec<AccountBalance>, AppError> {
+        let mut builder = QueryBuilder::<Postgres>::new(BALANCE_SELECT);
+        builder.push(" WHERE 1 = 1");
+        if let Some(kind) = &query.kind {
+            builder.push(" AND a.kind = ").push_bind(kind.clone());
+        }
+        if let Some(asset) = &query.asset {
+            builder.push(" AND a.asset = ").push_bind(asset.to_lowercase());
+        }
+        builder.push(" GROUP BY a.id ORDER BY a.code");
+
+        let balances = builder
+            .build_query_as::<AccountBalance>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(balances)
+    }
+
+    /// 查询单个账户余额
+    pub async fn get_balance(&self, account_id: i64) -> Result<Option<AccountBalance>, AppError> {
+        let balance = sqlx::query_as::<_, AccountBalance>(
+            &format!("{} WHERE a.id = $1 GROUP BY a.id", BALANCE_SELECT)
+        )
+        .bind(account_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(balance)
+    }
+
+    /// 查询用户的各资产余额
+    pub async fn user_balances(&self, user_id: Uuid) -> Result<Vec<AccountBalance>, AppError> {
+        let balances = sqlx::query_as::<_, AccountBalance>(
+            &format!("{} WHERE a.user_id = $1 GROUP BY a.id ORDER BY a.asset", BALANCE_SELECT)
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(balances)
+    }
+
+    /// 账本中 vault 资产账户的余额（未建账时为 0）
+    pub async fn vault_balance(&self, asset: &str) -> Result<i128, AppError> {
+        let balance: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(p.amount), 0)::TEXT
+            FROM ledger_accounts a LEFT JOIN ledger_postings p ON p.account_id = a.id
+            WHERE a.code = $1
+            GROUP BY a.id
+            "#,
+        )
+        .bind(vault_account_code(asset))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match balance {
+            Some(balance) => balance
+                .parse()
+                .map_err(|_| AppError::InternalServerError(format!("账本余额无法解析: {}", balance))),
+            None => Ok(0),
+        }
+    }
+
+    /// 保存一次对账结果，差额为链上余额减去账本余额
+    pub async fn record_reconciliation(
+        &self,
+        asset: &str,
+        block_number: i64,
+        ledger_balance: i128,
+        onchain_balance: i128,
+    ) -> Result<Reconciliation, AppError> {
+        let difference = onchain_balance - ledger_balance;
+        let reconciliation = sqlx::query_as::<_, Reconciliation>(&format!(
+            r#"
+            INSERT INTO ledger_reconciliations (asset, block_number, ledger_balance, onchain_balance, difference, matched)
+            VALUES ($1, $2, $3::NUMERIC, $4::NUMERIC, $5::NUMERIC, $6)
+            RETURNING {}
+            "#,
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(asset)
+        .bind(block_number)
+        .bind(ledger_balance.to_string())
+        .bind(onchain_balance.to_string())
+        .bind(difference.to_string())
+        .bind(difference == 0)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(reconciliation)
+    }
+
+    /// 查询最近的对账记录
+    pub async fn list_reconciliations(&self, limit: i64) -> Result<Vec<Reconciliation>, AppError> {
+        let reconciliations = sqlx::query_as::<_, Reconciliation>(&format!(
+            "SELECT {} FROM ledger_reconciliations ORDER BY id DESC LIMIT $1",
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reconciliations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_postings() {
+        let p = |account_id, amount| Posting { account_id, amount };
+
+        assert!(validate_postings(&[p(1, 100), p(2, -100)]).is_ok());
+        assert!(validate_postings(&[p(1, 100), p(2, -60), p(3, -40)]).is_ok());
+        assert!(validate_postings(&[p(1, 100)]).is_err()); // 单条分录
+        assert!(validate_postings(&[p(1, 100), p(2, -99)]).is_err()); // 不平衡
+        assert!(validate_postings(&[p(1, 0), p(2, 0)]).is_err()); // 零金额
+        assert!(validate_postings(&[p(1, i128::MAX), p(2, 1), p(3, i128::MIN)]).is_err()); // 溢出
+    }
+
+    #[test]
+    fn test_deposit_keys_change_on_reinclusion() {
+        assert_eq!(deposit_entry_key("0xabc", 0), "deposit:0xabc");
+        assert_eq!(deposit_entry_key("0xabc", 1), "deposit:0xabc:1");
+        assert_eq!(deposit_attribution_key("0xabc", 0), "deposit_attribution:0xabc");
+        assert_eq!(deposit_attribution_key("0xabc", 2), "deposit_attribution:0xabc:2");
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1000000"), Ok(1_000_000));
+        assert_eq!(
+            parse_amount("115792089237316195423570985008687907853"),
+            Ok(115792089237316195423570985008687907853)
+        );
+        assert!(parse_amount("0").is_err());
+        assert!(parse_amount("-5").is_err());
+        assert!(parse_amount("1.5").is_err());
+        // 超出 i128 范围的 uint256 无法记账
+        assert!(parse_amount(&"9".repeat(60)).is_err());
+    }
+}
@@ -1,4 +1,23 @@
+pub mod api_key;
 pub mod cache;
+pub mod deposit;
+pub mod hyperliquid;
+pub mod hyperliquid_history;
+pub mod ledger;
 pub mod user;
+pub mod user_bulk;
+pub mod wallet;
+pub mod webhook;
+pub mod withdrawal;
+pub mod withdrawal_executor;
 
-pub use user::UserService;
\ No newline at end of file
+pub use api_key::ApiKeyService;
+pub use deposit::{DepositNotifier, DepositService};
+pub use hyperliquid::HyperliquidService;
+pub use hyperliquid_history::HyperliquidHistoryService;
+pub use ledger::LedgerService;
+pub use user::UserService;
+pub use wallet::{SiweSettings, WalletService};
+pub use webhook::{WebhookService, WebhookSettings};
+pub use withdrawal::{WithdrawalPolicy, WithdrawalService};
+pub use withdrawal_executor::{ExecutionStatus, SubmitError, SubmittedWithdrawal, WithdrawalExecutor};
@@ -1,8 +1,15 @@
 use crate::database::DatabasePool;
 use crate::errors::AppError;
-use crate::models::user::{CreateUserRequest, UpdateUserRequest, User};
+use crate::models::user::{CreateUserRequest, UpdateUserRequest, User, UserImportReport, UserResponse};
+use crate::models::webhook::{EVENT_USER_CREATED, EVENT_USER_DELETED, EVENT_USER_UPDATED};
 use crate::services::cache::CacheService;
+use crate::services::user_bulk::screen_rows;
+use crate::services::webhook::WebhookService;
+use crate::utils::IfMatch;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
 use sqlx::Row;
+use std::collections::HashSet;
 use uuid::Uuid;
 
 /// 用户服务
@@ -22,7 +29,7 @@ impl UserService {
         // 验证输入数据
         request
             .validate()
-            .map_err(|e| AppError::ValidationError(e))?;
+            .map_err(AppError::ValidationError)?;
 
         // 检查用户名是否已存在
         if self.username_exists(&request.username).await? {
@@ -37,20 +44,23 @@ impl UserService {
         // 加密密码
         let password_hash = bcrypt::hash(&request.password, bcrypt::DEFAULT_COST)?;
 
-        // 插入用户数据
+        // 插入用户数据（与 webhook 事件同一事务提交）
+        let mut tx = self.pool.begin().await?;
         let user = sqlx::query_as::<_, User>(
             r#"
             INSERT INTO users (username, email, password_hash, full_name)
             VALUES ($1, $2, $3, $4)
-            RETURNING id, username, email, password_hash, full_name, created_at, updated_at
+            RETURNING id, username, email, password_hash, full_name, version, created_at, updated_at
             "#,
         )
         .bind(&request.username)
         .bind(&request.email)
         .bind(&password_hash)
         .bind(&request.full_name)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
+        WebhookService::enqueue(&mut *tx, EVENT_USER_CREATED, &UserResponse::from(user.clone())).await?;
+        tx.commit().await?;
 
         // 清除所有用户列表缓存
         self.cache.delete(&CacheService::all_users_cache_key()).await?;
@@ -70,7 +80,7 @@ impl UserService {
 
         // 缓存未命中，从数据库查询
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, username, email, password_hash, full_name, created_at, updated_at FROM users WHERE id = $1"
+            "SELECT id, username, email, password_hash, full_name, version, created_at, updated_at FROM users WHERE id = $1"
         )
         .bind(user_id)
         .fetch_optional(&self.pool)
@@ -97,7 +107,7 @@ impl UserService {
 
         // 缓存未命中，从数据库查询
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, username, email, password_hash, full_name, created_at, updated_at FROM users WHERE username = $1"
+            "SELECT id, username, email, password_hash, full_name, version, created_at, updated_at FROM users WHERE username = $1"
         )
         .bind(username)
         .fetch_optional(&self.pool)
@@ -126,7 +136,7 @@ impl UserService {
 
         // 缓存未命中，从数据库查询
         let users = sqlx::query_as::<_, User>(
-            "SELECT id, username, email, password_hash, full_name, created_at, updated_at FROM users ORDER BY created_at DESC"
+            "SELECT id, username, email, password_hash, full_name, version, created_at, updated_at FROM users ORDER BY created_at DESC"
         )
         .fetch_all(&self.pool)
         .await?;
@@ -138,16 +148,17 @@ impl UserService {
         Ok(users)
     }
 
-    /// 更新用户信息
+    /// 更新用户信息；携带 If-Match 时只有版本一致才会写入（乐观并发控制）
     pub async fn update_user(
         &self,
         user_id: Uuid,
         request: UpdateUserRequest,
+        if_match: Option<&IfMatch>,
     ) -> Result<User, AppError> {
         // 验证输入数据
         request
             .validate()
-            .map_err(|e| AppError::ValidationError(e))?;
+            .map_err(AppError::ValidationError)?;
 
         // 检查用户是否存在
         let mut user = self
@@ -172,21 +183,26 @@ impl UserService {
             user.password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST)?;
         }
 
-        // 执行更新
+        // 执行更新（与 webhook 事件同一事务提交）
+        let mut tx = self.pool.begin().await?;
         let updated_user = sqlx::query_as::<_, User>(
             r#"
             UPDATE users 
             SET email = $2, full_name = $3, password_hash = $4, updated_at = NOW()
-            WHERE id = $1
-            RETURNING id, username, email, password_hash, full_name, created_at, updated_at
+            WHERE id = $1 AND ($5::BIGINT[] IS NULL OR version = ANY($5))
+            RETURNING id, username, email, password_hash, full_name, version, created_at, updated_at
             "#,
         )
         .bind(user_id)
         .bind(&user.email)
         .bind(&user.full_name)
         .bind(&user.password_hash)
-        .fetch_one(&self.pool)
-        .await?;
+        .bind(if_match.and_then(IfMatch::versions))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(Self::version_mismatch)?;
+        WebhookService::enqueue(&mut *tx, EVENT_USER_UPDATED, &UserResponse::from(updated_user.clone())).await?;
+        tx.commit().await?;
 
         // 清除相关缓存
         self.cache.invalidate_user_cache(&updated_user.id, &updated_user.username).await?;
@@ -194,18 +210,44 @@ impl UserService {
         Ok(updated_user)
     }
 
-    /// 删除用户
-    pub async fn delete_user(&self, user_id: Uuid) -> Result<bool, AppError> {
+    /// 删除用户；携带 If-Match 时只有版本一致才会删除
+    pub async fn delete_user(&self, user_id: Uuid, if_match: Option<&IfMatch>) -> Result<bool, AppError> {
         // 先获取用户信息以获得用户名（用于清除缓存）
         let user = self.get_user_by_id(user_id).await?;
-        
-        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query("DELETE FROM users WHERE id = $1 AND ($2::BIGINT[] IS NULL OR version = ANY($2))")
             .bind(user_id)
-            .execute(&self.pool)
-            .await?;
+            .bind(if_match.and_then(IfMatch::versions))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                // 用户已有账本账户时不允许删除，避免账本失去归属
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    AppError::Conflict("用户存在账本记录，无法删除".to_string())
+                }
+                e => AppError::from(e),
+            })?;
 
         let deleted = result.rows_affected() > 0;
-        
+        if !deleted && if_match.is_some() {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if exists {
+                return Err(Self::version_mismatch());
+            }
+        }
+        if deleted {
+            let payload = serde_json::json!({
+                "id": user_id,
+                "username": user.as_ref().map(|u| u.username.clone()),
+            });
+            WebhookService::enqueue(&mut *tx, EVENT_USER_DELETED, &payload).await?;
+        }
+        tx.commit().await?;
+
         // 如果删除成功且找到了用户，清除相关缓存
         if deleted {
             if let Some(user) = user {
@@ -225,6 +267,100 @@ impl UserService {
         Ok(deleted)
     }
 
+    /// 批量导入一批用户：校验、并行加密密码，再用一条多行 INSERT 写入
+    /// 用户名或邮箱已存在的行记为失败，不影响同批其他行；结果累加到 report
+    pub async fn import_users_batch(
+        &self,
+        rows: Vec<(usize, CreateUserRequest)>,
+        report: &mut UserImportReport,
+    ) -> Result<(), AppError> {
+        report.total += rows.len();
+        let rows = screen_rows(rows, report);
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        // bcrypt 是 CPU 密集操作，放到阻塞线程池中并行执行
+        let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let hashes: Vec<String> = stream::iter(rows.iter().map(|(_, r)| r.password.clone()))
+            .map(|password| tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST)))
+            .buffered(parallelism)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|joined| match joined {
+                Ok(hash) => hash.map_err(AppError::from),
+                Err(e) => Err(AppError::InternalServerError(format!("密码加密任务失败: {}", e))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let usernames: Vec<&str> = rows.iter().map(|(_, r)| r.username.as_str()).collect();
+        let emails: Vec<&str> = rows.iter().map(|(_, r)| r.email.as_str()).collect();
+        let full_names: Vec<&str> = rows.iter().map(|(_, r)| r.full_name.as_str()).collect();
+
+        // 插入用户数据（与 webhook 事件同一事务提交）
+        let mut tx = self.pool.begin().await?;
+        let created = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (username, email, password_hash, full_name)
+            SELECT * FROM UNNEST($1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[], $4::VARCHAR[])
+            ON CONFLICT DO NOTHING
+            RETURNING id, username, email, password_hash, full_name, version, created_at, updated_at
+            "#,
+        )
+        .bind(&usernames)
+        .bind(&emails)
+        .bind(&hashes)
+        .bind(&full_names)
+        .fetch_all(&mut *tx)
+        .await?;
+        for user in &created {
+            WebhookService::enqueue(&mut *tx, EVENT_USER_CREATED, &UserResponse::from(user.clone())).await?;
+        }
+        tx.commit().await?;
+
+        let created_names: HashSet<&str> = created.iter().map(|u| u.username.as_str()).collect();
+        for (line, request) in &rows {
+            if !created_names.contains(request.username.as_str()) {
+                report.reject(*line, Some(request.username.clone()), "用户名或邮箱已存在");
+            }
+        }
+        report.created += created.len();
+
+        // 清除所有用户列表缓存
+        self.cache.delete(&CacheService::all_users_cache_key()).await?;
+
+        Ok(())
+    }
+
+    /// 按创建时间顺序分页读取用户（键集分页），用于流式导出
+    pub async fn export_users_page(
+        &self,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<User>, AppError> {
+        let (after_created, after_id) = after.unzip();
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, username, email, password_hash, full_name, version, created_at, updated_at
+            FROM users
+            WHERE $1::TIMESTAMPTZ IS NULL OR (created_at, id) > ($1, $2)
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+        )
+        .bind(after_created)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    fn version_mismatch() -> AppError {
+        AppError::PreconditionFailed("用户已被其他请求修改，请重新获取最新版本后再试".to_string())
+    }
+
     /// 检查用户名是否存在
     async fn username_exists(&self, username: &str) -> Result<bool, AppError> {
         let row = sqlx::query("SELECT COUNT(*) as count FROM users WHERE username = $1")
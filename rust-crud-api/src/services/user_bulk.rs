@@ -0,0 +1,275 @@
+use crate::errors::AppError;
+use crate::models::user::{CreateUserRequest, UserBulkFormat, UserImportReport, UserResponse};
+use std::collections::HashSet;
+
+/// 每批写入数据库的行数
+pub const IMPORT_BATCH_SIZE: usize = 500;
+/// 导出时每次从数据库读取的行数
+pub const EXPORT_PAGE_SIZE: i64 = 1000;
+/// CSV 导出的列
+pub const EXPORT_COLUMNS: [&str; 6] = ["id", "username", "email", "full_name", "created_at", "updated_at"];
+
+const REQUIRED_COLUMNS: [&str; 4] = ["username", "email", "password", "full_name"];
+
+/// 把分块到达的请求体切分为行（兼容 \r\n），行可以跨越多个分块
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// 追加一个分块，返回其中所有完整的行
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.pending.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// 请求体结束时取出最后一行（没有换行结尾的情况）
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        let mut line = std::mem::take(&mut self.pending);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        (!line.is_empty()).then_some(line)
+    }
+}
+
+/// 逐行解析导入数据；CSV 的第一行为表头，列顺序任意，多余的列会被忽略
+/// 注意：CSV 字段中不支持换行
+pub struct RowParser {
+    format: UserBulkFormat,
+    /// 必填列在表头中的位置
+    columns: Option<[usize; 4]>,
+}
+
+impl RowParser {
+    pub fn new(format: UserBulkFormat) -> Self {
+        Self { format, columns: None }
+    }
+
+    /// 是否还在等待 CSV 表头
+    pub fn needs_header(&self) -> bool {
+        self.format == UserBulkFormat::Csv && self.columns.is_none()
+    }
+
+    /// 解析 CSV 表头；缺少必填列时整个请求无效
+    pub fn parse_header(&mut self, line: &str) -> Result<(), AppError> {
+        let record = parse_csv_record(line.trim_start_matches('\u{feff}')).map_err(AppError::BadRequest)?;
+        let headers: Vec<String> = record.iter().map(|h| h.trim().to_ascii_lowercase()).collect();
+
+        let mut columns = [0; 4];
+        for (i, name) in REQUIRED_COLUMNS.iter().enumerate() {
+            columns[i] = headers
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| AppError::BadRequest(format!("CSV 表头缺少列: {}", name)))?;
+        }
+        self.columns = Some(columns);
+        Ok(())
+    }
+
+    /// 解析一行数据
+    pub fn parse_row(&self, line: &str) -> Result<CreateUserRequest, String> {
+        match self.format {
+            UserBulkFormat::Ndjson => serde_json::from_str(line).map_err(|e| format!("JSON 格式错误: {}", e)),
+            UserBulkFormat::Csv => {
+                let columns = self.columns.ok_or_else(|| "缺少 CSV 表头".to_string())?;
+                let record = parse_csv_record(line)?;
+                let field = |i: usize| record.get(columns[i]).unwrap_or_default().trim().to_string();
+                if columns.iter().any(|c| *c >= record.len()) {
+                    return Err(format!("列数不足：需要至少 {} 列", columns.iter().max().unwrap_or(&0) + 1));
+                }
+                Ok(CreateUserRequest {
+                    username: field(0),
+                    email: field(1),
+                    password: field(2),
+                    full_name: field(3),
+                })
+            }
+        }
+    }
+}
+
+fn parse_csv_record(line: &str) -> Result<csv::StringRecord, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(line.as_bytes());
+    match reader.records().next() {
+        Some(record) => record.map_err(|e| format!("CSV 格式错误: {}", e)),
+        None => Ok(csv::StringRecord::new()),
+    }
+}
+
+/// 批次预检：按 CreateUserRequest 规则校验，并剔除与同批前面行重复的用户名或邮箱
+/// 失败的行写入 report，返回可以写入数据库的行
+pub fn screen_rows(rows: Vec<(usize, CreateUserRequest)>, report: &mut UserImportReport) -> Vec<(usize, CreateUserRequest)> {
+    let mut usernames = HashSet::new();
+    let mut emails = HashSet::new();
+    let mut valid = Vec::with_capacity(rows.len());
+
+    for (line, request) in rows {
+        if let Err(e) = request.validate() {
+            report.reject(line, Some(request.username), e);
+            continue;
+        }
+        if !usernames.insert(request.username.clone()) {
+            report.reject(line, Some(request.username), "用户名在导入数据中重复");
+            continue;
+        }
+        if !emails.insert(request.email.clone()) {
+            report.reject(line, Some(request.username), "邮箱在导入数据中重复");
+            continue;
+        }
+        valid.push((line, request));
+    }
+
+    valid
+}
+
+/// 导出文件的开头（CSV 表头；NDJSON 没有表头）
+pub fn export_header(format: UserBulkFormat) -> String {
+    match format {
+        UserBulkFormat::Csv => format!("{}\n", EXPORT_COLUMNS.join(",")),
+        UserBulkFormat::Ndjson => String::new(),
+    }
+}
+
+/// 导出一行
+pub fn export_line(format: UserBulkFormat, user: &UserResponse) -> Result<String, AppError> {
+    match format {
+        UserBulkFormat::Ndjson => {
+            let json = serde_json::to_string(user).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            Ok(format!("{}\n", json))
+        }
+        UserBulkFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record([
+                    user.id.to_string(),
+                    user.username.clone(),
+                    user.email.clone(),
+                    user.full_name.clone(),
+                    user.created_at.to_rfc3339(),
+                    user.updated_at.to_rfc3339(),
+                ])
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            let bytes = writer.into_inner().map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            String::from_utf8(bytes).map_err(|e| AppError::InternalServerError(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn request(username: &str, email: &str) -> CreateUserRequest {
+        CreateUserRequest {
+            username: username.to_string(),
+            email: email.to_string(),
+            password: "password123".to_string(),
+            full_name: "Test User".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_line_buffer_splits_across_chunks() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"user").is_empty());
+        assert_eq!(buffer.push(b"name,email\r\nal"), vec![b"username,email".to_vec()]);
+        assert_eq!(buffer.push(b"ice,a@x.io\n\n"), vec![b"alice,a@x.io".to_vec(), Vec::new()]);
+        assert_eq!(buffer.push(b"bob,b@x.io"), Vec::<Vec<u8>>::new());
+        assert_eq!(buffer.finish(), Some(b"bob,b@x.io".to_vec()));
+        assert_eq!(buffer.finish(), None);
+    }
+
+    #[test]
+    fn test_csv_rows_follow_header_order() {
+        let mut parser = RowParser::new(UserBulkFormat::Csv);
+        assert!(parser.needs_header());
+        parser.parse_header("\u{feff}full_name,Email,username,password,team").unwrap();
+        assert!(!parser.needs_header());
+
+        let row = parser.parse_row(r#""Doe, John",john@example.com,johndoe,secret123,ops"#).unwrap();
+        assert_eq!(row.username, "johndoe");
+        assert_eq!(row.email, "john@example.com");
+        assert_eq!(row.password, "secret123");
+        assert_eq!(row.full_name, "Doe, John");
+
+        assert!(parser.parse_row("only,two").is_err());
+    }
+
+    #[test]
+    fn test_csv_header_requires_columns() {
+        let mut parser = RowParser::new(UserBulkFormat::Csv);
+        assert!(matches!(parser.parse_header("username,email,full_name"), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_ndjson_rows() {
+        let parser = RowParser::new(UserBulkFormat::Ndjson);
+        assert!(!parser.needs_header());
+        let row = parser
+            .parse_row(r#"{"username":"alice","email":"a@x.io","password":"password1","full_name":"Alice"}"#)
+            .unwrap();
+        assert_eq!(row.username, "alice");
+        assert!(parser.parse_row(r#"{"username":"bob"}"#).is_err());
+        assert!(parser.parse_row("not json").is_err());
+    }
+
+    #[test]
+    fn test_screen_rows() {
+        let mut report = UserImportReport::default();
+        let rows = vec![
+            (2, request("alice", "alice@x.io")),
+            (3, request("al", "short@x.io")),
+            (4, request("alice", "other@x.io")),
+            (5, request("bob", "alice@x.io")),
+            (6, request("carol", "carol@x.io")),
+        ];
+
+        let valid = screen_rows(rows, &mut report);
+        let lines: Vec<usize> = valid.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![2, 6]);
+        assert_eq!(report.failed, 3);
+        let failed: Vec<usize> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(failed, vec![3, 4, 5]);
+        assert_eq!(report.errors[0].error, "用户名至少需要3个字符");
+    }
+
+    #[test]
+    fn test_export_line() {
+        let now = Utc::now();
+        let user = UserResponse {
+            id: Uuid::nil(),
+            username: "johndoe".to_string(),
+            email: "john@example.com".to_string(),
+            full_name: "Doe, John".to_string(),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        };
+
+        assert_eq!(export_header(UserBulkFormat::Csv), "id,username,email,full_name,created_at,updated_at\n");
+        let csv_line = export_line(UserBulkFormat::Csv, &user).unwrap();
+        assert!(csv_line.starts_with(&format!("{},johndoe,john@example.com,\"Doe, John\",", Uuid::nil())));
+        assert!(csv_line.ends_with('\n'));
+
+        let json_line = export_line(UserBulkFormat::Ndjson, &user).unwrap();
+        let value: serde_json::Value = serde_json::from_str(json_line.trim_end()).unwrap();
+        assert_eq!(value["username"], "johndoe");
+        assert!(value.get("password_hash").is_none());
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::Config;
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::wallet::{
+    LinkWalletRequest, UserWallet, WalletLinkNonce, WalletNonceRequest, WalletNonceResponse,
+};
+use crate::services::LedgerService;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use ethers::types::{Address, Signature};
+use ethers::utils::to_checksum;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Sign-In with Ethereum (EIP-4361) 消息参数
+#[derive(Debug, Clone)]
+pub struct SiweSettings {
+    pub domain: String,
+    pub uri: String,
+    pub chain_id: u64,
+    pub nonce_ttl: Duration,
+}
+
+impl SiweSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            domain: config.siwe_domain.clone(),
+            uri: config.siwe_uri.clone(),
+            chain_id: config.siwe_chain_id,
+            nonce_ttl: Duration::seconds(config.siwe_nonce_ttl_seconds as i64),
+        }
+    }
+}
+
+/// 钱包绑定服务
+#[derive(Clone)]
+pub struct WalletService {
+    pool: DatabasePool,
+    siwe: SiweSettings,
+}
+
+impl WalletService {
+    pub fn new(pool: DatabasePool, siwe: SiweSettings) -> Self {
+        Self { pool, siwe }
+    }
+
+    /// 为用户生成一次性的绑定挑战（nonce + 待签名消息）
+    pub async fn create_link_nonce(
+        &self,
+        user_id: Uuid,
+        request: WalletNonceRequest,
+    ) -> Result<WalletNonceResponse, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+        self.ensure_user_exists(user_id).await?;
+
+        let address: Address = request
+            .address
+            .parse()
+            .map_err(|_| AppError::ValidationError("钱包地址格式无效".to_string()))?;
+        let address_lower = format!("0x{:x}", address);
+
+        // 已被其他用户绑定的地址不再下发挑战
+        if let Some(owner) = self.wallet_owner(&address_lower).await?
+            && owner != user_id
+        {
+            return Err(AppError::Conflict("该钱包地址已绑定到其他用户".to_string()));
+        }
+
+        let nonce = Uuid::new_v4().simple().to_string();
+        let issued_at = Utc::now();
+        let expires_at = issued_at + self.siwe.nonce_ttl;
+        let message = build_siwe_message(&self.siwe, &to_checksum(&address, None), &nonce, issued_at, expires_at);
+
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_link_nonces (nonce, user_id, address, message, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(&nonce)
+        .bind(user_id)
+        .bind(&address_lower)
+        .bind(&message)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(WalletNonceResponse {
+            address: address_lower,
+            nonce,
+            message,
+            expires_at,
+        })
+    }
+
+    /// 校验签名并绑定钱包；nonce 只能成功使用一次
+    pub async fn link_wallet(&self, user_id: Uuid, request: LinkWalletRequest) -> Result<UserWallet, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let challenge = sqlx::query_as::<_, WalletLinkNonce>(
+            r#"
+            SELECT nonce, user_id, address, message, expires_at, used_at
+            FROM wallet_link_nonces
+            WHERE nonce = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(&request.nonce)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("nonce 无效".to_string()))?;
+
+        if challenge.used_at.is_some() {
+            return Err(AppError::BadRequest("nonce 已被使用".to_string()));
+        }
+        if challenge.expires_at < Utc::now() {
+            return Err(AppError::BadRequest("nonce 已过期".to_string()));
+        }
+
+        let signer = recover_signer(&challenge.message, &request.signature)?;
+        if signer != challenge.address {
+            return Err(AppError::Unauthorized("签名与钱包地址不匹配".to_string()));
+        }
+
+        sqlx::query("UPDATE wallet_link_nonces SET used_at = NOW() WHERE nonce = $1")
+            .bind(&challenge.nonce)
+            .execute(&mut *tx)
+            .await?;
+
+        let inserted = sqlx::query_as::<_, UserWallet>(
+            r#"
+            INSERT INTO user_wallets (user_id, address)
+            VALUES ($1, $2)
+            ON CONFLICT (address) DO NOTHING
+            RETURNING id, user_id, address, linked_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(&challenge.address)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let wallet = match inserted {
+            Some(wallet) => {
+                // 绑定前从该地址收到的入金记在挂账账户，随绑定一起转入用户余额
+                LedgerService::attribute_deposits(&mut tx, &wallet.address, user_id).await?;
+                wallet
+            }
+            None => {
+                // 地址已绑定：同一用户重复绑定视为成功，其他用户则冲突
+                let existing = sqlx::query_as::<_, UserWallet>(
+                    "SELECT id, user_id, address, linked_at FROM user_wallets WHERE address = $1"
+                )
+                .bind(&challenge.address)
+                .fetch_one(&mut *tx)
+                .await?;
+                if existing.user_id != user_id {
+                    return Err(AppError::Conflict("该钱包地址已绑定到其他用户".to_string()));
+                }
+                existing
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(wallet)
+    }
+
+    /// 获取用户绑定的所有钱包
+    pub async fn list_wallets(&self, user_id: Uuid) -> Result<Vec<UserWallet>, AppError> {
+        self.ensure_user_exists(user_id).await?;
+
+        let wallets = sqlx::query_as::<_, UserWallet>(
+            "SELECT id, user_id, address, linked_at FROM user_wallets WHERE user_id = $1 ORDER BY linked_at"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(wallets)
+    }
+
+    /// 解绑钱包
+    pub async fn unlink_wallet(&self, user_id: Uuid, address: &str) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM user_wallets WHERE user_id = $1 AND address = $2")
+            .bind(user_id)
+            .bind(address.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 检查用户是否存在，不存在时返回 NotFound
+    pub async fn ensure_user_exists(&self, user_id: Uuid) -> Result<(), AppError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        if exists {
+            Ok(())
+        } else {
+            Err(AppError::NotFound("用户不存在".to_string()))
+        }
+    }
+
+    /// 查询地址当前绑定的用户
+    async fn wallet_owner(&self, address: &str) -> Result<Option<Uuid>, AppError> {
+        let owner = sqlx::query_scalar("SELECT user_id FROM user_wallets WHERE address = $1")
+            .bind(address)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(owner)
+    }
+}
+
+/// 按 EIP-4361 格式生成待签名消息
+fn build_siwe_message(
+    siwe: &SiweSettings,
+    checksum_address: &str,
+    nonce: &str,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> String {
+    format!(
+        "{domain} wants you to sign in with your Ethereum account:\n\
+         {address}\n\
+         \n\
+         Link this wallet to your account.\n\
+         \n\
+         URI: {uri}\n\
+         Version: 1\n\
+         Chain ID: {chain_id}\n\
+         Nonce: {nonce}\n\
+         Issued At: {issued_at}\n\
+         Expiration Time: {expires_at}",
+        domain = siwe.domain,
+        address = checksum_address,
+        uri = siwe.uri,
+        chain_id = siwe.chain_id,
+        nonce = nonce,
+        issued_at = issued_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires_at = expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    )
+}
+
+/// 从 personal_sign (EIP-191) 签名中恢复签名者地址（小写）
+fn recover_signer(message: &str, signature: &str) -> Result<String, AppError> {
+    let signature = Signature::from_str(signature)
+        .map_err(|_| AppError::BadRequest("签名格式无效".to_string()))?;
+    let signer = signature
+        .recover(message)
+        .map_err(|_| AppError::Unauthorized("签名校验失败".to_string()))?;
+
+    Ok(format!("0x{:x}", signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    fn settings() -> SiweSettings {
+        SiweSettings {
+            domain: "example.com".to_string(),
+            uri: "https://example.com".to_string(),
+            chain_id: 421614,
+            nonce_ttl: Duration::minutes(10),
+        }
+    }
+
+    #[test]
+    fn test_build_siwe_message() {
+        let issued_at = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let message = build_siwe_message(
+            &settings(),
+            "0x8bcbe680c8d401C1a3024A9364445232E04De64E",
+            "abc123",
+            issued_at,
+            issued_at + Duration::minutes(10),
+        );
+
+        assert!(message.starts_with(
+            "example.com wants you to sign in with your Ethereum account:\n0x8bcbe680c8d401C1a3024A9364445232E04De64E\n\n"
+        ));
+        assert!(message.contains("\nChain ID: 421614\n"));
+        assert!(message.contains("\nNonce: abc123\n"));
+        assert!(message.ends_with("Issued At: 2024-01-01T00:00:00Z\nExpiration Time: 2024-01-01T00:10:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_recover_signer() {
+        let wallet: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let message = "hello";
+        let signature = wallet.sign_message(message).await.unwrap().to_string();
+
+        let signer = recover_signer(message, &signature).unwrap();
+        assert_eq!(signer, format!("0x{:x}", wallet.address()));
+
+        let other = recover_signer("tampered", &signature).unwrap();
+        assert_ne!(other, signer);
+
+        assert!(recover_signer(message, "0x1234").is_err());
+    }
+}
@@ -0,0 +1,514 @@
+use crate::Config;
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::webhook::{
+    CreateWebhookEndpointRequest, CreatedWebhookEndpoint, DeliveryStatus, UpdateWebhookEndpointRequest,
+    WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryDetail, WebhookDeliveryQuery, WebhookEndpoint,
+};
+use chrono::Utc;
+use ethers::core::rand::{RngCore, thread_rng};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{FromRow, PgExecutor, Postgres, QueryBuilder};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// 端点查询字段
+const ENDPOINT_COLUMNS: &str = "id, url, secret, event_types, description, active, created_at, updated_at";
+
+/// 投递查询字段
+const DELIVERY_COLUMNS: &str = "d.id, d.event_id, e.event_type, d.endpoint_id, d.status, d.attempts, \
+    d.next_attempt_at, d.last_status_code, d.last_error, d.delivered_at, d.created_at";
+
+/// 签名请求头：t=<unix 秒>,v1=<hex(HMAC-SHA256(secret, "<t>.<body>"))>
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// 投递日志中保存的响应体最大长度
+const MAX_RESPONSE_BODY_CHARS: usize = 1024;
+
+/// 领取投递后的租约时长；进程崩溃时租约到期后会被重新投递
+const DELIVERY_LEASE_SECS: i64 = 300;
+
+/// 投递重试设置
+#[derive(Debug, Clone)]
+pub struct WebhookSettings {
+    pub max_attempts: i32,
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    pub request_timeout: Duration,
+}
+
+impl WebhookSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_attempts: config.webhook_max_attempts.max(1),
+            backoff_base: Duration::from_secs(config.webhook_backoff_base_secs),
+            backoff_max: Duration::from_secs(config.webhook_backoff_max_secs),
+            request_timeout: Duration::from_secs(config.webhook_timeout_secs),
+        }
+    }
+
+    /// 第 attempt 次失败后的重试间隔：base * 2^(attempt-1)，不超过 max
+    pub fn retry_delay(&self, attempt: i32) -> Duration {
+        let exponent = attempt.saturating_sub(1).clamp(0, 31) as u32;
+        self.backoff_base
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.backoff_max)
+    }
+}
+
+/// 计算签名：hex(HMAC-SHA256(secret, "<timestamp>.<body>"))
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 生成签名请求头的值
+pub fn signature_header(secret: &str, timestamp: i64, body: &str) -> String {
+    format!("t={},v1={}", timestamp, sign_payload(secret, timestamp, body))
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+fn truncate_body(body: &str) -> String {
+    body.chars().take(MAX_RESPONSE_BODY_CHARS).collect()
+}
+
+/// 到期待投递的记录
+#[derive(Debug, FromRow)]
+pub struct DueDelivery {
+    pub id: i64,
+    pub attempts: i32,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub payload: String,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Webhook 服务：端点管理、事件入队（outbox）与投递
+#[derive(Clone)]
+pub struct WebhookService {
+    pool: DatabasePool,
+    client: reqwest::Client,
+    settings: WebhookSettings,
+}
+
+impl WebhookService {
+    pub fn new(pool: DatabasePool, settings: WebhookSettings) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(settings.request_timeout)
+            .build()
+            .expect("Failed to build webhook HTTP client");
+        Self { pool, client, settings }
+    }
+
+    /// 写入事件并为每个订阅该事件的活跃端点生成投递记录
+    /// 单条语句完成，可以传入调用方的事务，保证事件与业务数据一起提交
+    pub async fn enqueue<'e, E, T>(executor: E, event_type: &str, data: &T) -> Result<Uuid, AppError>
+    where
+        E: PgExecutor<'e>,
+        T: Serialize,
+    {
+        let event_id = Uuid::new_v4();
+        let payload = serde_json::json!({
+            "id": event_id,
+            "type": event_type,
+            "created_at": Utc::now(),
+            "data": data,
+        });
+
+        sqlx::query(
+            r#"
+            WITH event AS (
+                INSERT INTO webhook_events (id, event_type, payload)
+                VALUES ($1, $2, $3::JSONB)
+                RETURNING id
+            )
+            INSERT INTO webhook_deliveries (event_id, endpoint_id)
+            SELECT event.id, e.id
+            FROM event, webhook_endpoints e
+            WHERE e.active AND ($2 = ANY(e.event_types) OR '*' = ANY(e.event_types))
+            "#,
+        )
+        .bind(event_id)
+        .bind(event_type)
+        .bind(payload.to_string())
+        .execute(executor)
+        .await?;
+
+        Ok(event_id)
+    }
+
+    /// 注册端点，返回的 secret 仅展示这一次
+    pub async fn create_endpoint(
+        &self,
+        request: CreateWebhookEndpointRequest,
+    ) -> Result<CreatedWebhookEndpoint, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+
+        let secret = generate_secret();
+        let event_types = request.event_types.unwrap_or_else(|| vec!["*".to_string()]);
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(&format!(
+            r#"
+            INSERT INTO webhook_endpoints (url, secret, event_types, description)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {}
+            "#,
+            ENDPOINT_COLUMNS
+        ))
+        .bind(&request.url)
+        .bind(&secret)
+        .bind(&event_types)
+        .bind(&request.description)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CreatedWebhookEndpoint { endpoint, secret })
+    }
+
+    /// 获取全部端点
+    pub async fn list_endpoints(&self) -> Result<Vec<WebhookEndpoint>, AppError> {
+        let endpoints = sqlx::query_as::<_, WebhookEndpoint>(&format!(
+            "SELECT {} FROM webhook_endpoints ORDER BY created_at DESC",
+            ENDPOINT_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(endpoints)
+    }
+
+    /// 根据 ID 获取端点
+    pub async fn get_endpoint(&self, id: Uuid) -> Result<WebhookEndpoint, AppError> {
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(&format!(
+            "SELECT {} FROM webhook_endpoints WHERE id = $1",
+            ENDPOINT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        endpoint.ok_or_else(|| AppError::NotFound("Webhook 端点不存在".to_string()))
+    }
+
+    /// 更新端点
+    pub async fn update_endpoint(
+        &self,
+        id: Uuid,
+        request: UpdateWebhookEndpointRequest,
+    ) -> Result<WebhookEndpoint, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(&format!(
+            r#"
+            UPDATE webhook_endpoints
+            SET url = COALESCE($2, url),
+                event_types = COALESCE($3, event_types),
+                description = COALESCE($4, description),
+                active = COALESCE($5, active)
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            ENDPOINT_COLUMNS
+        ))
+        .bind(id)
+        .bind(&request.url)
+        .bind(&request.event_types)
+        .bind(&request.description)
+        .bind(request.active)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        endpoint.ok_or_else(|| AppError::NotFound("Webhook 端点不存在".to_string()))
+    }
+
+    /// 轮换签名密钥，返回新的 secret
+    pub async fn rotate_secret(&self, id: Uuid) -> Result<CreatedWebhookEndpoint, AppError> {
+        let secret = generate_secret();
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(&format!(
+            "UPDATE webhook_endpoints SET secret = $2 WHERE id = $1 RETURNING {}",
+            ENDPOINT_COLUMNS
+        ))
+        .bind(id)
+        .bind(&secret)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook 端点不存在".to_string()))?;
+
+        Ok(CreatedWebhookEndpoint { endpoint, secret })
+    }
+
+    /// 删除端点（其投递记录一并删除）
+    pub async fn delete_endpoint(&self, id: Uuid) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 查询投递日志
+    pub async fn list_deliveries(
+        &self,
+        endpoint_id: Uuid,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, AppError> {
+        let limit = query.limit.unwrap_or(50);
+        if !(1..=200).contains(&limit) {
+            return Err(AppError::ValidationError("limit 必须在 1-200 之间".to_string()));
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {} FROM webhook_deliveries d JOIN webhook_events e ON e.id = d.event_id WHERE d.endpoint_id = ",
+            DELIVERY_COLUMNS
+        ));
+        builder.push_bind(endpoint_id);
+        if let Some(status) = &query.status {
+            if DeliveryStatus::parse(status).is_none() {
+                return Err(AppError::ValidationError(format!("未知的投递状态: {}", status)));
+            }
+            builder.push(" AND d.status = ").push_bind(status.clone());
+        }
+        if let Some(event_type) = &query.event_type {
+            builder.push(" AND e.event_type = ").push_bind(event_type.clone());
+        }
+        builder.push(" ORDER BY d.id DESC LIMIT ").push_bind(limit);
+
+        let deliveries = builder
+            .build_query_as::<WebhookDelivery>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deliveries)
+    }
+
+    /// 查询单条投递的详情
+    pub async fn get_delivery(&self, id: i64) -> Result<WebhookDeliveryDetail, AppError> {
+        let delivery = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            "SELECT {} FROM webhook_deliveries d JOIN webhook_events e ON e.id = d.event_id WHERE d.id = $1",
+            DELIVERY_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("投递记录不存在".to_string()))?;
+
+        let payload: String = sqlx::query_scalar("SELECT payload::TEXT FROM webhook_events WHERE id = $1")
+            .bind(delivery.event_id)
+            .fetch_one(&self.pool)
+            .await?;
+        let payload = serde_json::from_str(&payload)
+            .map_err(|e| AppError::InternalServerError(format!("事件内容无法解析: {}", e)))?;
+
+        let attempts = sqlx::query_as::<_, WebhookDeliveryAttempt>(
+            r#"
+            SELECT id, attempt, status_code, error, response_body, duration_ms, created_at
+            FROM webhook_delivery_attempts WHERE delivery_id = $1 ORDER BY attempt
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(WebhookDeliveryDetail { delivery, payload, attempts })
+    }
+
+    /// 重新投递（用于死信），重试次数清零
+    pub async fn retry_delivery(&self, id: i64) -> Result<WebhookDeliveryDetail, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE id = $1 AND status <> 'delivered'
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            // 区分不存在与已成功投递
+            self.get_delivery(id).await?;
+            return Err(AppError::Conflict("投递已成功，无需重试".to_string()));
+        }
+
+        self.get_delivery(id).await
+    }
+
+    /// 领取到期的投递并设置租约，多实例部署时通过 SKIP LOCKED 避免重复投递
+    pub async fn claim_due(&self, limit: i64) -> Result<Vec<DueDelivery>, AppError> {
+        let deliveries = sqlx::query_as::<_, DueDelivery>(
+            r#"
+            WITH due AS (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            ), claimed AS (
+                UPDATE webhook_deliveries d
+                SET next_attempt_at = NOW() + make_interval(secs => $2)
+                FROM due WHERE d.id = due.id
+                RETURNING d.id, d.attempts, d.event_id, d.endpoint_id
+            )
+            SELECT c.id, c.attempts, c.event_id, e.event_type, e.payload::TEXT AS payload, w.url, w.secret
+            FROM claimed c
+            JOIN webhook_events e ON e.id = c.event_id
+            JOIN webhook_endpoints w ON w.id = c.endpoint_id
+            "#,
+        )
+        .bind(limit)
+        .bind(DELIVERY_LEASE_SECS as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// 投递一次并记录结果：2xx 视为成功，否则按指数退避重试，超过上限进入死信
+    pub async fn deliver(&self, delivery: &DueDelivery) -> Result<DeliveryStatus, AppError> {
+        let attempt = delivery.attempts + 1;
+        let timestamp = Utc::now().timestamp();
+        let started = Instant::now();
+
+        let result = self
+            .client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header(SIGNATURE_HEADER, signature_header(&delivery.secret, timestamp, &delivery.payload))
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        let (status_code, response_body, error) = match result {
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                let error = (!status.is_success()).then(|| format!("HTTP {}", status));
+                (Some(status.as_u16() as i32), Some(truncate_body(&body)), error)
+            }
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        let status = if error.is_none() {
+            DeliveryStatus::Delivered
+        } else if attempt >= self.settings.max_attempts {
+            DeliveryStatus::Dead
+        } else {
+            DeliveryStatus::Pending
+        };
+        let retry_in = self.settings.retry_delay(attempt);
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_delivery_attempts (delivery_id, attempt, status_code, error, response_body, duration_ms)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(attempt)
+        .bind(status_code)
+        .bind(&error)
+        .bind(&response_body)
+        .bind(duration_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2, attempts = $3, last_status_code = $4, last_error = $5,
+                next_attempt_at = NOW() + make_interval(secs => $6),
+                delivered_at = CASE WHEN $2 = 'delivered' THEN NOW() ELSE delivered_at END
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(status.as_str())
+        .bind(attempt)
+        .bind(status_code)
+        .bind(&error)
+        .bind(retry_in.as_secs_f64())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        match status {
+            DeliveryStatus::Delivered => {
+                log::debug!("📨 Webhook 投递成功: delivery={}, event={}", delivery.id, delivery.event_type);
+            }
+            DeliveryStatus::Pending => log::warn!(
+                "⚠️ Webhook 投递失败，{}秒后重试: delivery={}, attempt={}, error={:?}",
+                retry_in.as_secs(), delivery.id, attempt, error
+            ),
+            DeliveryStatus::Dead => log::error!(
+                "🪦 Webhook 投递进入死信: delivery={}, attempts={}, error={:?}",
+                delivery.id, attempt, error
+            ),
+        }
+
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        assert_eq!(
+            sign_payload("whsec_test", 1_700_000_000, r#"{"id":1}"#),
+            "2f441ba4b3b2d50d28a9ab9d9fd8880376ecd1eb5d0435401553f5d8d0a5dcf8"
+        );
+        assert_eq!(
+            signature_header("whsec_test", 1_700_000_000, r#"{"id":1}"#),
+            "t=1700000000,v1=2f441ba4b3b2d50d28a9ab9d9fd8880376ecd1eb5d0435401553f5d8d0a5dcf8"
+        );
+        // 时间戳参与签名，防止重放
+        assert_ne!(
+            sign_payload("whsec_test", 1_700_000_001, r#"{"id":1}"#),
+            sign_payload("whsec_test", 1_700_000_000, r#"{"id":1}"#)
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let settings = WebhookSettings {
+            max_attempts: 8,
+            backoff_base: Duration::from_secs(30),
+            backoff_max: Duration::from_secs(3600),
+            request_timeout: Duration::from_secs(10),
+        };
+
+        assert_eq!(settings.retry_delay(1), Duration::from_secs(30));
+        assert_eq!(settings.retry_delay(2), Duration::from_secs(60));
+        assert_eq!(settings.retry_delay(4), Duration::from_secs(240));
+        assert_eq!(settings.retry_delay(10), Duration::from_secs(3600));
+        assert_eq!(settings.retry_delay(i32::MAX), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+        assert!(secret.starts_with("whsec_"));
+        assert_eq!(secret.len(), "whsec_".len() + 64);
+        assert_ne!(secret, generate_secret());
+    }
+}
@@ -0,0 +1,683 @@
+use crate::Config;
+use crate::database::DatabasePool;
+use crate::errors::AppError;
+use crate::models::ledger::AccountKind;
+use crate::models::withdrawal::{
+    CreateWithdrawalRequest, RejectWithdrawalRequest, SettlementLog, Withdrawal, WithdrawalQuery, WithdrawalStatus,
+};
+use crate::services::ledger::{
+    LedgerService, Posting, parse_amount, user_account_code, vault_account_code, withdrawal_hold_account_code,
+};
+use sqlx::{PgConnection, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// 提现查询字段（金额转为文本，审批数来自审批记录）
+const WITHDRAWAL_COLUMNS: &str = "w.id, w.user_id, w.asset, w.amount::TEXT AS amount, w.destination, w.status, \
+    w.approvals_required, \
+    (SELECT COUNT(*) FROM withdrawal_approvals a WHERE a.withdrawal_id = w.id) AS approvals, \
+    w.executor, w.external_ref, w.submitted_block, w.settlement_tx_hash, w.settlement_log_index, w.failure_reason, \
+    w.created_at, w.updated_at, w.submitted_at, w.finalized_at";
+
+/// 列表默认/最大返回条数
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// 提现风控规则
+#[derive(Debug, Clone)]
+pub struct WithdrawalPolicy {
+    /// 单笔最小金额
+    pub min_amount: i128,
+    /// 单笔最大金额
+    pub max_amount: Option<i128>,
+    /// 用户 24 小时内累计提现上限（按资产）
+    pub daily_limit: Option<i128>,
+    /// 达到该金额的提现需要人工审批
+    pub approval_threshold: Option<i128>,
+    /// 大额提现需要的不同审批人数量
+    pub required_approvals: i32,
+    /// 允许审批的用户；为空时禁用审批
+    pub approver_ids: Vec<Uuid>,
+}
+
+impl WithdrawalPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            min_amount: config.withdrawal_min_amount,
+            max_amount: config.withdrawal_max_amount,
+            daily_limit: config.withdrawal_daily_limit,
+            approval_threshold: config.withdrawal_approval_threshold,
+            required_approvals: config.withdrawal_required_approvals.max(1),
+            approver_ids: config.withdrawal_approver_ids.clone(),
+        }
+    }
+
+    /// 风控检查，返回需要的审批人数（0 表示自动通过）
+    /// daily_total 为该用户 24 小时内已占用额度（不含本笔）
+    pub fn evaluate(&self, amount: i128, daily_total: i128) -> Result<i32, String> {
+        if amount < self.min_amount {
+            return Err(format!("提现金额低于最小限额 {}", self.min_amount));
+        }
+
+        if let Some(max) = self.max_amount
+            && amount > max
+        {
+            return Err(format!("提现金额超过单笔限额 {}", max));
+        }
+
+        if let Some(limit) = self.daily_limit
+            && daily_total.saturating_add(amount) > limit
+        {
+            return Err(format!("超过24小时提现限额 {}，已使用 {}", limit, daily_total));
+        }
+
+        match self.approval_threshold {
+            Some(threshold) if amount >= threshold => Ok(self.required_approvals),
+            _ => Ok(0),
+        }
+    }
+
+    /// 审批人是否有权审批该用户的提现；未配置审批人名单时任何人都不能审批
+    pub fn can_approve(&self, approver_id: Uuid, requester_id: Uuid) -> bool {
+        approver_id != requester_id && self.approver_ids.contains(&approver_id)
+    }
+
+    /// 未配置审批人名单时拒绝一切审批操作
+    pub fn check_approvers(&self) -> Result<(), AppError> {
+        if self.approver_ids.is_empty() {
+            return Err(AppError::Forbidden("未配置 WITHDRAWAL_APPROVER_IDS，提现审批已禁用".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 提现服务：申请时把资金从用户余额转入冻结账户，终态时完成出账或退回
+#[derive(Clone)]
+pub struct WithdrawalService {
+    pool: DatabasePool,
+    policy: WithdrawalPolicy,
+    default_asset: Option<String>,
+}
+
+impl WithdrawalService {
+    pub fn new(pool: DatabasePool, policy: WithdrawalPolicy, default_asset: Option<String>) -> Self {
+        if policy.approval_threshold.is_some() && policy.approver_ids.is_empty() {
+            log::error!("❌ 已配置大额提现审批阈值但 WITHDRAWAL_APPROVER_IDS 为空，大额提现将无法审批");
+        }
+
+        Self {
+            pool,
+            policy,
+            default_asset: default_asset.map(|a| a.to_lowercase()),
+        }
+    }
+
+    /// 申请提现：风控检查 + 余额冻结在同一事务中完成
+    pub async fn create_withdrawal(
+        &self,
+        user_id: Uuid,
+        request: CreateWithdrawalRequest,
+    ) -> Result<Withdrawal, AppError> {
+        request.validate().map_err(AppError::ValidationError)?;
+
+        let asset = match request.asset.as_ref().or(self.default_asset.as_ref()) {
+            Some(asset) => asset.to_lowercase(),
+            None => return Err(AppError::ValidationError("未指定提现资产".to_string())),
+        };
+        let destination = request.destination.to_lowercase();
+        let amount = parse_amount(&request.amount).map_err(AppError::ValidationError)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // 锁定用户行，串行化同一用户的提现申请，保证余额与限额检查不被并发绕过
+        let user_exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if user_exists.is_none() {
+            return Err(AppError::NotFound("用户不存在".to_string()));
+        }
+
+        // 只能提现到用户自己签名绑定过的钱包
+        let destination_linked: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM user_wallets WHERE user_id = $1 AND address = $2)",
+        )
+        .bind(user_id)
+        .bind(&destination)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !destination_linked {
+            return Err(AppError::ValidationError("收款地址必须是用户已绑定的钱包".to_string()));
+        }
+
+        let user_account = LedgerService::ensure_account(
+            &mut tx,
+            &user_account_code(&user_id, &asset),
+            AccountKind::Liability,
+            Some(user_id),
+            &asset,
+        )
+        .await?;
+        let balance = liability_balance(&mut tx, user_account).await?;
+        if balance < amount {
+            return Err(AppError::BadRequest(format!("余额不足，可用余额: {}", balance)));
+        }
+
+        let daily_total: String = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(amount), 0)::TEXT FROM withdrawals
+            WHERE user_id = $1 AND asset = $2
+              AND created_at > NOW() - INTERVAL '24 hours'
+              AND status NOT IN ('rejected', 'cancelled', 'failed')
+            "#,
+        )
+        .bind(user_id)
+        .bind(&asset)
+        .fetch_one(&mut *tx)
+        .await?;
+        let daily_total = parse_numeric(&daily_total)?;
+
+        let approvals_required = self
+            .policy
+            .evaluate(amount, daily_total)
+            .map_err(AppError::BadRequest)?;
+        let status = if approvals_required > 0 {
+            WithdrawalStatus::PendingApproval
+        } else {
+            WithdrawalStatus::Approved
+        };
+
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO withdrawals (id, user_id, asset, amount, destination, status, approvals_required)
+            VALUES ($1, $2, $3, $4::NUMERIC, $5, $6, $7)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&asset)
+        .bind(amount.to_string())
+        .bind(&destination)
+        .bind(status.as_str())
+        .bind(approvals_required)
+        .execute(&mut *tx)
+        .await?;
+
+        // 借：用户余额（减少负债）；贷：提现冻结账户
+        let hold_account = LedgerService::ensure_account(
+            &mut tx,
+            &withdrawal_hold_account_code(&asset),
+            AccountKind::Liability,
+            None,
+            &asset,
+        )
+        .await?;
+        LedgerService::post_entry(
+            &mut tx,
+            &format!("withdrawal:{}:hold", id),
+            &format!("提现冻结 {}", id),
+            &[
+                Posting { account_id: user_account, amount },
+                Posting { account_id: hold_account, amount: -amount },
+            ],
+        )
+        .await?;
+
+        let withdrawal = fetch_withdrawal(&mut tx, id).await?;
+        tx.commit().await?;
+
+        log::info!(
+            "💸 提现申请已创建: id={}, user_id={}, amount={}, status={}",
+            id, user_id, amount, withdrawal.status
+        );
+
+        Ok(withdrawal)
+    }
+
+    /// 根据 ID 获取提现
+    pub async fn get_withdrawal(&self, id: Uuid) -> Result<Withdrawal, AppError> {
+        let withdrawal = sqlx::query_as::<_, Withdrawal>(&format!(
+            "SELECT {} FROM withdrawals w WHERE w.id = $1",
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        withdrawal.ok_or_else(|| AppError::NotFound("提现记录不存在".to_string()))
+    }
+
+    /// 查询提现列表，user_id 为空时查询全部用户
+    pub async fn list_withdrawals(
+        &self,
+        user_id: Option<Uuid>,
+        query: &WithdrawalQuery,
+    ) -> Result<Vec<Withdrawal>, AppError> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+        if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+            return Err(AppError::ValidationError(format!("limit 必须在 1-{} 之间", MAX_LIST_LIMIT)));
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {} FROM withdrawals w WHERE 1 = 1",
+            WITHDRAWAL_COLUMNS
+        ));
+        if let Some(user_id) = user_id {
+            builder.push(" AND w.user_id = ").push_bind(user_id);
+        }
+        if let Some(status) = &query.status {
+            if WithdrawalStatus::parse(status).is_none() {
+                return Err(AppError::ValidationError(format!("未知的提现状态: {}", status)));
+            }
+            builder.push(" AND w.status = ").push_bind(status.clone());
+        }
+        builder.push(" ORDER BY w.created_at DESC LIMIT ").push_bind(limit);
+
+        let withdrawals = builder
+            .build_query_as::<Withdrawal>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(withdrawals)
+    }
+
+    /// 审批大额提现；达到所需审批人数后进入待执行状态
+    /// approver_id 必须来自认证后的调用方，不能取自请求体
+    pub async fn approve_withdrawal(&self, id: Uuid, approver_id: Uuid) -> Result<Withdrawal, AppError> {
+        self.policy.check_approvers()?;
+
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+
+        if withdrawal.status() != Some(WithdrawalStatus::PendingApproval) {
+            return Err(AppError::Conflict(format!("当前状态不可审批: {}", withdrawal.status)));
+        }
+        if !self.policy.can_approve(approver_id, withdrawal.user_id) {
+            return Err(AppError::Forbidden("无权审批该提现".to_string()));
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO withdrawal_approvals (withdrawal_id, approver_id)
+            VALUES ($1, $2)
+            ON CONFLICT (withdrawal_id, approver_id) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(approver_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+                AppError::NotFound("审批人不存在".to_string())
+            }
+            _ => AppError::DatabaseError(e),
+        })?;
+        if inserted.rows_affected() == 0 {
+            return Err(AppError::Conflict("该审批人已审批过此提现".to_string()));
+        }
+
+        let approvals = withdrawal.approvals + 1;
+        if approvals >= withdrawal.approvals_required as i64 {
+            set_status(&mut tx, id, WithdrawalStatus::Approved).await?;
+            log::info!("✅ 提现审批通过: id={}, approvals={}", id, approvals);
+        }
+
+        let withdrawal = fetch_withdrawal(&mut tx, id).await?;
+        tx.commit().await?;
+
+        Ok(withdrawal)
+    }
+
+    /// 拒绝提现并退回冻结资金
+    pub async fn reject_withdrawal(
+        &self,
+        id: Uuid,
+        approver_id: Uuid,
+        request: RejectWithdrawalRequest,
+    ) -> Result<Withdrawal, AppError> {
+        self.policy.check_approvers()?;
+        if request.reason.trim().is_empty() {
+            return Err(AppError::ValidationError("拒绝原因不能为空".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+        if !self.policy.can_approve(approver_id, withdrawal.user_id) {
+            return Err(AppError::Forbidden("无权审批该提现".to_string()));
+        }
+
+        let withdrawal = close_with_release(&mut tx, withdrawal, WithdrawalStatus::Rejected, Some(&request.reason)).await?;
+        tx.commit().await?;
+
+        log::info!("🚫 提现已拒绝: id={}, reason={}", id, request.reason);
+        Ok(withdrawal)
+    }
+
+    /// 用户在执行前取消提现并退回冻结资金
+    pub async fn cancel_withdrawal(&self, user_id: Uuid, id: Uuid) -> Result<Withdrawal, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+        if withdrawal.user_id != user_id {
+            return Err(AppError::NotFound("提现记录不存在".to_string()));
+        }
+
+        let withdrawal = close_with_release(&mut tx, withdrawal, WithdrawalStatus::Cancelled, None).await?;
+        tx.commit().await?;
+
+        log::info!("↩️ 提现已取消: id={}", id);
+        Ok(withdrawal)
+    }
+
+    /// 领取待执行的提现并标记为提交中，多实例部署时通过 SKIP LOCKED 避免重复执行
+    pub async fn claim_approved(&self, executor: &str, limit: i64) -> Result<Vec<Withdrawal>, AppError> {
+        let withdrawals = sqlx::query_as::<_, Withdrawal>(&format!(
+            r#"
+            UPDATE withdrawals w SET status = 'submitting', executor = $1
+            WHERE w.id IN (
+                SELECT id FROM withdrawals WHERE status = 'approved'
+                ORDER BY created_at LIMIT $2 FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(executor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(withdrawals)
+    }
+
+    /// 记录执行器返回的链上引用
+    pub async fn mark_submitted(
+        &self,
+        id: Uuid,
+        external_ref: &str,
+        submitted_block: Option<u64>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+        ensure_transition(&withdrawal, WithdrawalStatus::Submitted)?;
+
+        sqlx::query(
+            r#"
+            UPDATE withdrawals
+            SET status = 'submitted', external_ref = $2, submitted_block = $3, submitted_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(external_ref)
+        .bind(submitted_block.map(|b| b as i64))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 查询已提交、等待链上确认的提现
+    pub async fn list_submitted(&self, limit: i64) -> Result<Vec<Withdrawal>, AppError> {
+        let withdrawals = sqlx::query_as::<_, Withdrawal>(&format!(
+            "SELECT {} FROM withdrawals w WHERE w.status = 'submitted' ORDER BY w.submitted_at LIMIT $1",
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(withdrawals)
+    }
+
+    /// 链上最终确认：借冻结账户，贷 vault 资产账户（资金离开托管）
+    pub async fn mark_finalized(&self, id: Uuid) -> Result<(), AppError> {
+        self.finalize(id, None).await
+    }
+
+    /// 认领第一条未被其他提现占用的出金事件并最终确认；全部已被占用时返回 false，等待新的事件
+    pub async fn mark_settled(&self, id: Uuid, candidates: &[SettlementLog]) -> Result<bool, AppError> {
+        for log in candidates {
+            let claimed: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM withdrawals WHERE settlement_tx_hash = $1 AND settlement_log_index = $2)",
+            )
+            .bind(&log.tx_hash)
+            .bind(log.log_index)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if !claimed {
+                self.finalize(id, Some(log)).await?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn finalize(&self, id: Uuid, settlement: Option<&SettlementLog>) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+        ensure_transition(&withdrawal, WithdrawalStatus::Finalized)?;
+
+        let amount = parse_amount(&withdrawal.amount).map_err(AppError::InternalServerError)?;
+        let hold_account = LedgerService::ensure_account(
+            &mut tx,
+            &withdrawal_hold_account_code(&withdrawal.asset),
+            AccountKind::Liability,
+            None,
+            &withdrawal.asset,
+        )
+        .await?;
+        let vault_account = LedgerService::ensure_account(
+            &mut tx,
+            &vault_account_code(&withdrawal.asset),
+            AccountKind::Asset,
+            None,
+            &withdrawal.asset,
+        )
+        .await?;
+        LedgerService::post_entry(
+            &mut tx,
+            &format!("withdrawal:{}:finalize", id),
+            &format!("提现出账 {}", id),
+            &[
+                Posting { account_id: hold_account, amount },
+                Posting { account_id: vault_account, amount: -amount },
+            ],
+        )
+        .await?;
+
+        // 唯一约束保证同一条链上事件不会被并发认领给两笔提现
+        sqlx::query(
+            r#"
+            UPDATE withdrawals
+            SET status = 'finalized', finalized_at = NOW(), settlement_tx_hash = $2, settlement_log_index = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(settlement.map(|log| log.tx_hash.as_str()))
+        .bind(settlement.map(|log| log.log_index))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                AppError::Conflict("该链上出金事件已被其他提现认领".to_string())
+            }
+            _ => AppError::DatabaseError(e),
+        })?;
+
+        tx.commit().await?;
+
+        log::info!("🏁 提现已最终确认: id={}, ref={:?}, settlement={:?}", id, withdrawal.external_ref, settlement);
+        Ok(())
+    }
+
+    /// 执行失败：标记失败并退回冻结资金
+    pub async fn mark_failed(&self, id: Uuid, reason: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let withdrawal = lock_withdrawal(&mut tx, id).await?;
+        close_with_release(&mut tx, withdrawal, WithdrawalStatus::Failed, Some(reason)).await?;
+        tx.commit().await?;
+
+        log::error!("❌ 提现执行失败: id={}, reason={}", id, reason);
+        Ok(())
+    }
+}
+
+/// 负债账户余额（贷方合计）
+async fn liability_balance(conn: &mut PgConnection, account_id: i64) -> Result<i128, AppError> {
+    let balance: String = sqlx::query_scalar(
+        "SELECT (-COALESCE(SUM(amount), 0))::TEXT FROM ledger_postings WHERE account_id = $1"
+    )
+    .bind(account_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    parse_numeric(&balance)
+}
+
+fn parse_numeric(raw: &str) -> Result<i128, AppError> {
+    raw.parse()
+        .map_err(|_| AppError::InternalServerError(format!("金额无法解析: {}", raw)))
+}
+
+async fn fetch_withdrawal(conn: &mut PgConnection, id: Uuid) -> Result<Withdrawal, AppError> {
+    let withdrawal = sqlx::query_as::<_, Withdrawal>(&format!(
+        "SELECT {} FROM withdrawals w WHERE w.id = $1",
+        WITHDRAWAL_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(withdrawal)
+}
+
+/// 锁定提现行，状态流转必须在持有行锁时判断
+async fn lock_withdrawal(conn: &mut PgConnection, id: Uuid) -> Result<Withdrawal, AppError> {
+    let withdrawal = sqlx::query_as::<_, Withdrawal>(&format!(
+        "SELECT {} FROM withdrawals w WHERE w.id = $1 FOR UPDATE OF w",
+        WITHDRAWAL_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    withdrawal.ok_or_else(|| AppError::NotFound("提现记录不存在".to_string()))
+}
+
+fn ensure_transition(withdrawal: &Withdrawal, next: WithdrawalStatus) -> Result<(), AppError> {
+    match withdrawal.status() {
+        Some(current) if current.can_transition_to(next) => Ok(()),
+        _ => Err(AppError::Conflict(format!(
+            "提现状态不允许从 {} 变为 {}",
+            withdrawal.status,
+            next.as_str()
+        ))),
+    }
+}
+
+async fn set_status(conn: &mut PgConnection, id: Uuid, status: WithdrawalStatus) -> Result<(), AppError> {
+    sqlx::query("UPDATE withdrawals SET status = $2 WHERE id = $1")
+        .bind(id)
+        .bind(status.as_str())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 以退款终态关闭提现：借冻结账户，贷用户余额
+async fn close_with_release(
+    conn: &mut PgConnection,
+    withdrawal: Withdrawal,
+    next: WithdrawalStatus,
+    reason: Option<&str>,
+) -> Result<Withdrawal, AppError> {
+    debug_assert!(next.releases_hold());
+    ensure_transition(&withdrawal, next)?;
+
+    let amount = parse_amount(&withdrawal.amount).map_err(AppError::InternalServerError)?;
+    let hold_account = LedgerService::ensure_account(
+        conn,
+        &withdrawal_hold_account_code(&withdrawal.asset),
+        AccountKind::Liability,
+        None,
+        &withdrawal.asset,
+    )
+    .await?;
+    let user_account = LedgerService::ensure_account(
+        conn,
+        &user_account_code(&withdrawal.user_id, &withdrawal.asset),
+        AccountKind::Liability,
+        Some(withdrawal.user_id),
+        &withdrawal.asset,
+    )
+    .await?;
+    LedgerService::post_entry(
+        conn,
+        &format!("withdrawal:{}:release", withdrawal.id),
+        &format!("提现退回 {}", withdrawal.id),
+        &[
+            Posting { account_id: hold_account, amount },
+            Posting { account_id: user_account, amount: -amount },
+        ],
+    )
+    .await?;
+
+    sqlx::query("UPDATE withdrawals SET status = $2, failure_reason = $3 WHERE id = $1")
+        .bind(withdrawal.id)
+        .bind(next.as_str())
+        .bind(reason)
+        .execute(&mut *conn)
+        .await?;
+
+    fetch_withdrawal(conn, withdrawal.id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> WithdrawalPolicy {
+        WithdrawalPolicy {
+            min_amount: 10,
+            max_amount: Some(10_000),
+            daily_limit: Some(15_000),
+            approval_threshold: Some(5_000),
+            required_approvals: 2,
+            approver_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_policy_limits() {
+        let policy = policy();
+
+        assert_eq!(policy.evaluate(100, 0), Ok(0));
+        assert!(policy.evaluate(9, 0).is_err()); // 低于最小限额
+        assert!(policy.evaluate(10_001, 0).is_err()); // 超过单笔限额
+        assert!(policy.evaluate(6_000, 10_000).is_err()); // 超过日限额
+        assert_eq!(policy.evaluate(5_000, 10_000), Ok(2)); // 恰好用满日限额，需要审批
+    }
+
+    #[test]
+    fn test_policy_approvers() {
+        let requester = Uuid::new_v4();
+        let approver = Uuid::new_v4();
+
+        let mut policy = policy();
+        assert!(!policy.can_approve(approver, requester)); // 未配置审批人时禁用审批
+        assert!(policy.check_approvers().is_err());
+
+        policy.approver_ids = vec![approver, requester];
+        assert!(policy.check_approvers().is_ok());
+        assert!(policy.can_approve(approver, requester));
+        assert!(!policy.can_approve(requester, requester)); // 不能审批自己的提现
+
+        policy.approver_ids = vec![Uuid::new_v4()];
+        assert!(!policy.can_approve(approver, requester)); // 不在审批人名单中
+    }
+}
@@ -0,0 +1,570 @@
+use crate::Config;
+use crate::listeners::rpc_pool::RpcPool;
+use crate::models::withdrawal::{SettlementLog, Withdrawal};
+use crate::services::hyperliquid::hyperliquid_base_url;
+use alloy::signers::local::PrivateKeySigner;
+use ethers::abi::{Token, encode};
+use ethers::middleware::SignerMiddleware;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+use futures_util::future::BoxFuture;
+use hyperliquid_rust_sdk::{ExchangeClient, ExchangeResponseStatus};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// 执行器提交结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmittedWithdrawal {
+    /// 链上交易哈希或执行器内部引用
+    pub external_ref: String,
+    /// 提交时的区块高度，用于后续按区块范围确认
+    pub block_number: Option<u64>,
+}
+
+/// 已提交提现的链上状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    Pending,
+    Finalized,
+    /// 找到了可能对应的出金事件（按链上顺序），认领第一条未被其他提现占用的事件后最终确认
+    Settled(Vec<SettlementLog>),
+    Failed(String),
+}
+
+/// 提交提现失败的原因
+#[derive(Debug)]
+pub enum SubmitError {
+    /// 确定没有发出（被交易所拒绝或广播前失败），可以退回冻结资金
+    Rejected(String),
+    /// 结果未知（超时、连接中断等），可能已经上链，只能人工对账
+    Unknown(anyhow::Error),
+}
+
+impl SubmitError {
+    pub fn rejected(e: impl std::fmt::Display) -> Self {
+        SubmitError::Rejected(e.to_string())
+    }
+}
+
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::Rejected(reason) => write!(f, "提交被拒绝: {}", reason),
+            SubmitError::Unknown(e) => write!(f, "提交结果未知: {}", e),
+        }
+    }
+}
+
+/// 提现执行器：负责把已审批的提现发送上链并查询最终状态
+pub trait WithdrawalExecutor: Send + Sync {
+    /// 执行器名称，写入 withdrawals.executor
+    fn name(&self) -> &'static str;
+
+    /// 提交提现；只有 SubmitError::Rejected 表示确定未上链，资金才会被退回
+    fn submit<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, Result<SubmittedWithdrawal, SubmitError>>;
+
+    /// 查询已提交提现的状态；返回错误表示暂时查询失败，下一轮重试
+    fn check_status<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, anyhow::Result<ExecutionStatus>>;
+}
+
+/// 把最小单位的整数金额格式化为十进制字符串（去掉多余的 0）
+pub fn format_units(amount: i128, decimals: u32) -> String {
+    let base = 10i128.pow(decimals);
+    let whole = amount / base;
+    let fraction = amount % base;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+fn parse_withdrawal_amount(withdrawal: &Withdrawal) -> anyhow::Result<U256> {
+    U256::from_dec_str(&withdrawal.amount).map_err(|e| anyhow::anyhow!("提现金额无法解析: {}", e))
+}
+
+/// 根据配置创建执行器；WITHDRAWAL_EXECUTOR 未设置时不执行提现
+pub async fn build_withdrawal_executor(config: &Config) -> anyhow::Result<Option<Arc<dyn WithdrawalExecutor>>> {
+    let Some(kind) = config.withdrawal_executor.as_deref() else {
+        return Ok(None);
+    };
+
+    let executor: Arc<dyn WithdrawalExecutor> = match kind {
+        "erc20" => Arc::new(Erc20TransferExecutor::from_config(config).await?),
+        "hyperliquid" => Arc::new(HyperliquidBridgeExecutor::from_config(config).await?),
+        "mock" => Arc::new(MockWithdrawalExecutor::new()),
+        other => anyhow::bail!("未知的 WITHDRAWAL_EXECUTOR: {}", other),
+    };
+
+    Ok(Some(executor))
+}
+
+/// 从热钱包直接转出 ERC20 代币，可直接对接本地 Anvil 节点
+pub struct Erc20TransferExecutor {
+    client: SignerMiddleware<Provider<Http>, LocalWallet>,
+    rpc: RpcPool,
+    confirmations: u64,
+}
+
+impl Erc20TransferExecutor {
+    pub async fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let url = config
+            .arbitrum_http_url
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("ARBITRUM_HTTP_URL 未设置"))?;
+        let key = config
+            .withdrawal_private_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("WITHDRAWAL_PRIVATE_KEY 未设置"))?;
+
+        let provider = Provider::<Http>::try_from(url.as_str())?;
+        let chain_id = provider.get_chainid().await?.as_u64();
+        let wallet: LocalWallet = key.parse::<LocalWallet>()?.with_chain_id(chain_id);
+        log::info!("🔑 ERC20 提现热钱包: 0x{:x}, chain_id={}", wallet.address(), chain_id);
+
+        Ok(Self {
+            client: SignerMiddleware::new(provider, wallet),
+            rpc: RpcPool::from_config(config)?,
+            confirmations: config.withdrawal_confirmations,
+        })
+    }
+}
+
+impl WithdrawalExecutor for Erc20TransferExecutor {
+    fn name(&self) -> &'static str {
+        "erc20"
+    }
+
+    fn submit<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, Result<SubmittedWithdrawal, SubmitError>> {
+        Box::pin(async move {
+            let token: Address = withdrawal.asset.parse().map_err(SubmitError::rejected)?;
+            let destination: Address = withdrawal.destination.parse().map_err(SubmitError::rejected)?;
+            let amount = parse_withdrawal_amount(withdrawal).map_err(SubmitError::rejected)?;
+
+            // transfer(address,uint256) 选择器 0xa9059cbb
+            let mut calldata = vec![0xa9, 0x05, 0x9c, 0xbb];
+            calldata.extend(encode(&[Token::Address(destination), Token::Uint(amount)]));
+
+            let block_number = self.rpc.get_block_number().await.ok();
+            let mut tx: TypedTransaction = TransactionRequest::new().to(token).data(calldata).into();
+
+            // 先在本地填充 nonce 和 gas（预执行 revert 会在这里报错），此时交易还没有广播
+            self.client
+                .fill_transaction(&mut tx, None)
+                .await
+                .map_err(|e| SubmitError::Rejected(format!("交易预执行失败: {}", e)))?;
+
+            // 广播阶段出错时无法确定节点是否已收到交易
+            let pending = self
+                .client
+                .send_transaction(tx, None)
+                .await
+                .map_err(|e| SubmitError::Unknown(e.into()))?;
+
+            Ok(SubmittedWithdrawal {
+                external_ref: format!("{:?}", pending.tx_hash()),
+                block_number,
+            })
+        })
+    }
+
+    fn check_status<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, anyhow::Result<ExecutionStatus>> {
+        Box::pin(async move {
+            let tx_hash: H256 = withdrawal
+                .external_ref
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("缺少交易哈希"))?
+                .parse()?;
+
+            let Some(receipt) = self.rpc.get_transaction_receipt(tx_hash).await? else {
+                return Ok(ExecutionStatus::Pending);
+            };
+            if receipt.status == Some(U64::zero()) {
+                return Ok(ExecutionStatus::Failed(format!("交易执行失败: {:?}", tx_hash)));
+            }
+
+            let Some(mined_at) = receipt.block_number else {
+                return Ok(ExecutionStatus::Pending);
+            };
+            let latest = self.rpc.get_block_number().await?;
+            if latest.saturating_sub(mined_at.as_u64()) >= self.confirmations {
+                Ok(ExecutionStatus::Finalized)
+            } else {
+                Ok(ExecutionStatus::Pending)
+            }
+        })
+    }
+}
+
+/// 通过 Hyperliquid Withdraw3 动作从 Bridge2 出金
+/// 最终状态通过扫描 Bridge2 的 FinalizedWithdrawal 事件确认
+pub struct HyperliquidBridgeExecutor {
+    exchange: ExchangeClient,
+    scanner: BridgeWithdrawalScanner,
+}
+
+/// Bridge2 上 USDC 的精度
+const BRIDGE_USD_DECIMALS: u32 = 6;
+
+impl HyperliquidBridgeExecutor {
+    pub async fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let key = config
+            .withdrawal_private_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("WITHDRAWAL_PRIVATE_KEY 未设置"))?;
+        let bridge: Address = config
+            .hyperliquid_bridge_address
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("HYPERLIQUID_BRIDGE_ADDRESS 未设置"))?
+            .parse()?;
+        let base_url = hyperliquid_base_url(&config.hyperliquid_network)?;
+
+        let signer: PrivateKeySigner = key.parse()?;
+        let user = Address::from_slice(signer.address().as_slice());
+        let exchange = ExchangeClient::new(None, signer, Some(base_url), None, None).await?;
+        log::info!("🔑 Hyperliquid 提现账户: 0x{:x}, bridge=0x{:x}", user, bridge);
+
+        Ok(Self {
+            exchange,
+            scanner: BridgeWithdrawalScanner::new(RpcPool::from_config(config)?, bridge, user, config.hyperliquid_withdraw_fee),
+        })
+    }
+}
+
+impl WithdrawalExecutor for HyperliquidBridgeExecutor {
+    fn name(&self) -> &'static str {
+        "hyperliquid"
+    }
+
+    fn submit<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, Result<SubmittedWithdrawal, SubmitError>> {
+        Box::pin(async move {
+            let amount: i128 = withdrawal.amount.parse().map_err(SubmitError::rejected)?;
+            if amount <= self.scanner.fee {
+                return Err(SubmitError::Rejected(format!("提现金额不足以支付手续费 {}", self.scanner.fee)));
+            }
+            let usd = format_units(amount, BRIDGE_USD_DECIMALS);
+
+            // 确认时从该区块开始扫描事件，拿不到高度就不发出提现
+            let block_number = self
+                .scanner
+                .rpc
+                .get_block_number()
+                .await
+                .map_err(|e| SubmitError::Rejected(format!("获取区块高度失败: {}", e)))?;
+
+            // 请求超时或连接中断时交易所可能已经受理，不能当作失败退款
+            let response = self
+                .exchange
+                .withdraw_from_bridge(&usd, &withdrawal.destination, None)
+                .await
+                .map_err(|e| SubmitError::Unknown(e.into()))?;
+
+            match response {
+                ExchangeResponseStatus::Ok(_) => Ok(SubmittedWithdrawal {
+                    external_ref: format!("hyperliquid:{}", withdrawal.id),
+                    block_number: Some(block_number),
+                }),
+                ExchangeResponseStatus::Err(e) => Err(SubmitError::Rejected(format!("Hyperliquid 拒绝提现: {}", e))),
+            }
+        })
+    }
+
+    fn check_status<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, anyhow::Result<ExecutionStatus>> {
+        Box::pin(async move {
+            let candidates = self.scanner.find_settlements(withdrawal).await?;
+            if candidates.is_empty() {
+                Ok(ExecutionStatus::Pending)
+            } else {
+                Ok(ExecutionStatus::Settled(candidates))
+            }
+        })
+    }
+}
+
+/// 扫描 Bridge2 的 FinalizedWithdrawal 事件，找出与提现金额和收款地址一致的出金记录
+/// 事件是否已被其他提现认领由数据库的唯一约束判定
+pub struct BridgeWithdrawalScanner {
+    rpc: RpcPool,
+    bridge: Address,
+    user: Address,
+    /// Hyperliquid 从提现金额中扣除的手续费（最小单位）
+    fee: i128,
+}
+
+impl BridgeWithdrawalScanner {
+    pub fn new(rpc: RpcPool, bridge: Address, user: Address, fee: i128) -> Self {
+        Self { rpc, bridge, user, fee }
+    }
+
+    /// 返回提交区块之后所有匹配的事件，按链上顺序排列
+    pub async fn find_settlements(&self, withdrawal: &Withdrawal) -> anyhow::Result<Vec<SettlementLog>> {
+        let from_block = withdrawal
+            .submitted_block
+            .ok_or_else(|| anyhow::anyhow!("提现缺少提交区块高度，无法确认: {}", withdrawal.id))?;
+        let destination: Address = withdrawal.destination.parse()?;
+        let amount = parse_withdrawal_amount(withdrawal)?;
+        let net_amount = amount
+            .checked_sub(U256::from(self.fee))
+            .ok_or_else(|| anyhow::anyhow!("提现金额小于手续费: {}", withdrawal.id))?;
+
+        let topic = H256::from(keccak256("FinalizedWithdrawal(address,address,uint64,uint64,bytes32)"));
+        let filter = Filter::new()
+            .address(self.bridge)
+            .topic0(topic)
+            .topic1(H256::from(self.user))
+            .from_block(from_block as u64);
+        let logs = self.rpc.get_logs(&filter).await?;
+
+        Ok(match_settlement_logs(&logs, destination, net_amount))
+    }
+}
+
+/// 筛选收款地址一致且到账金额恰好等于扣除手续费后金额的事件
+fn match_settlement_logs(logs: &[Log], destination: Address, net_amount: U256) -> Vec<SettlementLog> {
+    logs.iter()
+        .filter_map(|log| {
+            // data: destination | usd | nonce | message，每个字段 32 字节
+            if log.data.len() < 64 || log.removed == Some(true) {
+                return None;
+            }
+            let log_destination = Address::from_slice(&log.data[12..32]);
+            let usd = U256::from_big_endian(&log.data[32..64]);
+            if log_destination != destination || usd != net_amount {
+                return None;
+            }
+
+            Some(SettlementLog {
+                tx_hash: format!("{:?}", log.transaction_hash?),
+                log_index: log.log_index?.as_u64() as i64,
+            })
+        })
+        .collect()
+}
+
+/// 可编排结果的执行器，用于测试和本地联调
+/// 未编排时提交成功且立即最终确认
+#[derive(Default)]
+pub struct MockWithdrawalExecutor {
+    submit_results: Mutex<VecDeque<Result<SubmittedWithdrawal, SubmitError>>>,
+    statuses: Mutex<VecDeque<ExecutionStatus>>,
+    submitted: Mutex<Vec<Uuid>>,
+}
+
+impl MockWithdrawalExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一次提交结果
+    pub fn push_submit(&self, result: Result<SubmittedWithdrawal, SubmitError>) {
+        self.submit_results.lock().unwrap().push_back(result);
+    }
+
+    /// 追加一次状态查询结果
+    pub fn push_status(&self, status: ExecutionStatus) {
+        self.statuses.lock().unwrap().push_back(status);
+    }
+
+    /// 已提交过的提现 ID
+    pub fn submitted(&self) -> Vec<Uuid> {
+        self.submitted.lock().unwrap().clone()
+    }
+}
+
+impl WithdrawalExecutor for MockWithdrawalExecutor {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn submit<'a>(&'a self, withdrawal: &'a Withdrawal) -> BoxFuture<'a, Result<SubmittedWithdrawal, SubmitError>> {
+        Box::pin(async move {
+            self.submitted.lock().unwrap().push(withdrawal.id);
+            match self.submit_results.lock().unwrap().pop_front() {
+                Some(result) => result,
+                None => Ok(SubmittedWithdrawal {
+                    external_ref: format!("mock:{}", withdrawal.id),
+                    block_number: None,
+                }),
+            }
+        })
+    }
+
+    fn check_status<'a>(&'a self, _withdrawal: &'a Withdrawal) -> BoxFuture<'a, anyhow::Result<ExecutionStatus>> {
+        Box::pin(async move {
+            Ok(self
+                .statuses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or(ExecutionStatus::Finalized))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listeners::rpc_pool::RetryPolicy;
+    use std::time::Duration;
+
+    fn withdrawal() -> Withdrawal {
+        let now = chrono::Utc::now();
+        Withdrawal {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            asset: "0x0000000000000000000000000000000000000001".to_string(),
+            amount: "1500000".to_string(),
+            destination: "0x0000000000000000000000000000000000000002".to_string(),
+            status: "submitting".to_string(),
+            approvals_required: 0,
+            approvals: 0,
+            executor: Some("mock".to_string()),
+            external_ref: None,
+            submitted_block: None,
+            settlement_tx_hash: None,
+            settlement_log_index: None,
+            failure_reason: None,
+            created_at: now,
+            updated_at: now,
+            submitted_at: None,
+            finalized_at: None,
+        }
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(1_500_000, 6), "1.5");
+        assert_eq!(format_units(1_000_000, 6), "1");
+        assert_eq!(format_units(1, 6), "0.000001");
+        assert_eq!(format_units(123_456_789, 6), "123.456789");
+        assert_eq!(format_units(42, 0), "42");
+    }
+
+    #[tokio::test]
+    async fn test_mock_executor_scripted_results() {
+        let executor = MockWithdrawalExecutor::new();
+        let w = withdrawal();
+
+        executor.push_submit(Err(SubmitError::rejected("insufficient balance")));
+        assert!(matches!(executor.submit(&w).await, Err(SubmitError::Rejected(_))));
+
+        executor.push_submit(Err(SubmitError::Unknown(anyhow::anyhow!("request timed out"))));
+        assert!(matches!(executor.submit(&w).await, Err(SubmitError::Unknown(_))));
+
+        let submitted = executor.submit(&w).await.unwrap();
+        assert_eq!(submitted.external_ref, format!("mock:{}", w.id));
+        assert_eq!(executor.submitted(), vec![w.id, w.id, w.id]);
+
+        executor.push_status(ExecutionStatus::Pending);
+        assert_eq!(executor.check_status(&w).await.unwrap(), ExecutionStatus::Pending);
+        assert_eq!(executor.check_status(&w).await.unwrap(), ExecutionStatus::Finalized);
+    }
+
+    fn finalized_log(destination: Address, usd: u64, tx_hash: H256, log_index: u64) -> Log {
+        Log {
+            data: encode(&[
+                Token::Address(destination),
+                Token::Uint(U256::from(usd)),
+                Token::Uint(U256::from(7u64)),
+                Token::FixedBytes(vec![0u8; 32]),
+            ])
+            .into(),
+            transaction_hash: Some(tx_hash),
+            log_index: Some(U256::from(log_index)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_match_settlement_logs_requires_exact_net_amount() {
+        let destination = Address::repeat_byte(2);
+        let tx_hash = H256::repeat_byte(9);
+        let logs = vec![
+            finalized_log(destination, 1_000_000, tx_hash, 0), // 金额更小，旧逻辑会误匹配
+            finalized_log(Address::repeat_byte(3), 4_000_000, tx_hash, 1), // 收款地址不同
+            finalized_log(destination, 4_000_000, tx_hash, 2),
+            finalized_log(destination, 4_000_000, tx_hash, 3),
+        ];
+
+        let matched = match_settlement_logs(&logs, destination, U256::from(4_000_000u64));
+        assert_eq!(
+            matched,
+            vec![
+                SettlementLog { tx_hash: format!("{:?}", tx_hash), log_index: 2 },
+                SettlementLog { tx_hash: format!("{:?}", tx_hash), log_index: 3 },
+            ]
+        );
+    }
+
+    /// 只包含 LOG2 的最小合约：calldata = topic0 | topic1 | data，用来在 Anvil 上模拟 Bridge2 事件
+    const LOG_EMITTER_BYTECODE: &str = "0x6014600c60003960146000f3366000600037602051600051604036036040a200";
+
+    async fn emit_finalized(
+        client: &SignerMiddleware<Provider<Http>, LocalWallet>,
+        bridge: Address,
+        user: Address,
+        destination: Address,
+        usd: u64,
+    ) -> TransactionReceipt {
+        let topic = H256::from(keccak256("FinalizedWithdrawal(address,address,uint64,uint64,bytes32)"));
+        let mut calldata = topic.as_bytes().to_vec();
+        calldata.extend(H256::from(user).as_bytes());
+        calldata.extend(finalized_log(destination, usd, H256::zero(), 0).data.to_vec());
+
+        let tx = TransactionRequest::new().to(bridge).data(calldata);
+        client.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地安装 anvil（foundry）"]
+    async fn test_bridge_scanner_against_anvil() {
+        let anvil = ethers::utils::Anvil::new().spawn();
+        let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+        let wallet: LocalWallet = LocalWallet::from(anvil.keys()[0].clone()).with_chain_id(anvil.chain_id());
+        let client = SignerMiddleware::new(provider, wallet);
+
+        let deploy = TransactionRequest::new().data(LOG_EMITTER_BYTECODE.parse::<Bytes>().unwrap());
+        let receipt = client.send_transaction(deploy, None).await.unwrap().await.unwrap().unwrap();
+        let bridge = receipt.contract_address.unwrap();
+
+        let user = Address::repeat_byte(1);
+        let destination: Address = withdrawal().destination.parse().unwrap();
+        let policy = RetryPolicy {
+            max_retries: 0,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            request_timeout: Duration::from_secs(5),
+        };
+        let rpc = RpcPool::new(&[anvil.endpoint()], policy, false, 1).unwrap();
+        let scanner = BridgeWithdrawalScanner::new(rpc, bridge, user, 1_000_000);
+
+        // 提交前已存在的同额事件不能被认领
+        let before = emit_finalized(&client, bridge, user, destination, 500_000).await;
+        let before = before.block_number.unwrap().as_u64();
+
+        let mut w = withdrawal();
+        assert!(scanner.find_settlements(&w).await.is_err()); // 没有提交区块高度
+        w.submitted_block = Some(before as i64 + 1);
+        assert!(scanner.find_settlements(&w).await.unwrap().is_empty());
+
+        // 收款地址不同、金额不等于扣费后金额、其他账户发起的事件都不匹配
+        emit_finalized(&client, bridge, user, Address::repeat_byte(3), 500_000).await;
+        emit_finalized(&client, bridge, user, destination, 400_000).await;
+        emit_finalized(&client, bridge, Address::repeat_byte(4), destination, 500_000).await;
+        assert!(scanner.find_settlements(&w).await.unwrap().is_empty());
+
+        let first = emit_finalized(&client, bridge, user, destination, 500_000).await;
+        let second = emit_finalized(&client, bridge, user, destination, 500_000).await;
+
+        // 两笔同额提现得到相同的候选列表，按链上顺序排列，由数据库唯一约束决定各自认领哪一条
+        let matched = scanner.find_settlements(&w).await.unwrap();
+        assert_eq!(
+            matched.iter().map(|log| log.tx_hash.clone()).collect::<Vec<_>>(),
+            vec![format!("{:?}", first.transaction_hash), format!("{:?}", second.transaction_hash)]
+        );
+        assert!(matched.iter().all(|log| log.log_index == 0));
+    }
+}
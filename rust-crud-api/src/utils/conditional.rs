@@ -0,0 +1,97 @@
+use actix_web::HttpRequest;
+use actix_web::http::header::{HeaderName, IF_MATCH, IF_NONE_MATCH};
+
+/// If-Match 请求头
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `If-Match: *`，只要求资源存在
+    Any,
+    /// 实体标签列表（已去掉引号，弱标签不参与 If-Match 比较）
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    /// 按标签解析出的版本号；`*` 返回 None（不限制版本）
+    pub fn versions(&self) -> Option<Vec<i64>> {
+        match self {
+            IfMatch::Any => None,
+            IfMatch::Tags(tags) => Some(tags.iter().filter_map(|t| t.parse().ok()).collect()),
+        }
+    }
+}
+
+/// 解析实体标签列表，返回 (是否为弱标签, 去掉引号的值)；`*` 返回 None
+fn parse_entity_tags(value: &str) -> Option<Vec<(bool, String)>> {
+    if value.trim() == "*" {
+        return None;
+    }
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| match t.strip_prefix("W/") {
+                Some(weak) => (true, weak.trim_matches('"').to_string()),
+                None => (false, t.trim_matches('"').to_string()),
+            })
+            .collect(),
+    )
+}
+
+fn header_str<'a>(req: &'a HttpRequest, name: &HeaderName) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+/// If-None-Match 是否命中当前 ETag（弱比较）；命中时 GET 应返回 304
+pub fn if_none_match_hits(req: &HttpRequest, etag: &str) -> bool {
+    let Some(value) = header_str(req, &IF_NONE_MATCH) else {
+        return false;
+    };
+    let current = etag.trim_start_matches("W/").trim_matches('"');
+    match parse_entity_tags(value) {
+        None => true,
+        Some(tags) => tags.iter().any(|(_, tag)| tag == current),
+    }
+}
+
+/// 读取 If-Match 请求头；未携带时返回 None
+pub fn if_match(req: &HttpRequest) -> Option<IfMatch> {
+    let value = header_str(req, &IF_MATCH)?;
+    Some(match parse_entity_tags(value) {
+        None => IfMatch::Any,
+        // If-Match 使用强比较，弱标签永远不匹配
+        Some(tags) => IfMatch::Tags(tags.into_iter().filter(|(weak, _)| !weak).map(|(_, tag)| tag).collect()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_if_none_match() {
+        let req = TestRequest::default().insert_header((IF_NONE_MATCH, "\"1\", W/\"3\"")).to_http_request();
+        assert!(if_none_match_hits(&req, "\"3\""));
+        assert!(if_none_match_hits(&req, "\"1\""));
+        assert!(!if_none_match_hits(&req, "\"2\""));
+
+        let any = TestRequest::default().insert_header((IF_NONE_MATCH, "*")).to_http_request();
+        assert!(if_none_match_hits(&any, "\"2\""));
+        assert!(!if_none_match_hits(&TestRequest::default().to_http_request(), "\"2\""));
+    }
+
+    #[test]
+    fn test_if_match() {
+        assert_eq!(if_match(&TestRequest::default().to_http_request()), None);
+
+        let any = TestRequest::default().insert_header((IF_MATCH, "*")).to_http_request();
+        assert_eq!(if_match(&any), Some(IfMatch::Any));
+        assert_eq!(IfMatch::Any.versions(), None);
+
+        let tags = TestRequest::default().insert_header((IF_MATCH, "\"4\", W/\"5\", \"abc\"")).to_http_request();
+        let parsed = if_match(&tags).unwrap();
+        assert_eq!(parsed, IfMatch::Tags(vec!["4".to_string(), "abc".to_string()]));
+        assert_eq!(parsed.versions(), Some(vec![4]));
+    }
+}
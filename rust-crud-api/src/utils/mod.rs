@@ -3,6 +3,8 @@
 
 pub mod validation;
 pub mod password;
+pub mod conditional;
 
 pub use validation::*;
-pub use password::*;
\ No newline at end of file
+pub use password::*;
+pub use conditional::*;
\ No newline at end of file
@@ -24,6 +24,22 @@ pub fn is_strong_password(password: &str) -> bool {
     has_letter && has_digit
 }
 
+/// 验证以太坊地址格式（0x + 40位十六进制，不校验大小写校验和）
+pub fn is_valid_eth_address(address: &str) -> bool {
+    is_prefixed_hex(address, 40)
+}
+
+/// 验证交易哈希格式（0x + 64位十六进制）
+pub fn is_valid_tx_hash(tx_hash: &str) -> bool {
+    is_prefixed_hex(tx_hash, 64)
+}
+
+fn is_prefixed_hex(value: &str, len: usize) -> bool {
+    value
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == len && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,4 +70,14 @@ mod tests {
         assert!(!is_strong_password("12345678")); // no letters
         assert!(!is_strong_password("Pass1")); // too short
     }
+
+    #[test]
+    fn test_eth_hex_validation() {
+        assert!(is_valid_eth_address("0x8bcbe680c8d401C1a3024A9364445232E04De64E"));
+        assert!(!is_valid_eth_address("8bcbe680c8d401C1a3024A9364445232E04De64E")); // no prefix
+        assert!(!is_valid_eth_address("0x8bcbe680c8d401C1a3024A9364445232E04De6")); // too short
+        assert!(!is_valid_eth_address("0x8bcbe680c8d401C1a3024A9364445232E04De6G")); // not hex
+        assert!(is_valid_tx_hash(&format!("0x{}", "ab".repeat(32))));
+        assert!(!is_valid_tx_hash("0x1234"));
+    }
 }
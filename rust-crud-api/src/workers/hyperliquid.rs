@@ -0,0 +1,170 @@
+use crate::config::Config;
+use crate::models::hyperliquid::{HyperliquidFill, HyperliquidFunding};
+use crate::services::hyperliquid::{build_info_client, hyperliquid_base_url};
+use crate::services::hyperliquid_history::{HyperliquidHistoryService, address_key};
+use alloy::primitives::Address;
+use hyperliquid_rust_sdk::{InfoClient, Message, Subscription};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio::time;
+
+/// 对比已订阅地址与当前绑定的地址，返回 (需要订阅的, 需要取消订阅的)
+fn plan_subscriptions(subscribed: &HashMap<String, Vec<u32>>, linked: &[String]) -> (Vec<String>, Vec<String>) {
+    let to_add = linked.iter().filter(|a| !subscribed.contains_key(*a)).cloned().collect();
+    let mut to_remove: Vec<String> = subscribed.keys().filter(|a| !linked.contains(a)).cloned().collect();
+    to_remove.sort();
+    (to_add, to_remove)
+}
+
+/// 为新绑定的地址订阅成交与资金费推送，取消已解绑地址的订阅
+async fn sync_subscriptions(
+    ws: &mut InfoClient,
+    subscriptions: &mut HashMap<String, Vec<u32>>,
+    linked: &[String],
+    sender: &UnboundedSender<Message>,
+) {
+    let (to_add, to_remove) = plan_subscriptions(subscriptions, linked);
+
+    for address in to_remove {
+        for id in subscriptions.remove(&address).unwrap_or_default() {
+            if let Err(e) = ws.unsubscribe(id).await {
+                log::warn!("⚠️ 取消 Hyperliquid 订阅失败: address={}, {}", address, e);
+            }
+        }
+        log::info!("🔕 已取消 Hyperliquid 订阅: {}", address);
+    }
+
+    for address in to_add {
+        let Ok(user) = Address::from_str(&address) else {
+            log::warn!("⚠️ 跳过无效地址: {}", address);
+            continue;
+        };
+        let mut ids = Vec::new();
+        for subscription in [Subscription::UserFills { user }, Subscription::UserFundings { user }] {
+            match ws.subscribe(subscription, sender.clone()).await {
+                Ok(id) => ids.push(id),
+                Err(e) => log::error!("❌ Hyperliquid 订阅失败: address={}, {}", address, e),
+            }
+        }
+        // 两个订阅都成功才记录，否则下一轮重试
+        if ids.len() == 2 {
+            log::info!("📡 已订阅 Hyperliquid 成交与资金费: {}", address);
+            subscriptions.insert(address, ids);
+        } else {
+            for id in ids {
+                let _ = ws.unsubscribe(id).await;
+            }
+        }
+    }
+}
+
+/// 写入一条推送消息
+async fn handle_message(history: &HyperliquidHistoryService, message: Message) -> bool {
+    match message {
+        Message::UserFills(fills) => {
+            let address = address_key(&fills.data.user);
+            let rows: Vec<HyperliquidFill> = fills.data.fills.into_iter().map(HyperliquidFill::from).collect();
+            match history.store_fills(&address, &rows).await {
+                Ok(0) => {}
+                Ok(inserted) => log::info!("💹 写入 {} 条 Hyperliquid 成交: {}", inserted, address),
+                Err(e) => log::error!("❌ 写入 Hyperliquid 成交失败: address={}, {}", address, e),
+            }
+        }
+        Message::UserFundings(fundings) => {
+            let address = address_key(&fundings.data.user);
+            let rows: Vec<HyperliquidFunding> =
+                fundings.data.fundings.into_iter().map(HyperliquidFunding::from).collect();
+            match history.store_fundings(&address, &rows).await {
+                Ok(0) => {}
+                Ok(inserted) => log::info!("💸 写入 {} 条 Hyperliquid 资金费: {}", inserted, address),
+                Err(e) => log::error!("❌ 写入 Hyperliquid 资金费失败: address={}, {}", address, e),
+            }
+        }
+        Message::Disconnected(disconnected) => {
+            log::warn!("⚠️ Hyperliquid WebSocket 已断开: {}", disconnected.reason)
+        }
+        // 重连完成：断线期间的推送可能丢失，需要立即补拉
+        Message::Reconnected(reconnected) => {
+            if !reconnected.all_resubscribed() {
+                log::error!("❌ Hyperliquid WebSocket 重连后部分订阅失败: {:?}", reconnected.resubscriptions);
+            }
+            return true;
+        }
+        Message::HyperliquidError(e) => log::error!("❌ Hyperliquid WebSocket 错误: {}", e),
+        _ => {}
+    }
+    false
+}
+
+/// Hyperliquid 成交与资金费入库任务
+///
+/// 通过 WebSocket 订阅所有已绑定地址的 UserFills 与 UserFundings 并实时写入；
+/// 每隔 backfill_interval 用 REST 补拉一次，覆盖断线、重启期间的缺口。两条路径写入的数据由唯一约束去重。
+pub async fn start_hyperliquid_ingester(config: Config, history: HyperliquidHistoryService) -> anyhow::Result<()> {
+    let rest = build_info_client(&config.hyperliquid_network).await?;
+    let mut ws = InfoClient::with_reconnect(None, Some(hyperliquid_base_url(&config.hyperliquid_network)?)).await?;
+    let lookback = chrono::Duration::days(config.hyperliquid_funding_lookback_days as i64);
+
+    let (sender, mut receiver) = unbounded_channel();
+    let mut subscriptions: HashMap<String, Vec<u32>> = HashMap::new();
+    let mut interval = time::interval(Duration::from_secs(config.hyperliquid_backfill_interval_secs.max(1)));
+
+    log::info!(
+        "📥 Hyperliquid 入库任务已启动（{}），每{}秒补拉一次",
+        config.hyperliquid_network,
+        config.hyperliquid_backfill_interval_secs
+    );
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let linked = match history.linked_addresses().await {
+                    Ok(linked) => linked,
+                    Err(e) => {
+                        log::error!("❌ 查询已绑定地址失败: {}", e);
+                        continue;
+                    }
+                };
+
+                // 先订阅再补拉，两者之间的成交不会遗漏
+                sync_subscriptions(&mut ws, &mut subscriptions, &linked, &sender).await;
+                for address in &linked {
+                    match history.backfill_address(&rest, address, lookback).await {
+                        Ok(stats) if stats.fills + stats.fundings > 0 => log::info!(
+                            "🔄 Hyperliquid 补拉 {}: {} 条成交, {} 条资金费",
+                            address,
+                            stats.fills,
+                            stats.fundings
+                        ),
+                        Ok(_) => {}
+                        Err(e) => log::error!("❌ Hyperliquid 补拉失败: address={}, {}", address, e),
+                    }
+                }
+            }
+            Some(message) = receiver.recv() => {
+                if handle_message(&history, message).await {
+                    log::info!("🔌 Hyperliquid WebSocket 已重连，立即补拉缺口");
+                    interval.reset_immediately();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_subscriptions() {
+        let subscribed: HashMap<String, Vec<u32>> =
+            [("0xaa".to_string(), vec![1, 2]), ("0xbb".to_string(), vec![3, 4])].into_iter().collect();
+        let linked = vec!["0xbb".to_string(), "0xcc".to_string()];
+
+        let (to_add, to_remove) = plan_subscriptions(&subscribed, &linked);
+        assert_eq!(to_add, vec!["0xcc".to_string()]);
+        assert_eq!(to_remove, vec!["0xaa".to_string()]);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{Config, database::DatabasePool};
+use crate::listeners::arbitrum_vault::{VAULT_SOURCE, get_last_block};
+use crate::listeners::rpc_pool::RpcPool;
+use crate::services::LedgerService;
+use ethers::types::Address;
+use std::time::Duration;
+use tokio::time;
+
+/// 每轮最多记账的入金条数
+const POSTING_BATCH_SIZE: i64 = 100;
+
+/// 入金记账任务：定期把已确认但尚未记账的入金写入账本
+pub async fn start_deposit_posting_worker(ledger: LedgerService, interval_secs: u64) {
+    log::info!("📒 入金记账任务已启动，每{}秒检查一次", interval_secs);
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        // 一轮内持续处理直到没有待记账入金
+        loop {
+            match ledger.post_pending_deposits(POSTING_BATCH_SIZE).await {
+                Ok(posted) if posted as i64 == POSTING_BATCH_SIZE => continue,
+                Ok(posted) => {
+                    if posted > 0 {
+                        log::info!("📒 本轮记账 {} 笔入金", posted);
+                    }
+                    break;
+                }
+                Err(e) => {
+                    log::error!("❌ 入金记账任务失败: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 对账任务：定期比较账本中 vault 资产账户余额与链上 vault 的 balanceOf
+/// 链上余额按监听器已处理到的区块查询，避免把尚未索引的转账算作差异
+pub async fn start_reconciliation_worker(
+    config: Config,
+    pool: DatabasePool,
+    ledger: LedgerService,
+) -> anyhow::Result<()> {
+    if !config.enable_vault_watcher {
+        log::info!("🔕 对账任务已禁用（ENABLE_VAULT_WATCHER=false）");
+        return Ok(());
+    }
+
+    let vault_addr: Address = match &config.vault_contract_address {
+        Some(a) => a.parse()?,
+        None => anyhow::bail!("VAULT_CONTRACT_ADDRESS 未设置"),
+    };
+    let usdc_addr: Address = match &config.usdc_token_address {
+        Some(a) => a.parse()?,
+        None => anyhow::bail!("USDC_TOKEN_ADDRESS 未设置"),
+    };
+    let asset = format!("0x{:x}", usdc_addr);
+
+    let rpc = RpcPool::from_config(&config)?;
+
+    log::info!("⚖️ 对账任务已启动，每{}秒执行一次", config.reconciliation_interval_secs);
+    let mut interval = time::interval(Duration::from_secs(config.reconciliation_interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        let block_number = match get_last_block(&pool, VAULT_SOURCE).await {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                log::info!("⏳ 监听器尚未产生进度，跳过本轮对账");
+                continue;
+            }
+            Err(e) => {
+                log::error!("❌ 对账查询监听进度失败: {}", e);
+                continue;
+            }
+        };
+
+        // 先把已索引的入金全部记账，再比较余额
+        if let Err(e) = ledger.post_pending_deposits(i64::MAX).await {
+            log::error!("❌ 对账前记账失败: {}", e);
+            continue;
+        }
+
+        let onchain = match rpc.erc20_balance_of(usdc_addr, vault_addr, Some(block_number as u64)).await {
+            Ok(balance) if balance <= ethers::types::U256::from(i128::MAX as u128) => balance.as_u128() as i128,
+            Ok(balance) => {
+                log::error!("❌ 链上余额超出账本可表示范围: {}", balance);
+                continue;
+            }
+            Err(e) => {
+                log::error!("❌ 查询链上 vault 余额失败: {}", e);
+                continue;
+            }
+        };
+
+        let ledger_balance = match ledger.vault_balance(&asset).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                log::error!("❌ 查询账本余额失败: {}", e);
+                continue;
+            }
+        };
+
+        match ledger.record_reconciliation(&asset, block_number, ledger_balance, onchain).await {
+            Ok(r) if r.matched => {
+                log::info!("✅ 对账一致: asset={}, block={}, balance={}", asset, block_number, r.ledger_balance);
+            }
+            Ok(r) => {
+                log::error!(
+                    "🚨 对账不一致: asset={}, block={}, 账本={}, 链上={}, 差额={}",
+                    asset, block_number, r.ledger_balance, r.onchain_balance, r.difference
+                );
+            }
+            Err(e) => log::error!("❌ 保存对账结果失败: {}", e),
+        }
+    }
+}
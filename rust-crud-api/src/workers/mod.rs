@@ -0,0 +1,4 @@
+pub mod hyperliquid;
+pub mod ledger;
+pub mod webhook;
+pub mod withdrawal;
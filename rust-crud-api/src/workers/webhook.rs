@@ -0,0 +1,40 @@
+use crate::services::WebhookService;
+use futures_util::future::join_all;
+use std::time::Duration;
+use tokio::time;
+
+/// 每轮最多领取的投递条数（同一轮内并发投递）
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Webhook 投递任务：定期领取到期的投递并发送
+pub async fn start_webhook_delivery_worker(service: WebhookService, interval_secs: u64) {
+    log::info!("📨 Webhook 投递任务已启动，每{}秒检查一次", interval_secs);
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        // 一轮内持续处理直到没有到期投递
+        loop {
+            let deliveries = match service.claim_due(DELIVERY_BATCH_SIZE).await {
+                Ok(deliveries) => deliveries,
+                Err(e) => {
+                    log::error!("❌ 领取 Webhook 投递失败: {}", e);
+                    break;
+                }
+            };
+            let claimed = deliveries.len() as i64;
+
+            let results = join_all(deliveries.iter().map(|d| service.deliver(d))).await;
+            for (delivery, result) in deliveries.iter().zip(results) {
+                if let Err(e) = result {
+                    log::error!("❌ 保存 Webhook 投递结果失败: delivery={}, {}", delivery.id, e);
+                }
+            }
+
+            if claimed < DELIVERY_BATCH_SIZE {
+                break;
+            }
+        }
+    }
+}
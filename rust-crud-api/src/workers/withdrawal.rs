@@ -0,0 +1,103 @@
+use crate::services::{ExecutionStatus, SubmitError, WithdrawalExecutor, WithdrawalService};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+
+/// 每轮最多处理的提现条数
+const WITHDRAWAL_BATCH_SIZE: i64 = 20;
+
+/// 提现执行任务：提交已审批的提现，并跟踪已提交提现直到链上最终确认
+pub async fn start_withdrawal_worker(
+    service: WithdrawalService,
+    executor: Arc<dyn WithdrawalExecutor>,
+    interval_secs: u64,
+) {
+    log::info!("💸 提现执行任务已启动，执行器: {}，每{}秒检查一次", executor.name(), interval_secs);
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        submit_approved(&service, executor.as_ref()).await;
+        track_submitted(&service, executor.as_ref()).await;
+    }
+}
+
+/// 提交已审批的提现；状态先落库为 submitting，再调用执行器
+/// 只有确定未发出的提现才会标记失败并退款
+async fn submit_approved(service: &WithdrawalService, executor: &dyn WithdrawalExecutor) {
+    let withdrawals = match service.claim_approved(executor.name(), WITHDRAWAL_BATCH_SIZE).await {
+        Ok(withdrawals) => withdrawals,
+        Err(e) => {
+            log::error!("❌ 领取待执行提现失败: {}", e);
+            return;
+        }
+    };
+
+    for withdrawal in withdrawals {
+        match executor.submit(&withdrawal).await {
+            Ok(submitted) => {
+                log::info!("📤 提现已提交: id={}, ref={}", withdrawal.id, submitted.external_ref);
+                if let Err(e) = service
+                    .mark_submitted(withdrawal.id, &submitted.external_ref, submitted.block_number)
+                    .await
+                {
+                    // 已上链但未能记录引用，保持 submitting 状态等待人工核对
+                    log::error!(
+                        "🚨 提现已提交但保存状态失败: id={}, ref={}, {}",
+                        withdrawal.id, submitted.external_ref, e
+                    );
+                }
+            }
+            Err(SubmitError::Rejected(reason)) => {
+                log::warn!("⚠️ 提现被拒绝，退回冻结资金: id={}, {}", withdrawal.id, reason);
+                if let Err(err) = service.mark_failed(withdrawal.id, &reason).await {
+                    log::error!("❌ 标记提现失败时出错: id={}, {}", withdrawal.id, err);
+                }
+            }
+            Err(SubmitError::Unknown(e)) => {
+                // 可能已经上链，退款会导致重复出金；保持 submitting 状态等待人工对账
+                log::error!("🚨 提现提交结果未知，保持 submitting 等待对账: id={}, {}", withdrawal.id, e);
+            }
+        }
+    }
+}
+
+/// 查询已提交提现的链上状态
+async fn track_submitted(service: &WithdrawalService, executor: &dyn WithdrawalExecutor) {
+    let withdrawals = match service.list_submitted(WITHDRAWAL_BATCH_SIZE).await {
+        Ok(withdrawals) => withdrawals,
+        Err(e) => {
+            log::error!("❌ 查询已提交提现失败: {}", e);
+            return;
+        }
+    };
+
+    for withdrawal in withdrawals {
+        // 由其他执行器提交的提现不在这里确认
+        if withdrawal.executor.as_deref() != Some(executor.name()) {
+            continue;
+        }
+
+        let result = match executor.check_status(&withdrawal).await {
+            Ok(ExecutionStatus::Pending) => continue,
+            Ok(ExecutionStatus::Finalized) => service.mark_finalized(withdrawal.id).await,
+            Ok(ExecutionStatus::Settled(candidates)) => match service.mark_settled(withdrawal.id, &candidates).await {
+                Ok(true) => Ok(()),
+                Ok(false) => {
+                    log::debug!("⏳ 匹配的出金事件均已被其他提现认领，继续等待: id={}", withdrawal.id);
+                    continue;
+                }
+                Err(e) => Err(e),
+            },
+            Ok(ExecutionStatus::Failed(reason)) => service.mark_failed(withdrawal.id, &reason).await,
+            Err(e) => {
+                log::warn!("⚠️ 查询提现状态失败，下轮重试: id={}, {}", withdrawal.id, e);
+                continue;
+            }
+        };
+
+        if let Err(e) = result {
+            log::error!("❌ 更新提现状态失败: id={}, {}", withdrawal.id, e);
+        }
+    }
+}